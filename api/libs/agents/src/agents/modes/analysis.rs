@@ -15,8 +15,8 @@ use super::{ModeAgentData, ModeConfiguration};
 use crate::tools::{
     categories::{
         file_tools::{
-            CreateDashboardFilesTool, CreateMetricFilesTool, ModifyDashboardFilesTool,
-            ModifyMetricFilesTool, SearchDataCatalogTool,
+            CompileMetricSqlTool, CreateDashboardFilesTool, CreateMetricFilesTool,
+            ModifyDashboardFilesTool, ModifyMetricFilesTool, SearchDataCatalogTool,
         },
         response_tools::Done,
     },
@@ -63,6 +63,7 @@ pub fn get_configuration(agent_data: &ModeAgentData, data_source_syntax: Option<
             let modify_dashboard_files_tool = ModifyDashboardFilesTool::new(agent_clone.clone());
             let done_tool = Done::new(agent_clone.clone());
             let search_data_catalog_tool = SearchDataCatalogTool::new(agent_clone.clone());
+            let compile_metric_sql_tool = CompileMetricSqlTool::new(agent_clone.clone());
 
             // --- Define Conditions based on Agent State (as per original load_tools) ---
             // Base condition: Plan and context must exist (implicitly true if we are in this mode)
@@ -149,6 +150,13 @@ pub fn get_configuration(agent_data: &ModeAgentData, data_source_syntax: Option<
                     always_available,
                 )
                 .await;
+            agent_clone
+                .add_tool(
+                    compile_metric_sql_tool.get_name(),
+                    compile_metric_sql_tool.into_tool_call_executor(),
+                    base_condition,
+                )
+                .await;
 
             Ok(())
         })
@@ -326,6 +334,7 @@ To conclude your worklow, you use the `finish_and_respond` tool to send a final
 - **Default Time Range**: If the user does not specify a time range for analysis, **default to the last 12 months** from {TODAYS_DATE}. Clearly state this assumption if making it.
 - **Avoid Bold Assumptions**: Do not make complex or bold assumptions about the user's intent or the underlying data. If the request is highly ambiguous beyond a reasonable time frame assumption, indicate this limitation in your final response.
 - **Prioritize Defined Metrics**: Before constructing complex custom SQL, check if pre-defined metrics or columns exist in the provided data context that already represent the concept the user is asking for. Prefer using these established definitions.
+- **Compile Semantic Metrics**: When a dataset's model defines the measures or metrics you need, use `compile_metric_sql` to generate the aggregation SQL rather than re-implementing it. Bucket time dimensions with a grain suffix, e.g. `order_date__month`, or `order_date__fiscal_quarter` for fiscal periods.
 - **Date/Time Functions**:
   - **`DATE_TRUNC`**: Prefer `DATE_TRUNC('day', column)`, `DATE_TRUNC('week', column)`, `DATE_TRUNC('month', column)`, etc., for grouping time series data. Note that `'week'` starts on Monday.
   - **`EXTRACT`**:
@@ -2,6 +2,7 @@ use anyhow::{anyhow, bail, Result};
 use chrono::Utc;
 use database::{
     enums::Verification,
+    helpers::metric_sql::metric_query_sql,
     models::{DashboardFile, MetricFile},
     organization::get_user_organization_id,
     pool::get_pg_pool,
@@ -10,9 +11,8 @@ use database::{
 };
 use indexmap::IndexMap;
 use query_engine::{data_source_query_routes::query_engine::query_engine, data_types::DataType};
-use serde_json::Value;
 use serde_yaml;
-use tracing::{debug, error, warn};
+use tracing::{debug, error};
 use uuid::Uuid;
 
 use diesel::{ExpressionMethods, QueryDsl};
@@ -24,13 +24,16 @@ use super::file_types::file::FileWithId;
 
 // Import dataset_security for permission check
 use dataset_security::has_dataset_access;
-use dataset_security::has_all_datasets_access;
+use dataset_security::sql_references::{format_reference_errors, resolve_sql_references};
 
 // Import the types needed for the modification function
 
-use database::models::Dataset;
-use database::schema::datasets;
-use sql_analyzer::{analyze_query, types::TableKind};
+use once_cell::sync::Lazy;
+use sql_analyzer::{
+    analyze_query, detect_fan_out_joins, lint_sql, FanOutWarning, LintDiagnostic, LintOptions,
+    LintSeverity, ValidationMode,
+};
+use std::collections::HashMap;
 
 /// Validates SQL query using existing query engine by attempting to run it
 /// Returns a tuple with a message, results (if ≤ 13 records), metadata, and validated dataset IDs
@@ -50,51 +53,51 @@ pub async fn validate_sql(
         return Err(anyhow!("SQL query cannot be empty"));
     }
 
-    // Analyze the SQL to extract base table names
+    // Resolve every table and column against the deployed datasets the user may query
     let analysis_result = analyze_query(sql.to_string()).await?;
+    let resolved = resolve_sql_references(&analysis_result, data_source_id, user_id).await?;
+    if !resolved.errors.is_empty() {
+        bail!(
+            "SQL references unknown or inaccessible datasets or columns:\n{}",
+            format_reference_errors(&resolved.errors)
+        );
+    }
 
-    // Extract base table names
-    let table_names: Vec<String> = analysis_result
-        .tables
+    // Column counts let the linter flag `SELECT *` on wide datasets
+    let lint_options = LintOptions {
+        table_column_counts: resolved.column_counts,
+        ..Default::default()
+    };
+    let validated_dataset_ids = resolved.dataset_ids;
+
+    // Lint before running so the model can fix mistakes the database would
+    // either reject or silently compute wrong. Only errors, which the linter
+    // reserves for queries certain to be wrong, block the metric; anything
+    // that may be a false positive comes back as a warning
+    let diagnostics = lint_sql(sql.to_string(), lint_options).await?;
+    let (lint_errors, lint_warnings): (Vec<_>, Vec<_>) = diagnostics
         .into_iter()
-        .filter(|t| t.kind == TableKind::Base)
-        .map(|t| t.table_identifier.clone())
-        .collect();
-
-    let mut validated_dataset_ids = Vec::new(); // Store IDs of datasets user has access to
-
-    if !table_names.is_empty() {
-        let mut conn = get_pg_pool().get().await?;
-
-        // Find corresponding datasets
-        let found_datasets = datasets::table
-            .filter(datasets::data_source_id.eq(data_source_id))
-            .filter(datasets::name.eq_any(&table_names))
-            .filter(datasets::deleted_at.is_null())
-            .load::<Dataset>(&mut conn)
-            .await?;
-
-        let dataset_ids: Vec<Uuid> = found_datasets.iter().map(|ds| ds.id).collect();
-
-        // Check dataset access
-        if !dataset_ids.is_empty() {
-            if !has_all_datasets_access(user_id, &dataset_ids).await? {
-                bail!(
-                    "Permission denied: User {} does not have access to one or more datasets required by the query: {:?}",
-                    user_id,
-                    table_names
-                );
-            }
-            // If access is granted, add these IDs to the validated list
-            validated_dataset_ids.extend(dataset_ids);
-        } else {
-            warn!(
-                "Tables {:?} mentioned in query not found as datasets for data source {}",
-                table_names, data_source_id
-            );
-        }
+        .partition(|d| d.severity == LintSeverity::Error);
+    if !lint_errors.is_empty() {
+        bail!(
+            "SQL lint errors:\n{}",
+            format_lint_diagnostics(&lint_errors)
+        );
     }
 
+    // The cardinalities the models declare show which joins repeat the rows
+    // of an aggregated measure. Models can be out of date, so these warn too
+    let fan_outs = if resolved.relationships.relationships.is_empty() {
+        Vec::new()
+    } else {
+        detect_fan_out_joins(
+            sql.to_string(),
+            resolved.relationships,
+            ValidationMode::Flexible,
+        )
+        .await?
+    };
+
     // Try to execute the query
     let query_result = match query_engine(data_source_id, sql, Some(15)).await {
         Ok(result) => result,
@@ -109,6 +112,22 @@ pub async fn validate_sql(
     } else {
         format!("{} records were returned", num_records)
     };
+    let mut warnings = Vec::new();
+    if !lint_warnings.is_empty() {
+        warnings.push(format_lint_diagnostics(&lint_warnings));
+    }
+    if !fan_outs.is_empty() {
+        warnings.push(format_fan_out_warnings(&fan_outs));
+    }
+    let message = if warnings.is_empty() {
+        message
+    } else {
+        format!(
+            "{}\nSQL lint warnings (consider fixing these):\n{}",
+            message,
+            warnings.join("\n")
+        )
+    };
     let return_records = query_result.data.into_iter().take(13).collect();
 
     // Return validated IDs along with other results
@@ -120,6 +139,40 @@ pub async fn validate_sql(
     ))
 }
 
+/// Formats lint diagnostics as one line per issue, with position and suggested fix,
+/// so the model can locate and correct each problem
+pub fn format_lint_diagnostics(diagnostics: &[LintDiagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|d| {
+            let location = d
+                .span
+                .map(|s| format!("line {}, column {}: ", s.start_line, s.start_column))
+                .unwrap_or_default();
+            let fix = d
+                .fix
+                .as_ref()
+                .map(|f| match &f.replacement {
+                    Some(replacement) => format!(" Fix: {} (`{}`)", f.description, replacement),
+                    None => format!(" Fix: {}", f.description),
+                })
+                .unwrap_or_default();
+            format!("- {}{}.{}", location, d.message, fix)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Formats fan-out warnings as one line per inflated measure, with the
+/// pre-aggregating CTE that fixes it
+pub fn format_fan_out_warnings(warnings: &[FanOutWarning]) -> String {
+    warnings
+        .iter()
+        .map(|w| format!("- {}. Fix: {}", w.message, w.suggestion))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 /// Validates existence of metric IDs in database
 /// Returns Result with list of missing IDs if any
 pub async fn validate_metric_ids(ids: &[Uuid]) -> Result<Vec<Uuid>> {
@@ -143,7 +196,17 @@ pub async fn validate_metric_ids(ids: &[Uuid]) -> Result<Vec<Uuid>> {
     Ok(missing_ids)
 }
 
-pub const METRIC_YML_SCHEMA: &str = r##"
+/// Writing rules for metric files, followed by the JSON Schema generated from
+/// `MetricYml` so the tool descriptions can't drift from what is parsed.
+pub static METRIC_YML_SCHEMA: Lazy<String> = Lazy::new(|| {
+    format!(
+        "{}\n# --- FORMAL SCHEMA --- (JSON Schema, reflects rules above)\n{}\n",
+        METRIC_YML_RULES,
+        serde_json::to_string(&MetricYml::json_schema()).expect("metric schema serializes")
+    )
+});
+
+const METRIC_YML_RULES: &str = r##"
 # METRIC CONFIGURATION - YML STRUCTURE
 # -------------------------------------
 # REQUIRED Top-Level Fields: `name`, `description`, `timeFrame`, `sql`, `chartConfig`
@@ -167,7 +230,7 @@ pub const METRIC_YML_SCHEMA: &str = r##"
 #     sql: |
 #       SELECT ... 
 # `chartConfig`: Visualization settings.
-#   - RULE: Must contain `selectedChartType` (bar, line, scatter, pie, combo, metric, table).
+#   - RULE: Must contain `selectedChartType` (bar, line, scatter, pie, combo, metric, table, area, funnel, heatmap, histogram, waterfall, pivot).
 #   - RULE: Must contain `columnLabelFormats` defining format for ALL columns in the SQL result.
 #   - RULE: Must contain ONE chart-specific config block based on `selectedChartType`:
 #     - `barAndLineAxis` (for type: bar, line)
@@ -176,600 +239,65 @@ pub const METRIC_YML_SCHEMA: &str = r##"
 #     - `comboChartAxis` (for type: combo)
 #     - `metricColumnId` (for type: metric)
 #     - `tableConfig` (for type: table) - [Optional, if needed beyond basic columns]
+#     - `areaChartAxis` (for type: area)
+#     - `funnelChartAxis` (for type: funnel) - stages in the order the SQL returns them
+#     - `heatmapAxis` (for type: heatmap) - e.g. cohort retention: x = period, y = cohort, value = rate
+#     - `histogramAxis` (for type: histogram) - x is the RAW numeric column; do NOT bucket or count in SQL
+#     - `waterfallAxis` (for type: waterfall) - one row per step with its signed change
+#     - `pivotAxis` (for type: pivot) - `rows` and `columns` dimensions plus `values` to aggregate; return UNPIVOTED rows
+#       and let the server pivot, with subtotals and totals. Do NOT pivot in SQL (no CASE WHEN per column, no PIVOT).
+#   - RULE: Use the dedicated chart type instead of approximating it (e.g. a sorted bar chart for a funnel).
+# `parameters`: [Optional] Typed values the `sql` refers to as `{{ name }}`.
+#   - Use ONE parameterized metric instead of near-duplicate metrics that differ only in a literal (a date, threshold or category).
+#   - Each parameter has `name`, `type` (string, number, date, boolean), `default`, and optionally `allowedValues` and `description`.
+#   - RULE: Every `{{ name }}` in the `sql` must be declared, and every declared parameter must be used.
+#   - RULE: Use `{{ name }}` only where a value goes, never inside quotes or as a table/column name. Dates are YYYY-MM-DD.
+#   - Example:
+#     sql: |
+#       SELECT region, COUNT(*) AS large_orders FROM orders WHERE amount >= {{ min_amount }} GROUP BY region
+#     parameters:
+#       - name: min_amount
+#         type: number
+#         default: 100
+#         allowedValues: [100, 500, 1000]
+# `timeRange`: [Optional] Limits the metric to a date range on one date/timestamp `column` of the SQL's tables.
+#   - PREFER `timeRange` over hardcoding dates in the `sql` for relative periods, so the metric stays current.
+#   - Types: `last` (`count` + `unit`, e.g. last 30 days, including today), `previous` (`unit`, the last complete one, e.g. last quarter),
+#     `toDate` (`unit`, e.g. quarter to date), `absolute` (`start` and/or `end` as YYYY-MM-DD, both inclusive).
+#   - Units: day, week (starting Monday), month, quarter, year. Relative ranges use the organization's time zone.
+#   - RULE: Do NOT also filter the same dates in the `sql`. `column` must be filterable in the outermost SELECT's WHERE clause.
+#   - Example:
+#     timeRange:
+#       column: orders.created_at
+#       type: last
+#       count: 30
+#       unit: day
+# `comparison`: [Optional] Re-runs the metric over an earlier period so both series show together. Requires `timeRange`.
+#   - Types: `previousPeriod` (the same length just before, e.g. the 30 days before the last 30 days), `samePeriodLastYear`,
+#     `offset` (`count` + `unit` back, e.g. 3 months earlier). `previousPeriod` on an `absolute` range needs both `start` and `end`.
+#   - `columnId` is the date/timestamp bucket column of the SQL result; rows of both periods are lined up by it.
+#   - For metric charts, set `comparisonDelta: absolute` or `comparisonDelta: percent` in the chartConfig to show the change.
+#   - Example:
+#     comparison:
+#       type: samePeriodLastYear
+#       columnId: month
+# `drills`: [Optional] Links a viewer can follow from a clicked data point, passing the clicked value of `columnId`.
+#   - `type: metric` opens metric `id` with the value bound to its `parameter`; `type: dashboard` opens dashboard `id` with its filter `filter` set.
+#   - RULE: Only link to existing metrics and dashboards, and only bind a parameter or filter the target declares.
+#   - Example - click a region bar to open revenue by rep for that region:
+#     drills:
+#       - columnId: region
+#         type: metric
+#         id: 00000000-0000-0000-0000-000000000000
+#         parameter: region
+#         label: Revenue by rep
 #
 # --- GENERAL YAML RULES ---
 # 1. Use standard YAML syntax (indentation, colons for key-value, `-` for arrays).
 # 2. Quoting: Generally avoid quotes for simple strings. Use double quotes (`"...") ONLY if a string contains special characters (like :, {, }, [, ], ,, &, *, #, ?, |, -, <, >, =, !, %, @, `) or needs to preserve leading/trailing whitespace. 
-# 3. Metric name, timeframe, or description CANNOT contain `:`
+# 3. The file is read exactly as written. Double-quote a name, timeFrame, description or label that contains `: ` or starts with `#`, e.g. `name: "Revenue: Q1 vs Q2"`.
+# 4. Errors give the line, column and path of each problem, e.g. `line 6, column 5, chartConfig.barAndLineAxis.x`. Fix those fields and leave the rest of the file as it is.
 # -------------------------------------
-
-# --- FORMAL SCHEMA --- (Used for validation, reflects rules above)
-type: object
-name: Metric Configuration Schema
-description: Metric definition with SQL query and visualization settings
-
-properties:
-  # NAME
-  name:
-    required: true
-    type: string
-    description: Human-readable title (e.g., Total Sales). NO underscores. Follow quoting rules. Should not contain `:`
-
-  # DESCRIPTION
-  description:
-    required: true
-    type: string
-    description: |
-      A natural language description of the metric, essentially rephrasing the 'name' field as a question or statement. 
-      Example: If name is "Total Sales", description could be "What are the total sales?".
-      RULE: Should NOT describe the chart type, axes, or any visualization aspects.
-      RULE: Follow general quoting rules. 
-      RULE: Should not contain ':'.
-
-  # TIME FRAME
-  timeFrame:
-    required: true
-    type: string
-    description: |
-      Human-readable time period covered by the SQL query, similar to a filter in a BI tool.
-      RULE: Must accurately reflect the date/time filter used in the `sql` field. Do not misrepresent the time range.
-      Examples:
-      - Fixed Dates: "January 1, 2020 - December 31, 2020", "2024", "Q2 2024", "June 1, 2025"
-      - Relative Dates: "Today", "Yesterday", "Last 7 days", "Last 30 days", "Last Quarter", "Last 12 Months", "Year to Date", "All time"
-      - Comparisons: Use the format "Comparison: [Period 1] vs [Period 2]". Examples:
-        - "Comparison: Last 30 days vs Previous 30 days"
-        - "Comparison: June 1, 2025 - June 30, 2025 vs July 1, 2025 - July 31, 2025"
-      RULE: Use full month names for dates, e.g., "January", not "Jan".
-      RULE: Follow general quoting rules. CANNOT contain ':'.
-
-  # SQL QUERY
-  # Describes how the SQL should be formatted within the YAML
-  sql:
-    required: true
-    type: string
-    description: |
-      SQL query using YAML pipe syntax (|).
-      The SQL query should be formatted with proper indentation using the YAML pipe (|) syntax.
-      This ensures the multi-line SQL is properly parsed while preserving whitespace and newlines.
-      Example:
-        sql: |
-          SELECT column1, column2
-          FROM my_table
-          WHERE condition;
-
-  # CHART CONFIGURATION
-  chartConfig:
-    required: true
-    description: Visualization settings (must include selectedChartType, columnLabelFormats, and ONE chart-specific block)
-    allOf: # Base requirements for ALL chart types
-      - $ref: '#/definitions/base_chart_config'
-    oneOf: # Specific block required based on type 
-      - $ref: #/definitions/bar_line_chart_config
-      - $ref: #/definitions/scatter_chart_config
-      - $ref: #/definitions/pie_chart_config
-      - $ref: #/definitions/combo_chart_config
-      - $ref: #/definitions/metric_chart_config
-      - $ref: #/definitions/table_chart_config
-
-required:
-  - name
-  - datasetIds
-  - timeFrame
-  - sql
-  - chartConfig
-
-definitions:
-  # BASE CHART CONFIG (common parts used by ALL chart types)
-  base_chart_config:
-    type: object
-    properties:
-      selectedChartType:
-        type: string
-        description: Chart type (bar, line, scatter, pie, combo, metric, table)
-        enum: [bar, line, scatter, pie, combo, metric, table]
-      columnLabelFormats:
-        type: object
-        description: REQUIRED formatting for ALL columns returned by the SQL query.
-        additionalProperties:
-          $ref: #/definitions/column_label_format
-      # Optional base properties below
-      columnSettings:
-        type: object
-        description: |-
-          Visual settings applied per column. 
-          Keys MUST be LOWERCASE column names from the SQL query results. 
-          Example: `total_sales: { showDataLabels: true }`
-        additionalProperties:
-          $ref: #/definitions/column_settings
-      colors:
-        type: array
-        items:
-          type: string
-        description: |
-          Default color palette. 
-          RULE: Hex color codes (e.g., #FF0000) MUST be enclosed in quotes (e.g., "#FF0000" or '#FF0000') because '#' signifies a comment otherwise. Double quotes are preferred for consistency.
-          Use this parameter when the user asks about customizing chart colors, unless specified otherwise.
-      showLegend:
-        type: boolean
-      gridLines:
-        type: boolean
-      showLegendHeadline:
-        oneOf:
-          - type: boolean
-          - type: string
-      goalLines:
-        type: array
-        items:
-          $ref: #/definitions/goal_line
-      trendlines:
-        type: array
-        items:
-          $ref: #/definitions/trendline
-      disableTooltip:
-        type: boolean
-      # Axis Configurations
-      # RULE: By default, only add `xAxisConfig` and ONLY set its `xAxisTimeInterval` property 
-      #       when visualizing date/time data on the X-axis (e.g., line, bar, combo charts). 
-      #       Do NOT add other `xAxisConfig` properties, `yAxisConfig`, or `y2AxisConfig` 
-      #       unless the user explicitly asks for specific axis modifications.
-      xAxisConfig:
-        description: Controls X-axis properties. For date/time axes, MUST contain `xAxisTimeInterval` (day, week, month, quarter, year). Other properties control label visibility, title, rotation, and zoom. Only add when needed (dates) or requested by user.
-        $ref: '#/definitions/x_axis_config'
-      yAxisConfig:
-        description: Controls Y-axis properties. Only add if the user explicitly requests Y-axis modifications (e.g., hiding labels, changing title). Properties control label visibility, title, rotation, and zoom.
-        $ref: '#/definitions/y_axis_config'
-      y2AxisConfig:
-        description: Controls secondary Y-axis (Y2) properties, primarily for combo charts. Only add if the user explicitly requests Y2-axis modifications. Properties control label visibility, title, rotation, and zoom.
-        $ref: '#/definitions/y2_axis_config'
-      categoryAxisStyleConfig:
-        description: Optional style configuration for the category axis (color/grouping).
-        $ref: '#/definitions/category_axis_style_config'
-    required:
-      - selectedChartType
-      - columnLabelFormats
-
-  # AXIS CONFIGURATIONS
-  x_axis_config:
-    type: object
-    properties:
-      xAxisTimeInterval:
-        type: string
-        enum: [day, week, month, quarter, year, 'null']
-        description: REQUIRED time interval for grouping date/time values on the X-axis (e.g., for line/combo charts). MUST be set if the X-axis represents time. Default: null.
-      xAxisShowAxisLabel:
-        type: boolean
-        description: Show X-axis labels. Default: true.
-      xAxisShowAxisTitle:
-        type: boolean
-        description: Show X-axis title. Default: true.
-      xAxisAxisTitle:
-        type: [string, 'null']
-        description: X-axis title. Default: null (auto-generates from column names).
-      xAxisLabelRotation:
-        type: string # Representing numbers or 'auto'
-        enum: ["0", "45", "90", auto]
-        description: Label rotation. Default: auto.
-      xAxisDataZoom:
-        type: boolean
-        description: Enable data zoom on X-axis. Default: false (User only).
-    additionalProperties: false
-    required:
-      - xAxisTimeInterval
-
-  y_axis_config:
-    type: object
-    properties:
-      yAxisShowAxisLabel:
-        type: boolean
-        description: Show Y-axis labels. Default: true.
-      yAxisShowAxisTitle:
-        type: boolean
-        description: Show Y-axis title. Default: true.
-      yAxisAxisTitle:
-        type: [string, 'null']
-        description: Y-axis title. Default: null (uses first plotted column name).
-      yAxisStartAxisAtZero:
-        type: [boolean, 'null']
-        description: Start Y-axis at zero. Default: true.
-      yAxisScaleType:
-        type: string
-        enum: [log, linear]
-        description: Scale type for Y-axis. Default: linear.
-    additionalProperties: false
-
-  y2_axis_config:
-    type: object
-    description: Secondary Y-axis configuration (for combo charts).
-    properties:
-      y2AxisShowAxisLabel:
-        type: boolean
-        description: Show Y2-axis labels. Default: true.
-      y2AxisShowAxisTitle:
-        type: boolean
-        description: Show Y2-axis title. Default: true.
-      y2AxisAxisTitle:
-        type: [string, 'null']
-        description: Y2-axis title. Default: null (uses first plotted column name).
-      y2AxisStartAxisAtZero:
-        type: [boolean, 'null']
-        description: Start Y2-axis at zero. Default: true.
-      y2AxisScaleType:
-        type: string
-        enum: [log, linear]
-        description: Scale type for Y2-axis. Default: linear.
-    additionalProperties: false
-
-  category_axis_style_config:
-    type: object
-    description: Style configuration for the category axis (color/grouping).
-    properties:
-      categoryAxisTitle:
-        type: [string, 'null']
-        description: Title for the category axis.
-    additionalProperties: false
-
-  # COLUMN FORMATTING
-  columnLabelFormat:
-    type: object
-    properties:
-      columnType:
-        type: string
-        description: number, string, date
-        enum: [number, string, date]
-      style:
-        type: string
-        enum:
-          -
-            currency # Note: The "$" sign is automatically prepended.
-          -
-            percent # Note: "%" sign is appended. For percentage values: 
-            # - If the value comes directly from a database column, use multiplier: 1
-            # - If the value is calculated in your SQL query and not already multiplied by 100, use multiplier: 100
-          - number
-          - date # Note: For date columns, consider setting xAxisTimeInterval in xAxisConfig to control date grouping (day, week, month, quarter, year)
-          - string
-      multiplier:
-        type: number
-        description: Value to multiply the number by before display. Default value is 1. For percentages, the multiplier depends on how the data is sourced: if the value comes directly from a database column, use multiplier: 1; if the value is calculated in your SQL query and not already multiplied by 100, use multiplier: 100.
-      displayName:
-        type: string
-        description: Custom display name for the column
-      numberSeparatorStyle:
-        type: string
-        description: Style for number separators. Your option is ',' or a null value.  Not null wrapped in quotes, a null value.
-      minimumFractionDigits:
-        type: integer
-        description: Minimum number of fraction digits to display
-      maximumFractionDigits:
-        type: integer
-        description: Maximum number of fraction digits to display
-      prefix:
-        type: string
-      suffix:
-        type: string
-      replaceMissingDataWith:
-        type: number
-        description: Value to display when data is missing, needs to be set to 0. Should only be set on number columns. All others should be set to null.
-      compactNumbers:
-        type: boolean
-        description: Whether to display numbers in compact form (e.g., 1K, 1M)
-      currency:
-        type: string
-        description: Currency code for currency formatting (e.g., USD, EUR)
-      dateFormat:
-        type: string
-        description: |
-          Format string for date display (must be compatible with Day.js format strings). 
-          RULE: Choose format based on xAxisTimeInterval:
-            - year: 'YYYY' (e.g., 2025)
-            - quarter: '[Q]Q YYYY' (e.g., Q1 2025)
-            - month: 'MMM YYYY' (e.g., Jan 2025) or 'MMMM' (e.g., January) if context is clear.
-            - week/day: 'MMM D, YYYY' (e.g., Jan 25, 2025) or 'MMM D' (e.g., Jan 25) if context is clear.
-      useRelativeTime:
-        type: boolean
-        description: Whether to display dates as relative time (e.g., 2 days ago)
-      isUtc:
-        type: boolean
-        description: Whether to interpret dates as UTC
-      convertNumberTo:
-        type: string
-        description: Optional. Convert numeric values to time units or date parts.  This is a necessity for time series data when numbers are passed instead of the date.
-        enum:
-          - day_of_week
-          - month_of_year
-          - quarter
-
-    required:
-      - columnType
-      - style
-      - replaceMissingDataWith
-      - numberSeparatorStyle
-
-  # COLUMN VISUAL SETTINGS
-  column_settings:
-    type: object
-    description: Optional visual settings per LOWERCASE column name.
-    properties:
-      showDataLabels:
-        type: boolean
-      columnVisualization:
-        type: string
-        enum:
-          - bar
-          - line
-          - dot
-      lineWidth:
-        type: number
-      lineStyle:
-        type: string
-        enum:
-          - area
-          - line
-      lineType:
-        type: string
-        enum:
-          - normal
-          - smooth
-          - step
-
-  # CHART-SPECIFIC CONFIGURATIONS
-  bar_line_chart_config:
-    allOf:
-      - $ref: #/definitions/base_chart_config
-      - type: object
-        properties:
-          selectedChartType:
-            enum:
-              - bar
-              - line
-          barAndLineAxis:
-            type: object
-            properties:
-              x:
-                type: array
-                items:
-                  type: string
-              y:
-                type: array
-                items:
-                  type: string
-                description: LOWERCASE column name from SQL for X-axis.
-              category:
-                type: array
-                items:
-                  type: string
-                description: LOWERCASE column name from SQL for category grouping.
-            required:
-              - x
-              - y
-          barLayout:
-            type: string
-            enum:
-              - horizontal
-              - vertical
-          barGroupType:
-            type: string
-            enum:
-              - stack
-              - group
-              - percentage-stack
-        required:
-          - selectedChartType
-          - barAndLineAxis
-
-  scatter_chart_config:
-    allOf:
-      - $ref: #/definitions/base_chart_config
-      - type: object
-        properties:
-          selectedChartType:
-            enum:
-              - scatter
-          scatterAxis:
-            type: object
-            properties:
-              x:
-                type: array
-                items:
-                  type: string
-              y:
-                type: array
-                items:
-                  type: string
-              category:
-                type: array
-                items:
-                  type: string
-              size:
-                type: array
-                items:
-                  type: string
-            required:
-              - x
-              - y
-        required:
-          - selectedChartType
-          - scatterAxis
-
-  pie_chart_config:
-    allOf:
-      - $ref: #/definitions/base_chart_config
-      - type: object
-        properties:
-          selectedChartType:
-            enum:
-              - pie
-          pieChartAxis:
-            type: object
-            properties:
-              x:
-                type: array
-                items:
-                  type: string
-              y:
-                type: array
-                items:
-                  type: string
-            required:
-              - x
-              - y
-        required:
-          - selectedChartType
-          - pieChartAxis
-
-  combo_chart_config:
-    allOf:
-      - $ref: #/definitions/base_chart_config
-      - type: object
-        properties:
-          selectedChartType:
-            enum:
-              - combo
-          comboChartAxis:
-            type: object
-            properties:
-              x:
-                type: array
-                items:
-                  type: string
-              y:
-                type: array
-                items:
-                  type: string
-            required:
-              - x
-              - y
-        required:
-          - selectedChartType
-          - comboChartAxis
-
-  metric_chart_config:
-    allOf:
-      - $ref: #/definitions/base_chart_config
-      - type: object
-        properties:
-          selectedChartType:
-            enum:
-              - metric
-          metricColumnId:
-            type: string
-            description: LOWERCASE column name from SQL for the main metric value.
-          metricValueAggregate:
-            type: string
-            enum:
-              - sum
-              - average
-              - median
-              - max
-              - min
-              - count
-              - first
-            description: Aggregate function for metric value
-          metricHeader:
-            oneOf:
-              - type: string
-                description: Simple string title for the metric header
-              - type: object
-                properties:
-                  columnId:
-                    type: string
-                    description: Which column to use for the header
-                  useValue:
-                    type: boolean
-                    description: Whether to display the key or the value in the chart
-                  aggregate:
-                    type: string
-                    enum:
-                      - sum
-                      - average
-                      - median
-                      - max
-                      - min
-                      - count
-                      - first
-                    description: Optional aggregation method, defaults to sum
-                required:
-                  - columnId
-                  - useValue
-                description: Configuration for a derived metric header
-          metricSubHeader:
-            oneOf:
-              - type: string
-                description: Simple string title for the metric sub-header
-              - type: object
-                properties:
-                  columnId:
-                    type: string
-                    description: Which column to use for the sub-header
-                  useValue:
-                    type: boolean
-                    description: Whether to display the key or the value in the chart
-                  aggregate:
-                    type: string
-                    enum:
-                      - sum
-                      - average
-                      - median
-                      - max
-                      - min
-                      - count
-                      - first
-                    description: Optional aggregation method, defaults to sum
-                required:
-                  - columnId
-                  - useValue
-                description: Configuration for a derived metric sub-header
-          metricValueLabel:
-            oneOf:
-              - type: string
-                description: Custom label to display with the metric value
-        required:
-          - selectedChartType
-          - metricColumnId
-
-  table_chart_config:
-    allOf:
-      - $ref: #/definitions/base_chart_config
-      - type: object
-        properties:
-          selectedChartType:
-            enum:
-              - table
-          tableColumnOrder:
-            type: array
-            items:
-              type: string
-        required:
-          - selectedChartType
-          # No additional required fields for table chart
-
-  # HELPER OBJECTS
-  goal_line:
-    type: object
-    properties:
-      show:
-        type: boolean
-      value:
-        type: number
-      goalLineLabel:
-        type: string
-
-  trendline:
-    type: object
-    properties:
-      type:
-        type: string
-        enum:
-          - average
-          - linear_regression
-          - min
-          - max
-          - median
-      columnId:
-        type: string
-    required:
-      - type
-      - columnId
 "##;
 
 pub const DASHBOARD_YML_SCHEMA: &str = r##"
@@ -777,20 +305,28 @@ pub const DASHBOARD_YML_SCHEMA: &str = r##"
 # ----------------------------------------
 # Required fields:
 #
-# name: Your Dashboard Title  # Do NOT use quotes for string values
-# description: A description of the dashboard, its metrics, and its purpose.  # NO quotes
+# name: Your Dashboard Title  # Quote only if it contains special characters (rule 8)
+# description: A description of the dashboard, its metrics, and its purpose.  # Same as name
 # rows: 
 #   - id: 1               # Required row ID (integer)
 #     items:
-#       - id: metric-uuid-1  # UUIDv4 of an existing metric, NO quotes
+#       - type: metric     # metric, markdown or divider
+#         id: metric-uuid-1  # UUIDv4 of an existing metric, NO quotes
 #     columnSizes: [12]   # Required - must sum to exactly 12
 #   - id: 2 # REQUIRED
 #     items:
-#       - id: metric-uuid-2
-#       - id: metric-uuid-3
+#       - type: divider
+#         title: Revenue breakdown  # Optional section heading
+#     columnSizes: [12]
+#   - id: 3
+#     items:
+#       - type: markdown
+#         content: "Revenue is net of refunds. See [the glossary](https://example.com/glossary)."
+#       - type: metric
+#         id: metric-uuid-2
 #     columnSizes: 
-#       - 6
-#       - 6
+#       - 4
+#       - 8
 #
 # Rules:
 # 1. Each row can have up to 4 items
@@ -803,6 +339,28 @@ pub const DASHBOARD_YML_SCHEMA: &str = r##"
 # 8. String values generally should NOT use quotes unless they contain special characters (like :, {, }, [, ], ,, &, *, #, ?, |, -, <, >, =, !, %, @, `) or start/end with whitespace.
 # 9. If a string contains special characters or needs to preserve leading/trailing whitespace, enclose it in double quotes (`"`). Example: `name: "Sales & Marketing Dashboard"`
 # 10. Avoid special characters in names and descriptions where possible, but if needed, use quotes as described in rule 9. UUIDs should NEVER be quoted.
+# 11. Items are metric tiles, markdown text tiles (headings, notes, links) or section dividers. A divider must be the only item in its row.
+# 12. A divider with `collapsible: true` starts a section viewers can fold away; add `collapsed: true` to start it folded. Its metrics load once opened, so use this for detail below the headline metrics.
+# 13. For large dashboards (roughly 15+ metrics), split the rows into tabs instead of top-level rows:
+#
+# tabs:
+#   - id: overview        # Short lowercase id, unique per dashboard
+#     name: Overview
+#     rows:
+#       - id: 1
+#         items:
+#           - id: metric-uuid-1
+#         columnSizes: [12]
+#   - id: regions
+#     name: Regions
+#     rows:
+#       - id: 2           # Row ids are unique across ALL tabs
+#         items:
+#           - id: metric-uuid-2
+#         columnSizes: [12]
+#
+#     A dashboard with tabs must not also have top-level rows.
+# 14. Errors give the line, column and path of each problem, e.g. `line 9, column 5, rows[1].columnSizes`. Fix those fields and leave the rest of the file as it is.
 # ----------------------------------------
 
 type: object
@@ -811,13 +369,13 @@ description: Specifies the structure and constraints of a dashboard config file.
 properties:
   name:
     type: string
-    description: The title of the dashboard (e.g. Sales & Marketing Dashboard) - do NOT use quotes
+    description: The title of the dashboard (e.g. Sales & Marketing Dashboard) - quote it only if it contains special characters
   description:
     type: string
     description: A description of the dashboard, its metrics, and its purpose
   rows:
     type: array
-    description: Array of row objects, each containing metric items
+    description: Array of row objects, each containing metric, markdown or divider items. Required unless the dashboard uses tabs.
     items:
       type: object
       properties:
@@ -826,16 +384,35 @@ properties:
           description: This is just an integer representing the row number 1 -> n
         items:
           type: array
-          description: Array of metrics to display in this row (max 4 items)
+          description: Array of items to display in this row (max 4 items)
           maxItems: 4
           items:
             type: object
             properties:
+              type:
+                type: string
+                enum:
+                  - metric
+                  - markdown
+                  - divider
+                description: Kind of item; defaults to metric
               id:
                 type: string
-                description: UUIDv4 identifier of an existing metric
+                description: UUIDv4 identifier of an existing metric (metric items only)
+              content:
+                type: string
+                description: Markdown text (markdown items only)
+              title:
+                type: string
+                description: Optional section heading (divider items only)
+              collapsible:
+                type: boolean
+                description: Viewers can fold the section away (divider items only)
+              collapsed:
+                type: boolean
+                description: The section starts folded; requires collapsible (divider items only)
             required:
-              - id
+              - type
         columnSizes:
           type: array
           description: Required array of column sizes (must sum to exactly 12)
@@ -847,10 +424,25 @@ properties:
         - id
         - items
         - columnSizes
+  tabs:
+    type: array
+    description: Optional named tabs, each with its own rows (same shape as the top-level rows). Use instead of top-level rows.
+    items:
+      type: object
+      properties:
+        id:
+          type: string
+        name:
+          type: string
+        rows:
+          type: array
+      required:
+        - id
+        - name
+        - rows
 required:
   - name
   - description
-  - rows
 "##;
 
 /// Process a metric file creation request
@@ -880,17 +472,29 @@ pub async fn process_metric_file(
     };
 
     // Validate MetricYml structure
-    if let Err(e) = metric_yml.validate() {
+    if let Err(e) = metric_yml.validate(None) {
         return Err(format!("Invalid metric structure: {}", e));
     }
 
+    // Parameterized SQL is validated with every parameter at its default,
+    // and with the time range applied as it will be when queried
+    let sql = match metric_query_sql(&metric_yml, &HashMap::new(), &data_source_id).await {
+        Ok(sql) => sql,
+        Err(e) => return Err(format!("Invalid parameters or time range: {}", e)),
+    };
+
     // Validate SQL and get results + validated dataset IDs
     let (message, results, metadata, validated_dataset_ids) =
-        match validate_sql(&metric_yml.sql, &data_source_id, user_id).await {
+        match validate_sql(&sql, &data_source_id, user_id).await {
             Ok(results) => results,
             Err(e) => return Err(format!("Invalid SQL query: {}", e)),
         };
 
+    // Check the chart config against the columns the SQL returns
+    if let Err(e) = metric_yml.validate(metadata.as_ref()) {
+        return Err(format!("Invalid chart config: {}", e));
+    }
+
     let organization_id = match get_user_organization_id(user_id).await {
         Ok(Some(org_id)) => org_id,
         Ok(None) => return Err("User does not belong to any organization".to_string()),
@@ -994,12 +598,7 @@ pub async fn process_dashboard_file_modification(
                     );
 
                     // Collect and validate metric IDs from rows
-                    let metric_ids: Vec<Uuid> = new_yml
-                        .rows
-                        .iter()
-                        .flat_map(|row| row.items.iter())
-                        .map(|item| item.id)
-                        .collect();
+                    let metric_ids: Vec<Uuid> = new_yml.metric_ids().collect();
 
                     if !metric_ids.is_empty() {
                         match validate_metric_ids(&metric_ids).await {
@@ -1262,6 +861,31 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("cannot be empty"));
     }
 
+    #[test]
+    fn test_format_lint_diagnostics() {
+        let diagnostics = vec![LintDiagnostic {
+            rule: sql_analyzer::LintRule::UnguardedDivision,
+            severity: LintSeverity::Warning,
+            message: "Division by `o.orders` fails or returns an error when the divisor is zero"
+                .to_string(),
+            span: Some(sql_analyzer::SourceSpan {
+                start_line: 2,
+                start_column: 5,
+                end_line: 2,
+                end_column: 27,
+            }),
+            fix: Some(sql_analyzer::LintFix {
+                description: "Wrap the divisor in NULLIF".to_string(),
+                replacement: Some("o.revenue / NULLIF(o.orders, 0)".to_string()),
+            }),
+        }];
+
+        let formatted = format_lint_diagnostics(&diagnostics);
+
+        assert!(formatted.starts_with("- line 2, column 5: Division by `o.orders`"));
+        assert!(formatted.ends_with("Fix: Wrap the divisor in NULLIF (`o.revenue / NULLIF(o.orders, 0)`)"));
+    }
+
     #[test]
     fn test_apply_modifications_multiple_matches() {
         // Content with repeated text
@@ -1587,7 +1211,7 @@ async fn process_metric_file_update(
     };
 
     // Validate MetricYml structure
-    if let Err(e) = new_yml.validate() {
+    if let Err(e) = new_yml.validate(None) {
         let error = format!("Invalid metric structure: {}", e);
         return Err(anyhow::anyhow!(error));
     }
@@ -1595,10 +1219,34 @@ async fn process_metric_file_update(
     let mut results = Vec::new();
 
     // Check if SQL or metadata has changed
-    if file.content.sql != new_yml.sql {
+    if file.content.sql != new_yml.sql
+        || file.content.parameters != new_yml.parameters
+        || file.content.time_range != new_yml.time_range
+        || file.content.comparison != new_yml.comparison
+    {
+        // Parameterized SQL is validated with every parameter at its default
+        let sql = metric_query_sql(&new_yml, &HashMap::new(), data_source_id)
+            .await
+            .map_err(|e| anyhow!("Invalid parameters or time range: {}", e))?;
+
         // SQL changed or metadata missing, perform validation
-        match validate_sql(&new_yml.sql, data_source_id, user_id).await {
+        match validate_sql(&sql, data_source_id, user_id).await {
             Ok((message, validation_results, metadata, validated_ids)) => {
+                // Check the chart config against the columns the new SQL returns
+                if let Err(e) = new_yml.validate(metadata.as_ref()) {
+                    let error = format!("Invalid chart config: {}", e);
+                    results.push(ModificationResult {
+                        file_id: file.id,
+                        file_name: file.file_name.clone(),
+                        success: false,
+                        error: Some(error.clone()),
+                        modification_type: "validation".to_string(),
+                        timestamp: Utc::now(),
+                        duration,
+                    });
+                    return Err(anyhow::anyhow!(error));
+                }
+
                 // Update file record
                 file.content = new_yml.clone();
                 file.name = new_yml.name.clone();
@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use dataset_security::get_permissioned_datasets;
+use semantic_layer::{
+    compile::{compile_query, Dialect, MetricQuery},
+    models::{Model, SemanticLayerSpec},
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{agent::Agent, tools::ToolExecutor};
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompileMetricSqlParams {
+    pub dataset: String,
+    pub metrics: Vec<String>,
+    #[serde(default)]
+    pub dimensions: Vec<String>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompileMetricSqlOutput {
+    pub sql: String,
+}
+
+/// Compiles measures and metrics defined in a dataset's semantic model into
+/// SQL for the current data source, so the agent doesn't hand-write
+/// aggregates or date bucketing.
+pub struct CompileMetricSqlTool {
+    agent: Arc<Agent>,
+}
+
+impl CompileMetricSqlTool {
+    pub fn new(agent: Arc<Agent>) -> Self {
+        Self { agent }
+    }
+}
+
+#[async_trait]
+impl ToolExecutor for CompileMetricSqlTool {
+    type Output = CompileMetricSqlOutput;
+    type Params = CompileMetricSqlParams;
+
+    fn get_name(&self) -> String {
+        "compile_metric_sql".to_string()
+    }
+
+    async fn execute(&self, params: Self::Params, _tool_call_id: String) -> Result<Self::Output> {
+        let data_source_id = match self.agent.get_state_value("data_source_id").await {
+            Some(Value::String(id_str)) => Uuid::parse_str(&id_str)
+                .map_err(|e| anyhow!("Invalid data source ID format: {}", e))?,
+            Some(_) => bail!("Data source ID is not a string"),
+            None => bail!("Data source ID not found in agent state"),
+        };
+        let syntax = match self.agent.get_state_value("data_source_syntax").await {
+            Some(Value::String(syntax)) => syntax,
+            _ => "postgres".to_string(),
+        };
+        let dialect = Dialect::from_data_source_type(&syntax).unwrap_or(Dialect::Postgres);
+
+        let dataset = get_permissioned_datasets(&self.agent.get_user_id(), 0, 10000)
+            .await?
+            .into_iter()
+            .find(|d| {
+                d.data_source_id == data_source_id && d.name.eq_ignore_ascii_case(&params.dataset)
+            })
+            .ok_or_else(|| anyhow!("Dataset `{}` was not found or is not accessible", params.dataset))?;
+        let yml_content = dataset
+            .yml_content
+            .ok_or_else(|| anyhow!("Dataset `{}` has no semantic model", dataset.name))?;
+        let model: Model = serde_yaml::from_str(&yml_content)
+            .map_err(|e| anyhow!("Semantic model of `{}` could not be parsed: {}", dataset.name, e))?;
+
+        let query = MetricQuery {
+            model: model.name.clone(),
+            metrics: params.metrics,
+            dimensions: params.dimensions,
+            limit: params.limit,
+        };
+        let spec = SemanticLayerSpec {
+            models: vec![model],
+        };
+        let sql = compile_query(&spec, &query, dialect).map_err(|e| anyhow!("{}", e))?;
+
+        Ok(CompileMetricSqlOutput { sql })
+    }
+
+    async fn get_schema(&self) -> Value {
+        serde_json::json!({
+          "name": self.get_name(),
+          "description": "Generates SQL for measures and metrics defined in a dataset's semantic model, grouped by the requested dimensions. Prefer this over hand-writing aggregates, ratios or running totals when the dataset defines them. Time dimensions are bucketed with a grain suffix: `<dimension>__day`, `__week`, `__month`, `__quarter`, `__year`, or `__fiscal_quarter` / `__fiscal_year` when the dimension has a fiscal calendar. The returned SQL can be used as-is or extended (e.g. with filters) in a metric.",
+          "parameters": {
+            "type": "object",
+            "required": ["dataset", "metrics"],
+            "properties": {
+              "dataset": {
+                "type": "string",
+                "description": "Name of the dataset whose semantic model defines the metrics"
+              },
+              "metrics": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Names of measures or metrics to compute, e.g. [\"revenue\"]"
+              },
+              "dimensions": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Dimensions to group by, e.g. [\"region\", \"order_date__fiscal_quarter\"]"
+              },
+              "limit": {
+                "type": "integer",
+                "description": "Maximum number of rows to return"
+              }
+            },
+            "additionalProperties": false
+          }
+        })
+    }
+}
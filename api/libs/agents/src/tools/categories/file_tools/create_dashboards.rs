@@ -85,12 +85,7 @@ async fn process_dashboard_file(
     let dashboard_id = generate_deterministic_uuid(&tool_call_id, &file.name, "dashboard").unwrap();
 
     // Collect and validate metric IDs from rows
-    let metric_ids: Vec<Uuid> = dashboard_yml
-        .rows
-        .iter()
-        .flat_map(|row| row.items.iter())
-        .map(|item| item.id)
-        .collect();
+    let metric_ids: Vec<Uuid> = dashboard_yml.metric_ids().collect();
 
     if !metric_ids.is_empty() {
         match validate_metric_ids(&metric_ids).await {
@@ -240,12 +235,7 @@ impl ToolExecutor for CreateDashboardFilesTool {
                     
                     // Create associations between metrics and dashboards
                     for (i, dashboard_record) in dashboard_records.iter().enumerate() {
-                        let metric_ids: Vec<Uuid> = dashboard_record.content
-                            .rows
-                            .iter()
-                            .flat_map(|row| row.items.iter())
-                            .map(|item| item.id)
-                            .collect();
+                        let metric_ids: Vec<Uuid> = dashboard_record.content.metric_ids().collect();
                         
                         if !metric_ids.is_empty() {
                             // Create a Vec of MetricFileToDashboardFile objects for bulk insert
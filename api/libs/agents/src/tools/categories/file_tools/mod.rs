@@ -1,4 +1,5 @@
 pub mod common;
+pub mod compile_metric_sql;
 pub mod create_dashboards;
 pub mod create_metrics;
 pub mod file_types;
@@ -7,6 +8,7 @@ pub mod modify_dashboards;
 pub mod modify_metrics;
 pub mod search_data_catalog;
 
+pub use compile_metric_sql::CompileMetricSqlTool;
 pub use create_dashboards::CreateDashboardFilesTool;
 pub use create_metrics::CreateMetricFilesTool;
 // pub use filter_dashboards::FilterDashboardsTool;
@@ -93,12 +93,7 @@ async fn process_dashboard_file_update(
             );
 
             // Collect and validate metric IDs from rows
-            let metric_ids: Vec<Uuid> = new_yml
-                .rows
-                .iter()
-                .flat_map(|row| row.items.iter())
-                .map(|item| item.id)
-                .collect();
+            let metric_ids: Vec<Uuid> = new_yml.metric_ids().collect();
 
             if !metric_ids.is_empty() {
                 match validate_metric_ids(&metric_ids).await {
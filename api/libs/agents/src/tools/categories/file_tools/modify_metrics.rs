@@ -19,12 +19,11 @@ use serde_json::Value;
 use tracing::{debug, error, info};
 use uuid::Uuid;
 use chrono::Utc;
+use database::helpers::metric_sql::metric_query_sql;
 use std::collections::HashMap;
 
 use super::{
-    common::{
-        validate_sql, ModificationResult, ModifyFilesOutput, FailedFileModification,
-    },
+    common::{validate_sql, FailedFileModification, ModificationResult, ModifyFilesOutput},
     file_types::file::FileWithId,
     FileModificationTool,
 };
@@ -100,8 +99,12 @@ async fn process_metric_file_update(
                 "Successfully parsed and validated new metric content"
             );
 
-            // Check if SQL has changed to avoid unnecessary validation
-            let sql_changed = file.content.sql != new_yml.sql;
+            // Check if SQL has changed to avoid unnecessary validation. New
+            // parameter defaults or a new time range change the query that
+            // gets validated too.
+            let sql_changed = file.content.sql != new_yml.sql
+                || file.content.parameters != new_yml.parameters
+                || file.content.time_range != new_yml.time_range;
             
             // If SQL hasn't changed, we can use existing metadata and skip validation
             if !sql_changed && file.data_metadata.is_some() {
@@ -111,6 +114,21 @@ async fn process_metric_file_update(
                     "SQL unchanged, skipping validation"
                 );
                 
+                // The chart config may still have changed, so check it against the existing metadata
+                if let Err(e) = new_yml.validate(file.data_metadata.as_ref()) {
+                    let error = format!("Invalid chart config: {}", e);
+                    results.push(ModificationResult {
+                        file_id: file.id,
+                        file_name: file.name.clone(),
+                        success: false,
+                        error: Some(error.clone()),
+                        modification_type: "chart_config_validation".to_string(),
+                        timestamp: Utc::now(),
+                        duration,
+                    });
+                    return Err(anyhow::anyhow!(error));
+                }
+
                 // Update file record
                 file.content = new_yml.clone();
                 file.name = new_yml.name.clone();
@@ -153,9 +171,41 @@ async fn process_metric_file_update(
                 );
             }
 
+            // Parameterized SQL is validated with every parameter at its default
+            let sql = match metric_query_sql(&new_yml, &HashMap::new(), &data_source_id).await {
+                Ok(sql) => sql,
+                Err(e) => {
+                    let error = format!("Invalid parameters or time range: {}", e);
+                    results.push(ModificationResult {
+                        file_id: file.id,
+                        file_name: file.name.clone(),
+                        success: false,
+                        error: Some(error.clone()),
+                        modification_type: "validation".to_string(),
+                        timestamp: Utc::now(),
+                        duration,
+                    });
+                    return Err(anyhow::anyhow!(error));
+                }
+            };
 
-            match validate_sql(&new_yml.sql, &data_source_id, user_id).await {
+            match validate_sql(&sql, &data_source_id, user_id).await {
                 Ok((message, validation_results, metadata, validated_dataset_ids)) => {
+                    // Check the chart config against the columns the new SQL returns
+                    if let Err(e) = new_yml.validate(metadata.as_ref()) {
+                        let error = format!("Invalid chart config: {}", e);
+                        results.push(ModificationResult {
+                            file_id: file.id,
+                            file_name: file.name.clone(),
+                            success: false,
+                            error: Some(error.clone()),
+                            modification_type: "chart_config_validation".to_string(),
+                            timestamp: Utc::now(),
+                            duration,
+                        });
+                        return Err(anyhow::anyhow!(error));
+                    }
+
                     // Update file record
                     file.content = new_yml.clone();
                     file.name = new_yml.name.clone();
@@ -550,7 +600,7 @@ async fn get_modify_metrics_yml_description() -> String {
 async fn get_metric_yml_description() -> String {
     if env::var("USE_BRAINTRUST_PROMPTS").is_err() {
         // Revert to just returning the schema string plus basic instruction
-        return format!("The complete new YAML content for the metric, following the metric schema specification. This will replace the entire existing content of the file. Ensure all required fields are present and properly formatted according to the schema. When modifying multiple metrics, provide each in the 'files' array. **Prefer bulk modifications.**\n\n{}", METRIC_YML_SCHEMA);
+        return format!("The complete new YAML content for the metric, following the metric schema specification. This will replace the entire existing content of the file. Ensure all required fields are present and properly formatted according to the schema. When modifying multiple metrics, provide each in the 'files' array. **Prefer bulk modifications.**\n\n{}", *METRIC_YML_SCHEMA);
     }
 
     let client = BraintrustClient::new(None, "96af8b2b-cf3c-494f-9092-44eb3d5b96ff").unwrap();
@@ -559,7 +609,7 @@ async fn get_metric_yml_description() -> String {
         Err(e) => {
             eprintln!("Failed to get prompt system message: {}", e);
             // Revert to just returning the schema string plus basic instruction on error
-            format!("The complete new YAML content for the metric, following the metric schema specification. This will replace the entire existing content of the file. Ensure all required fields are present and properly formatted according to the schema. When modifying multiple metrics, provide each in the 'files' array. **Prefer bulk modifications.**\n\n{}", METRIC_YML_SCHEMA)
+            format!("The complete new YAML content for the metric, following the metric schema specification. This will replace the entire existing content of the file. Ensure all required fields are present and properly formatted according to the schema. When modifying multiple metrics, provide each in the 'files' array. **Prefer bulk modifications.**\n\n{}", *METRIC_YML_SCHEMA)
         }
     }
 }
@@ -0,0 +1,174 @@
+//! Builds the SQL a metric runs: parameters bound for the data source's
+//! dialect, and its time range or comparison period applied as a filter.
+//! Shared by metric creation, metric updates, data fetching and deploy
+//! impact checks.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{NaiveDate, Utc};
+use chrono_tz::Tz;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use serde_json::Value;
+use sql_analyzer::{
+    apply_column_filters, bind_parameters, ColumnFilter, ColumnFilterCondition, FilterValue,
+    ParameterValue, WarehouseDialect,
+};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::enums::DataSourceType;
+use crate::pool::get_pg_pool;
+use crate::schema::{data_sources, organizations};
+use crate::types::metric_yml::{MetricParameter, MetricParameterType};
+use crate::types::MetricYml;
+
+/// Binds a metric's `{{ name }}` parameters into its SQL, written for the
+/// dialect of the data source it runs on. `values` overrides the defaults;
+/// SQL without parameters is returned unchanged.
+pub async fn bind_metric_parameters(
+    metric_yml: &MetricYml,
+    values: &HashMap<String, Value>,
+    data_source_id: &Uuid,
+) -> Result<String> {
+    if metric_yml.parameters.is_empty() {
+        if let Some(name) = values.keys().next() {
+            bail!("Metric has no parameter '{}'", name);
+        }
+        return Ok(metric_yml.sql.clone());
+    }
+
+    let values = metric_yml
+        .resolve_parameters(values)?
+        .into_iter()
+        .map(|(parameter, value)| Ok((parameter.name.clone(), parameter_value(parameter, value)?)))
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let dialect = data_source_dialect(data_source_id).await?;
+    Ok(bind_parameters(metric_yml.sql.clone(), values, dialect).await?)
+}
+
+/// The SQL dialect of the warehouse a data source connects to
+pub async fn data_source_dialect(data_source_id: &Uuid) -> Result<WarehouseDialect> {
+    let mut conn = get_pg_pool().get().await?;
+    let data_source_type = data_sources::table
+        .filter(data_sources::id.eq(data_source_id))
+        .select(data_sources::type_)
+        .first::<DataSourceType>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to find data source {}: {}", data_source_id, e))?;
+    WarehouseDialect::from_data_source_type(data_source_type.to_str()).ok_or_else(|| {
+        anyhow!(
+            "Unsupported data source type: {}",
+            data_source_type.to_str()
+        )
+    })
+}
+
+/// Builds the SQL a metric runs: its parameters bound as in
+/// [`bind_metric_parameters`], then its rows limited to its time range.
+/// Relative ranges resolve against today's date in the time zone of the
+/// organization that owns the data source.
+pub async fn metric_query_sql(
+    metric_yml: &MetricYml,
+    values: &HashMap<String, Value>,
+    data_source_id: &Uuid,
+) -> Result<String> {
+    let sql = bind_metric_parameters(metric_yml, values, data_source_id).await?;
+    let Some(time_range) = &metric_yml.time_range else {
+        return Ok(sql);
+    };
+
+    let today = organization_today(data_source_id).await?;
+    let (start, end) = time_range.resolve(today)?;
+    let dialect = data_source_dialect(data_source_id).await?;
+    let filter = date_range_filter(&time_range.column, start, end);
+    apply_column_filters(sql, vec![filter], dialect)
+        .await
+        .map_err(|e| anyhow!("Failed to apply time range: {}", e))
+}
+
+/// The SQL for a metric's comparison period and the dates it covers, with
+/// `end` exclusive
+#[derive(Debug, Clone)]
+pub struct ComparisonQuery {
+    pub sql: String,
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+/// Builds the SQL for a metric's comparison: the same query as
+/// [`metric_query_sql`], with the time range moved back to the comparison
+/// period. Returns `None` for metrics without a comparison.
+pub async fn metric_comparison_sql(
+    metric_yml: &MetricYml,
+    values: &HashMap<String, Value>,
+    data_source_id: &Uuid,
+) -> Result<Option<ComparisonQuery>> {
+    let (Some(comparison), Some(time_range)) = (&metric_yml.comparison, &metric_yml.time_range)
+    else {
+        return Ok(None);
+    };
+
+    let sql = bind_metric_parameters(metric_yml, values, data_source_id).await?;
+    let today = organization_today(data_source_id).await?;
+    let (start, end) = comparison.resolve(time_range, today)?;
+    let dialect = data_source_dialect(data_source_id).await?;
+    let filter = date_range_filter(&time_range.column, start, end);
+    let sql = apply_column_filters(sql, vec![filter], dialect)
+        .await
+        .map_err(|e| anyhow!("Failed to apply comparison period: {}", e))?;
+    Ok(Some(ComparisonQuery { sql, start, end }))
+}
+
+/// Today's date in the time zone of the organization that owns the data source
+async fn organization_today(data_source_id: &Uuid) -> Result<NaiveDate> {
+    let mut conn = get_pg_pool().get().await?;
+    let timezone = data_sources::table
+        .inner_join(organizations::table)
+        .filter(data_sources::id.eq(data_source_id))
+        .select(organizations::timezone)
+        .first::<String>(&mut conn)
+        .await
+        .map_err(|e| anyhow!("Failed to find data source {}: {}", data_source_id, e))?;
+    let timezone = timezone.parse::<Tz>().unwrap_or_else(|_| {
+        warn!("Unknown organization timezone '{}', using UTC", timezone);
+        Tz::UTC
+    });
+    Ok(Utc::now().with_timezone(&timezone).date_naive())
+}
+
+fn date_range_filter(
+    column: &str,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+) -> ColumnFilter {
+    let as_value = |date: NaiveDate| FilterValue::String(date.format("%Y-%m-%d").to_string());
+    ColumnFilter {
+        column: column.to_string(),
+        condition: ColumnFilterCondition::Range {
+            min: start.map(as_value),
+            max: end.map(as_value),
+            max_inclusive: false,
+        },
+    }
+}
+
+fn parameter_value(parameter: &MetricParameter, value: Value) -> Result<ParameterValue> {
+    let converted = match parameter.param_type {
+        MetricParameterType::String => value
+            .as_str()
+            .map(|s| ParameterValue::String(s.to_string())),
+        MetricParameterType::Number => value.as_f64().map(ParameterValue::Number),
+        MetricParameterType::Date => value.as_str().map(|s| ParameterValue::Date(s.to_string())),
+        MetricParameterType::Boolean => value.as_bool().map(ParameterValue::Boolean),
+    };
+    converted.ok_or_else(|| {
+        anyhow!(
+            "Invalid value for parameter '{}': {}",
+            parameter.name,
+            value
+        )
+    })
+}
@@ -1,6 +1,7 @@
 pub mod collections;
 pub mod dashboard_files;
 pub mod metric_files;
+pub mod metric_sql;
 pub mod chats;
 pub mod organization;
 pub mod test_utils;
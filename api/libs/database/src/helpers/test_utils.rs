@@ -72,7 +72,11 @@ impl TestDb {
             description: Some("Test metric description".to_string()),
             sql: "SELECT * FROM test".to_string(),
             time_frame: "last 30 days".to_string(),
+            time_range: None,
+            comparison: None,
             chart_config: create_default_chart_config(),
+            parameters: Vec::new(),
+            drills: Vec::new(),
         };
 
         let metric_file = MetricFile {
@@ -108,6 +112,8 @@ impl TestDb {
             name: "Test Dashboard".to_string(),
             description: Some("Test dashboard description".to_string()),
             rows: Vec::new(),
+            tabs: Vec::new(),
+            filters: Vec::new(),
         };
 
         let dashboard_file = DashboardFile {
@@ -40,6 +40,20 @@ pub struct DashboardFile {
     pub public_password: Option<String>,
 }
 
+#[derive(Queryable, Insertable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = dashboard_templates)]
+pub struct DashboardTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub content: DashboardTemplateContent,
+    pub organization_id: Uuid,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Queryable, Insertable, Identifiable, Associations, Debug, Clone, Serialize)]
 #[diesel(belongs_to(User, foreign_key = created_by))]
 #[diesel(table_name = messages)]
@@ -340,6 +354,7 @@ pub struct Organization {
     pub updated_at: DateTime<Utc>,
     pub deleted_at: Option<DateTime<Utc>>,
     pub payment_required: bool,
+    pub timezone: String,
 }
 
 #[derive(
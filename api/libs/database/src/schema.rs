@@ -152,6 +152,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    dashboard_templates (id) {
+        id -> Uuid,
+        name -> Varchar,
+        description -> Nullable<Text>,
+        content -> Jsonb,
+        organization_id -> Uuid,
+        created_by -> Uuid,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+        deleted_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     dashboard_versions (id) {
         id -> Uuid,
@@ -444,6 +458,7 @@ diesel::table! {
         updated_at -> Timestamptz,
         deleted_at -> Nullable<Timestamptz>,
         payment_required -> Bool,
+        timezone -> Text,
     }
 }
 
@@ -655,6 +670,8 @@ diesel::joinable!(api_keys -> organizations (organization_id));
 diesel::joinable!(api_keys -> users (owner_id));
 diesel::joinable!(chats -> organizations (organization_id));
 diesel::joinable!(collections -> organizations (organization_id));
+diesel::joinable!(dashboard_templates -> organizations (organization_id));
+diesel::joinable!(dashboard_templates -> users (created_by));
 diesel::joinable!(dashboard_versions -> dashboards (dashboard_id));
 diesel::joinable!(dashboards -> organizations (organization_id));
 diesel::joinable!(data_sources -> organizations (organization_id));
@@ -704,6 +721,7 @@ diesel::allow_tables_to_appear_in_same_query!(
     collections,
     collections_to_assets,
     dashboard_files,
+    dashboard_templates,
     dashboard_versions,
     dashboards,
     data_sources,
@@ -0,0 +1,385 @@
+use anyhow::{anyhow, Result};
+use diesel::{
+    deserialize::FromSql,
+    pg::Pg,
+    serialize::{IsNull, Output, ToSql},
+    sql_types::Jsonb,
+    AsExpression, FromSqlRow,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use uuid::Uuid;
+
+use crate::types::{DashboardYml, DrillTarget, MetricYml, RowItem};
+
+/// A dashboard and copies of its metrics, saved with placeholders that are
+/// filled in each time the template is instantiated.
+#[derive(Debug, Serialize, Deserialize, Clone, FromSqlRow, AsExpression)]
+#[diesel(sql_type = Jsonb)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardTemplateContent {
+    /// Id of the dashboard the template was saved from. Drills pointing at
+    /// it are pointed at each new dashboard instead.
+    pub dashboard_id: Uuid,
+
+    pub dashboard: DashboardYml,
+
+    pub metrics: Vec<TemplateMetric>,
+
+    pub placeholders: Vec<TemplatePlaceholder>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateMetric {
+    /// Id of the metric the copy was taken from, as the dashboard and other
+    /// metrics' drills refer to it
+    pub id: Uuid,
+
+    pub metric: MetricYml,
+
+    /// Name of the data source placeholder the metric runs on
+    pub data_source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplatePlaceholder {
+    /// Name values are given under, e.g. `warehouse` or `sales_schema`
+    pub name: String,
+
+    pub kind: TemplatePlaceholderKind,
+
+    /// What the template was saved with: a data source id, or the schema or
+    /// table name the metrics' SQL uses. Placeholders left without a value
+    /// keep it.
+    pub default: String,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum TemplatePlaceholderKind {
+    DataSource,
+    Schema,
+    Table,
+}
+
+impl DashboardTemplateContent {
+    /// Fills in every placeholder, from `values` or its default. Values for
+    /// placeholders the template doesn't have are rejected.
+    pub fn resolve(&self, values: &HashMap<String, String>) -> Result<HashMap<String, String>> {
+        let unknown: Vec<&str> = values
+            .keys()
+            .filter(|name| !self.placeholders.iter().any(|p| &p.name == *name))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            return Err(anyhow!(
+                "Unknown template placeholders: {}",
+                unknown.join(", ")
+            ));
+        }
+
+        self.placeholders
+            .iter()
+            .map(|placeholder| {
+                let value = values
+                    .get(&placeholder.name)
+                    .map(|value| value.trim())
+                    .unwrap_or(&placeholder.default);
+                if value.is_empty() {
+                    return Err(anyhow!("Placeholder '{}' needs a value", placeholder.name));
+                }
+                Ok((placeholder.name.clone(), value.to_string()))
+            })
+            .collect()
+    }
+
+    /// The `default -> value` renames for placeholders of `kind` that were
+    /// given a different value
+    pub fn renames(
+        &self,
+        kind: TemplatePlaceholderKind,
+        values: &HashMap<String, String>,
+    ) -> HashMap<String, String> {
+        self.placeholders
+            .iter()
+            .filter(|placeholder| placeholder.kind == kind)
+            .filter_map(|placeholder| {
+                let value = values.get(&placeholder.name)?;
+                (value != &placeholder.default)
+                    .then(|| (placeholder.default.clone(), value.clone()))
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        let mut names = HashSet::new();
+        for placeholder in &self.placeholders {
+            if !is_placeholder_name(&placeholder.name) {
+                problems.push(format!(
+                    "Placeholder name '{}' must start with a letter or underscore and hold only letters, digits and underscores",
+                    placeholder.name
+                ));
+            }
+            if !names.insert(placeholder.name.as_str()) {
+                problems.push(format!(
+                    "Placeholder '{}' is defined twice",
+                    placeholder.name
+                ));
+            }
+        }
+
+        for metric in &self.metrics {
+            let data_source = self
+                .placeholders
+                .iter()
+                .find(|placeholder| placeholder.name == metric.data_source);
+            if !matches!(data_source, Some(p) if p.kind == TemplatePlaceholderKind::DataSource) {
+                problems.push(format!(
+                    "Metric '{}' runs on '{}', which isn't a data source placeholder",
+                    metric.metric.name, metric.data_source
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("{}", problems.join("\n")))
+        }
+    }
+
+    /// The dashboard and metric copies with template metric ids swapped for
+    /// `metric_ids`, and drills to the source dashboard pointed at
+    /// `dashboard_id`. References to metrics outside the template are kept.
+    pub fn with_ids(
+        &self,
+        metric_ids: &HashMap<Uuid, Uuid>,
+        dashboard_id: Uuid,
+    ) -> (DashboardYml, Vec<MetricYml>) {
+        let remap = |id: &Uuid| *metric_ids.get(id).unwrap_or(id);
+
+        let mut dashboard = self.dashboard.clone();
+        let rows = dashboard.rows.iter_mut().chain(
+            dashboard
+                .tabs
+                .iter_mut()
+                .flat_map(|tab| tab.rows.iter_mut()),
+        );
+        for row in rows {
+            for item in &mut row.items {
+                if let RowItem::Metric { id } = item {
+                    *id = remap(id);
+                }
+            }
+        }
+        for filter in &mut dashboard.filters {
+            filter.columns = filter
+                .columns
+                .iter()
+                .map(|(id, column)| (remap(id), column.clone()))
+                .collect();
+        }
+
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|template_metric| {
+                let mut metric = template_metric.metric.clone();
+                for drill in &mut metric.drills {
+                    match &mut drill.target {
+                        DrillTarget::Metric { id, .. } => *id = remap(id),
+                        DrillTarget::Dashboard { id, .. } if *id == self.dashboard_id => {
+                            *id = dashboard_id
+                        }
+                        DrillTarget::Dashboard { .. } => {}
+                    }
+                }
+                metric
+            })
+            .collect();
+
+        (dashboard, metrics)
+    }
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl FromSql<Jsonb, Pg> for DashboardTemplateContent {
+    fn from_sql(bytes: diesel::pg::PgValue) -> diesel::deserialize::Result<Self> {
+        let value = <serde_json::Value as FromSql<Jsonb, Pg>>::from_sql(bytes)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl ToSql<Jsonb, Pg> for DashboardTemplateContent {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> diesel::serialize::Result {
+        out.write_all(&[1])?;
+        out.write_all(&serde_json::to_vec(self)?)?;
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> DashboardTemplateContent {
+        let metric_id = Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap();
+        let other_id = Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap();
+        let dashboard_id = Uuid::parse_str("00000000-0000-0000-0000-0000000000d1").unwrap();
+
+        let metric = MetricYml::parse(&format!(
+            r#"
+name: MRR
+timeFrame: Last 12 months
+sql: SELECT month, mrr FROM finance.subscriptions
+drills:
+  - columnId: month
+    type: metric
+    id: {other_id}
+    parameter: month
+  - columnId: month
+    type: dashboard
+    id: {dashboard_id}
+    filter: period
+chartConfig:
+  selectedChartType: metric
+  metricColumnId: mrr
+  columnLabelFormats:
+    month:
+      columnType: date
+      style: date
+    mrr:
+      columnType: number
+      style: currency
+"#
+        ))
+        .unwrap();
+
+        let dashboard = DashboardYml::parse(&format!(
+            r#"
+name: SaaS KPIs
+rows:
+  - items:
+      - id: {metric_id}
+      - id: {other_id}
+    columnSizes: [6, 6]
+filters:
+  - id: period
+    name: Period
+    type: dateRange
+    columns:
+      {metric_id}: month
+"#
+        ))
+        .unwrap();
+
+        DashboardTemplateContent {
+            dashboard_id,
+            dashboard,
+            metrics: vec![
+                TemplateMetric {
+                    id: metric_id,
+                    metric: metric.clone(),
+                    data_source: "warehouse".to_string(),
+                },
+                TemplateMetric {
+                    id: other_id,
+                    metric,
+                    data_source: "warehouse".to_string(),
+                },
+            ],
+            placeholders: vec![
+                TemplatePlaceholder {
+                    name: "warehouse".to_string(),
+                    kind: TemplatePlaceholderKind::DataSource,
+                    default: Uuid::nil().to_string(),
+                    description: None,
+                },
+                TemplatePlaceholder {
+                    name: "finance_schema".to_string(),
+                    kind: TemplatePlaceholderKind::Schema,
+                    default: "finance".to_string(),
+                    description: Some("Schema holding subscriptions".to_string()),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_resolve_fills_defaults_and_rejects_unknown_placeholders() {
+        let template = template();
+        assert!(template.validate().is_ok());
+
+        let values = HashMap::from([("finance_schema".to_string(), "finance_eu".to_string())]);
+        let resolved = template.resolve(&values).unwrap();
+        assert_eq!(resolved["warehouse"], Uuid::nil().to_string());
+        assert_eq!(resolved["finance_schema"], "finance_eu");
+        assert_eq!(
+            template.renames(TemplatePlaceholderKind::Schema, &resolved),
+            HashMap::from([("finance".to_string(), "finance_eu".to_string())])
+        );
+
+        let unknown = HashMap::from([("region".to_string(), "eu".to_string())]);
+        let err = template.resolve(&unknown).unwrap_err().to_string();
+        assert!(
+            err.contains("Unknown template placeholders: region"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_with_ids_points_references_at_the_copies() {
+        let template = template();
+        let metric_ids: HashMap<Uuid, Uuid> = template
+            .metrics
+            .iter()
+            .map(|metric| (metric.id, Uuid::new_v4()))
+            .collect();
+        let dashboard_id = Uuid::new_v4();
+
+        let (dashboard, metrics) = template.with_ids(&metric_ids, dashboard_id);
+
+        let row_ids: Vec<Uuid> = dashboard.rows[0]
+            .items
+            .iter()
+            .filter_map(RowItem::metric_id)
+            .collect();
+        assert_eq!(
+            row_ids,
+            vec![
+                metric_ids[&template.metrics[0].id],
+                metric_ids[&template.metrics[1].id]
+            ]
+        );
+        assert!(dashboard.filters[0]
+            .columns
+            .contains_key(&metric_ids[&template.metrics[0].id]));
+        assert_eq!(
+            metrics[0].drills[0].target,
+            DrillTarget::Metric {
+                id: metric_ids[&template.metrics[1].id],
+                parameter: "month".to_string()
+            }
+        );
+        assert_eq!(
+            metrics[0].drills[1].target,
+            DrillTarget::Dashboard {
+                id: dashboard_id,
+                filter: "period".to_string()
+            }
+        );
+    }
+}
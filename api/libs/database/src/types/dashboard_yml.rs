@@ -1,4 +1,5 @@
 use anyhow::Result;
+use indexmap::IndexMap;
 use diesel::{
     deserialize::FromSql,
     pg::Pg,
@@ -6,18 +7,15 @@ use diesel::{
     sql_types::Jsonb,
     AsExpression, FromSqlRow,
 };
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io::Write;
 use uuid::Uuid;
-use regex::Regex;
-use lazy_static::lazy_static;
 
-lazy_static! {
-    static ref DASHBOARD_NAME_DESC_RE: Regex = Regex::new(r#"^(\s*(?:name|description):\s*)(.*)$"#).unwrap();
-}
+use crate::types::yml_errors::{read_yml, YmlError, YmlErrors};
 
-#[derive(Debug, Serialize, Deserialize, Clone, FromSqlRow, AsExpression)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, FromSqlRow, AsExpression)]
 #[diesel(sql_type = Jsonb)]
 #[serde(rename_all = "camelCase")]
 pub struct DashboardYml {
@@ -27,104 +25,270 @@ pub struct DashboardYml {
     #[serde(alias = "description")]
     pub description: Option<String>,
     
-    #[serde(alias = "rows")]
+    #[serde(default, alias = "rows")]
     pub rows: Vec<Row>,
+
+    /// Named tabs, each with its own rows. A dashboard with tabs keeps no
+    /// rows of its own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<DashboardTab>,
+
+    /// Filters viewers can apply to every metric on the dashboard at once
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filters: Vec<DashboardFilter>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardTab {
+    /// Stable id the tab is requested by, e.g. `overview`
+    pub id: String,
+
+    pub name: String,
+
+    #[serde(default)]
+    pub rows: Vec<Row>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Row {
+    #[serde(deserialize_with = "deserialize_row_items")]
+    #[schemars(with = "Vec<RowItemSchema>", length(min = 1, max = 4))]
     pub items: Vec<RowItem>, // max number of items in a row is 4, min is 1
     
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "row_height")]
+    #[schemars(range(min = 320, max = 550))]
     pub row_height: Option<u32>, // max is 550, min is 320
     
     #[serde(alias = "column_sizes")]
+    #[schemars(length(min = 1, max = 4))]
     pub column_sizes: Vec<u32>, // sum of elements must be exactly 12, min size is 3
     
-    #[serde(alias = "id")]
-    pub id: u32, // incremental id for rows
+    #[serde(default, alias = "id")]
+    pub id: u32, // incremental id for rows, assigned when left out
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RowItem {
+    /// A metric, by its id
+    Metric { id: Uuid },
+
+    /// Markdown text, for headings, notes and links
+    Markdown { content: String },
+
+    /// Divider that starts a new section, with an optional heading. It
+    /// takes up a whole row. The section runs to the next divider.
+    Divider {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+
+        /// Viewers can fold the section away
+        #[serde(default, skip_serializing_if = "is_false")]
+        collapsible: bool,
+
+        /// The section starts folded, and its metrics load once opened
+        #[serde(default, skip_serializing_if = "is_false")]
+        collapsed: bool,
+    },
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl RowItem {
+    pub fn metric_id(&self) -> Option<Uuid> {
+        match self {
+            RowItem::Metric { id } => Some(*id),
+            RowItem::Markdown { .. } | RowItem::Divider { .. } => None,
+        }
+    }
+}
+
+/// Deserializes row items, reading items without a `type` as metric tiles.
+/// Dashboards saved before tiles were typed only hold `{ id }` metric
+/// references.
+pub fn deserialize_row_items<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::de::DeserializeOwned,
+{
+    Vec::<Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|mut item| {
+            if let Value::Object(fields) = &mut item {
+                fields
+                    .entry("type")
+                    .or_insert_with(|| Value::String("metric".to_string()));
+            }
+            serde_json::from_value(item).map_err(serde::de::Error::custom)
+        })
+        .collect()
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Row items as `deserialize_row_items` reads them, for the JSON Schema:
+/// typed items, or metric references without a `type`.
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)]
+enum RowItemSchema {
+    Typed(RowItem),
+    UntypedMetric { id: Uuid },
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardFilter {
+    /// Stable id that filter values are submitted under
+    pub id: String,
+
+    pub name: String,
+
+    #[serde(rename = "type")]
+    pub filter_type: DashboardFilterType,
+
+    /// Choices offered by select and multi-select filters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<String>,
+
+    /// The column each metric is filtered on, keyed by metric id. Metrics
+    /// without an entry are left unfiltered.
+    #[schemars(length(min = 1))]
+    pub columns: IndexMap<Uuid, String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
-pub struct RowItem {
-    // This id is the id of the metric or item reference that goes here in the dashboard.
-    #[serde(alias = "id")]
-    pub id: Uuid,
+pub enum DashboardFilterType {
+    DateRange,
+    Select,
+    MultiSelect,
+    NumberRange,
 }
 
 impl DashboardYml {
     pub fn new(yml_content: String) -> Result<Self> {
-        let processed_yml_content = yml_content
-            .lines()
-            .map(|line| {
-                if let Some(captures) = DASHBOARD_NAME_DESC_RE.captures(line) {
-                    let key_part = captures.get(1).map_or("", |m| m.as_str());
-                    let value_part = captures.get(2).map_or("", |m| m.as_str());
-                    let sanitized_value = value_part.replace(':', ""); 
-                    format!("{}{}", key_part, sanitized_value)
-                } else {
-                    line.to_string()
-                }
-            })
-            .collect::<Vec<String>>()
-            .join("\n");
+        Ok(Self::parse(&yml_content)?)
+    }
 
-        let mut file: DashboardYml = match serde_yaml::from_str(&processed_yml_content) {
-            Ok(file) => file,
-            Err(e) => return Err(anyhow::anyhow!("Error parsing YAML: {}", e)),
-        };
+    /// Reads a dashboard file as written, collecting every problem with its
+    /// line, column and path. Parsing stops at a syntax or schema error;
+    /// otherwise every validation problem is reported.
+    pub fn parse(yml_content: &str) -> std::result::Result<Self, YmlErrors> {
+        let (mut file, positions) = read_yml::<DashboardYml>(yml_content, |_| {}, |_| None)?;
 
         if file.name.is_empty() {
             file.name = String::from("New Dashboard");
         }
 
-        for (index, row) in file.rows.iter_mut().enumerate() {
-            if row.id == 0 {
-                row.id = (index + 1) as u32;
-            }
-        }
+        file.assign_row_ids();
 
-        match file.validate() {
-            Ok(_) => Ok(file),
-            Err(e) => Err(anyhow::anyhow!("Error compiling file: {}", e)),
+        let errors: Vec<YmlError> = file
+            .problems()
+            .iter()
+            .map(|(path, problem)| positions.problem(path, problem))
+            .collect();
+        if errors.is_empty() {
+            Ok(file)
+        } else {
+            Err(YmlErrors(errors))
         }
     }
 
+    /// Checks rows, tabs and filters, reporting every problem one per line
     pub fn validate(&self) -> Result<()> {
+        let problems = self.problems();
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        let mut message = format!("{} problem(s) in dashboard:", problems.len());
+        for (_, problem) in &problems {
+            message.push_str("\n- ");
+            message.push_str(problem);
+        }
+        Err(anyhow::anyhow!(message))
+    }
+
+    /// Every problem validation finds, with the path of the part of the file
+    /// it is about
+    fn problems(&self) -> Vec<(String, String)> {
+        let mut problems = Vec::new();
+
         if self.name.is_empty() {
-            return Err(anyhow::anyhow!("Dashboard file name is required"));
+            problems.push((
+                "name".to_string(),
+                "Dashboard file name is required".to_string(),
+            ));
         }
 
-        for row in &self.rows {
+        let mut row_ids = std::collections::HashSet::new();
+        for (path, row) in self.rows_with_paths() {
+            let mut problem = |message: String| problems.push((path.clone(), message));
+
+            if !row_ids.insert(row.id) {
+                problem(format!("Duplicate row id {}", row.id));
+            }
+
             if let Some(row_height) = row.row_height {
                 if !(320..=550).contains(&row_height) {
-                    return Err(anyhow::anyhow!(
-                        "Row height must be between 320 and 550, got {}",
+                    problem(format!(
+                        "rowHeight: Row height must be between 320 and 550, got {}",
                         row_height
                     ));
                 }
             }
 
             if row.items.is_empty() || row.items.len() > 4 {
-                return Err(anyhow::anyhow!(
-                    "Number of items in row must be between 1 and 4, got {}",
+                problem(format!(
+                    "items: Number of items in row must be between 1 and 4, got {}",
                     row.items.len()
                 ));
             }
 
+            for (index, item) in row.items.iter().enumerate() {
+                match item {
+                    RowItem::Metric { .. } => {}
+                    RowItem::Markdown { content } => {
+                        if content.trim().is_empty() {
+                            problem(format!(
+                                "items[{}]: Markdown items must have content (row {})",
+                                index, row.id
+                            ));
+                        }
+                    }
+                    RowItem::Divider {
+                        collapsible,
+                        collapsed,
+                        ..
+                    } => {
+                        if row.items.len() > 1 {
+                            problem(format!(
+                                "items[{}]: A divider must be the only item in its row (row {})",
+                                index, row.id
+                            ));
+                        }
+                        if *collapsed && !*collapsible {
+                            problem(format!(
+                                "items[{}]: Only a collapsible divider can start collapsed (row {})",
+                                index, row.id
+                            ));
+                        }
+                    }
+                }
+            }
+
             if row.column_sizes.is_empty() || row.column_sizes.len() > 4 {
-                return Err(anyhow::anyhow!(
-                    "Number of column sizes must be between 1 and 4, got {}",
+                problem(format!(
+                    "columnSizes: Number of column sizes must be between 1 and 4, got {}",
                     row.column_sizes.len()
                 ));
-            }
-
-            if row.column_sizes.len() != row.items.len() {
-                return Err(anyhow::anyhow!(
-                    "Number of column sizes ({}) must match number of items ({})",
+            } else if row.column_sizes.len() != row.items.len() {
+                problem(format!(
+                    "columnSizes: Number of column sizes ({}) must match number of items ({})",
                     row.column_sizes.len(),
                     row.items.len()
                 ));
@@ -132,23 +296,172 @@ impl DashboardYml {
 
             let sum: u32 = row.column_sizes.iter().sum();
             if sum != 12 {
-                return Err(anyhow::anyhow!(
-                    "Sum of column sizes must be exactly 12, got {}",
+                problem(format!(
+                    "columnSizes: Sum of column sizes must be exactly 12, got {}",
                     sum
                 ));
             }
 
             for &size in &row.column_sizes {
                 if size < 3 {
-                    return Err(anyhow::anyhow!(
-                        "Each column size must be at least 3, got {}",
+                    problem(format!(
+                        "columnSizes: Each column size must be at least 3, got {}",
                         size
                     ));
                 }
             }
         }
 
-        Ok(())
+        problems.extend(self.tab_problems());
+        problems.extend(self.filter_problems());
+        problems
+    }
+
+    fn tab_problems(&self) -> Vec<(String, String)> {
+        let mut problems = Vec::new();
+        if self.tabs.is_empty() {
+            return problems;
+        }
+        if !self.rows.is_empty() {
+            problems.push((
+                "rows".to_string(),
+                "A dashboard with tabs must place all of its rows in tabs".to_string(),
+            ));
+        }
+
+        let mut seen_ids = std::collections::HashSet::new();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            let path = format!("tabs[{}]", index);
+            if tab.id.is_empty() {
+                problems.push((path.clone(), format!("Tab '{}' must have an id", tab.name)));
+            } else if !seen_ids.insert(tab.id.as_str()) {
+                problems.push((path.clone(), format!("Duplicate tab id '{}'", tab.id)));
+            }
+            if tab.name.trim().is_empty() {
+                problems.push((path, format!("Tab '{}' must have a name", tab.id)));
+            }
+        }
+
+        problems
+    }
+
+    fn filter_problems(&self) -> Vec<(String, String)> {
+        let mut problems = Vec::new();
+        let mut seen_ids = std::collections::HashSet::new();
+
+        for (index, filter) in self.filters.iter().enumerate() {
+            let path = format!("filters[{}]", index);
+            if filter.id.is_empty() {
+                problems.push((
+                    path.clone(),
+                    format!("Filter '{}' must have an id", filter.name),
+                ));
+            } else if !seen_ids.insert(filter.id.as_str()) {
+                problems.push((path.clone(), format!("Duplicate filter id '{}'", filter.id)));
+            }
+
+            let takes_options = matches!(
+                filter.filter_type,
+                DashboardFilterType::Select | DashboardFilterType::MultiSelect
+            );
+            if !takes_options && !filter.options.is_empty() {
+                problems.push((
+                    path.clone(),
+                    format!(
+                        "options: Filter '{}' has options, but only select filters can",
+                        filter.id
+                    ),
+                ));
+            }
+
+            if filter.columns.is_empty() {
+                problems.push((
+                    path.clone(),
+                    format!(
+                        "columns: Filter '{}' must bind a column for at least one metric",
+                        filter.id
+                    ),
+                ));
+            }
+
+            for (metric_id, column) in &filter.columns {
+                let column_path = format!("{}.columns.{}", path, metric_id);
+                if !self.metric_ids().any(|id| id == *metric_id) {
+                    problems.push((
+                        column_path.clone(),
+                        format!(
+                            "Filter '{}' binds metric {}, which is not on the dashboard",
+                            filter.id, metric_id
+                        ),
+                    ));
+                }
+                if !is_column_name(column) {
+                    problems.push((
+                        column_path,
+                        format!(
+                            "Filter '{}' binds '{}', which is not a valid column name",
+                            filter.id, column
+                        ),
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+
+    /// Every row with its path in the file, e.g. `tabs[1].rows[0]`
+    fn rows_with_paths(&self) -> impl Iterator<Item = (String, &Row)> {
+        let own = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| (format!("rows[{}]", index), row));
+        let tabs = self.tabs.iter().enumerate().flat_map(|(tab, t)| {
+            t.rows
+                .iter()
+                .enumerate()
+                .map(move |(index, row)| (format!("tabs[{}].rows[{}]", tab, index), row))
+        });
+        own.chain(tabs)
+    }
+
+    /// Ids of every metric placed in the dashboard's rows, on any tab
+    pub fn metric_ids(&self) -> impl Iterator<Item = Uuid> + '_ {
+        self.all_rows()
+            .flat_map(|row| row.items.iter().filter_map(RowItem::metric_id))
+    }
+
+    /// Every row on the dashboard: its own rows, then each tab's
+    pub fn all_rows(&self) -> impl Iterator<Item = &Row> {
+        self.rows
+            .iter()
+            .chain(self.tabs.iter().flat_map(|tab| tab.rows.iter()))
+    }
+
+    /// Rows shown on a tab, by tab id. `None` picks the first tab, or the
+    /// dashboard's own rows when it has no tabs.
+    pub fn tab_rows(&self, tab_id: Option<&str>) -> Result<&[Row]> {
+        let tab = match tab_id {
+            None if self.tabs.is_empty() => return Ok(&self.rows),
+            None => self.tabs.first(),
+            Some(id) => self.tabs.iter().find(|tab| tab.id == id),
+        };
+        tab.map(|tab| tab.rows.as_slice())
+            .ok_or_else(|| anyhow::anyhow!("Tab '{}' not found", tab_id.unwrap_or_default()))
+    }
+
+    /// Gives each row without an id (0) the next free one. Row ids are
+    /// unique across the whole dashboard, tabs included.
+    pub fn assign_row_ids(&mut self) {
+        let mut next_id = self.get_next_row_id();
+        let tab_rows = self.tabs.iter_mut().flat_map(|tab| tab.rows.iter_mut());
+        for row in self.rows.iter_mut().chain(tab_rows) {
+            if row.id == 0 {
+                row.id = next_id;
+                next_id += 1;
+            }
+        }
     }
 
     pub fn to_value(&self) -> Result<Value> {
@@ -157,8 +470,7 @@ impl DashboardYml {
     }
     
     pub fn get_next_row_id(&self) -> u32 {
-        self.rows
-            .iter()
+        self.all_rows()
             .map(|row| row.id)
             .max()
             .map_or(1, |max_id| max_id + 1)
@@ -173,6 +485,43 @@ impl DashboardYml {
             id: next_id,
         });
     }
+
+    /// JSON Schema for dashboard files, for editors to validate and autocomplete against.
+    pub fn json_schema() -> RootSchema {
+        schema_for!(DashboardYml)
+    }
+}
+
+/// Splits the metrics in `rows` into those shown on load and those inside
+/// collapsed sections, which load once the section is opened. `expanded`
+/// holds the row ids of collapsed dividers the viewer has opened.
+pub fn partition_collapsed_metrics(rows: &[Row], expanded: &[u32]) -> (Vec<Uuid>, Vec<Uuid>) {
+    let mut shown = Vec::new();
+    let mut deferred = Vec::new();
+    let mut in_collapsed_section = false;
+
+    for row in rows {
+        if let Some(RowItem::Divider { collapsed, .. }) = row.items.first() {
+            in_collapsed_section = *collapsed && !expanded.contains(&row.id);
+        }
+        let metric_ids = row.items.iter().filter_map(RowItem::metric_id);
+        if in_collapsed_section {
+            deferred.extend(metric_ids);
+        } else {
+            shown.extend(metric_ids);
+        }
+    }
+
+    (shown, deferred)
+}
+
+/// Plain or dot-qualified identifier, e.g. `created_at` or `orders.created_at`
+fn is_column_name(column: &str) -> bool {
+    column.split('.').all(|part| {
+        let mut chars = part.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
 }
 
 impl FromSql<Jsonb, Pg> for DashboardYml {
@@ -195,6 +544,20 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    /// Checks a fixture against the JSON Schema published for dashboard files
+    fn assert_schema_accepts(yaml: &str) {
+        let schema = serde_json::to_value(DashboardYml::json_schema()).unwrap();
+        let schema = jsonschema::JSONSchema::compile(&schema).unwrap();
+        let instance: serde_json::Value = serde_yaml::from_str(yaml).unwrap();
+        let errors: Vec<String> = match schema.validate(&instance) {
+            Ok(()) => return,
+            Err(errors) => errors
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect(),
+        };
+        panic!("schema rejected fixture: {:?}", errors);
+    }
+
     #[test]
     fn test_dashboard_yml_camel_case_serialization() {
         let dashboard = DashboardYml {
@@ -203,7 +566,7 @@ mod tests {
             rows: vec![
                 Row {
                     items: vec![
-                        RowItem {
+                        RowItem::Metric {
                             id: Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap(),
                         }
                     ],
@@ -212,6 +575,8 @@ mod tests {
                     id: 1,
                 }
             ],
+            tabs: vec![],
+            filters: vec![],
         };
         
         let json = serde_json::to_value(&dashboard).unwrap();
@@ -282,6 +647,7 @@ mod tests {
         });
         
         let yaml = serde_yaml::to_string(&json).unwrap();
+        assert_schema_accepts(&yaml);
         let dashboard = DashboardYml::new(yaml).unwrap();
         
         assert_eq!(dashboard.name, "Test Dashboard");
@@ -317,6 +683,7 @@ rows:
 "#;
         
         let dashboard = DashboardYml::new(yaml.to_string()).unwrap();
+        assert_schema_accepts(yaml);
         
         assert_eq!(dashboard.rows[0].id, 1);
         assert_eq!(dashboard.rows[1].id, 2);
@@ -330,22 +697,24 @@ rows:
             description: None,
             rows: vec![
                 Row {
-                    items: vec![RowItem { id: Uuid::new_v4() }],
+                    items: vec![RowItem::Metric { id: Uuid::new_v4() }],
                     row_height: None,
                     column_sizes: vec![12],
                     id: 1,
                 }
             ],
+            tabs: vec![],
+            filters: vec![],
         };
         
         dashboard.add_row(
-            vec![RowItem { id: Uuid::new_v4() }],
+            vec![RowItem::Metric { id: Uuid::new_v4() }],
             Some(400),
             vec![12],
         );
         
         dashboard.add_row(
-            vec![RowItem { id: Uuid::new_v4() }],
+            vec![RowItem::Metric { id: Uuid::new_v4() }],
             Some(320),
             vec![12],
         );
@@ -364,24 +733,26 @@ rows:
             description: None,
             rows: vec![
                 Row {
-                    items: vec![RowItem { id: Uuid::new_v4() }],
+                    items: vec![RowItem::Metric { id: Uuid::new_v4() }],
                     row_height: None,
                     column_sizes: vec![12],
                     id: 1,
                 },
                 Row {
-                    items: vec![RowItem { id: Uuid::new_v4() }],
+                    items: vec![RowItem::Metric { id: Uuid::new_v4() }],
                     row_height: None,
                     column_sizes: vec![12],
                     id: 5,
                 },
                 Row {
-                    items: vec![RowItem { id: Uuid::new_v4() }],
+                    items: vec![RowItem::Metric { id: Uuid::new_v4() }],
                     row_height: None,
                     column_sizes: vec![12],
                     id: 3,
                 }
             ],
+            tabs: vec![],
+            filters: vec![],
         };
         
         assert_eq!(dashboard.get_next_row_id(), 6);
@@ -411,4 +782,249 @@ rows:
         
         assert_eq!(dashboard.rows[0].id, 42);
     }
+
+    #[test]
+    fn test_dashboard_filters() {
+        let yaml = r#"
+name: Sales
+rows:
+  - id: 1
+    items:
+      - id: 00000000-0000-0000-0000-000000000001
+      - id: 00000000-0000-0000-0000-000000000002
+    columnSizes: [6, 6]
+filters:
+  - id: period
+    name: Order date
+    type: dateRange
+    columns:
+      00000000-0000-0000-0000-000000000001: created_at
+      00000000-0000-0000-0000-000000000002: orders.ordered_at
+  - id: region
+    name: Region
+    type: multiSelect
+    options: [EU, US]
+    columns:
+      00000000-0000-0000-0000-000000000001: region
+"#;
+
+        let dashboard = DashboardYml::new(yaml.to_string()).unwrap();
+        assert_schema_accepts(yaml);
+
+        assert_eq!(dashboard.filters.len(), 2);
+        assert_eq!(dashboard.filters[0].filter_type, DashboardFilterType::DateRange);
+        assert_eq!(dashboard.filters[1].options, vec!["EU", "US"]);
+        let metric = Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap();
+        assert_eq!(dashboard.filters[0].columns[&metric], "orders.ordered_at");
+
+        let unknown_metric = yaml.replace(
+            "00000000-0000-0000-0000-000000000001: region",
+            "00000000-0000-0000-0000-000000000009: region",
+        );
+        let err = DashboardYml::new(unknown_metric).unwrap_err().to_string();
+        assert!(err.contains("not on the dashboard"), "{}", err);
+
+        let bad_column = yaml.replace(": region", ": region; drop table orders");
+        let err = DashboardYml::new(bad_column).unwrap_err().to_string();
+        assert!(err.contains("not a valid column name"), "{}", err);
+
+        let duplicate_id = yaml.replace("id: region", "id: period");
+        let err = DashboardYml::new(duplicate_id).unwrap_err().to_string();
+        assert!(err.contains("Duplicate filter id"), "{}", err);
+    }
+
+    #[test]
+    fn test_markdown_and_divider_items() {
+        let yaml = r#"
+name: Sales
+rows:
+  - id: 1
+    items:
+      - type: divider
+        title: Revenue
+    columnSizes: [12]
+  - id: 2
+    items:
+      - type: markdown
+        content: "Revenue is net of refunds. See [the wiki](https://example.com)."
+      - type: metric
+        id: 00000000-0000-0000-0000-000000000001
+      - id: 00000000-0000-0000-0000-000000000002
+    columnSizes: [4, 4, 4]
+"#;
+
+        let dashboard = DashboardYml::new(yaml.to_string()).unwrap();
+        assert_schema_accepts(yaml);
+
+        assert_eq!(
+            dashboard.rows[0].items[0],
+            RowItem::Divider {
+                title: Some("Revenue".to_string()),
+                collapsible: false,
+                collapsed: false,
+            }
+        );
+        assert!(matches!(
+            dashboard.rows[1].items[0],
+            RowItem::Markdown { .. }
+        ));
+        assert_eq!(
+            dashboard.metric_ids().collect::<Vec<_>>(),
+            vec![
+                Uuid::parse_str("00000000-0000-0000-0000-000000000001").unwrap(),
+                Uuid::parse_str("00000000-0000-0000-0000-000000000002").unwrap(),
+            ]
+        );
+
+        let json = serde_json::to_value(&dashboard).unwrap();
+        assert_eq!(json["rows"][1]["items"][2]["type"], "metric");
+
+        let shared_divider = yaml
+            .replace(
+                "      - type: metric\n",
+                "      - type: divider\n      - type: metric\n",
+            )
+            .replace("[4, 4, 4]", "[3, 3, 3, 3]");
+        let err = DashboardYml::new(shared_divider).unwrap_err().to_string();
+        assert!(err.contains("only item in its row"), "{}", err);
+
+        let empty_markdown = yaml.replace(
+            "Revenue is net of refunds. See [the wiki](https://example.com).",
+            " ",
+        );
+        let err = DashboardYml::new(empty_markdown).unwrap_err().to_string();
+        assert!(err.contains("must have content"), "{}", err);
+    }
+
+    #[test]
+    fn test_tabs_and_collapsible_sections() {
+        let yaml = r#"
+name: Sales
+tabs:
+  - id: overview
+    name: Overview
+    rows:
+      - items:
+          - id: 00000000-0000-0000-0000-000000000001
+        columnSizes: [12]
+  - id: regions
+    name: Regions
+    rows:
+      - id: 7
+        items:
+          - id: 00000000-0000-0000-0000-000000000002
+        columnSizes: [12]
+      - items:
+          - type: divider
+            title: Details
+            collapsible: true
+            collapsed: true
+        columnSizes: [12]
+      - items:
+          - id: 00000000-0000-0000-0000-000000000003
+        columnSizes: [12]
+"#;
+
+        let dashboard = DashboardYml::new(yaml.to_string()).unwrap();
+        assert_schema_accepts(yaml);
+        let metric = |n: u32| Uuid::from_u128(n as u128);
+
+        let row_ids: Vec<u32> = dashboard.all_rows().map(|row| row.id).collect();
+        assert_eq!(row_ids, vec![8, 7, 9, 10]);
+        assert_eq!(dashboard.metric_ids().count(), 3);
+        assert_eq!(dashboard.get_next_row_id(), 11);
+
+        assert_eq!(dashboard.tab_rows(None).unwrap().len(), 1);
+        let regions = dashboard.tab_rows(Some("regions")).unwrap();
+        assert_eq!(
+            partition_collapsed_metrics(regions, &[]),
+            (vec![metric(2)], vec![metric(3)])
+        );
+        assert_eq!(
+            partition_collapsed_metrics(regions, &[9]),
+            (vec![metric(2), metric(3)], vec![])
+        );
+        let err = dashboard.tab_rows(Some("missing")).unwrap_err().to_string();
+        assert!(err.contains("Tab 'missing' not found"), "{}", err);
+
+        let json = serde_json::to_value(&dashboard).unwrap();
+        assert_eq!(json["rows"], json!([]));
+        assert_eq!(json["tabs"][1]["rows"][1]["items"][0]["collapsed"], true);
+
+        let duplicate_row = yaml.replace("      - id: 7\n", "      - id: 8\n");
+        let err = DashboardYml::new(duplicate_row.replace(
+            "      - items:\n          - id: 00000000-0000-0000-0000-000000000001",
+            "      - id: 8\n        items:\n          - id: 00000000-0000-0000-0000-000000000001",
+        ))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("Duplicate row id 8"), "{}", err);
+
+        let duplicate_tab = yaml.replace("id: regions", "id: overview");
+        let err = DashboardYml::new(duplicate_tab).unwrap_err().to_string();
+        assert!(err.contains("Duplicate tab id"), "{}", err);
+
+        let fixed_section = yaml.replace("            collapsible: true\n", "");
+        let err = DashboardYml::new(fixed_section).unwrap_err().to_string();
+        assert!(err.contains("Only a collapsible divider"), "{}", err);
+
+        let rows_and_tabs = format!(
+            "{}rows:\n  - id: 20\n    items:\n      - id: 00000000-0000-0000-0000-000000000004\n    columnSizes: [12]\n",
+            yaml
+        );
+        let err = DashboardYml::new(rows_and_tabs).unwrap_err().to_string();
+        assert!(
+            err.contains("must place all of its rows in tabs"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_errors_located_by_line_column_and_path() {
+        use crate::types::YmlErrorKind;
+
+        let errors = |yml: &str| DashboardYml::parse(yml).unwrap_err().0;
+
+        let syntax = errors("name: Sales: Q1\nrows: []\n");
+        assert_eq!(syntax[0].kind, YmlErrorKind::Syntax);
+        assert_eq!(syntax[0].path.as_deref(), Some("name"));
+
+        let schema = errors(
+            "name: D\nrows:\n  - items:\n      - type: metric\n        id: 1\n    columnSizes: [12]\n",
+        );
+        assert_eq!(schema[0].kind, YmlErrorKind::Schema);
+        assert_eq!(schema[0].path.as_deref(), Some("rows[0].items[0].id"));
+        assert_eq!((schema[0].line, schema[0].column), (Some(5), Some(9)));
+
+        // Every row's problems are reported, not just the first
+        let validation = errors(
+            r#"name: D
+rows:
+  - items:
+      - id: 00000000-0000-0000-0000-000000000001
+    columnSizes: [6]
+  - items:
+      - type: markdown
+        content: ""
+    columnSizes: [12]
+"#,
+        );
+        let found: Vec<(Option<&str>, Option<usize>)> = validation
+            .iter()
+            .map(|e| (e.path.as_deref(), e.line))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (Some("rows[0].columnSizes"), Some(5)),
+                (Some("rows[1].items[0]"), Some(7)),
+            ]
+        );
+        assert!(validation[1].message.contains("must have content"));
+
+        // A quoted name keeps its colon
+        let dashboard = DashboardYml::parse("name: \"Sales: Q1\"\nrows: []\n").unwrap();
+        assert_eq!(dashboard.name, "Sales: Q1");
+    }
 }
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use chrono::{Datelike, Duration, Months, NaiveDate};
 use diesel::{
     deserialize::{FromSql, FromSqlRow},
     expression::AsExpression,
@@ -9,22 +10,15 @@ use diesel::{
 use indexmap::IndexMap;
 use lazy_static::lazy_static;
 use regex::Regex;
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use std::io::Write;
+use uuid::Uuid;
 
-// Helper function to sanitize string values for YAML
-fn sanitize_yaml_string(value: &str) -> String {
-    value
-        .replace(':', "") // Remove colons
-        .replace('\'', "") // Remove single quotes
-        .replace('\"', "") // Remove double quotes
-        .replace('\n', " ") // Replace newlines with spaces
-        .replace('\t', " ") // Replace tabs with spaces
-        .replace('%', "Percent") // Replace % with Percent
-        .trim() // Trim leading/trailing whitespace
-        .to_string()
-}
+use crate::types::yml_errors::{read_yml, YmlError, YmlErrors};
+use crate::types::{DataMetadata, SimpleType};
 
 // Helper function to recursively lowercase relevant keys and string values in serde_yaml::Value
 fn lowercase_column_identifiers(value: &mut serde_yaml::Value) {
@@ -59,7 +53,9 @@ fn lowercase_column_identifiers(value: &mut serde_yaml::Value) {
                         }
                     }
                     // Lowercase string values for specific keys holding single column names
-                    else if ["metricColumnId", "columnId"].contains(&key_str.as_str()) {
+                    else if ["metricColumnId", "columnId", "comparisonColumnId"]
+                        .contains(&key_str.as_str())
+                    {
                         if let serde_yaml::Value::String(val_str) = &mut current_v {
                             *val_str = val_str.to_lowercase();
                         }
@@ -69,9 +65,12 @@ fn lowercase_column_identifiers(value: &mut serde_yaml::Value) {
                         "x",
                         "y",
                         "y2",
+                        "value",
                         "category",
                         "tooltip",
                         "size",
+                        "rows",
+                        "columns",
                         "tableColumnOrder",
                         "barSortBy", // Added barSortBy as it likely contains column names
                     ]
@@ -102,34 +101,520 @@ fn lowercase_column_identifiers(value: &mut serde_yaml::Value) {
 }
 
 lazy_static! {
-    // Combined regex for keys whose string values need sanitization
-    static ref SANITIZE_KEYS_RE: Regex = Regex::new(
-        r#"^(?P<indent>\s*)(?P<key>name|description|timeFrame|displayName|prefix|suffix|goalLineLabel|trendlineLabel|pieInnerLabelTitle|metricValueLabel):\s*(?P<value>.*)$"#
-    ).unwrap();
-    // Regex to find the start of the colors block and capture indentation
-    static ref COLORS_START_RE: Regex = Regex::new(r#"^(\s*)colors:\s*$"#).unwrap();
-    // Regex to find unquoted hex color list items and capture indent/marker and color value
-    static ref COLOR_LINE_RE: Regex = Regex::new(r#"^(\s*-\s+)(#[0-9a-fA-F]+)\s*$"#).unwrap();
-    // Regex to capture the indentation of a line (used for colors block and timeFrame insertion)
-    static ref INDENT_RE: Regex = Regex::new(r#"^(\s*)\S"#).unwrap();
-    // Regex to find the sql key to determine insertion point for timeFrame
-    static ref SQL_KEY_RE: Regex = Regex::new(r#"^(\s*)sql:\s*.*$"#).unwrap();
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone, FromSqlRow, AsExpression)]
+    // Regex to find `{{ name }}` parameter placeholders in the sql
+    static ref PARAMETER_RE: Regex = Regex::new(r#"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}"#).unwrap();
+    static ref PARAMETER_NAME_RE: Regex = Regex::new(r#"^[A-Za-z_][A-Za-z0-9_]*$"#).unwrap();
+    static ref ISO_DATE_RE: Regex = Regex::new(r#"^\d{4}-\d{2}-\d{2}$"#).unwrap();
+    static ref HEX_COLOR_RE: Regex = Regex::new(r#"^#(?:[0-9a-fA-F]{3}|[0-9a-fA-F]{6})$"#).unwrap();
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, FromSqlRow, AsExpression)]
 #[diesel(sql_type = Jsonb)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricYml {
+    /// Human-readable title, e.g. Total Sales. No underscores or `:`.
     pub name: String,
+    /// The metric rephrased as a question or statement, e.g. "What are the total
+    /// sales?". Doesn't describe the chart.
     pub description: Option<String>,
-    #[serde(alias = "time_frame")]
+    /// Display label for the period the metric covers. Filled in from
+    /// `time_range` (or "all_time") when left out.
+    #[serde(default, alias = "time_frame")]
     pub time_frame: String,
+    /// The dates the metric's data is limited to, applied when it's queried
+    #[serde(default, alias = "time_range", skip_serializing_if = "Option::is_none")]
+    pub time_range: Option<TimeRange>,
+    /// An earlier period to plot alongside `time_range`, such as last year
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<Comparison>,
+    /// The query, written as a `|` block scalar so its newlines are kept
     pub sql: String,
+    /// Visualization settings: `selectedChartType`, `columnLabelFormats` and the
+    /// block for that chart type
     #[serde(alias = "chart_config")]
     pub chart_config: ChartConfig,
+    /// Typed values the sql refers to as `{{ name }}`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<MetricParameter>,
+    /// Metrics and dashboards a viewer can open from a clicked data point
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub drills: Vec<DrillLink>,
+}
+
+/// Opens another metric or dashboard when a data point is clicked, passing
+/// along the clicked point's value in `column_id`
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DrillLink {
+    #[serde(alias = "column_id")]
+    pub column_id: String,
+    /// Shown in the click menu, e.g. "Revenue by rep". Defaults to the
+    /// target's name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(flatten)]
+    pub target: DrillTarget,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DrillTarget {
+    /// Binds the clicked value to one of the target metric's parameters
+    Metric { id: Uuid, parameter: String },
+    /// Binds the clicked value to one of the target dashboard's filters,
+    /// by filter id
+    Dashboard { id: Uuid, filter: String },
+}
+
+impl DrillLink {
+    fn problems(&self, metadata: Option<&DataMetadata>) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.column_id.is_empty() {
+            problems.push("A drill must name the clicked column in columnId".to_string());
+        } else if let Some(metadata) = metadata {
+            if !metadata
+                .column_metadata
+                .iter()
+                .any(|column| column.name.eq_ignore_ascii_case(&self.column_id))
+            {
+                problems.push(format!(
+                    "Drill column '{}' is not returned by the SQL",
+                    self.column_id
+                ));
+            }
+        }
+
+        match &self.target {
+            DrillTarget::Metric { parameter, .. } => {
+                if !PARAMETER_NAME_RE.is_match(parameter) {
+                    problems.push(format!(
+                        "Drill on '{}' binds '{}', which is not a valid parameter name",
+                        self.column_id, parameter
+                    ));
+                }
+            }
+            DrillTarget::Dashboard { filter, .. } => {
+                if filter.is_empty() {
+                    problems.push(format!(
+                        "Drill on '{}' must name the dashboard filter to set",
+                        self.column_id
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricParameter {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: MetricParameterType,
+    /// Used whenever a request doesn't supply a value
+    pub default: serde_json::Value,
+    /// The only values accepted, when set
+    #[serde(
+        default,
+        alias = "allowed_values",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub allowed_values: Vec<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricParameterType {
+    String,
+    Number,
+    /// A calendar date written as `YYYY-MM-DD`
+    Date,
+    Boolean,
+}
+
+impl MetricParameter {
+    /// The value to bind: `value` when given and not null, otherwise the default
+    pub fn resolve(&self, value: Option<&serde_json::Value>) -> Result<serde_json::Value> {
+        let value = match value {
+            Some(value) if !value.is_null() => value,
+            _ => return Ok(self.default.clone()),
+        };
+        self.check_value(value)?;
+        Ok(value.clone())
+    }
+
+    fn check_value(&self, value: &serde_json::Value) -> Result<()> {
+        let matches_type = match self.param_type {
+            MetricParameterType::String => value.is_string(),
+            MetricParameterType::Number => value.is_number(),
+            MetricParameterType::Date => value.as_str().is_some_and(|s| ISO_DATE_RE.is_match(s)),
+            MetricParameterType::Boolean => value.is_boolean(),
+        };
+        if !matches_type {
+            return Err(anyhow::anyhow!(
+                "Parameter '{}' expects a {}, got {}",
+                self.name,
+                self.param_type.describe(),
+                value
+            ));
+        }
+        if !self.allowed_values.is_empty() && !self.allowed_values.contains(value) {
+            return Err(anyhow::anyhow!(
+                "{} is not an allowed value for parameter '{}'",
+                value,
+                self.name
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl MetricParameterType {
+    fn describe(&self) -> &'static str {
+        match self {
+            MetricParameterType::String => "string",
+            MetricParameterType::Number => "number",
+            MetricParameterType::Date => "date formatted as YYYY-MM-DD",
+            MetricParameterType::Boolean => "boolean",
+        }
+    }
+}
+
+/// A range of dates compared against a date or timestamp column in the
+/// metric's SQL. Relative periods are resolved against the current date each
+/// time the metric is queried.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeRange {
+    /// Plain or table-qualified column the range filters on
+    pub column: String,
+    #[serde(flatten)]
+    pub period: TimePeriod,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum TimePeriod {
+    /// The current unit and the `count - 1` before it, e.g. the last 30 days
+    Last { count: u32, unit: TimeUnit },
+    /// The last complete unit, e.g. last quarter
+    Previous { unit: TimeUnit },
+    /// The current unit up to and including today, e.g. quarter to date
+    ToDate { unit: TimeUnit },
+    /// Fixed `YYYY-MM-DD` dates, both inclusive. Either may be left open.
+    Absolute {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        start: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        end: Option<String>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeUnit {
+    Day,
+    /// Weeks start on Monday
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl TimeRange {
+    /// The dates covered as of `today`, as an inclusive start and an exclusive
+    /// end. Absolute ranges may leave either side open.
+    pub fn resolve(&self, today: NaiveDate) -> Result<(Option<NaiveDate>, Option<NaiveDate>)> {
+        let out_of_range = || anyhow::anyhow!("Time range on '{}' is out of range", self.column);
+        match &self.period {
+            TimePeriod::Last { count, unit } => {
+                let current = unit.start_of(today);
+                let back = i32::try_from(*count).map_err(|_| out_of_range())? - 1;
+                let start = unit.shift(current, -back).ok_or_else(out_of_range)?;
+                let end = unit.shift(current, 1).ok_or_else(out_of_range)?;
+                Ok((Some(start), Some(end)))
+            }
+            TimePeriod::Previous { unit } => {
+                let end = unit.start_of(today);
+                let start = unit.shift(end, -1).ok_or_else(out_of_range)?;
+                Ok((Some(start), Some(end)))
+            }
+            TimePeriod::ToDate { unit } => {
+                let end = today.succ_opt().ok_or_else(out_of_range)?;
+                Ok((Some(unit.start_of(today)), Some(end)))
+            }
+            TimePeriod::Absolute { start, end } => {
+                let start = start.as_deref().map(parse_iso_date).transpose()?;
+                let end = match end.as_deref().map(parse_iso_date).transpose()? {
+                    Some(end) => Some(end.succ_opt().ok_or_else(out_of_range)?),
+                    None => None,
+                };
+                Ok((start, end))
+            }
+        }
+    }
+
+    /// A short label such as "Last 30 days" or "Quarter to date"
+    pub fn describe(&self) -> String {
+        match &self.period {
+            TimePeriod::Last { count: 1, unit } => format!("Last {}", unit.name()),
+            TimePeriod::Last { count, unit } => format!("Last {} {}s", count, unit.name()),
+            TimePeriod::Previous { unit } => format!("Previous {}", unit.name()),
+            TimePeriod::ToDate { unit } => {
+                let name = unit.name();
+                format!("{}{} to date", name[..1].to_uppercase(), &name[1..])
+            }
+            TimePeriod::Absolute { start, end } => match (start, end) {
+                (Some(start), Some(end)) => format!("{} to {}", start, end),
+                (Some(start), None) => format!("Since {}", start),
+                (None, Some(end)) => format!("Through {}", end),
+                (None, None) => "All time".to_string(),
+            },
+        }
+    }
+
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let valid_column = self
+            .column
+            .split('.')
+            .all(|part| PARAMETER_NAME_RE.is_match(part));
+        if !valid_column {
+            problems.push(format!(
+                "'{}' is not a valid column name; use a plain or table-qualified column",
+                self.column
+            ));
+        }
+        match &self.period {
+            TimePeriod::Last { count: 0, .. } => {
+                problems.push("count must be at least 1".to_string());
+            }
+            TimePeriod::Absolute { start, end } => {
+                let parsed: Vec<Option<NaiveDate>> = [start, end]
+                    .into_iter()
+                    .map(|date| match date.as_deref().map(parse_iso_date) {
+                        Some(Ok(date)) => Some(date),
+                        Some(Err(e)) => {
+                            problems.push(e.to_string());
+                            None
+                        }
+                        None => None,
+                    })
+                    .collect();
+                if start.is_none() && end.is_none() {
+                    problems.push("an absolute range needs a start, an end, or both".to_string());
+                }
+                if let [Some(start), Some(end)] = parsed.as_slice() {
+                    if start > end {
+                        problems.push(format!("start {} is after end {}", start, end));
+                    }
+                }
+            }
+            _ => {}
+        }
+        problems
+    }
+}
+
+impl TimeUnit {
+    fn name(&self) -> &'static str {
+        match self {
+            TimeUnit::Day => "day",
+            TimeUnit::Week => "week",
+            TimeUnit::Month => "month",
+            TimeUnit::Quarter => "quarter",
+            TimeUnit::Year => "year",
+        }
+    }
+
+    /// First day of the unit `date` falls in
+    fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        let month_start = |month: u32| NaiveDate::from_ymd_opt(date.year(), month, 1).unwrap();
+        match self {
+            TimeUnit::Day => date,
+            TimeUnit::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            TimeUnit::Month => month_start(date.month()),
+            TimeUnit::Quarter => month_start((date.month() - 1) / 3 * 3 + 1),
+            TimeUnit::Year => month_start(1),
+        }
+    }
+
+    /// Moves `date` by `n` units, or `None` past the end of the calendar
+    fn shift(&self, date: NaiveDate, n: i32) -> Option<NaiveDate> {
+        let months = |m: i32| {
+            let delta = Months::new(m.unsigned_abs());
+            if m < 0 {
+                date.checked_sub_months(delta)
+            } else {
+                date.checked_add_months(delta)
+            }
+        };
+        match self {
+            TimeUnit::Day => date.checked_add_signed(Duration::days(n as i64)),
+            TimeUnit::Week => date.checked_add_signed(Duration::weeks(n as i64)),
+            TimeUnit::Month => months(n),
+            TimeUnit::Quarter => months(n.checked_mul(3)?),
+            TimeUnit::Year => months(n.checked_mul(12)?),
+        }
+    }
+}
+
+fn parse_iso_date(date: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .filter(|_| ISO_DATE_RE.is_match(date))
+        .ok_or_else(|| anyhow::anyhow!("'{}' is not a date formatted as YYYY-MM-DD", date))
+}
+
+/// Re-runs the metric over an earlier period so both series can be shown
+/// together. Rows are lined up by the date bucket in `column_id`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Comparison {
+    /// Result column holding each row's date or timestamp bucket
+    #[serde(alias = "column_id")]
+    pub column_id: String,
+    #[serde(flatten)]
+    pub period: ComparisonPeriod,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ComparisonPeriod {
+    /// The period of the same length just before the time range, e.g. the
+    /// 30 days before the last 30 days
+    PreviousPeriod,
+    /// The time range moved back one year
+    SamePeriodLastYear,
+    /// The time range moved back `count` units
+    Offset { count: u32, unit: TimeUnit },
+}
+
+impl Comparison {
+    /// The comparison period's dates as of `today`, in the same form as
+    /// [`TimeRange::resolve`]
+    pub fn resolve(
+        &self,
+        time_range: &TimeRange,
+        today: NaiveDate,
+    ) -> Result<(Option<NaiveDate>, Option<NaiveDate>)> {
+        let (unit, count) = self.offset(time_range)?;
+        let (start, end) = time_range.resolve(today)?;
+        let back = |date: Option<NaiveDate>| match date {
+            Some(date) => unit
+                .shift(date, -count)
+                .map(Some)
+                .ok_or_else(|| anyhow::anyhow!("Comparison period is out of range")),
+            None => Ok(None),
+        };
+        Ok((back(start)?, back(end)?))
+    }
+
+    /// Moves a date from the comparison period onto the matching date of the
+    /// time range, e.g. a bucket from last March onto this March
+    pub fn align(&self, time_range: &TimeRange, date: NaiveDate) -> Option<NaiveDate> {
+        let (unit, count) = self.offset(time_range).ok()?;
+        unit.shift(date, count)
+    }
+
+    /// A short label such as "Previous period" or "3 months earlier"
+    pub fn describe(&self) -> String {
+        match &self.period {
+            ComparisonPeriod::PreviousPeriod => "Previous period".to_string(),
+            ComparisonPeriod::SamePeriodLastYear => "Same period last year".to_string(),
+            ComparisonPeriod::Offset { count: 1, unit } => format!("1 {} earlier", unit.name()),
+            ComparisonPeriod::Offset { count, unit } => {
+                format!("{} {}s earlier", count, unit.name())
+            }
+        }
+    }
+
+    /// How many units back the comparison period lies
+    fn offset(&self, time_range: &TimeRange) -> Result<(TimeUnit, i32)> {
+        let (unit, count) = match (&self.period, &time_range.period) {
+            (ComparisonPeriod::SamePeriodLastYear, _) => (TimeUnit::Year, 1),
+            (ComparisonPeriod::Offset { count, unit }, _) => (*unit, i64::from(*count)),
+            (ComparisonPeriod::PreviousPeriod, TimePeriod::Last { count, unit }) => {
+                (*unit, i64::from(*count))
+            }
+            (
+                ComparisonPeriod::PreviousPeriod,
+                TimePeriod::Previous { unit } | TimePeriod::ToDate { unit },
+            ) => (*unit, 1),
+            (
+                ComparisonPeriod::PreviousPeriod,
+                TimePeriod::Absolute {
+                    start: Some(start),
+                    end: Some(end),
+                },
+            ) => {
+                let days = parse_iso_date(end)? - parse_iso_date(start)?;
+                (TimeUnit::Day, days.num_days() + 1)
+            }
+            (ComparisonPeriod::PreviousPeriod, TimePeriod::Absolute { .. }) => {
+                return Err(anyhow::anyhow!(
+                    "previousPeriod needs an absolute timeRange with both a start and an end"
+                ));
+            }
+        };
+        let count = i32::try_from(count)
+            .map_err(|_| anyhow::anyhow!("Comparison period is out of range"))?;
+        Ok((unit, count))
+    }
+
+    fn problems(
+        &self,
+        time_range: Option<&TimeRange>,
+        metadata: Option<&DataMetadata>,
+    ) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        if self.column_id.is_empty() {
+            problems.push("A comparison must name the date bucket column in columnId".to_string());
+        } else if let Some(metadata) = metadata {
+            match metadata
+                .column_metadata
+                .iter()
+                .find(|column| column.name.eq_ignore_ascii_case(&self.column_id))
+            {
+                None => problems.push(format!(
+                    "Comparison column '{}' is not returned by the SQL",
+                    self.column_id
+                )),
+                Some(column)
+                    if !matches!(column.simple_type, SimpleType::Date | SimpleType::Other) =>
+                {
+                    problems.push(format!(
+                        "Comparison column '{}' must hold dates or timestamps",
+                        self.column_id
+                    ))
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let ComparisonPeriod::Offset { count: 0, .. } = self.period {
+            problems.push("count must be at least 1".to_string());
+        }
+        match time_range {
+            Some(time_range) => {
+                if let Err(e) = self.offset(time_range) {
+                    problems.push(e.to_string());
+                }
+            }
+            None => problems.push("A comparison needs a timeRange to shift".to_string()),
+        }
+
+        problems
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(tag = "selectedChartType")]
 #[serde(rename_all = "camelCase")]
 pub enum ChartConfig {
@@ -147,9 +632,21 @@ pub enum ChartConfig {
     Metric(MetricChartConfig),
     #[serde(rename = "table")]
     Table(TableChartConfig),
+    #[serde(rename = "area")]
+    Area(AreaChartConfig),
+    #[serde(rename = "funnel")]
+    Funnel(FunnelChartConfig),
+    #[serde(rename = "heatmap")]
+    Heatmap(HeatmapChartConfig),
+    #[serde(rename = "histogram")]
+    Histogram(HistogramChartConfig),
+    #[serde(rename = "waterfall")]
+    Waterfall(WaterfallChartConfig),
+    #[serde(rename = "pivot")]
+    Pivot(PivotChartConfig),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 #[serde(untagged)]
 pub enum ShowLegendHeadline {
@@ -159,14 +656,14 @@ pub enum ShowLegendHeadline {
 
 // --- Axis Configuration Enums ---
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum YAxisScaleType {
     Log,
     Linear,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum XAxisTimeInterval {
     Day,
@@ -177,7 +674,7 @@ pub enum XAxisTimeInterval {
 }
 
 // Use strings to represent numbers and 'auto' for compatibility with TS/JSON
-#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
 pub enum XAxisLabelRotation {
     #[serde(rename = "0")]
     Rotate0,
@@ -191,7 +688,7 @@ pub enum XAxisLabelRotation {
 
 // --- Axis Configuration Structs ---
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct YAxisConfig {
     #[serde(
@@ -215,7 +712,7 @@ pub struct YAxisConfig {
     pub y_axis_scale_type: Option<YAxisScaleType>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct Y2AxisConfig {
     #[serde(
@@ -239,9 +736,10 @@ pub struct Y2AxisConfig {
     pub y2_axis_scale_type: Option<YAxisScaleType>, // Reuses YAxisScaleType enum
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct XAxisConfig {
+    /// Grouping for a date x-axis. Set it whenever the x-axis is a date.
     #[serde(
         skip_serializing_if = "Option::is_none",
         alias = "x_axis_time_interval"
@@ -268,7 +766,7 @@ pub struct XAxisConfig {
     pub x_axis_data_zoom: Option<bool>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct CategoryAxisStyleConfig {
     #[serde(skip_serializing_if = "Option::is_none", alias = "category_axis_title")]
@@ -278,16 +776,17 @@ pub struct CategoryAxisStyleConfig {
 // --- Base Chart Config ---
 
 // Base chart config shared by all chart types
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BaseChartConfig {
-    // Keys of this map are column names, need lowercasing.
+    /// Formatting for every column the sql returns, keyed by lowercase column name
     #[serde(alias = "column_label_formats")]
     pub column_label_formats: IndexMap<String, ColumnLabelFormat>,
+    /// Visual settings keyed by lowercase column name
     #[serde(skip_serializing_if = "Option::is_none")]
-    // Keys of this map are column names, need lowercasing.
     #[serde(alias = "column_settings")]
     pub column_settings: Option<IndexMap<String, ColumnSettings>>,
+    /// Hex colors, quoted so `#` isn't read as a comment, e.g. "#FF0000"
     #[serde(skip_serializing_if = "Option::is_none")]
     pub colors: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -322,7 +821,7 @@ pub struct BaseChartConfig {
     pub y2_axis_config: Option<Y2AxisConfig>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum NumberSeparatorStyle {
     #[serde(rename = ",")]
@@ -330,16 +829,19 @@ pub enum NumberSeparatorStyle {
     None,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnLabelFormat {
+    /// number, string or date
     #[serde(alias = "column_type")]
     pub column_type: String,
+    /// currency (prefixed with $), percent (suffixed with %), number, date or string
     #[serde(alias = "style")]
     pub style: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "display_name")]
     pub display_name: Option<String>,
+    /// `,` or null
     #[serde(alias = "number_separator_style")]
     pub number_separator_style: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -348,6 +850,8 @@ pub struct ColumnLabelFormat {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "maximum_fraction_digits")]
     pub maximum_fraction_digits: Option<i32>,
+    /// Applied before display. Percentages read straight from a column use 1, ratios
+    /// computed in the sql use 100.
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "multiplier")]
     pub multiplier: Option<f64>,
@@ -357,6 +861,7 @@ pub struct ColumnLabelFormat {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "suffix")]
     pub suffix: Option<String>,
+    /// 0 for number columns, null for the rest and for heatmap value columns
     #[serde(alias = "replace_missing_data_with")]
     pub replace_missing_data_with: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -365,6 +870,8 @@ pub struct ColumnLabelFormat {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "currency")]
     pub currency: Option<String>,
+    /// Day.js format matching the time interval: YYYY, [Q]Q YYYY, MMM YYYY or
+    /// MMM D, YYYY
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "date_format")]
     pub date_format: Option<String>,
@@ -374,6 +881,7 @@ pub struct ColumnLabelFormat {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "is_utc")]
     pub is_utc: Option<bool>,
+    /// day_of_week, month_of_year or quarter, for dates returned as numbers
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "convert_number_to")]
     pub convert_number_to: Option<String>,
@@ -475,9 +983,32 @@ impl ColumnLabelFormat {
 
         formats
     }
+
+    /// Generate column formats from data metadata for a particular chart.
+    /// A heatmap cell with no data stays empty rather than reading as zero,
+    /// so its value columns don't replace missing data.
+    pub fn generate_formats_for_chart(
+        metadata: &crate::types::DataMetadata,
+        chart_config: &ChartConfig,
+    ) -> indexmap::IndexMap<String, Self> {
+        let mut formats = Self::generate_formats_from_metadata(metadata);
+
+        if let ChartConfig::Heatmap(config) = chart_config {
+            for column in &config.heatmap_axis.value {
+                let format = formats
+                    .iter_mut()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(column));
+                if let Some((_, format)) = format {
+                    format.replace_missing_data_with = None;
+                }
+            }
+        }
+
+        formats
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ColumnSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -509,7 +1040,7 @@ pub struct ColumnSettings {
     pub line_symbol_size_dot: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct GoalLine {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -529,7 +1060,7 @@ pub struct GoalLine {
     pub goal_line_color: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Trendline {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -550,7 +1081,7 @@ pub struct Trendline {
     pub trend_line_color: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BarLineChartConfig {
     #[serde(flatten)]
@@ -574,9 +1105,10 @@ pub struct BarLineChartConfig {
     pub line_group_type: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BarAndLineAxis {
+    /// Lowercase column names from the sql
     #[serde(alias = "x")]
     pub x: Vec<String>,
     #[serde(alias = "y")]
@@ -589,7 +1121,7 @@ pub struct BarAndLineAxis {
     pub tooltip: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ScatterChartConfig {
     #[serde(flatten)]
@@ -601,7 +1133,7 @@ pub struct ScatterChartConfig {
     pub scatter_dot_size: Option<Vec<f64>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ScatterAxis {
     pub x: Vec<String>,
@@ -614,7 +1146,7 @@ pub struct ScatterAxis {
     pub tooltip: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PieChartConfig {
     #[serde(flatten)]
@@ -644,7 +1176,7 @@ pub struct PieChartConfig {
     pub pie_minimum_slice_percentage: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PieChartAxis {
     pub x: Vec<String>,
@@ -653,7 +1185,7 @@ pub struct PieChartAxis {
     pub tooltip: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ComboChartConfig {
     #[serde(flatten)]
@@ -662,7 +1194,7 @@ pub struct ComboChartConfig {
     pub combo_chart_axis: ComboChartAxis,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ComboChartAxis {
     pub x: Vec<String>,
@@ -675,7 +1207,7 @@ pub struct ComboChartAxis {
     pub tooltip: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct MetricChartConfig {
     #[serde(flatten)]
@@ -694,9 +1226,26 @@ pub struct MetricChartConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "metric_value_label")]
     pub metric_value_label: Option<String>,
+    /// Shows the change against the metric's comparison period under the value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "comparison_delta")]
+    pub comparison_delta: Option<ComparisonDelta>,
+    // Only threshold and arrow rules apply to a single value
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(alias = "conditional_formats")]
+    pub conditional_formats: Vec<ConditionalFormat>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ComparisonDelta {
+    /// The difference in the metric's own units
+    Absolute,
+    /// The difference as a percentage of the comparison value
+    Percent,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum MetricValueAggregate {
     Sum,
@@ -708,7 +1257,7 @@ pub enum MetricValueAggregate {
     First
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TableChartConfig {
     #[serde(flatten)]
@@ -728,159 +1277,1046 @@ pub struct TableChartConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(alias = "table_column_font_color")]
     pub table_column_font_color: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[serde(alias = "conditional_formats")]
+    pub conditional_formats: Vec<ConditionalFormat>,
 }
 
-impl MetricYml {
-    pub fn new(yml_content: String) -> Result<Self> {
-        let mut processed_lines = Vec::new();
-        let mut in_colors_block = false;
-        let mut colors_indent: Option<usize> = None;
-        let mut time_frame_found = false;
-        let mut sql_line_index: Option<usize> = None;
-        let mut sql_line_indent: Option<String> = None;
-
-        for (index, line) in yml_content.lines().enumerate() {
-            // Store SQL line info for potential timeFrame insertion
-            if sql_line_index.is_none() {
-                if let Some(caps) = SQL_KEY_RE.captures(line) {
-                    sql_line_index = Some(index);
-                    sql_line_indent = Some(caps.get(1).map_or("", |m| m.as_str()).to_string());
-                }
-            }
-
-            let current_indent = INDENT_RE
-                .captures(line)
-                .map_or(0, |caps| caps.get(1).map_or(0, |m| m.as_str().len()));
+/// A rule that styles values by the data. Rules apply in order, so a later
+/// rule's color wins where several match.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ConditionalFormat {
+    /// Colors a column's values that meet a condition
+    #[serde(rename_all = "camelCase")]
+    Threshold {
+        #[serde(flatten)]
+        condition: FormatCondition,
+        color: String,
+    },
+    /// Shades a numeric column's values along a gradient. Without `min` and
+    /// `max` the scale spans the column's own range.
+    #[serde(rename_all = "camelCase")]
+    ColorScale {
+        #[serde(alias = "column_id")]
+        column_id: String,
+        colors: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        min: Option<f64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max: Option<f64>,
+    },
+    /// Shows an up or down arrow for each value against a comparison column,
+    /// e.g. this period against the last
+    #[serde(rename_all = "camelCase")]
+    Arrows {
+        #[serde(alias = "column_id")]
+        column_id: String,
+        #[serde(alias = "comparison_column_id")]
+        comparison_column_id: String,
+        // Colors a decrease as good, for columns like churn or cost
+        #[serde(skip_serializing_if = "Option::is_none")]
+        #[serde(alias = "lower_is_better")]
+        lower_is_better: Option<bool>,
+    },
+    /// Highlights whole table rows where every condition holds
+    #[serde(rename_all = "camelCase")]
+    RowHighlight {
+        conditions: Vec<FormatCondition>,
+        color: String,
+    },
+}
 
-            // --- Colors Block Logic ---
-            if in_colors_block && colors_indent.map_or(false, |indent| current_indent <= indent) {
-                in_colors_block = false;
-                colors_indent = None;
-            }
-            if !in_colors_block {
-                // Only check for start if not already in block
-                if let Some(caps) = COLORS_START_RE.captures(line) {
-                    in_colors_block = true;
-                    colors_indent = Some(caps.get(1).map_or(0, |m| m.as_str().len()));
-                    processed_lines.push(line.to_string());
-                    continue;
-                }
-            }
-            if in_colors_block {
-                if let Some(caps) = COLOR_LINE_RE.captures(line) {
-                    let marker_part = caps.get(1).map_or("", |m| m.as_str());
-                    let color_part = caps.get(2).map_or("", |m| m.as_str());
-                    processed_lines.push(format!("{}'{}'", marker_part, color_part));
-                    continue;
-                } else {
-                    processed_lines.push(line.to_string()); // Add line within color block as is if not a hex item
-                    continue;
-                }
-            }
-            // --- End Colors Block Logic ---
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FormatCondition {
+    #[serde(alias = "column_id")]
+    pub column_id: String,
+    pub operator: FormatOperator,
+    // A number for comparisons; a number, string or boolean for (in)equality
+    pub value: serde_json::Value,
+}
 
-            // --- String Sanitization & timeFrame Check ---
-            if let Some(caps) = SANITIZE_KEYS_RE.captures(line) {
-                let indent = caps.name("indent").map_or("", |m| m.as_str());
-                let key = caps.name("key").map_or("", |m| m.as_str());
-                let value = caps.name("value").map_or("", |m| m.as_str());
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum FormatOperator {
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Eq,
+    Neq,
+}
 
-                if key == "timeFrame" {
-                    time_frame_found = true;
-                }
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AreaChartConfig {
+    #[serde(flatten)]
+    pub base: BaseChartConfig,
+    #[serde(alias = "area_chart_axis")]
+    pub area_chart_axis: AreaChartAxis,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "area_group_type")]
+    pub area_group_type: Option<AreaGroupType>,
+}
 
-                let sanitized_value = sanitize_yaml_string(value);
-                processed_lines.push(format!("{}{}: {}", indent, key, sanitized_value));
-            } else {
-                // Add lines that don't match any processing rules (like the sql block)
-                processed_lines.push(line.to_string());
-            }
-        }
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AreaChartAxis {
+    pub x: Vec<String>,
+    pub y: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<Vec<String>>,
+}
 
-        // Insert default timeFrame if not found
-        if !time_frame_found {
-            if let Some(index) = sql_line_index {
-                // Use the indent captured from the sql line
-                let indent = sql_line_indent.unwrap_or_else(|| "  ".to_string()); // Default indent if sql indent capture failed
-                processed_lines.insert(index, format!("{}timeFrame: 'all_time'", indent));
-            } else {
-                // Fallback: append if sql key wasn't found (shouldn't happen for valid metric)
-                // Or maybe error out?
-                eprintln!("Warning: sql key not found in metric YAML, cannot insert default timeFrame correctly.");
-                // Append at end with default indent - might break YAML structure
-                processed_lines.push("  timeFrame: 'all_time'".to_string());
-            }
-        }
-
-        let processed_yml_content = processed_lines.join("\n");
-
-        // Parse into generic serde_yaml::Value first
-        let mut yaml_value: serde_yaml::Value = match serde_yaml::from_str(&processed_yml_content) {
-            Ok(value) => value,
-            Err(e) => {
-                // Keep existing error handling for initial parse
-                let error_message = format!("Error parsing YAML: {}", e);
-                let yaml_error_str = e.to_string();
-                let detailed_error =
-                    if yaml_error_str.contains("at line") && yaml_error_str.contains("column") {
-                        format!("Error parsing YAML at {}", yaml_error_str)
-                    } else {
-                        error_message
-                    };
-                return Err(anyhow::anyhow!(detailed_error));
-            }
-        };
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AreaGroupType {
+    Stack,
+    PercentageStack,
+}
 
-        // Recursively lowercase the relevant identifiers
-        lowercase_column_identifiers(&mut yaml_value);
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FunnelChartConfig {
+    #[serde(flatten)]
+    pub base: BaseChartConfig,
+    #[serde(alias = "funnel_chart_axis")]
+    pub funnel_chart_axis: FunnelChartAxis,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "funnel_show_conversion_rate")]
+    pub funnel_show_conversion_rate: Option<bool>,
+}
 
-        // Now deserialize the modified value into the specific struct
-        let file: MetricYml = match serde_yaml::from_value(yaml_value) {
-            Ok(file) => file,
-            Err(e) => {
-                // Add error context for the second deserialization step
-                return Err(anyhow::anyhow!(
-                    "Error deserializing processed YAML into MetricYml: {}",
-                    e
-                ));
-            }
-        };
+// Stages are drawn in row order, so the SQL decides the funnel's order
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FunnelChartAxis {
+    pub x: Vec<String>, // the stage column
+    pub y: Vec<String>, // the count at each stage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<Vec<String>>,
+}
 
-        match file.validate() {
-            Ok(_) => Ok(file),
-            Err(e) => Err(anyhow::anyhow!("Error validating metric: {}", e)),
-        }
-    }
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapChartConfig {
+    #[serde(flatten)]
+    pub base: BaseChartConfig,
+    #[serde(alias = "heatmap_axis")]
+    pub heatmap_axis: HeatmapAxis,
+    // Colors from the lowest value to the highest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "heatmap_color_scale")]
+    pub heatmap_color_scale: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "heatmap_show_values")]
+    pub heatmap_show_values: Option<bool>,
+}
 
-    pub fn validate(&self) -> Result<()> {
-        Ok(())
-    }
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HeatmapAxis {
+    pub x: Vec<String>,
+    pub y: Vec<String>,
+    pub value: Vec<String>, // the column that colors each cell
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<Vec<String>>,
 }
 
-impl FromSql<Jsonb, Pg> for MetricYml {
-    fn from_sql(bytes: diesel::pg::PgValue) -> diesel::deserialize::Result<Self> {
-        let value = <serde_json::Value as FromSql<Jsonb, Pg>>::from_sql(bytes)?;
-        Ok(serde_json::from_value(value)?)
-    }
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramChartConfig {
+    #[serde(flatten)]
+    pub base: BaseChartConfig,
+    #[serde(alias = "histogram_axis")]
+    pub histogram_axis: HistogramAxis,
+    // Set at most one of the bin count and bin width; the UI picks a count otherwise
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "histogram_bin_count")]
+    pub histogram_bin_count: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "histogram_bin_width")]
+    pub histogram_bin_width: Option<f64>,
 }
 
-impl ToSql<Jsonb, Pg> for MetricYml {
-    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> diesel::serialize::Result {
-        out.write_all(&[1])?;
-        out.write_all(&serde_json::to_vec(self)?)?;
-        Ok(IsNull::No)
-    }
+// Rows are binned by the UI, so `x` is the raw values, not pre-aggregated counts
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramAxis {
+    pub x: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<Vec<String>>,
 }
 
-#[cfg(test)]
-mod tests {
-    use serde_json::json;
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WaterfallChartConfig {
+    #[serde(flatten)]
+    pub base: BaseChartConfig,
+    #[serde(alias = "waterfall_axis")]
+    pub waterfall_axis: WaterfallAxis,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "waterfall_show_total")]
+    pub waterfall_show_total: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "waterfall_total_label")]
+    pub waterfall_total_label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "waterfall_increase_color")]
+    pub waterfall_increase_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "waterfall_decrease_color")]
+    pub waterfall_decrease_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "waterfall_total_color")]
+    pub waterfall_total_color: Option<String>,
+}
 
-    use crate::types::{ColumnMetaData, ColumnType, DataMetadata, SimpleType};
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WaterfallAxis {
+    pub x: Vec<String>, // the step label
+    pub y: Vec<String>, // the change at each step
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<Vec<String>>,
+}
 
-    use super::*;
+/// Largest `pivotMaxColumns` accepted
+pub const MAX_PIVOT_COLUMNS: u32 = 500;
 
-    fn normalize_whitespace(s: &str) -> String {
+/// Cross-tabulates the query result: one row per combination of the row
+/// dimensions and one column per combination of the column dimensions and
+/// value. The pivot is computed by the server when the metric's data is
+/// fetched.
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotChartConfig {
+    #[serde(flatten)]
+    pub base: BaseChartConfig,
+    #[serde(alias = "pivot_axis")]
+    pub pivot_axis: PivotAxis,
+    // Subtotal rows for each level of the row dimensions but the last; shown unless false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "pivot_show_subtotals")]
+    pub pivot_show_subtotals: Option<bool>,
+    // A grand total row, and a total column per value; shown unless false
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "pivot_show_grand_totals")]
+    pub pivot_show_grand_totals: Option<bool>,
+    // Cap on generated columns, not counting totals; 50 when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "pivot_max_columns")]
+    pub pivot_max_columns: Option<u32>,
+}
+
+impl PivotChartConfig {
+    pub fn max_columns(&self) -> u32 {
+        self.pivot_max_columns.unwrap_or(50)
+    }
+
+    pub fn show_subtotals(&self) -> bool {
+        self.pivot_show_subtotals.unwrap_or(true)
+    }
+
+    pub fn show_grand_totals(&self) -> bool {
+        self.pivot_show_grand_totals.unwrap_or(true)
+    }
+}
+
+// Dimensions are ordered as they first appear in the result, so the SQL's
+// ORDER BY decides the order of rows and columns
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotAxis {
+    #[serde(default)]
+    pub rows: Vec<String>,
+    #[serde(default)]
+    pub columns: Vec<String>,
+    pub values: Vec<PivotValue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PivotValue {
+    #[serde(alias = "column_id")]
+    pub column_id: String,
+    #[serde(default)]
+    pub aggregate: PivotAggregate,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum PivotAggregate {
+    #[default]
+    Sum,
+    Average,
+    /// Non-null values, so it works on any column
+    Count,
+    Min,
+    Max,
+}
+
+impl ChartConfig {
+    pub fn base(&self) -> &BaseChartConfig {
+        match self {
+            ChartConfig::Bar(config) | ChartConfig::Line(config) => &config.base,
+            ChartConfig::Scatter(config) => &config.base,
+            ChartConfig::Pie(config) => &config.base,
+            ChartConfig::Combo(config) => &config.base,
+            ChartConfig::Metric(config) => &config.base,
+            ChartConfig::Table(config) => &config.base,
+            ChartConfig::Area(config) => &config.base,
+            ChartConfig::Funnel(config) => &config.base,
+            ChartConfig::Heatmap(config) => &config.base,
+            ChartConfig::Histogram(config) => &config.base,
+            ChartConfig::Waterfall(config) => &config.base,
+            ChartConfig::Pivot(config) => &config.base,
+        }
+    }
+
+    pub fn base_mut(&mut self) -> &mut BaseChartConfig {
+        match self {
+            ChartConfig::Bar(config) | ChartConfig::Line(config) => &mut config.base,
+            ChartConfig::Scatter(config) => &mut config.base,
+            ChartConfig::Pie(config) => &mut config.base,
+            ChartConfig::Combo(config) => &mut config.base,
+            ChartConfig::Metric(config) => &mut config.base,
+            ChartConfig::Table(config) => &mut config.base,
+            ChartConfig::Area(config) => &mut config.base,
+            ChartConfig::Funnel(config) => &mut config.base,
+            ChartConfig::Heatmap(config) => &mut config.base,
+            ChartConfig::Histogram(config) => &mut config.base,
+            ChartConfig::Waterfall(config) => &mut config.base,
+            ChartConfig::Pivot(config) => &mut config.base,
+        }
+    }
+
+    /// Columns that split the chart's rows into categories or series, such
+    /// as a bar chart's x-axis and `category`. Numeric axes are left out.
+    pub fn dimension_columns(&self) -> Vec<&str> {
+        let columns: Vec<&String> = match self {
+            ChartConfig::Bar(config) | ChartConfig::Line(config) => {
+                let axis = &config.bar_and_line_axis;
+                axis.x
+                    .iter()
+                    .chain(axis.category.iter().flatten())
+                    .collect()
+            }
+            ChartConfig::Combo(config) => {
+                let axis = &config.combo_chart_axis;
+                axis.x
+                    .iter()
+                    .chain(axis.category.iter().flatten())
+                    .collect()
+            }
+            ChartConfig::Area(config) => {
+                let axis = &config.area_chart_axis;
+                axis.x
+                    .iter()
+                    .chain(axis.category.iter().flatten())
+                    .collect()
+            }
+            ChartConfig::Scatter(config) => config.scatter_axis.category.iter().flatten().collect(),
+            ChartConfig::Histogram(config) => {
+                config.histogram_axis.category.iter().flatten().collect()
+            }
+            ChartConfig::Pie(config) => config.pie_chart_axis.x.iter().collect(),
+            ChartConfig::Funnel(config) => config.funnel_chart_axis.x.iter().collect(),
+            ChartConfig::Waterfall(config) => config.waterfall_axis.x.iter().collect(),
+            ChartConfig::Heatmap(config) => {
+                let axis = &config.heatmap_axis;
+                axis.x.iter().chain(axis.y.iter()).collect()
+            }
+            ChartConfig::Pivot(config) => {
+                let axis = &config.pivot_axis;
+                axis.rows.iter().chain(axis.columns.iter()).collect()
+            }
+            ChartConfig::Metric(_) | ChartConfig::Table(_) => Vec::new(),
+        };
+        columns.into_iter().map(String::as_str).collect()
+    }
+
+    /// Everything wrong with the config, checking column references and
+    /// their types against `metadata` when it's available.
+    pub fn problems(&self, metadata: Option<&DataMetadata>) -> Vec<String> {
+        let mut check = ColumnCheck {
+            columns: metadata.map(|m| {
+                m.column_metadata
+                    .iter()
+                    .map(|c| (c.name.to_lowercase(), &c.simple_type))
+                    .collect()
+            }),
+            problems: Vec::new(),
+        };
+        let base = self.base();
+        // Time intervals only make sense on a date x axis
+        let x_kind = match base.x_axis_config.as_ref().and_then(|c| c.x_axis_time_interval.as_ref()) {
+            Some(_) => ColumnKind::Date,
+            None => ColumnKind::Any,
+        };
+
+        match self {
+            ChartConfig::Bar(config) | ChartConfig::Line(config) => {
+                let axis = &config.bar_and_line_axis;
+                check.required("barAndLineAxis.x", &axis.x, x_kind);
+                check.required("barAndLineAxis.y", &axis.y, ColumnKind::Number);
+                check.optional("barAndLineAxis.category", &axis.category, ColumnKind::Any);
+                check.optional("barAndLineAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Scatter(config) => {
+                let axis = &config.scatter_axis;
+                check.required("scatterAxis.x", &axis.x, ColumnKind::NumberOrDate);
+                check.required("scatterAxis.y", &axis.y, ColumnKind::Number);
+                check.optional("scatterAxis.category", &axis.category, ColumnKind::Any);
+                check.optional("scatterAxis.size", &axis.size, ColumnKind::Number);
+                check.optional("scatterAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Pie(config) => {
+                let axis = &config.pie_chart_axis;
+                check.required("pieChartAxis.x", &axis.x, ColumnKind::Any);
+                check.required("pieChartAxis.y", &axis.y, ColumnKind::Number);
+                if axis.y.len() > 1 {
+                    check.problems.push(format!(
+                        "pieChartAxis.y: pie charts take exactly one y column, got {}",
+                        axis.y.len()
+                    ));
+                }
+                check.optional("pieChartAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Combo(config) => {
+                let axis = &config.combo_chart_axis;
+                check.required("comboChartAxis.x", &axis.x, x_kind);
+                check.required("comboChartAxis.y", &axis.y, ColumnKind::Number);
+                check.optional("comboChartAxis.y2", &axis.y2, ColumnKind::Number);
+                check.optional("comboChartAxis.category", &axis.category, ColumnKind::Any);
+                check.optional("comboChartAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Metric(config) => {
+                // Summing or averaging needs numbers; other aggregates work on any column
+                let kind = match config.metric_value_aggregate.as_deref() {
+                    Some("sum" | "average" | "median") => ColumnKind::Number,
+                    _ => ColumnKind::Any,
+                };
+                if config.metric_column_id.is_empty() {
+                    check.problems.push("metricColumnId: a column is required".to_string());
+                } else {
+                    check.column("metricColumnId", &config.metric_column_id, kind);
+                }
+                for (index, format) in config.conditional_formats.iter().enumerate() {
+                    let path = format!("conditionalFormats[{}]", index);
+                    match format {
+                        ConditionalFormat::Threshold { .. } | ConditionalFormat::Arrows { .. } => {
+                            check.conditional_format(&path, format)
+                        }
+                        ConditionalFormat::ColorScale { .. }
+                        | ConditionalFormat::RowHighlight { .. } => check.problems.push(format!(
+                            "{}: metric charts only take threshold and arrows rules",
+                            path
+                        )),
+                    }
+                }
+            }
+            ChartConfig::Table(config) => {
+                check.optional("tableColumnOrder", &config.table_column_order, ColumnKind::Any);
+                if let Some(widths) = &config.table_column_widths {
+                    let mut columns: Vec<&String> = widths.keys().collect();
+                    columns.sort();
+                    for column in columns {
+                        check.column("tableColumnWidths", column, ColumnKind::Any);
+                    }
+                }
+                for (index, format) in config.conditional_formats.iter().enumerate() {
+                    check.conditional_format(&format!("conditionalFormats[{}]", index), format);
+                }
+            }
+            ChartConfig::Area(config) => {
+                let axis = &config.area_chart_axis;
+                check.required("areaChartAxis.x", &axis.x, x_kind);
+                check.required("areaChartAxis.y", &axis.y, ColumnKind::Number);
+                check.optional("areaChartAxis.category", &axis.category, ColumnKind::Any);
+                check.optional("areaChartAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Funnel(config) => {
+                let axis = &config.funnel_chart_axis;
+                check.single("funnelChartAxis.x", &axis.x, ColumnKind::Any);
+                check.single("funnelChartAxis.y", &axis.y, ColumnKind::Number);
+                check.optional("funnelChartAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Heatmap(config) => {
+                let axis = &config.heatmap_axis;
+                check.single("heatmapAxis.x", &axis.x, ColumnKind::Any);
+                check.single("heatmapAxis.y", &axis.y, ColumnKind::Any);
+                check.single("heatmapAxis.value", &axis.value, ColumnKind::Number);
+                check.optional("heatmapAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+                if let Some(scale) = &config.heatmap_color_scale {
+                    if scale.len() < 2 {
+                        check.problems.push(format!(
+                            "heatmapColorScale: at least two colors are needed, got {}",
+                            scale.len()
+                        ));
+                    }
+                }
+            }
+            ChartConfig::Histogram(config) => {
+                let axis = &config.histogram_axis;
+                check.single("histogramAxis.x", &axis.x, ColumnKind::Number);
+                check.optional("histogramAxis.category", &axis.category, ColumnKind::Any);
+                match (config.histogram_bin_count, config.histogram_bin_width) {
+                    (Some(_), Some(_)) => check.problems.push(
+                        "histogramBinCount, histogramBinWidth: set only one of them".to_string(),
+                    ),
+                    (Some(count), None) if !(1..=100).contains(&count) => {
+                        check.problems.push(format!(
+                            "histogramBinCount: must be between 1 and 100, got {}",
+                            count
+                        ))
+                    }
+                    (None, Some(width)) if !(width > 0.0 && width.is_finite()) => {
+                        check.problems.push(format!(
+                            "histogramBinWidth: must be a positive number, got {}",
+                            width
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+            ChartConfig::Waterfall(config) => {
+                let axis = &config.waterfall_axis;
+                check.single("waterfallAxis.x", &axis.x, ColumnKind::Any);
+                check.single("waterfallAxis.y", &axis.y, ColumnKind::Number);
+                check.optional("waterfallAxis.tooltip", &axis.tooltip, ColumnKind::Any);
+            }
+            ChartConfig::Pivot(config) => {
+                let axis = &config.pivot_axis;
+                if axis.rows.is_empty() && axis.columns.is_empty() {
+                    check.problems.push(
+                        "pivotAxis: at least one row or column dimension is required".to_string(),
+                    );
+                }
+                for column in &axis.rows {
+                    check.column("pivotAxis.rows", column, ColumnKind::Any);
+                    if axis.columns.contains(column) {
+                        check.problems.push(format!(
+                            "pivotAxis: `{}` can't be both a row and a column dimension",
+                            column
+                        ));
+                    }
+                }
+                for column in &axis.columns {
+                    check.column("pivotAxis.columns", column, ColumnKind::Any);
+                }
+                if axis.values.is_empty() {
+                    check
+                        .problems
+                        .push("pivotAxis.values: at least one value is required".to_string());
+                }
+                for (index, value) in axis.values.iter().enumerate() {
+                    // Counting works on any column; the other aggregates need numbers
+                    let kind = match value.aggregate {
+                        PivotAggregate::Count => ColumnKind::Any,
+                        _ => ColumnKind::Number,
+                    };
+                    let path = format!("pivotAxis.values[{}].columnId", index);
+                    check.column(&path, &value.column_id, kind);
+                }
+                let max_columns = config.max_columns();
+                if !(1..=MAX_PIVOT_COLUMNS).contains(&max_columns) {
+                    check.problems.push(format!(
+                        "pivotMaxColumns: must be between 1 and {}, got {}",
+                        MAX_PIVOT_COLUMNS, max_columns
+                    ));
+                } else if axis.values.len() > max_columns as usize {
+                    check.problems.push(format!(
+                        "pivotMaxColumns: {} is fewer than the {} values to show",
+                        max_columns,
+                        axis.values.len()
+                    ));
+                }
+            }
+        }
+
+        for (column, format) in &base.column_label_formats {
+            check.column("columnLabelFormats", column, ColumnKind::Any);
+            check.label_format(column, &format.column_type);
+        }
+        if let Some(settings) = &base.column_settings {
+            for column in settings.keys() {
+                check.column("columnSettings", column, ColumnKind::Any);
+            }
+        }
+        for trendline in base.trendlines.iter().flatten() {
+            check.column("trendlines.columnId", &trendline.column_id, ColumnKind::Number);
+        }
+
+        check.problems
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ColumnKind {
+    Any,
+    Number,
+    Date,
+    NumberOrDate,
+}
+
+impl ColumnKind {
+    fn accepts(self, simple_type: &SimpleType) -> bool {
+        match (self, simple_type) {
+            // Nothing is known about `other` columns, so don't reject them
+            (ColumnKind::Any, _) | (_, SimpleType::Other) => true,
+            (ColumnKind::Number, SimpleType::Number) => true,
+            (ColumnKind::Date, SimpleType::Date) => true,
+            (ColumnKind::NumberOrDate, SimpleType::Number | SimpleType::Date) => true,
+            _ => false,
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            ColumnKind::Any => "any",
+            ColumnKind::Number => "a number",
+            ColumnKind::Date => "a date",
+            ColumnKind::NumberOrDate => "a number or date",
+        }
+    }
+}
+
+fn simple_type_name(simple_type: &SimpleType) -> &'static str {
+    match simple_type {
+        SimpleType::Number => "number",
+        SimpleType::String => "string",
+        SimpleType::Date => "date",
+        SimpleType::Boolean => "boolean",
+        SimpleType::Other => "other",
+    }
+}
+
+/// Collects chart config problems, checking columns only when the result
+/// columns are known.
+struct ColumnCheck<'a> {
+    columns: Option<HashMap<String, &'a SimpleType>>,
+    problems: Vec<String>,
+}
+
+impl ColumnCheck<'_> {
+    fn column(&mut self, path: &str, column: &str, kind: ColumnKind) {
+        let Some(columns) = &self.columns else {
+            return;
+        };
+        match columns.get(&column.to_lowercase()) {
+            None => self
+                .problems
+                .push(format!("{}: `{}` is not returned by the SQL", path, column)),
+            Some(simple_type) if !kind.accepts(simple_type) => self.problems.push(format!(
+                "{}: `{}` is a {} column, but {} column is needed",
+                path,
+                column,
+                simple_type_name(simple_type),
+                kind.describe()
+            )),
+            Some(_) => {}
+        }
+    }
+
+    fn required(&mut self, path: &str, columns: &[String], kind: ColumnKind) {
+        if columns.is_empty() {
+            self.problems.push(format!("{}: at least one column is required", path));
+        }
+        for column in columns {
+            self.column(path, column, kind);
+        }
+    }
+
+    fn single(&mut self, path: &str, columns: &[String], kind: ColumnKind) {
+        if columns.len() > 1 {
+            self.problems.push(format!(
+                "{}: exactly one column is allowed, got {}",
+                path,
+                columns.len()
+            ));
+        }
+        self.required(path, columns, kind);
+    }
+
+    fn optional(&mut self, path: &str, columns: &Option<Vec<String>>, kind: ColumnKind) {
+        for column in columns.iter().flatten() {
+            self.column(path, column, kind);
+        }
+    }
+
+    fn conditional_format(&mut self, path: &str, format: &ConditionalFormat) {
+        match format {
+            ConditionalFormat::Threshold { condition, color } => {
+                self.condition(path, condition);
+                self.color(&format!("{}.color", path), color);
+            }
+            ConditionalFormat::ColorScale {
+                column_id,
+                colors,
+                min,
+                max,
+            } => {
+                self.column(&format!("{}.columnId", path), column_id, ColumnKind::Number);
+                if colors.len() < 2 {
+                    self.problems.push(format!(
+                        "{}.colors: at least two colors are needed, got {}",
+                        path,
+                        colors.len()
+                    ));
+                }
+                for color in colors {
+                    self.color(&format!("{}.colors", path), color);
+                }
+                if let (Some(min), Some(max)) = (min, max) {
+                    if min >= max {
+                        self.problems.push(format!(
+                            "{}: min ({}) must be less than max ({})",
+                            path, min, max
+                        ));
+                    }
+                }
+            }
+            ConditionalFormat::Arrows {
+                column_id,
+                comparison_column_id,
+                ..
+            } => {
+                self.column(&format!("{}.columnId", path), column_id, ColumnKind::Number);
+                self.column(
+                    &format!("{}.comparisonColumnId", path),
+                    comparison_column_id,
+                    ColumnKind::Number,
+                );
+            }
+            ConditionalFormat::RowHighlight { conditions, color } => {
+                if conditions.is_empty() {
+                    self.problems.push(format!(
+                        "{}.conditions: at least one condition is required",
+                        path
+                    ));
+                }
+                for (index, condition) in conditions.iter().enumerate() {
+                    self.condition(&format!("{}.conditions[{}]", path, index), condition);
+                }
+                self.color(&format!("{}.color", path), color);
+            }
+        }
+    }
+
+    fn condition(&mut self, path: &str, condition: &FormatCondition) {
+        let value = &condition.value;
+        let kind = match condition.operator {
+            FormatOperator::Gt | FormatOperator::Gte | FormatOperator::Lt | FormatOperator::Lte => {
+                if !value.is_number() {
+                    self.problems.push(format!(
+                        "{}.value: comparisons need a number, got {}",
+                        path, value
+                    ));
+                }
+                ColumnKind::Number
+            }
+            FormatOperator::Eq | FormatOperator::Neq => {
+                if !(value.is_number() || value.is_string() || value.is_boolean()) {
+                    self.problems.push(format!(
+                        "{}.value: must be a number, string or boolean, got {}",
+                        path, value
+                    ));
+                }
+                ColumnKind::Any
+            }
+        };
+        self.column(&format!("{}.columnId", path), &condition.column_id, kind);
+    }
+
+    fn color(&mut self, path: &str, color: &str) {
+        if !HEX_COLOR_RE.is_match(color) {
+            self.problems.push(format!(
+                "{}: `{}` is not a hex color like \"#E53935\"",
+                path, color
+            ));
+        }
+    }
+
+    /// `columnType` must describe the column's data, as the UI formats by it
+    fn label_format(&mut self, column: &str, column_type: &str) {
+        let Some(simple_type) = self
+            .columns
+            .as_ref()
+            .and_then(|columns| columns.get(&column.to_lowercase()))
+        else {
+            return;
+        };
+        let expected = ColumnLabelFormat::new_for_type(simple_type).column_type;
+        let checked = ["number", "string", "date"];
+        if checked.contains(&column_type) && column_type != expected && **simple_type != SimpleType::Other {
+            self.problems.push(format!(
+                "columnLabelFormats.{}: columnType is `{}` but the column is a {} column",
+                column,
+                column_type,
+                simple_type_name(simple_type)
+            ));
+        }
+    }
+}
+
+impl MetricYml {
+    pub fn new(yml_content: String) -> Result<Self> {
+        Ok(Self::parse(&yml_content)?)
+    }
+
+    /// Reads a metric file as written, collecting every problem with its
+    /// line, column and path. Parsing stops at a syntax or schema error;
+    /// otherwise every validation problem is reported.
+    pub fn parse(yml_content: &str) -> std::result::Result<Self, YmlErrors> {
+        let (mut file, positions) =
+            read_yml::<MetricYml>(yml_content, lowercase_column_identifiers, |document| {
+                // Errors inside the internally tagged chart config carry no path
+                let chart_config = document
+                    .get("chartConfig")
+                    .or_else(|| document.get("chart_config"))?;
+                serde_yaml::from_value::<ChartConfig>(chart_config.clone())
+                    .is_err()
+                    .then_some("chartConfig")
+            })?;
+
+        if file.time_frame.is_empty() {
+            file.time_frame = match &file.time_range {
+                Some(time_range) => time_range.describe(),
+                None => "all_time".to_string(),
+            };
+        }
+
+        let errors: Vec<YmlError> = file
+            .problems(None)
+            .iter()
+            .map(|(path, problem)| positions.problem(path, problem))
+            .collect();
+        if errors.is_empty() {
+            Ok(file)
+        } else {
+            Err(YmlErrors(errors))
+        }
+    }
+
+    /// Checks the chart config against the columns the metric's SQL returns.
+    /// Without `metadata` only the checks that don't need the result columns
+    /// run. Every problem is reported, one per line, grouped by section.
+    pub fn validate(&self, metadata: Option<&DataMetadata>) -> Result<()> {
+        let problems = self.problems(metadata);
+        if problems.is_empty() {
+            return Ok(());
+        }
+
+        let mut sections: Vec<(&str, Vec<&str>)> = Vec::new();
+        for (path, problem) in &problems {
+            let section = path.split(['.', '[']).next().unwrap_or_default();
+            match sections.iter_mut().find(|(name, _)| *name == section) {
+                Some((_, section_problems)) => section_problems.push(problem),
+                None => sections.push((section, vec![problem])),
+            }
+        }
+
+        let mut messages = Vec::new();
+        for (section, section_problems) in &sections {
+            let mut message = format!("{} problem(s) in {}:", section_problems.len(), section);
+            for problem in section_problems {
+                message.push_str("\n- ");
+                message.push_str(problem);
+            }
+            messages.push(message);
+        }
+        let mut message = messages.join("\n");
+        let chart_problems = sections
+            .iter()
+            .any(|(section, _)| *section == "chartConfig");
+        if let Some(metadata) = metadata.filter(|_| chart_problems) {
+            let columns: Vec<&str> = metadata
+                .column_metadata
+                .iter()
+                .map(|c| c.name.as_str())
+                .collect();
+            message.push_str(&format!(
+                "\nColumns returned by the SQL: {}",
+                columns.join(", ")
+            ));
+        }
+        Err(anyhow::anyhow!(message))
+    }
+
+    /// Every problem validation finds, with the path of the part of the file
+    /// it is about
+    fn problems(&self, metadata: Option<&DataMetadata>) -> Vec<(String, String)> {
+        let mut problems = self.parameter_problems();
+
+        if let Some(time_range) = &self.time_range {
+            for problem in time_range.problems() {
+                problems.push(("timeRange".to_string(), problem));
+            }
+        }
+
+        if let Some(comparison) = &self.comparison {
+            for problem in comparison.problems(self.time_range.as_ref(), metadata) {
+                problems.push(("comparison".to_string(), problem));
+            }
+        }
+        if let ChartConfig::Metric(config) = &self.chart_config {
+            if config.comparison_delta.is_some() && self.comparison.is_none() {
+                problems.push((
+                    "comparison".to_string(),
+                    "chartConfig.comparisonDelta needs a comparison to show".to_string(),
+                ));
+            }
+        }
+
+        for (index, drill) in self.drills.iter().enumerate() {
+            for problem in drill.problems(metadata) {
+                problems.push((format!("drills[{}]", index), problem));
+            }
+        }
+
+        for problem in self.chart_config.problems(metadata) {
+            problems.push(("chartConfig".to_string(), problem));
+        }
+
+        problems
+    }
+
+    /// Checks that parameter declarations are well formed and match the
+    /// `{{ name }}` placeholders used in the sql.
+    fn parameter_problems(&self) -> Vec<(String, String)> {
+        let mut problems = Vec::new();
+        let mut declared = HashMap::new();
+
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            let path = format!("parameters[{}]", index);
+            if !PARAMETER_NAME_RE.is_match(&parameter.name) {
+                problems.push((
+                    path.clone(),
+                    format!(
+                        "'{}' is not a valid parameter name; use letters, digits and underscores",
+                        parameter.name
+                    ),
+                ));
+            }
+            if declared
+                .insert(parameter.name.as_str(), parameter)
+                .is_some()
+            {
+                problems.push((
+                    path.clone(),
+                    format!("Parameter '{}' is declared more than once", parameter.name),
+                ));
+            }
+            for value in &parameter.allowed_values {
+                if let Err(e) = parameter.check_value(value) {
+                    problems.push((path.clone(), format!("allowedValues: {}", e)));
+                }
+            }
+            if let Err(e) = parameter.check_value(&parameter.default) {
+                problems.push((path, format!("default: {}", e)));
+            }
+        }
+
+        let mut used: Vec<&str> = Vec::new();
+        for captures in PARAMETER_RE.captures_iter(&self.sql) {
+            let name = captures.get(1).map_or("", |m| m.as_str());
+            if !used.contains(&name) {
+                used.push(name);
+            }
+        }
+        for name in &used {
+            if !declared.contains_key(name) {
+                problems.push((
+                    "sql".to_string(),
+                    format!(
+                        "'{}' is used in the sql but not declared in parameters",
+                        name
+                    ),
+                ));
+            }
+        }
+        for (index, parameter) in self.parameters.iter().enumerate() {
+            if !used.contains(&parameter.name.as_str()) {
+                problems.push((
+                    format!("parameters[{}]", index),
+                    format!(
+                        "Parameter '{}' is declared but never used in the sql",
+                        parameter.name
+                    ),
+                ));
+            }
+        }
+
+        problems
+    }
+
+    /// Resolves the value of every parameter, taking `values` where given and
+    /// defaults otherwise. Values for undeclared parameters are rejected.
+    pub fn resolve_parameters(
+        &self,
+        values: &HashMap<String, serde_json::Value>,
+    ) -> Result<Vec<(&MetricParameter, serde_json::Value)>> {
+        if let Some(unknown) = values
+            .keys()
+            .find(|name| !self.parameters.iter().any(|p| &p.name == *name))
+        {
+            return Err(anyhow::anyhow!("Metric has no parameter '{}'", unknown));
+        }
+
+        self.parameters
+            .iter()
+            .map(|parameter| Ok((parameter, parameter.resolve(values.get(&parameter.name))?)))
+            .collect()
+    }
+
+    /// JSON Schema for metric files, for editors to validate and autocomplete against.
+    pub fn json_schema() -> RootSchema {
+        schema_for!(MetricYml)
+    }
+}
+
+impl FromSql<Jsonb, Pg> for MetricYml {
+    fn from_sql(bytes: diesel::pg::PgValue) -> diesel::deserialize::Result<Self> {
+        let value = <serde_json::Value as FromSql<Jsonb, Pg>>::from_sql(bytes)?;
+        Ok(serde_json::from_value(value)?)
+    }
+}
+
+impl ToSql<Jsonb, Pg> for MetricYml {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> diesel::serialize::Result {
+        out.write_all(&[1])?;
+        out.write_all(&serde_json::to_vec(self)?)?;
+        Ok(IsNull::No)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::types::{ColumnMetaData, ColumnType, DataMetadata, SimpleType, YmlErrorKind};
+
+    use super::*;
+
+    /// Checks a fixture against the JSON Schema published for metric files
+    fn assert_schema_accepts(yml_content: &str) {
+        let schema = serde_json::to_value(MetricYml::json_schema()).unwrap();
+        let schema = jsonschema::JSONSchema::compile(&schema).unwrap();
+        let instance: serde_json::Value = serde_yaml::from_str(yml_content).unwrap();
+        let errors: Vec<String> = match schema.validate(&instance) {
+            Ok(()) => return,
+            Err(errors) => errors
+                .map(|e| format!("{}: {}", e.instance_path, e))
+                .collect(),
+        };
+        panic!("schema rejected fixture: {:?}", errors);
+    }
+
+    fn normalize_whitespace(s: &str) -> String {
         s.split_whitespace().collect::<Vec<&str>>().join(" ")
     }
 
@@ -1004,6 +2440,7 @@ chartConfig:
       type: linear
 "#;
         let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
         match metric.chart_config {
             ChartConfig::Line(config) => {
                 assert!(config.base.trendlines.is_some());
@@ -1032,6 +2469,7 @@ chartConfig:
   metricColumnId: TOTAL_COUNT # Uppercase ID
 "#;
         let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
         match metric.chart_config {
             ChartConfig::Metric(config) => {
                 assert_eq!(config.metric_column_id, "total_count"); // Verify lowercase
@@ -1060,6 +2498,7 @@ chartConfig:
     COL_B: 100 # Uppercase Key
 "#;
         let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
         match metric.chart_config {
             ChartConfig::Table(config) => {
                 assert!(config.table_column_order.is_some());
@@ -1095,6 +2534,7 @@ chartConfig:
     y: [VALUE_COL]
 "#;
         let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
 
         // Check default timeframe was inserted
         assert_eq!(metric.time_frame, "all_time");
@@ -1114,10 +2554,10 @@ chartConfig:
     }
 
     #[test]
-    fn test_mixed_case_sanitization_and_lowercase() -> Result<()> {
+    fn test_text_kept_as_written_and_columns_lowercased() -> Result<()> {
         // Use raw string literal
         let yml_content = r#"
-name: Mixed Case Test: Needs 'Sanitization' and LOWERCASE
+name: "Mixed Case Test: Keeps 'Quotes', 100% and LOWERCASE"
 sql: SELECT Category, "Total Sales" FROM sales_data
 timeFrame: all_time
 datasetIds: ["00000000-0000-0000-0000-000000000001"]
@@ -1131,24 +2571,25 @@ chartConfig:
     y: ["Total Sales"]
 "#;
         let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
 
-        // Check name was sanitized
+        // Check name was kept as written
         assert_eq!(
             metric.name,
-            "Mixed Case Test Needs Sanitization and LOWERCASE"
+            "Mixed Case Test: Keeps 'Quotes', 100% and LOWERCASE"
         );
 
         // Check column names were lowercased
         match metric.chart_config {
             ChartConfig::Bar(config) => {
                 assert!(config.base.column_label_formats.contains_key("category"));
-                assert!(config.base.column_label_formats.contains_key("total sales")); // Note: Sanitization happens before lowercasing structural keys
+                assert!(config.base.column_label_formats.contains_key("total sales"));
 
                 let cat_format = config.base.column_label_formats.get("category").unwrap();
                 assert_eq!(
                     cat_format.display_name,
-                    Some("Product Category".to_string())
-                ); // Check displayName sanitization
+                    Some("Product Category:".to_string())
+                );
 
                 assert_eq!(config.bar_and_line_axis.x, vec!["category"]);
                 assert_eq!(config.bar_and_line_axis.y, vec!["total sales"]);
@@ -1158,6 +2599,86 @@ chartConfig:
         Ok(())
     }
 
+    #[test]
+    fn test_errors_located_by_line_column_and_path() {
+        let errors = |yml: &str| MetricYml::parse(yml).unwrap_err().0;
+
+        // An unquoted `: ` in a name is a syntax error on that line
+        let syntax = errors("name: Revenue: by month\nsql: SELECT 1\n");
+        assert_eq!(syntax.len(), 1);
+        assert_eq!(syntax[0].kind, YmlErrorKind::Syntax);
+        assert_eq!(syntax[0].line, Some(1));
+        assert_eq!(syntax[0].path.as_deref(), Some("name"));
+
+        // Serde reports the path for fields outside the chart config
+        let sql = errors(
+            "name: A\nsql: [1]\nchartConfig:\n  selectedChartType: bar\n  barAndLineAxis:\n    x: [a]\n    y: [b]\n  columnLabelFormats: {}\n",
+        );
+        assert_eq!(sql[0].kind, YmlErrorKind::Schema);
+        assert_eq!(sql[0].path.as_deref(), Some("sql"));
+        assert_eq!(sql[0].line, Some(2));
+
+        // Inside the chart config the path comes from the value serde quotes
+        let axis = errors(
+            "name: A\nsql: SELECT 1\nchartConfig:\n  selectedChartType: bar\n  barAndLineAxis:\n    x: a\n    y: [b]\n  columnLabelFormats: {}\n",
+        );
+        assert_eq!(
+            axis[0].path.as_deref(),
+            Some("chartConfig.barAndLineAxis.x")
+        );
+        assert_eq!((axis[0].line, axis[0].column), (Some(6), Some(5)));
+
+        // An unquoted hex color reads as an empty value
+        let color = errors(
+            "name: A\nsql: SELECT 1\nchartConfig:\n  selectedChartType: bar\n  colors:\n    - #FF0000\n  barAndLineAxis:\n    x: [a]\n    y: [b]\n  columnLabelFormats: {}\n",
+        );
+        assert_eq!(color[0].path.as_deref(), Some("chartConfig.colors[0]"));
+        assert_eq!(color[0].line, Some(6));
+        assert!(color[0].message.contains("Quote values that start with #"));
+
+        let missing = errors("name: A\nsql: SELECT 1\nchart_config:\n  selectedChartType: bar\n");
+        assert_eq!(missing[0].path.as_deref(), Some("chartConfig"));
+        assert_eq!(missing[0].line, Some(3));
+        assert!(missing[0]
+            .message
+            .contains("missing field `barAndLineAxis`"));
+
+        // Every validation problem is reported, each at its own field
+        let validation = errors(
+            r#"name: A
+sql: SELECT {{ region }}
+parameters:
+  - name: start
+    type: date
+    default: 2024-01-01
+chartConfig:
+  selectedChartType: table
+  conditionalFormats:
+    - type: threshold
+      columnId: segment
+      operator: eq
+      value: Enterprise
+      color: green
+  columnLabelFormats: {}
+"#,
+        );
+        let found: Vec<(Option<&str>, Option<usize>)> = validation
+            .iter()
+            .map(|e| (e.path.as_deref(), e.line))
+            .collect();
+        assert!(validation
+            .iter()
+            .all(|e| e.kind == YmlErrorKind::Validation));
+        assert!(found.contains(&(Some("sql"), Some(2))), "{:?}", found);
+        assert!(
+            found.contains(&(Some("parameters[0]"), Some(4))),
+            "{:?}",
+            found
+        );
+        let color = Some("chartConfig.conditionalFormats[0].color");
+        assert!(found.contains(&(color, Some(14))), "{:?}", found);
+    }
+
     // ... existing tests ...
     #[test]
     fn test_column_label_format_constructors() {
@@ -1230,4 +2751,634 @@ chartConfig:
         assert_eq!(date_format.column_type, "date");
         assert_eq!(date_format.style, "date");
     }
+
+    fn column(name: &str, simple_type: SimpleType) -> ColumnMetaData {
+        ColumnMetaData {
+            name: name.to_string(),
+            min_value: json!(null),
+            max_value: json!(null),
+            unique_values: 1,
+            simple_type,
+            column_type: ColumnType::Other,
+        }
+    }
+
+    #[test]
+    fn test_validate_chart_config_against_metadata() -> Result<()> {
+        let yml_content = r#"name: Revenue by Region
+sql: SELECT region, order_date, revenue FROM orders
+timeFrame: all_time
+chartConfig:
+  selectedChartType: line
+  xAxisTimeInterval: month
+  barAndLineAxis:
+    x: [region]
+    y: [revenue, profit]
+  columnLabelFormats:
+    region:
+      columnType: string
+      style: string
+    revenue:
+      columnType: string
+      style: string
+    discount:
+      columnType: number
+      style: number
+  trendlines:
+    - type: linear_regression
+      columnId: region
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        let metadata = DataMetadata {
+            column_count: 3,
+            row_count: 1,
+            column_metadata: vec![
+                column("REGION", SimpleType::String),
+                column("order_date", SimpleType::Date),
+                column("revenue", SimpleType::Number),
+            ],
+        };
+
+        let problems = metric.chart_config.problems(Some(&metadata));
+        assert_eq!(
+            problems,
+            vec![
+                "barAndLineAxis.x: `region` is a string column, but a date column is needed",
+                "barAndLineAxis.y: `profit` is not returned by the SQL",
+                "columnLabelFormats.revenue: columnType is `string` but the column is a number column",
+                "columnLabelFormats: `discount` is not returned by the SQL",
+                "trendlines.columnId: `region` is a string column, but a number column is needed",
+            ]
+        );
+
+        let error = metric.validate(Some(&metadata)).unwrap_err().to_string();
+        assert!(error.starts_with("5 problem(s) in chartConfig:"));
+        assert!(error.ends_with("Columns returned by the SQL: REGION, order_date, revenue"));
+
+        // Column checks need the result columns
+        assert!(metric.validate(None).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_pie_chart_shape() -> Result<()> {
+        let yml_content = r#"name: Revenue Share
+sql: SELECT region, revenue, profit FROM orders
+chartConfig:
+  selectedChartType: pie
+  pieChartAxis:
+    x: [region]
+    y: [revenue, profit]
+  columnLabelFormats: {}
+"#;
+        let error = MetricYml::new(yml_content.to_string()).unwrap_err().to_string();
+        assert!(error.contains("pieChartAxis.y: pie charts take exactly one y column, got 2"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_chart_types() -> Result<()> {
+        let metric = |chart_config: &str| {
+            MetricYml::new(format!(
+                "name: Chart\nsql: SELECT 1\nchartConfig:\n{}  columnLabelFormats: {{}}\n",
+                chart_config
+            ))
+        };
+
+        let area = metric("  selectedChartType: area\n  areaChartAxis:\n    x: [Month]\n    y: [revenue]\n  areaGroupType: percentage-stack\n")?;
+        match &area.chart_config {
+            ChartConfig::Area(config) => {
+                assert_eq!(config.area_chart_axis.x, vec!["month"]);
+                assert_eq!(config.area_group_type, Some(AreaGroupType::PercentageStack));
+            }
+            _ => panic!("Expected area chart config"),
+        }
+
+        let funnel = metric("  selectedChartType: funnel\n  funnelChartAxis:\n    x: [stage, step]\n    y: [users]\n")
+            .unwrap_err()
+            .to_string();
+        assert!(funnel.contains("funnelChartAxis.x: exactly one column is allowed, got 2"));
+
+        let heatmap = metric("  selectedChartType: heatmap\n  heatmap_axis:\n    x: [months_since_signup]\n    y: [cohort]\n    value: []\n  heatmapColorScale: [\"#FFFFFF\"]\n")
+            .unwrap_err()
+            .to_string();
+        assert!(heatmap.contains("heatmapAxis.value: at least one column is required"));
+        assert!(heatmap.contains("heatmapColorScale: at least two colors are needed, got 1"));
+
+        let histogram = metric("  selectedChartType: histogram\n  histogramAxis:\n    x: [order_amount]\n  histogramBinCount: 20\n  histogramBinWidth: 5\n")
+            .unwrap_err()
+            .to_string();
+        assert!(histogram.contains("set only one of them"));
+
+        let waterfall = metric("  selectedChartType: waterfall\n  waterfallAxis:\n    x: [step]\n    y: [change]\n  waterfallShowTotal: true\n")?;
+        let json = serde_json::to_value(&waterfall.chart_config)?;
+        assert_eq!(json["selectedChartType"], "waterfall");
+        assert_eq!(json["waterfallShowTotal"], true);
+
+        let metadata = DataMetadata {
+            column_count: 2,
+            row_count: 1,
+            column_metadata: vec![
+                column("step", SimpleType::String),
+                column("change", SimpleType::String),
+            ],
+        };
+        let problems = waterfall.chart_config.problems(Some(&metadata));
+        assert_eq!(
+            problems,
+            vec!["waterfallAxis.y: `change` is a string column, but a number column is needed"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_heatmap_formats_leave_missing_cells_empty() -> Result<()> {
+        let metric = MetricYml::new(
+            "name: Retention\nsql: SELECT 1\nchartConfig:\n  selectedChartType: heatmap\n  heatmapAxis:\n    x: [months_since_signup]\n    y: [cohort]\n    value: [retention]\n  columnLabelFormats: {}\n"
+                .to_string(),
+        )?;
+        let metadata = DataMetadata {
+            column_count: 3,
+            row_count: 1,
+            column_metadata: vec![
+                column("cohort", SimpleType::Date),
+                column("months_since_signup", SimpleType::Number),
+                column("retention", SimpleType::Number),
+            ],
+        };
+
+        let formats =
+            ColumnLabelFormat::generate_formats_for_chart(&metadata, &metric.chart_config);
+
+        assert_eq!(formats["retention"].replace_missing_data_with, None);
+        assert_eq!(
+            formats["months_since_signup"].replace_missing_data_with,
+            Some(json!(0))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pivot_chart_config() -> Result<()> {
+        let yml_content = r#"name: Revenue by Region and Quarter
+sql: SELECT region, country, quarter, revenue, order_id FROM orders
+chartConfig:
+  selectedChartType: pivot
+  pivotAxis:
+    rows: [Region, Country]
+    columns: [Quarter]
+    values:
+      - columnId: Revenue
+      - columnId: order_id
+        aggregate: count
+  pivotShowSubtotals: false
+  columnLabelFormats: {}
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        let ChartConfig::Pivot(config) = &metric.chart_config else {
+            panic!("Expected pivot chart config");
+        };
+        assert_eq!(config.pivot_axis.rows, vec!["region", "country"]);
+        assert_eq!(config.pivot_axis.columns, vec!["quarter"]);
+        assert_eq!(config.pivot_axis.values[0].column_id, "revenue");
+        assert_eq!(config.pivot_axis.values[0].aggregate, PivotAggregate::Sum);
+        assert_eq!(config.pivot_axis.values[1].aggregate, PivotAggregate::Count);
+        assert!(!config.show_subtotals());
+        assert!(config.show_grand_totals());
+        assert_eq!(config.max_columns(), 50);
+
+        let metadata = DataMetadata {
+            column_count: 5,
+            row_count: 1,
+            column_metadata: vec![
+                column("region", SimpleType::String),
+                column("country", SimpleType::String),
+                column("quarter", SimpleType::String),
+                column("revenue", SimpleType::String),
+                column("order_id", SimpleType::String),
+            ],
+        };
+        assert_eq!(
+            metric.chart_config.problems(Some(&metadata)),
+            vec![
+                "pivotAxis.values[0].columnId: `revenue` is a string column, but a number column is needed"
+            ]
+        );
+
+        let invalid = yml_content
+            .replace("columns: [Quarter]", "columns: [country]")
+            .replace("pivotShowSubtotals: false", "pivotMaxColumns: 1");
+        let error = MetricYml::new(invalid).unwrap_err().to_string();
+        assert!(error.contains("`country` can't be both a row and a column dimension"));
+        assert!(error.contains("pivotMaxColumns: 1 is fewer than the 2 values to show"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_conditional_formats() -> Result<()> {
+        let yml_content = r##"name: Churn by Segment
+sql: SELECT segment, churn_rate, prior_churn_rate, accounts FROM churn
+chartConfig:
+  selectedChartType: table
+  conditionalFormats:
+    - type: rowHighlight
+      conditions:
+        - columnId: Churn_Rate
+          operator: gt
+          value: 0.05
+      color: "#E53935"
+    - type: colorScale
+      columnId: accounts
+      colors: ["#FFFFFF", "#1E88E5"]
+    - type: arrows
+      columnId: churn_rate
+      comparisonColumnId: prior_churn_rate
+      lowerIsBetter: true
+    - type: threshold
+      columnId: segment
+      operator: eq
+      value: Enterprise
+      color: "#43A047"
+  columnLabelFormats: {}
+"##;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        let ChartConfig::Table(config) = &metric.chart_config else {
+            panic!("Expected table chart config");
+        };
+        assert_eq!(
+            config.conditional_formats[0],
+            ConditionalFormat::RowHighlight {
+                conditions: vec![FormatCondition {
+                    column_id: "churn_rate".to_string(),
+                    operator: FormatOperator::Gt,
+                    value: json!(0.05),
+                }],
+                color: "#E53935".to_string(),
+            }
+        );
+        let json = serde_json::to_value(&metric.chart_config)?;
+        assert_eq!(json["conditionalFormats"][3]["operator"], "eq");
+        assert_eq!(json["conditionalFormats"][3]["columnId"], "segment");
+
+        let metadata = DataMetadata {
+            column_count: 4,
+            row_count: 1,
+            column_metadata: vec![
+                column("segment", SimpleType::String),
+                column("churn_rate", SimpleType::Number),
+                column("prior_churn_rate", SimpleType::String),
+                column("accounts", SimpleType::Number),
+            ],
+        };
+        assert_eq!(
+            metric.chart_config.problems(Some(&metadata)),
+            vec!["conditionalFormats[2].comparisonColumnId: `prior_churn_rate` is a string column, but a number column is needed"]
+        );
+
+        let invalid = yml_content
+            .replace("value: 0.05", "value: 5%")
+            .replace("\"#43A047\"", "green");
+        let error = MetricYml::new(invalid).unwrap_err().to_string();
+        assert!(error.contains(
+            "conditionalFormats[0].conditions[0].value: comparisons need a number, got \"5%\""
+        ));
+        assert!(error.contains("conditionalFormats[3].color: `green` is not a hex color"));
+
+        let metric_chart = r##"name: Churn
+sql: SELECT churn_rate FROM churn
+chartConfig:
+  selectedChartType: metric
+  metricColumnId: churn_rate
+  conditionalFormats:
+    - type: colorScale
+      columnId: churn_rate
+      colors: ["#FFFFFF", "#E53935"]
+  columnLabelFormats: {}
+"##;
+        let error = MetricYml::new(metric_chart.to_string())
+            .unwrap_err()
+            .to_string();
+        assert!(error
+            .contains("conditionalFormats[0]: metric charts only take threshold and arrows rules"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_metric_parameters() -> Result<()> {
+        let yml_content = r#"name: Large Orders
+sql: |
+  SELECT region, COUNT(*) AS orders
+  FROM orders
+  WHERE amount >= {{ min_amount }} AND created_at >= {{start_date}}
+  GROUP BY region
+parameters:
+  - name: min_amount
+    type: number
+    default: 100
+    allowedValues: [100, 500, 1000]
+  - name: start_date
+    type: date
+    default: 2024-01-01
+chartConfig:
+  selectedChartType: bar
+  barAndLineAxis:
+    x: [region]
+    y: [orders]
+  columnLabelFormats: {}
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        assert_eq!(metric.parameters.len(), 2);
+        assert_eq!(metric.parameters[1].param_type, MetricParameterType::Date);
+
+        let values = HashMap::from([("min_amount".to_string(), json!(500))]);
+        let resolved = metric.resolve_parameters(&values)?;
+        assert_eq!(resolved[0].1, json!(500));
+        assert_eq!(resolved[1].1, json!("2024-01-01"));
+
+        let not_allowed = HashMap::from([("min_amount".to_string(), json!(250))]);
+        assert!(metric.resolve_parameters(&not_allowed).is_err());
+        let wrong_type = HashMap::from([("start_date".to_string(), json!("last week"))]);
+        assert!(metric.resolve_parameters(&wrong_type).is_err());
+        let unknown = HashMap::from([("region".to_string(), json!("EU"))]);
+        assert!(metric.resolve_parameters(&unknown).is_err());
+
+        let undeclared = yml_content.replace("{{start_date}}", "{{ end_date }}");
+        let error = MetricYml::new(undeclared).unwrap_err().to_string();
+        assert!(error.contains("'end_date' is used in the sql but not declared in parameters"));
+        assert!(error.contains("Parameter 'start_date' is declared but never used in the sql"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_ranges() -> Result<()> {
+        let yml_content = r#"name: Recent Revenue
+timeRange:
+  column: orders.created_at
+  type: last
+  count: 30
+  unit: day
+sql: SELECT created_at, SUM(amount) AS revenue FROM orders GROUP BY created_at
+chartConfig:
+  selectedChartType: line
+  barAndLineAxis:
+    x: [created_at]
+    y: [revenue]
+  columnLabelFormats: {}
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        assert_eq!(metric.time_frame, "Last 30 days");
+        let range = metric.time_range.clone().unwrap();
+        assert_eq!(range.column, "orders.created_at");
+
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let today = date("2024-05-15");
+        assert_eq!(
+            range.resolve(today)?,
+            (Some(date("2024-04-16")), Some(date("2024-05-16")))
+        );
+
+        let resolve = |period: TimePeriod| {
+            TimeRange {
+                column: "created_at".to_string(),
+                period,
+            }
+            .resolve(today)
+            .unwrap()
+        };
+        assert_eq!(
+            resolve(TimePeriod::ToDate {
+                unit: TimeUnit::Quarter
+            }),
+            (Some(date("2024-04-01")), Some(date("2024-05-16")))
+        );
+        assert_eq!(
+            resolve(TimePeriod::Previous {
+                unit: TimeUnit::Quarter
+            }),
+            (Some(date("2024-01-01")), Some(date("2024-04-01")))
+        );
+        // 2024-05-15 is a Wednesday
+        assert_eq!(
+            resolve(TimePeriod::Last {
+                count: 2,
+                unit: TimeUnit::Week
+            }),
+            (Some(date("2024-05-06")), Some(date("2024-05-20")))
+        );
+        assert_eq!(
+            resolve(TimePeriod::Absolute {
+                start: None,
+                end: Some("2023-12-31".to_string()),
+            }),
+            (None, Some(date("2024-01-01")))
+        );
+
+        // An explicit timeFrame label is kept
+        let labelled = yml_content.replace("timeRange:", "timeFrame: Past month\ntimeRange:");
+        assert_eq!(MetricYml::new(labelled)?.time_frame, "Past month");
+
+        let invalid = yml_content.replace(
+            "  type: last\n  count: 30\n  unit: day",
+            "  type: absolute\n  start: 2024-03-01\n  end: 2024-02-01",
+        );
+        let error = MetricYml::new(invalid).unwrap_err().to_string();
+        assert!(error.contains("start 2024-03-01 is after end 2024-02-01"));
+
+        let bad_column = yml_content.replace("orders.created_at", "created_at; DROP TABLE x");
+        let error = MetricYml::new(bad_column).unwrap_err().to_string();
+        assert!(error.contains("is not a valid column name"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drills() -> Result<()> {
+        let yml_content = r#"name: Revenue by Region
+sql: SELECT region, SUM(amount) AS revenue FROM orders GROUP BY region
+chartConfig:
+  selectedChartType: bar
+  barAndLineAxis:
+    x: [region]
+    y: [revenue]
+  columnLabelFormats: {}
+drills:
+  - columnId: Region
+    type: metric
+    id: 00000000-0000-0000-0000-000000000001
+    parameter: region
+    label: Revenue by rep
+  - columnId: region
+    type: dashboard
+    id: 00000000-0000-0000-0000-000000000002
+    filter: region
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        assert_eq!(metric.drills.len(), 2);
+        assert_eq!(metric.drills[0].column_id, "region");
+        assert_eq!(
+            metric.drills[0].target,
+            DrillTarget::Metric {
+                id: Uuid::from_u128(1),
+                parameter: "region".to_string(),
+            }
+        );
+        assert_eq!(metric.drills[1].label, None);
+
+        let json = serde_json::to_value(&metric)?;
+        assert_eq!(json["drills"][1]["type"], json!("dashboard"));
+        assert_eq!(json["drills"][1]["filter"], json!("region"));
+
+        let column = |name: &str| ColumnMetaData {
+            name: name.to_string(),
+            min_value: json!(null),
+            max_value: json!(null),
+            unique_values: 1,
+            simple_type: SimpleType::String,
+            column_type: ColumnType::Text,
+        };
+        let metadata = DataMetadata {
+            column_count: 1,
+            row_count: 1,
+            column_metadata: vec![column("revenue")],
+        };
+        let error = metric.validate(Some(&metadata)).unwrap_err().to_string();
+        assert!(error.contains("2 problem(s) in drills"), "{}", error);
+        assert!(error.contains("Drill column 'region' is not returned by the SQL"));
+
+        // Warehouses like Snowflake return column names in upper case
+        let metadata = DataMetadata {
+            column_count: 2,
+            row_count: 1,
+            column_metadata: vec![column("REGION"), column("REVENUE")],
+        };
+        assert!(metric
+            .drills
+            .iter()
+            .all(|drill| drill.problems(Some(&metadata)).is_empty()));
+
+        let bad_parameter = yml_content.replace("parameter: region", "parameter: the region");
+        let error = MetricYml::new(bad_parameter).unwrap_err().to_string();
+        assert!(error.contains("not a valid parameter name"), "{}", error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_comparisons() -> Result<()> {
+        let yml_content = r#"name: Monthly Revenue
+timeRange:
+  column: created_at
+  type: previous
+  unit: quarter
+comparison:
+  type: samePeriodLastYear
+  columnId: MONTH
+sql: SELECT DATE_TRUNC('month', created_at) AS month, SUM(amount) AS revenue FROM orders GROUP BY 1
+chartConfig:
+  selectedChartType: metric
+  metricColumnId: revenue
+  comparisonDelta: percent
+  columnLabelFormats: {}
+"#;
+        let metric = MetricYml::new(yml_content.to_string())?;
+        assert_schema_accepts(yml_content);
+        let comparison = metric.comparison.clone().unwrap();
+        let range = metric.time_range.clone().unwrap();
+        assert_eq!(comparison.column_id, "month");
+        assert_eq!(comparison.describe(), "Same period last year");
+
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let today = date("2024-05-15");
+        assert_eq!(
+            comparison.resolve(&range, today)?,
+            (Some(date("2023-01-01")), Some(date("2023-04-01")))
+        );
+        assert_eq!(
+            comparison.align(&range, date("2023-02-01")),
+            Some(date("2024-02-01"))
+        );
+
+        let previous = Comparison {
+            column_id: "month".to_string(),
+            period: ComparisonPeriod::PreviousPeriod,
+        };
+        assert_eq!(
+            previous.resolve(&range, today)?,
+            (Some(date("2023-10-01")), Some(date("2024-01-01")))
+        );
+        let fixed = TimeRange {
+            column: "created_at".to_string(),
+            period: TimePeriod::Absolute {
+                start: Some("2024-03-01".to_string()),
+                end: Some("2024-03-10".to_string()),
+            },
+        };
+        assert_eq!(
+            previous.resolve(&fixed, today)?,
+            (Some(date("2024-02-20")), Some(date("2024-03-01")))
+        );
+
+        let offset = Comparison {
+            column_id: "month".to_string(),
+            period: ComparisonPeriod::Offset {
+                count: 2,
+                unit: TimeUnit::Month,
+            },
+        };
+        assert_eq!(offset.describe(), "2 months earlier");
+        assert_eq!(
+            offset.align(&range, date("2023-11-01")),
+            Some(date("2024-01-01"))
+        );
+
+        let metadata = |month_type: SimpleType| DataMetadata {
+            column_count: 2,
+            row_count: 1,
+            column_metadata: vec![
+                column("month", month_type),
+                column("revenue", SimpleType::Number),
+            ],
+        };
+        metric.validate(Some(&metadata(SimpleType::Date)))?;
+        metric.validate(Some(&DataMetadata {
+            column_metadata: vec![
+                column("MONTH", SimpleType::Date),
+                column("REVENUE", SimpleType::Number),
+            ],
+            ..metadata(SimpleType::Date)
+        }))?;
+        let error = metric
+            .validate(Some(&metadata(SimpleType::String)))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("'month' must hold dates or timestamps"));
+
+        let open_range = yml_content
+            .replace(
+                "type: previous\n  unit: quarter",
+                "type: absolute\n  start: 2024-01-01",
+            )
+            .replace("samePeriodLastYear", "previousPeriod");
+        let error = MetricYml::new(open_range).unwrap_err().to_string();
+        assert!(error.contains("needs an absolute timeRange with both"));
+
+        let without_range = yml_content.replace(
+            "timeRange:\n  column: created_at\n  type: previous\n  unit: quarter\n",
+            "",
+        );
+        let error = MetricYml::new(without_range).unwrap_err().to_string();
+        assert!(error.contains("A comparison needs a timeRange"));
+
+        let delta_only = yml_content.replace(
+            "comparison:\n  type: samePeriodLastYear\n  columnId: MONTH\n",
+            "",
+        );
+        let error = MetricYml::new(delta_only).unwrap_err().to_string();
+        assert!(error.contains("comparisonDelta needs a comparison"));
+        Ok(())
+    }
 }
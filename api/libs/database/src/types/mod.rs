@@ -1,9 +1,15 @@
 pub mod version_history;
+pub mod version_diff;
 pub mod metric_yml;
 pub mod dashboard_yml;
+pub mod dashboard_template;
 pub mod data_metadata;
+pub mod yml_errors;
 
 pub use version_history::*;
+pub use version_diff::*;
 pub use metric_yml::*;
 pub use dashboard_yml::*;
+pub use dashboard_template::*;
 pub use data_metadata::*;
+pub use yml_errors::*;
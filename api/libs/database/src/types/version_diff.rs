@@ -0,0 +1,453 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::{DashboardYml, MetricYml, Row};
+
+/// A value that differs between two versions. `path` is dotted, using the
+/// YAML keys (e.g. `columnLabelFormats.revenue.style`). `from` is null for an
+/// added value and `to` is null for a removed one. Arrays are compared whole.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct FieldChange {
+    pub path: String,
+    pub from: Option<Value>,
+    pub to: Option<Value>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LineChangeKind {
+    Added,
+    Removed,
+}
+
+/// A line of SQL only one of the versions has. `line` is 1-based, in the old
+/// version for removals and in the new version for additions.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct SqlLineChange {
+    pub kind: LineChangeKind,
+    pub line: usize,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct MetricVersionDiff {
+    /// Changes outside `sql` and `chartConfig`, such as the name or parameters
+    pub fields: Vec<FieldChange>,
+    pub sql: Vec<SqlLineChange>,
+    pub chart_config: Vec<FieldChange>,
+}
+
+/// Rows are compared across the dashboard's own rows and every tab's, so a
+/// row moved to another tab is a change to its `tab`, not a removal.
+#[derive(Debug, Serialize, Clone)]
+pub struct DashboardVersionDiff {
+    /// Changes outside rows, such as the name, filters or tab names
+    pub fields: Vec<FieldChange>,
+    pub added_rows: Vec<Row>,
+    pub removed_rows: Vec<Row>,
+    /// Rows in both versions, matched by id, whose items, sizes or tab differ
+    pub changed_rows: Vec<RowChange>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct RowChange {
+    pub id: u32,
+    pub changes: Vec<FieldChange>,
+}
+
+impl MetricVersionDiff {
+    pub fn between(from: &MetricYml, to: &MetricYml) -> serde_json::Result<Self> {
+        let (mut from_value, mut to_value) =
+            (serde_json::to_value(from)?, serde_json::to_value(to)?);
+        let mut chart_config = Vec::new();
+        value_changes(
+            "",
+            &take_key(&mut from_value, "chartConfig"),
+            &take_key(&mut to_value, "chartConfig"),
+            &mut chart_config,
+        );
+        take_key(&mut from_value, "sql");
+        take_key(&mut to_value, "sql");
+        let mut fields = Vec::new();
+        value_changes("", &from_value, &to_value, &mut fields);
+
+        Ok(Self {
+            fields,
+            sql: line_changes(&from.sql, &to.sql),
+            chart_config,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty() && self.sql.is_empty() && self.chart_config.is_empty()
+    }
+}
+
+impl DashboardVersionDiff {
+    pub fn between(from: &DashboardYml, to: &DashboardYml) -> serde_json::Result<Self> {
+        let (mut from_value, mut to_value) =
+            (serde_json::to_value(from)?, serde_json::to_value(to)?);
+        for value in [&mut from_value, &mut to_value] {
+            take_key(value, "rows");
+            if let Some(Value::Array(tabs)) = value.get_mut("tabs") {
+                for tab in tabs {
+                    take_key(tab, "rows");
+                }
+            }
+        }
+        let mut fields = Vec::new();
+        value_changes("", &from_value, &to_value, &mut fields);
+
+        let (from_rows, to_rows) = (rows_with_tabs(from), rows_with_tabs(to));
+        let mut changed_rows = Vec::new();
+        let mut removed_rows = Vec::new();
+        for (tab, row) in &from_rows {
+            match to_rows.iter().find(|(_, candidate)| candidate.id == row.id) {
+                Some((new_tab, new_row)) => {
+                    let mut changes = Vec::new();
+                    value_changes(
+                        "",
+                        &serde_json::to_value(row)?,
+                        &serde_json::to_value(new_row)?,
+                        &mut changes,
+                    );
+                    value_changes(
+                        "tab",
+                        &Value::from(*tab),
+                        &Value::from(*new_tab),
+                        &mut changes,
+                    );
+                    if !changes.is_empty() {
+                        changed_rows.push(RowChange {
+                            id: row.id,
+                            changes,
+                        });
+                    }
+                }
+                None => removed_rows.push((*row).clone()),
+            }
+        }
+        let added_rows = to_rows
+            .iter()
+            .filter(|(_, row)| !from_rows.iter().any(|(_, old)| old.id == row.id))
+            .map(|(_, row)| (*row).clone())
+            .collect();
+
+        Ok(Self {
+            fields,
+            added_rows,
+            removed_rows,
+            changed_rows,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+            && self.added_rows.is_empty()
+            && self.removed_rows.is_empty()
+            && self.changed_rows.is_empty()
+    }
+}
+
+/// Every row with the id of the tab it's on, or `None` for the dashboard's
+/// own rows
+fn rows_with_tabs(dashboard: &DashboardYml) -> Vec<(Option<&str>, &Row)> {
+    dashboard
+        .rows
+        .iter()
+        .map(|row| (None, row))
+        .chain(
+            dashboard
+                .tabs
+                .iter()
+                .flat_map(|tab| tab.rows.iter().map(move |row| (Some(tab.id.as_str()), row))),
+        )
+        .collect()
+}
+
+fn take_key(value: &mut Value, key: &str) -> Value {
+    value
+        .as_object_mut()
+        .and_then(|object| object.remove(key))
+        .unwrap_or(Value::Null)
+}
+
+/// Collects the leaf values that differ, descending into objects on both sides
+fn value_changes(path: &str, from: &Value, to: &Value, changes: &mut Vec<FieldChange>) {
+    if let (Value::Object(from), Value::Object(to)) = (from, to) {
+        for key in from
+            .keys()
+            .chain(to.keys().filter(|key| !from.contains_key(*key)))
+        {
+            let child = if path.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", path, key)
+            };
+            let value_of =
+                |object: &Map<String, Value>| object.get(key).cloned().unwrap_or_default();
+            value_changes(&child, &value_of(from), &value_of(to), changes);
+        }
+        return;
+    }
+    if from != to {
+        changes.push(FieldChange {
+            path: path.to_string(),
+            from: (!from.is_null()).then(|| from.clone()),
+            to: (!to.is_null()).then(|| to.clone()),
+        });
+    }
+}
+
+/// Line-level diff from the longest common subsequence of the two texts
+fn line_changes(from: &str, to: &str) -> Vec<SqlLineChange> {
+    let old: Vec<&str> = from.lines().collect();
+    let new: Vec<&str> = to.lines().collect();
+
+    // common[i][j] is the LCS length of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            i += 1;
+            j += 1;
+        } else if i < old.len() && (j == new.len() || common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(SqlLineChange {
+                kind: LineChangeKind::Removed,
+                line: i + 1,
+                text: old[i].to_string(),
+            });
+            i += 1;
+        } else {
+            changes.push(SqlLineChange {
+                kind: LineChangeKind::Added,
+                line: j + 1,
+                text: new[j].to_string(),
+            });
+            j += 1;
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_metric_version_diff() -> anyhow::Result<()> {
+        let old = MetricYml::new(
+            r#"name: Revenue by Region
+sql: |
+  SELECT region, SUM(amount) AS revenue
+  FROM orders
+  GROUP BY region
+chartConfig:
+  selectedChartType: bar
+  barAndLineAxis:
+    x: [region]
+    y: [revenue]
+  columnLabelFormats:
+    revenue: { columnType: number, style: number }
+"#
+            .to_string(),
+        )?;
+        let new = MetricYml::new(
+            r#"name: Revenue by Region
+description: Revenue per region since 2024
+sql: |
+  SELECT region, SUM(amount) AS revenue
+  FROM orders
+  WHERE created_at >= '2024-01-01'
+  GROUP BY region
+chartConfig:
+  selectedChartType: bar
+  barAndLineAxis:
+    x: [region]
+    y: [revenue]
+  columnLabelFormats:
+    revenue: { columnType: number, style: currency }
+"#
+            .to_string(),
+        )?;
+
+        let diff = MetricVersionDiff::between(&old, &new)?;
+        assert_eq!(
+            diff.fields,
+            vec![FieldChange {
+                path: "description".to_string(),
+                from: None,
+                to: Some(json!("Revenue per region since 2024")),
+            }]
+        );
+        assert_eq!(
+            diff.sql,
+            vec![SqlLineChange {
+                kind: LineChangeKind::Added,
+                line: 3,
+                text: "WHERE created_at >= '2024-01-01'".to_string(),
+            }]
+        );
+        assert_eq!(
+            diff.chart_config,
+            vec![FieldChange {
+                path: "columnLabelFormats.revenue.style".to_string(),
+                from: Some(json!("number")),
+                to: Some(json!("currency")),
+            }]
+        );
+        assert!(MetricVersionDiff::between(&new, &new)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dashboard_version_diff() -> anyhow::Result<()> {
+        let old = DashboardYml::new(
+            r#"name: Sales
+rows:
+  - id: 1
+    items:
+      - id: 00000000-0000-0000-0000-000000000001
+    columnSizes: [12]
+  - id: 2
+    items:
+      - id: 00000000-0000-0000-0000-000000000002
+    columnSizes: [12]
+"#
+            .to_string(),
+        )?;
+        let new = DashboardYml::new(
+            r#"name: Sales Overview
+rows:
+  - id: 1
+    items:
+      - id: 00000000-0000-0000-0000-000000000001
+      - type: markdown
+        content: Revenue is net of refunds
+    columnSizes: [6, 6]
+  - id: 3
+    items:
+      - id: 00000000-0000-0000-0000-000000000003
+    columnSizes: [12]
+"#
+            .to_string(),
+        )?;
+
+        let diff = DashboardVersionDiff::between(&old, &new)?;
+        assert_eq!(diff.fields.len(), 1);
+        assert_eq!(diff.fields[0].path, "name");
+        assert_eq!(
+            diff.added_rows.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![3]
+        );
+        assert_eq!(
+            diff.removed_rows.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert_eq!(diff.changed_rows.len(), 1);
+        let paths: Vec<&str> = diff.changed_rows[0]
+            .changes
+            .iter()
+            .map(|c| c.path.as_str())
+            .collect();
+        assert_eq!(paths, vec!["items", "columnSizes"]);
+        Ok(())
+    }
+    #[test]
+    fn test_dashboard_version_diff_across_tabs() -> anyhow::Result<()> {
+        let old = DashboardYml::new(
+            r#"name: Sales
+tabs:
+  - id: overview
+    name: Overview
+    rows:
+      - id: 1
+        items:
+          - id: 00000000-0000-0000-0000-000000000001
+        columnSizes: [12]
+      - id: 2
+        items:
+          - id: 00000000-0000-0000-0000-000000000002
+        columnSizes: [12]
+  - id: regions
+    name: Regions
+    rows:
+      - id: 3
+        items:
+          - id: 00000000-0000-0000-0000-000000000003
+        columnSizes: [12]
+"#
+            .to_string(),
+        )?;
+        let new = DashboardYml::new(
+            r#"name: Sales
+tabs:
+  - id: overview
+    name: Summary
+    rows:
+      - id: 1
+        items:
+          - id: 00000000-0000-0000-0000-000000000001
+          - id: 00000000-0000-0000-0000-000000000004
+        columnSizes: [6, 6]
+  - id: regions
+    name: Regions
+    rows:
+      - id: 3
+        items:
+          - id: 00000000-0000-0000-0000-000000000003
+        columnSizes: [12]
+      - id: 2
+        items:
+          - id: 00000000-0000-0000-0000-000000000002
+        columnSizes: [12]
+      - id: 5
+        items:
+          - id: 00000000-0000-0000-0000-000000000005
+        columnSizes: [12]
+"#
+            .to_string(),
+        )?;
+
+        let diff = DashboardVersionDiff::between(&old, &new)?;
+        let field_paths: Vec<&str> = diff.fields.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(field_paths, vec!["tabs"]);
+        assert_eq!(
+            diff.added_rows.iter().map(|r| r.id).collect::<Vec<_>>(),
+            vec![5]
+        );
+        assert!(diff.removed_rows.is_empty());
+
+        let changed: Vec<(u32, Vec<&str>)> = diff
+            .changed_rows
+            .iter()
+            .map(|row| {
+                (
+                    row.id,
+                    row.changes.iter().map(|c| c.path.as_str()).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changed,
+            vec![(1, vec!["items", "columnSizes"]), (2, vec!["tab"])]
+        );
+        let moved = &diff.changed_rows[1].changes[0];
+        assert_eq!(moved.from, Some(Value::from("overview")));
+        assert_eq!(moved.to, Some(Value::from("regions")));
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@ use diesel::{
     AsExpression, FromSqlRow,
 };
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use super::{DashboardYml, MetricYml};
 
@@ -22,6 +23,18 @@ pub struct Version {
     pub version_number: i32,
     pub updated_at: DateTime<Utc>,
     pub content: VersionContent,
+    /// Set when the version was created by restoring an earlier one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<VersionRestore>,
+}
+
+/// The version a restore copied, who restored it, and why
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct VersionRestore {
+    pub version_number: i32,
+    pub restored_by: Uuid,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -51,6 +64,7 @@ impl VersionHistory {
                 content: content.into(),
                 version_number,
                 updated_at: Utc::now(),
+                restored_from: None,
             },
         )]))
     }
@@ -62,6 +76,25 @@ impl VersionHistory {
                 content: content.into(),
                 version_number,
                 updated_at: Utc::now(),
+                restored_from: None,
+            },
+        );
+    }
+
+    /// Adds a version whose content was copied from an earlier one
+    pub fn add_restored_version(
+        &mut self,
+        version_number: i32,
+        content: impl Into<VersionContent>,
+        restored_from: VersionRestore,
+    ) {
+        self.0.insert(
+            version_number.to_string(),
+            Version {
+                content: content.into(),
+                version_number,
+                updated_at: Utc::now(),
+                restored_from: Some(restored_from),
             },
         );
     }
@@ -96,6 +129,7 @@ impl VersionHistory {
                     content: content.into(),
                     version_number,
                     updated_at: Utc::now(),
+                    restored_from: None,
                 },
             );
         } else {
@@ -0,0 +1,462 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+lazy_static! {
+    // The value serde quotes in invalid type and invalid value errors
+    static ref UNEXPECTED_RE: Regex = Regex::new(
+        r#"invalid (?:type|value): (?:string ("(?:[^"\\]|\\.)*")|integer `([^`]*)`|floating point `([^`]*)`|boolean `([^`]*)`|(unit value))"#
+    ).unwrap();
+    static ref UNKNOWN_VARIANT_RE: Regex = Regex::new(r#"unknown variant `([^`]*)`"#).unwrap();
+    static ref UNKNOWN_FIELD_RE: Regex = Regex::new(r#"unknown field `([^`]*)`"#).unwrap();
+    static ref LOCATION_RE: Regex = Regex::new(r#" at line \d+ column \d+$"#).unwrap();
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum YmlErrorKind {
+    /// The text isn't valid YAML, e.g. bad indentation or an unquoted `: `
+    Syntax,
+    /// Valid YAML that doesn't fit the file's shape: a missing field, an
+    /// unknown chart type, a string where a list belongs
+    Schema,
+    /// A well-formed file whose values don't hold together, e.g. an axis on
+    /// a column the SQL doesn't return
+    Validation,
+}
+
+impl fmt::Display for YmlErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YmlErrorKind::Syntax => write!(f, "syntax"),
+            YmlErrorKind::Schema => write!(f, "schema"),
+            YmlErrorKind::Validation => write!(f, "validation"),
+        }
+    }
+}
+
+/// A problem in a metric or dashboard file, with the path of the field it
+/// is about (e.g. `chartConfig.barAndLineAxis.x`) and where that field
+/// starts in the text. Lines and columns count from 1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct YmlError {
+    pub kind: YmlErrorKind,
+    pub path: Option<String>,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for YmlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error", self.kind)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at line {}, column {}", line, column)?;
+            if let Some(path) = &self.path {
+                write!(f, ", {}", path)?;
+            }
+        } else if let Some(path) = &self.path {
+            write!(f, " at {}", path)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Every problem found in a file. Syntax and schema errors stop parsing, so
+/// there is at most one of those; validation reports all of its problems.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct YmlErrors(pub Vec<YmlError>);
+
+impl fmt::Display for YmlErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} problem(s):", self.0.len())?;
+        for error in &self.0 {
+            write!(f, "\n- {}", error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for YmlErrors {}
+
+/// Where each key and list item of a block-style YAML document starts, by
+/// path. Flow collections (`[a, b]`, `{}`) are located by their key.
+pub(crate) struct YmlPositions {
+    entries: Vec<(String, usize, usize)>,
+}
+
+struct Frame {
+    indent: usize,
+    path: String,
+    item: bool,
+}
+
+impl YmlPositions {
+    pub(crate) fn new(text: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut stack: Vec<Frame> = Vec::new();
+        let mut next_index: HashMap<String, usize> = HashMap::new();
+        // Indent of the key that owns the block scalar (`|` or `>`) being skipped
+        let mut block_scalar: Option<usize> = None;
+
+        for (number, raw) in text.lines().enumerate() {
+            let trimmed = raw.trim_start();
+            let mut column = raw.len() - trimmed.len();
+            if let Some(owner) = block_scalar {
+                if trimmed.is_empty() || column > owner {
+                    continue;
+                }
+                block_scalar = None;
+            }
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with("---") {
+                continue;
+            }
+
+            let mut rest = trimmed;
+            loop {
+                let item = rest == "-" || rest.starts_with("- ");
+                while let Some(top) = stack.last() {
+                    let sibling = top.indent == column && (top.item || !item);
+                    if top.indent > column || sibling {
+                        stack.pop();
+                    } else {
+                        break;
+                    }
+                }
+                let parent = stack.last().map_or("", |frame| frame.path.as_str());
+
+                if item {
+                    let index = next_index.entry(parent.to_string()).or_insert(0);
+                    let path = format!("{}[{}]", parent, index);
+                    *index += 1;
+                    entries.push((path.clone(), number + 1, column + 1));
+                    stack.push(Frame {
+                        indent: column,
+                        path,
+                        item: true,
+                    });
+
+                    let after = &rest[1..];
+                    let value = after.trim_start();
+                    column += 1 + after.len() - value.len();
+                    rest = value;
+                    if rest.is_empty() || rest.starts_with('#') {
+                        break;
+                    }
+                    continue;
+                }
+
+                if let Some((key, value)) = split_key(rest) {
+                    let path = join(parent, key);
+                    entries.push((path.clone(), number + 1, column + 1));
+                    let value = value.trim();
+                    if value.is_empty() || value.starts_with('#') {
+                        stack.push(Frame {
+                            indent: column,
+                            path,
+                            item: false,
+                        });
+                    } else if value.starts_with('|') || value.starts_with('>') {
+                        block_scalar = Some(column);
+                    }
+                }
+                break;
+            }
+        }
+
+        Self { entries }
+    }
+
+    /// Line and column of `path`, or of its nearest ancestor that could be
+    /// found. Keys match regardless of case and underscores, so
+    /// `chart_config` is found for `chartConfig`.
+    pub(crate) fn locate(&self, path: &str) -> Option<(usize, usize)> {
+        let mut current = Some(path);
+        while let Some(path) = current {
+            let wanted = normalize(path);
+            if let Some((_, line, column)) =
+                self.entries.iter().find(|(p, ..)| normalize(p) == wanted)
+            {
+                return Some((*line, *column));
+            }
+            current = parent_path(path);
+        }
+        None
+    }
+
+    /// Path of the last key or item that starts at or before `line`
+    pub(crate) fn path_at(&self, line: usize) -> Option<&str> {
+        self.entries
+            .iter()
+            .take_while(|(_, l, _)| *l <= line)
+            .last()
+            .map(|(path, ..)| path.as_str())
+    }
+
+    /// A validation problem found in `section`. Problems that start with a
+    /// path of their own (`barAndLineAxis.x: ...`) are placed under it.
+    pub(crate) fn problem(&self, section: &str, problem: &str) -> YmlError {
+        let (path, message) = match split_path(problem) {
+            Some((inner, message)) => (format!("{}.{}", section, inner), message),
+            None => (section.to_string(), problem),
+        };
+        let position = self.locate(&path);
+        YmlError {
+            kind: YmlErrorKind::Validation,
+            path: Some(path),
+            line: position.map(|(line, _)| line),
+            column: position.map(|(_, column)| column),
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Reads `text` as a `T`, stopping at the first syntax or schema error.
+/// `prepare` adjusts the parsed document before it is deserialized.
+/// `unlocated` names the section a schema error without a path falls in,
+/// for sections serde can't report paths inside of (internally tagged
+/// enums).
+pub(crate) fn read_yml<T: DeserializeOwned>(
+    text: &str,
+    prepare: impl FnOnce(&mut Value),
+    unlocated: impl FnOnce(&Value) -> Option<&'static str>,
+) -> Result<(T, YmlPositions), YmlErrors> {
+    let positions = YmlPositions::new(text);
+
+    let document: Value = match serde_yaml::from_str(text) {
+        Ok(document) => document,
+        Err(e) => {
+            let location = e.location().map(|l| (l.line(), l.column()));
+            let (_, message) = split_serde_error(&e);
+            return Err(YmlErrors(vec![YmlError {
+                kind: YmlErrorKind::Syntax,
+                path: location
+                    .and_then(|(line, _)| positions.path_at(line))
+                    .map(str::to_string),
+                line: location.map(|(line, _)| line),
+                column: location.map(|(_, column)| column),
+                message,
+            }]));
+        }
+    };
+
+    let mut prepared = document.clone();
+    prepare(&mut prepared);
+    let error = match serde_yaml::from_value::<T>(prepared) {
+        Ok(file) => return Ok((file, positions)),
+        Err(e) => e,
+    };
+
+    // The text as written gives the error a path, where serde can tell it
+    let error = serde_yaml::from_str::<T>(text).err().unwrap_or(error);
+    let (serde_path, message) = split_serde_error(&error);
+    let path = serde_path
+        .clone()
+        .or_else(|| unlocated(&document).map(str::to_string));
+    let path = path.map(|path| refine_path(&document, &path, &message).unwrap_or(path));
+    // Serde's position is only meaningful for the path it reported itself
+    let position = match &path {
+        Some(path) if serde_path.as_ref() == Some(path) => error
+            .location()
+            .map(|l| (l.line(), l.column()))
+            .or_else(|| positions.locate(path)),
+        Some(path) => positions.locate(path),
+        None => error.location().map(|l| (l.line(), l.column())),
+    };
+
+    let mut message = message;
+    if message.contains("unit value") {
+        message.push_str(
+            "; the value is empty. Quote values that start with #, e.g. '#FF0000', \
+             or the rest of the line is read as a comment",
+        );
+    }
+
+    Err(YmlErrors(vec![YmlError {
+        kind: YmlErrorKind::Schema,
+        path,
+        line: position.map(|(line, _)| line),
+        column: position.map(|(_, column)| column),
+        message,
+    }]))
+}
+
+/// Splits serde_yaml's `path: message at line L column C` into its path
+/// and message
+fn split_serde_error(error: &serde_yaml::Error) -> (Option<String>, String) {
+    let text = error.to_string();
+    let text = LOCATION_RE.replace(&text, "");
+    match split_path(&text) {
+        Some((path, message)) => (Some(path.to_string()), message.to_string()),
+        None => (None, text.to_string()),
+    }
+}
+
+/// Splits a leading `path: ` off `text`. A path has no spaces or quotes,
+/// which tells it apart from messages like `invalid type: ...`.
+fn split_path(text: &str) -> Option<(&str, &str)> {
+    let (path, message) = text.split_once(": ")?;
+    let is_path = !path.is_empty()
+        && path
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '[' | ']'));
+    is_path.then_some((path, message))
+}
+
+/// Narrows `path` to the one field under it holding the value the schema
+/// error complains about, when exactly one does
+fn refine_path(document: &Value, path: &str, message: &str) -> Option<String> {
+    let section = find(document, path)?;
+    let mut matches = Vec::new();
+
+    if let Some(captures) = UNEXPECTED_RE.captures(message) {
+        let wanted = |value: &Value| match value {
+            Value::String(s) => captures
+                .get(1)
+                .is_some_and(|m| format!("{:?}", s) == m.as_str()),
+            Value::Number(n) => {
+                let n = n.to_string();
+                captures.get(2).is_some_and(|m| m.as_str() == n)
+                    || captures.get(3).is_some_and(|m| m.as_str() == n)
+            }
+            Value::Bool(b) => captures.get(4).is_some_and(|m| m.as_str() == b.to_string()),
+            Value::Null => captures.get(5).is_some(),
+            _ => false,
+        };
+        collect_leaves(section, path, &wanted, &mut matches);
+    } else if let Some(captures) = UNKNOWN_VARIANT_RE.captures(message) {
+        let variant = captures.get(1).map_or("", |m| m.as_str());
+        let wanted = |value: &Value| value.as_str() == Some(variant);
+        collect_leaves(section, path, &wanted, &mut matches);
+    } else if let Some(captures) = UNKNOWN_FIELD_RE.captures(message) {
+        let field = captures.get(1).map_or("", |m| m.as_str());
+        collect_keys(section, path, field, &mut matches);
+    }
+
+    match matches.len() {
+        1 => matches.pop().filter(|refined| refined != path),
+        _ => None,
+    }
+}
+
+fn collect_leaves(
+    value: &Value,
+    path: &str,
+    wanted: &dyn Fn(&Value) -> bool,
+    out: &mut Vec<String>,
+) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map {
+                let key = key_name(key);
+                collect_leaves(child, &join(path, &key), wanted, out);
+            }
+        }
+        Value::Sequence(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect_leaves(child, &format!("{}[{}]", path, index), wanted, out);
+            }
+        }
+        Value::Tagged(tagged) => collect_leaves(&tagged.value, path, wanted, out),
+        leaf => {
+            if wanted(leaf) {
+                out.push(path.to_string());
+            }
+        }
+    }
+}
+
+fn collect_keys(value: &Value, path: &str, field: &str, out: &mut Vec<String>) {
+    match value {
+        Value::Mapping(map) => {
+            for (key, child) in map {
+                let key = key_name(key);
+                let child_path = join(path, &key);
+                if key == field {
+                    out.push(child_path.clone());
+                }
+                collect_keys(child, &child_path, field, out);
+            }
+        }
+        Value::Sequence(items) => {
+            for (index, child) in items.iter().enumerate() {
+                collect_keys(child, &format!("{}[{}]", path, index), field, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The value at `path`, matching keys the way `YmlPositions::locate` does
+fn find<'a>(document: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut value = document;
+    for segment in path.split('.') {
+        let (key, indexes) = match segment.find('[') {
+            Some(at) => (&segment[..at], &segment[at..]),
+            None => (segment, ""),
+        };
+        if !key.is_empty() {
+            let wanted = normalize(key);
+            value = value
+                .as_mapping()?
+                .iter()
+                .find(|(k, _)| normalize(&key_name(k)) == wanted)
+                .map(|(_, v)| v)?;
+        }
+        for index in indexes.split(']').filter(|s| !s.is_empty()) {
+            let index: usize = index.trim_start_matches('[').parse().ok()?;
+            value = value.as_sequence()?.get(index)?;
+        }
+    }
+    Some(value)
+}
+
+/// Splits `key: value` off a line, taking the key as written without quotes
+fn split_key(text: &str) -> Option<(&str, &str)> {
+    if let Some(quote) = text.chars().next().filter(|c| *c == '"' || *c == '\'') {
+        let end = text[1..].find(quote)? + 1;
+        let rest = text[end + 1..].strip_prefix(':')?;
+        return (rest.is_empty() || rest.starts_with(' ')).then_some((&text[1..end], rest));
+    }
+    if text.starts_with(['[', '{', '#', '&', '*', '!', '|', '>']) {
+        return None;
+    }
+    let end = text
+        .match_indices(':')
+        .map(|(at, _)| at)
+        .find(|at| text[at + 1..].is_empty() || text[at + 1..].starts_with(' '))?;
+    let key = text[..end].trim_end();
+    (!key.is_empty() && !key.contains(" #")).then_some((key, &text[end + 1..]))
+}
+
+fn key_name(key: &Value) -> String {
+    match key {
+        Value::String(s) => s.clone(),
+        other => serde_yaml::to_string(other)
+            .map(|s| s.trim().to_string())
+            .unwrap_or_default(),
+    }
+}
+
+fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn parent_path(path: &str) -> Option<&str> {
+    path.rfind(['.', '[']).map(|at| &path[..at])
+}
+
+fn normalize(path: &str) -> String {
+    path.chars()
+        .filter(|c| *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
@@ -30,8 +30,12 @@ impl AssetTestHelpers {
             description: Some(format!("Test metric description for {}", name)),
             sql: "SELECT * FROM test".to_string(),
             time_frame: "last 30 days".to_string(),
+            time_range: None,
+            comparison: None,
             chart_config: create_default_chart_config(),
             dataset_ids: Vec::new(),
+            parameters: Vec::new(),
+            drills: Vec::new(),
         };
 
         let metric_file = MetricFile {
@@ -78,6 +82,8 @@ impl AssetTestHelpers {
             name: name.to_string(),
             description: Some(format!("Test dashboard description for {}", name)),
             rows: Vec::new(),
+            tabs: Vec::new(),
+            filters: Vec::new(),
         };
 
         let dashboard_file = DashboardFile {
@@ -94,6 +94,7 @@ impl TestDb {
             updated_at: Utc::now(),
             deleted_at: None,
             payment_required: false,
+            timezone: "UTC".to_string(),
         };
         
         let mut conn = self.diesel_conn().await?;
@@ -1,5 +1,7 @@
 //! Library for handling dataset security and permissions.
 
+pub mod sql_references;
+
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
 use database::{
@@ -0,0 +1,208 @@
+//! Resolves the tables and columns a query reads against the deployed
+//! datasets the user is permitted to query, along with the relationships
+//! their models declare.
+
+use anyhow::Result;
+use database::{
+    pool::get_pg_pool,
+    schema::{dataset_columns, datasets},
+};
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use semantic_layer::models::ModelFile;
+use sql_analyzer::{
+    resolve_references, types::TableKind, Cardinality, CatalogTable, QuerySummary,
+    ReferenceCatalog, ReferenceError, Relationship, SemanticLayer,
+};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::get_permissioned_datasets;
+
+/// Datasets and reference problems found by [`resolve_sql_references`]
+pub struct ResolvedSqlReferences {
+    /// Tables and columns that don't resolve to a permitted dataset
+    pub errors: Vec<ReferenceError>,
+    /// IDs of the permitted datasets the query reads from
+    pub dataset_ids: Vec<Uuid>,
+    /// Number of defined columns per referenced dataset name
+    pub column_counts: HashMap<String, usize>,
+    /// Relationships declared by the permitted datasets' models, used to
+    /// detect joins that fan out an aggregated measure
+    pub relationships: SemanticLayer,
+}
+
+/// Resolves the base tables and columns of an analyzed query against the
+/// data source's datasets, their `dataset_columns` as the last deploy wrote
+/// them, and the datasets the user is permitted to query
+pub async fn resolve_sql_references(
+    analysis: &QuerySummary,
+    data_source_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<ResolvedSqlReferences> {
+    let has_base_tables = analysis.tables.iter().any(|t| t.kind == TableKind::Base);
+    if !has_base_tables {
+        return Ok(ResolvedSqlReferences {
+            errors: Vec::new(),
+            dataset_ids: Vec::new(),
+            column_counts: HashMap::new(),
+            relationships: SemanticLayer::new(),
+        });
+    }
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let data_source_datasets = datasets::table
+        .filter(datasets::data_source_id.eq(data_source_id))
+        .filter(datasets::deleted_at.is_null())
+        .select((datasets::id, datasets::name))
+        .load::<(Uuid, String)>(&mut conn)
+        .await?;
+
+    let permitted_datasets: Vec<_> = get_permissioned_datasets(user_id, 0, 10000)
+        .await?
+        .into_iter()
+        .filter(|ds| ds.data_source_id == *data_source_id)
+        .collect();
+    let permitted_ids: HashSet<Uuid> = permitted_datasets.iter().map(|ds| ds.id).collect();
+    let relationships = relationship_layer(
+        permitted_datasets
+            .iter()
+            .filter_map(|ds| ds.yml_content.as_deref()),
+    );
+
+    // Only the datasets the query mentions need their columns loaded
+    let referenced_ids: Vec<Uuid> = data_source_datasets
+        .iter()
+        .filter(|(id, name)| {
+            permitted_ids.contains(id)
+                && analysis.tables.iter().any(|t| {
+                    t.kind == TableKind::Base && t.table_identifier.eq_ignore_ascii_case(name)
+                })
+        })
+        .map(|(id, _)| *id)
+        .collect();
+
+    let mut columns_by_dataset: HashMap<Uuid, Vec<String>> = HashMap::new();
+    if !referenced_ids.is_empty() {
+        let columns = dataset_columns::table
+            .filter(dataset_columns::dataset_id.eq_any(&referenced_ids))
+            .filter(dataset_columns::deleted_at.is_null())
+            .select((dataset_columns::dataset_id, dataset_columns::name))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?;
+        for (dataset_id, name) in columns {
+            columns_by_dataset.entry(dataset_id).or_default().push(name);
+        }
+    }
+
+    let mut column_counts = HashMap::new();
+    let catalog = ReferenceCatalog {
+        tables: data_source_datasets
+            .iter()
+            .map(|(id, name)| {
+                let columns = columns_by_dataset.remove(id).unwrap_or_default();
+                if referenced_ids.contains(id) {
+                    column_counts.insert(name.clone(), columns.len());
+                }
+                CatalogTable {
+                    name: name.clone(),
+                    columns,
+                    accessible: permitted_ids.contains(id),
+                }
+            })
+            .collect(),
+    };
+
+    Ok(ResolvedSqlReferences {
+        errors: resolve_references(analysis, &catalog),
+        dataset_ids: referenced_ids,
+        column_counts,
+        relationships,
+    })
+}
+
+/// Builds a semantic layer holding the relationships that deployed model
+/// files declare, each read from the model to the entity it names, with the
+/// entity's cardinality when it has one. Files that no longer parse are
+/// skipped.
+pub fn relationship_layer<'a>(yml_files: impl IntoIterator<Item = &'a str>) -> SemanticLayer {
+    let mut layer = SemanticLayer::new();
+    for yml in yml_files {
+        let models = match serde_yaml::from_str::<ModelFile>(yml) {
+            Ok(ModelFile::Spec(spec)) => spec.models,
+            Ok(ModelFile::Model(model)) => vec![*model],
+            Err(e) => {
+                warn!(
+                    "Skipping relationships of a model file that doesn't parse: {}",
+                    e
+                );
+                continue;
+            }
+        };
+        for model in models {
+            for entity in model.relationships {
+                layer.add_relationship(Relationship {
+                    from_table: model.name.clone(),
+                    from_column: entity.primary_key,
+                    to_table: entity.name,
+                    to_column: entity.foreign_key,
+                    cardinality: entity.cardinality.as_deref().and_then(Cardinality::parse),
+                });
+            }
+        }
+    }
+    layer
+}
+
+/// Formats reference errors as one line per unknown table or column
+pub fn format_reference_errors(errors: &[ReferenceError]) -> String {
+    errors
+        .iter()
+        .map(|e| format!("- {}", e.message))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sql_analyzer::{detect_fan_out_joins, ValidationMode};
+
+    const MODELS: &str = r#"
+models:
+  - name: orders
+    dimensions:
+      - name: id
+      - name: shipping_cost
+    entities:
+      - name: order_items
+        primary_key: id
+        foreign_key: order_id
+        cardinality: one-to-many
+  - name: order_items
+    dimensions:
+      - name: order_id
+      - name: quantity
+"#;
+
+    #[tokio::test]
+    async fn test_relationship_layer_flags_measures_fanned_out_by_declared_joins() {
+        let layer = relationship_layer([MODELS, "not: [a, model"]);
+        assert_eq!(
+            layer.relationship_cardinality("order_items", "orders"),
+            Some(Cardinality::ManyToOne)
+        );
+
+        let sql = "SELECT SUM(o.shipping_cost), SUM(i.quantity) \
+                   FROM orders o JOIN order_items i ON o.id = i.order_id";
+        let warnings = detect_fan_out_joins(sql.to_string(), layer, ValidationMode::Flexible)
+            .await
+            .unwrap();
+
+        assert_eq!(warnings.len(), 1, "{:?}", warnings);
+        assert_eq!(warnings[0].table, "orders");
+        assert_eq!(warnings[0].fanned_out_by, "order_items");
+    }
+}
@@ -102,12 +102,8 @@ pub async fn generate_asset_messages(
             });
             
             // Extract metric IDs from dashboard
-            let mut metric_ids = std::collections::HashSet::new();
-            for row in &dashboard.content.rows {
-                for item in &row.items {
-                    metric_ids.insert(item.id);
-                }
-            }
+            let metric_ids: std::collections::HashSet<Uuid> =
+                dashboard.content.metric_ids().collect();
             
             // Load all associated metrics for context (they won't be shown in UI)
             let mut metric_files_data = Vec::new();
@@ -56,12 +56,7 @@ impl ContextLoader for DashboardContextLoader {
         let dashboard_yml = dashboard.content;
 
         // Collect all metric IDs from the dashboard
-        let mut metric_ids = HashSet::new();
-        for row in &dashboard_yml.rows {
-            for item in &row.items {
-                metric_ids.insert(item.id);
-            }
-        }
+        let metric_ids: HashSet<Uuid> = dashboard_yml.metric_ids().collect();
 
         // Load all referenced metrics
         let mut failed_metric_loads = Vec::new();
@@ -3084,11 +3084,7 @@ async fn apply_file_filtering_rules(
                 let context_metric_ids = match DashboardYml::new(
                     context_dashboard_info.content.clone(),
                 ) {
-                    Ok(yml) => yml
-                        .rows
-                        .iter()
-                        .flat_map(|r| r.items.iter().map(|i| i.id))
-                        .collect::<HashSet<Uuid>>(),
+                    Ok(yml) => yml.metric_ids().collect::<HashSet<Uuid>>(),
                     Err(e) => {
                         tracing::warn!("Failed to parse context dashboard {} for Case 2 check: {}. Assuming metrics might not belong.", ctx_id, e);
                         all_metrics_belong_to_context = false; // Cannot confirm, assume they don't all belong
@@ -3129,11 +3125,7 @@ async fn apply_file_filtering_rules(
             let mut modified_context_metric_this_turn = false;
             let context_metric_ids = match DashboardYml::new(context_dashboard_info.content.clone())
             {
-                Ok(yml) => yml
-                    .rows
-                    .iter()
-                    .flat_map(|r| r.items.iter().map(|i| i.id))
-                    .collect::<HashSet<Uuid>>(),
+                Ok(yml) => yml.metric_ids().collect::<HashSet<Uuid>>(),
                 Err(e) => {
                     tracing::warn!("Failed to parse context dashboard {} for Case 3 check: {}. Assuming no context metrics modified.", ctx_id, e);
                     HashSet::new() // Assume no overlap if parsing fails
@@ -3221,11 +3213,7 @@ fn process_current_turn_files(
         for dashboard_info in dashboards {
             match DashboardYml::new(dashboard_info.content.clone()) {
                 Ok(dashboard_yml) => {
-                    for row in dashboard_yml.rows {
-                        for item in row.items {
-                            referenced_metric_uuids.insert(item.id);
-                        }
-                    }
+                    referenced_metric_uuids.extend(dashboard_yml.metric_ids());
                 }
                 Err(e) => {
                     tracing::warn!(
@@ -25,6 +25,8 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
         name: "Untitled Dashboard".to_string(),
         description: None,
         rows: vec![],
+        tabs: vec![],
+        filters: vec![],
     };
 
     // Convert to YAML string for the file field
@@ -98,7 +100,10 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
 
     // Construct the dashboard
     let dashboard = BusterDashboard {
-        config: DashboardConfig { rows: vec![] },
+        config: DashboardConfig {
+            rows: vec![],
+            tabs: vec![],
+        },
         created_at: dashboard_file.4,
         created_by: dashboard_file.3,
         description: None,
@@ -116,6 +121,7 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
     let initial_version = Version {
         version_number: 1,
         updated_at: now,
+        restored_from: None,
     };
 
     Ok(BusterDashboardResponse {
@@ -130,6 +136,8 @@ pub async fn create_dashboard_handler(user: &AuthenticatedUser) -> Result<Buster
         public_expiry_date: None,
         public_enabled_by: None,
         versions: vec![initial_version],
+        active_tab: None,
+        deferred_metric_ids: vec![],
     })
 }
 
@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::enums::AssetPermissionRole;
+use database::helpers::dashboard_files::fetch_dashboard_file_with_permission;
+use database::helpers::metric_sql::data_source_dialect;
+use database::models::DashboardTemplate;
+use database::pool::get_pg_pool;
+use database::schema::{dashboard_templates, data_sources, metric_files};
+use database::types::{
+    DashboardTemplateContent, MetricYml, TemplateMetric, TemplatePlaceholder,
+    TemplatePlaceholderKind,
+};
+use diesel::{insert_into, ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::AuthenticatedUser;
+use serde::{Deserialize, Serialize};
+use sharing::check_permission_access;
+use sql_analyzer::{remap_tables, TableMapping, WarehouseDialect};
+use uuid::Uuid;
+
+use super::BusterDashboardTemplate;
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DashboardTemplateCreateRequest {
+    /// Defaults to the dashboard's name
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Schema and table placeholders, each defaulting to a name the metrics'
+    /// SQL uses. A data source placeholder is added for every data source
+    /// the metrics run on.
+    #[serde(default)]
+    pub placeholders: Vec<TemplatePlaceholder>,
+}
+
+/// Saves a dashboard and copies of its metrics as a template that can be
+/// instantiated on other data sources, schemas and tables.
+pub async fn create_dashboard_template_handler(
+    dashboard_id: Uuid,
+    user: &AuthenticatedUser,
+    request: DashboardTemplateCreateRequest,
+) -> Result<BusterDashboardTemplate> {
+    let dashboard_with_permission = fetch_dashboard_file_with_permission(&dashboard_id, &user.id)
+        .await?
+        .ok_or_else(|| anyhow!("Dashboard not found"))?;
+
+    // Users need CanEdit, FullAccess, or Owner permission
+    let has_permission = check_permission_access(
+        dashboard_with_permission.permission,
+        &[
+            AssetPermissionRole::CanEdit,
+            AssetPermissionRole::FullAccess,
+            AssetPermissionRole::Owner,
+        ],
+        dashboard_with_permission.dashboard_file.organization_id,
+        &user.organizations,
+    );
+    if !has_permission {
+        return Err(anyhow!(
+            "You don't have permission to save this dashboard as a template"
+        ));
+    }
+
+    let dashboard_file = dashboard_with_permission.dashboard_file;
+    let dashboard = dashboard_file.content;
+
+    let mut metric_ids: Vec<Uuid> = Vec::new();
+    let rows = dashboard
+        .rows
+        .iter()
+        .chain(dashboard.tabs.iter().flat_map(|tab| tab.rows.iter()));
+    for id in rows.flat_map(|row| row.items.iter().filter_map(|item| item.metric_id())) {
+        if !metric_ids.contains(&id) {
+            metric_ids.push(id);
+        }
+    }
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let mut metrics: HashMap<Uuid, (MetricYml, Uuid)> = metric_files::table
+        .filter(metric_files::id.eq_any(&metric_ids))
+        .filter(metric_files::deleted_at.is_null())
+        .select((
+            metric_files::id,
+            metric_files::content,
+            metric_files::data_source_id,
+        ))
+        .load::<(Uuid, MetricYml, Uuid)>(&mut conn)
+        .await?
+        .into_iter()
+        .map(|(id, content, data_source_id)| (id, (content, data_source_id)))
+        .collect();
+
+    // One data source placeholder per data source, in the order the
+    // dashboard first uses them
+    let mut data_source_ids: Vec<Uuid> = Vec::new();
+    for id in &metric_ids {
+        match metrics.get(id) {
+            Some((_, data_source_id)) if !data_source_ids.contains(data_source_id) => {
+                data_source_ids.push(*data_source_id)
+            }
+            Some(_) => {}
+            None => return Err(anyhow!("Metric {} on the dashboard was not found", id)),
+        }
+    }
+    let data_source_names: HashMap<Uuid, String> = data_sources::table
+        .filter(data_sources::id.eq_any(&data_source_ids))
+        .select((data_sources::id, data_sources::name))
+        .load::<(Uuid, String)>(&mut conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let data_source_placeholder = |index: usize| {
+        if data_source_ids.len() == 1 {
+            "data_source".to_string()
+        } else {
+            format!("data_source_{}", index + 1)
+        }
+    };
+    let mut placeholders: Vec<TemplatePlaceholder> = data_source_ids
+        .iter()
+        .enumerate()
+        .map(|(index, id)| TemplatePlaceholder {
+            name: data_source_placeholder(index),
+            kind: TemplatePlaceholderKind::DataSource,
+            default: id.to_string(),
+            description: data_source_names
+                .get(id)
+                .map(|name| format!("Saved from the {} data source", name)),
+        })
+        .collect();
+
+    let mut dialects: HashMap<String, WarehouseDialect> = HashMap::new();
+    for (index, id) in data_source_ids.iter().enumerate() {
+        dialects.insert(
+            data_source_placeholder(index),
+            data_source_dialect(id).await?,
+        );
+    }
+
+    let template_metrics: Vec<TemplateMetric> = metric_ids
+        .iter()
+        .filter_map(|id| {
+            let (metric, data_source_id) = metrics.remove(id)?;
+            let index = data_source_ids
+                .iter()
+                .position(|ds| *ds == data_source_id)?;
+            Some(TemplateMetric {
+                id: *id,
+                metric,
+                data_source: data_source_placeholder(index),
+            })
+        })
+        .collect();
+
+    // Every schema and table placeholder has to name something the
+    // metrics read from, or filling it in would change nothing
+    for placeholder in &request.placeholders {
+        if placeholder.kind == TemplatePlaceholderKind::DataSource {
+            return Err(anyhow!(
+                "Placeholder '{}': data source placeholders are added for you",
+                placeholder.name
+            ));
+        }
+        if !sql_uses_name(&template_metrics, &dialects, placeholder).await? {
+            return Err(anyhow!(
+                "Placeholder '{}': no metric on the dashboard reads from the {} '{}'",
+                placeholder.name,
+                match placeholder.kind {
+                    TemplatePlaceholderKind::Schema => "schema",
+                    _ => "table",
+                },
+                placeholder.default
+            ));
+        }
+    }
+    placeholders.extend(request.placeholders);
+
+    let name = request.name.unwrap_or_else(|| dashboard.name.clone());
+    let content = DashboardTemplateContent {
+        dashboard_id,
+        dashboard,
+        metrics: template_metrics,
+        placeholders,
+    };
+    content.validate()?;
+
+    let now = Utc::now();
+    let template = DashboardTemplate {
+        id: Uuid::new_v4(),
+        name,
+        description: request.description,
+        content,
+        organization_id: dashboard_file.organization_id,
+        created_by: user.id,
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+    };
+
+    insert_into(dashboard_templates::table)
+        .values(&template)
+        .execute(&mut conn)
+        .await?;
+
+    Ok(template.into())
+}
+
+/// Whether renaming the placeholder's schema or table changes any metric's
+/// SQL, i.e. some metric reads from it. Each metric is parsed in the dialect
+/// of the data source it was saved from.
+async fn sql_uses_name(
+    metrics: &[TemplateMetric],
+    dialects: &HashMap<String, WarehouseDialect>,
+    placeholder: &TemplatePlaceholder,
+) -> Result<bool> {
+    let mapping_to = |name: &str| {
+        let mut mapping = TableMapping::default();
+        let renames = match placeholder.kind {
+            TemplatePlaceholderKind::Schema => &mut mapping.schemas,
+            _ => &mut mapping.tables,
+        };
+        renames.insert(placeholder.default.clone(), name.to_string());
+        mapping
+    };
+    // Renaming to itself prints the SQL the same way as the real rename, so
+    // the two only differ where the name is used
+    let unchanged_mapping = mapping_to(&placeholder.default);
+    let mapping = mapping_to("template_placeholder");
+
+    for metric in metrics {
+        let sql = metric.metric.sql.clone();
+        let dialect = dialects[&metric.data_source];
+        let unchanged = remap_tables(sql.clone(), unchanged_mapping.clone(), dialect).await;
+        let renamed = remap_tables(sql, mapping.clone(), dialect).await;
+        match (unchanged, renamed) {
+            (Ok(unchanged), Ok(renamed)) if unchanged != renamed => return Ok(true),
+            (Ok(_), Ok(_)) => {}
+            (_, Err(e)) | (Err(e), _) => {
+                return Err(anyhow!(
+                    "Metric '{}' can't be used in a template: {}",
+                    metric.metric.name,
+                    e
+                ))
+            }
+        }
+    }
+    Ok(false)
+}
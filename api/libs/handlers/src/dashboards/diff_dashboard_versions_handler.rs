@@ -0,0 +1,46 @@
+use anyhow::{anyhow, Result};
+use database::types::{DashboardVersionDiff, DashboardYml};
+use middleware::AuthenticatedUser;
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::get_dashboard_handler;
+
+/// Differences between two versions of a dashboard
+#[derive(Debug, Serialize)]
+pub struct DashboardVersionDiffResponse {
+    pub dashboard_id: Uuid,
+    pub from_version: i32,
+    pub to_version: i32,
+    #[serde(flatten)]
+    pub diff: DashboardVersionDiff,
+}
+
+/// Compares two versions of a dashboard: added, removed and changed rows,
+/// and other top-level fields. Either version may be older.
+pub async fn diff_dashboard_versions_handler(
+    dashboard_id: &Uuid,
+    user: &AuthenticatedUser,
+    from_version: i32,
+    to_version: i32,
+) -> Result<DashboardVersionDiffResponse> {
+    // Loading each version through the get handler applies its permission checks
+    let from = get_dashboard_handler(dashboard_id, user, Some(from_version), None).await?;
+    let to = get_dashboard_handler(dashboard_id, user, Some(to_version), None).await?;
+
+    let parse = |file: &str| {
+        serde_yaml::from_str::<DashboardYml>(file)
+            .map_err(|e| anyhow!("Failed to parse dashboard version: {}", e))
+    };
+    let diff = DashboardVersionDiff::between(
+        &parse(&from.dashboard.file)?,
+        &parse(&to.dashboard.file)?,
+    )?;
+
+    Ok(DashboardVersionDiffResponse {
+        dashboard_id: *dashboard_id,
+        from_version,
+        to_version,
+        diff,
+    })
+}
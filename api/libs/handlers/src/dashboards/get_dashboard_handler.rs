@@ -12,7 +12,9 @@ use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use crate::dashboards::types::{BusterShareIndividual, DashboardCollection};
-use crate::metrics::{get_metric_for_dashboard_handler, get_metric_handler};
+use crate::metrics::{
+    get_metric_for_dashboard_handler, get_metric_handler, resolve_drill_links_for_metrics,
+};
 use crate::metrics::{BusterMetric, Dataset, Version};
 use database::enums::{AssetPermissionRole, AssetType, IdentityType, Verification};
 use database::helpers::dashboard_files::fetch_dashboard_file_with_permission;
@@ -20,11 +22,13 @@ use database::pool::get_pg_pool;
 use database::schema::{
     asset_permissions, collections, collections_to_assets, dashboard_files, metric_files, users,
 };
-use database::types::{MetricYml, VersionHistory};
+use database::types::dashboard_yml::{deserialize_row_items, partition_collapsed_metrics};
+use database::types::{DashboardYml, DrillLink, MetricYml, VersionHistory};
 use sharing::check_permission_access;
 
 use super::{
     BusterDashboard, BusterDashboardResponse, DashboardConfig, DashboardRow, DashboardRowItem,
+    DashboardTab, DashboardView,
 };
 
 #[derive(Queryable, Selectable)]
@@ -92,6 +96,26 @@ pub async fn get_dashboard_handler(
     user: &AuthenticatedUser,
     version_number: Option<i32>,
     password: Option<String>,
+) -> Result<BusterDashboardResponse> {
+    get_dashboard_view_handler(
+        dashboard_id,
+        user,
+        version_number,
+        password,
+        &DashboardView::default(),
+    )
+    .await
+}
+
+/// Gets a dashboard with the metrics for one tab, leaving out those in
+/// sections that start collapsed unless the view expands them. Metrics
+/// left out are listed in `deferred_metric_ids`.
+pub async fn get_dashboard_view_handler(
+    dashboard_id: &Uuid,
+    user: &AuthenticatedUser,
+    version_number: Option<i32>,
+    password: Option<String>,
+    view: &DashboardView,
 ) -> Result<BusterDashboardResponse> {
     // First check if the user has permission to view this dashboard
     let dashboard_with_permission_option =
@@ -188,6 +212,7 @@ pub async fn get_dashboard_handler(
     let resolved_name: String;
     let resolved_description: Option<String>;
     let resolved_content: Value;
+    let resolved_yml: DashboardYml;
     let resolved_version_num: i32;
     let resolved_updated_at: DateTime<Utc>;
 
@@ -198,6 +223,7 @@ pub async fn get_dashboard_handler(
             match &v.content {
                 database::types::VersionContent::DashboardYml(content) => {
                     resolved_content = content.to_value()?;
+                    resolved_yml = content.clone();
                     resolved_version_num = v.version_number;
                     resolved_updated_at = v.updated_at;
                     // Extract name and description from the version's content
@@ -219,6 +245,7 @@ pub async fn get_dashboard_handler(
     } else {
         // Use current content from the main dashboard file record
         resolved_content = dashboard_file.content.to_value()?;
+        resolved_yml = dashboard_file.content.clone();
         resolved_version_num = dashboard_file
             .version_history
             .get_latest_version()
@@ -235,16 +262,21 @@ pub async fn get_dashboard_handler(
     // Parse the config from the resolved content
     let config = parse_dashboard_config(&resolved_content)?;
 
-    // Collect all metric IDs from the rows
-    let metric_ids: Vec<Uuid> = config
-        .rows
-        .iter()
-        .flat_map(|row| {
-            row.items
-                .iter()
-                .filter_map(|item| Uuid::parse_str(&item.id).ok())
-        })
-        .collect();
+    // Collect the metric IDs shown on the active tab; the rest are deferred
+    let active_tab = view
+        .tab
+        .clone()
+        .or_else(|| resolved_yml.tabs.first().map(|tab| tab.id.clone()));
+    let (metric_ids, mut deferred_metric_ids) = partition_collapsed_metrics(
+        resolved_yml.tab_rows(view.tab.as_deref())?,
+        &view.expanded_sections,
+    );
+    for id in resolved_yml.metric_ids() {
+        if !deferred_metric_ids.contains(&id) {
+            deferred_metric_ids.push(id);
+        }
+    }
+    deferred_metric_ids.retain(|id| !metric_ids.contains(id));
 
     // Fetch metrics concurrently using get_metric_handler
     let mut metric_fetch_handles = Vec::new();
@@ -295,6 +327,26 @@ pub async fn get_dashboard_handler(
         }
     }
 
+    // Resolve every metric's drill links for this viewer at once
+    let drills: HashMap<Uuid, Vec<DrillLink>> = metrics
+        .values()
+        .map(|metric| (metric.id, metric.drills.clone()))
+        .collect();
+    match resolve_drill_links_for_metrics(&drills, user).await {
+        Ok(mut drill_links) => {
+            for metric in metrics.values_mut() {
+                metric.drill_links = drill_links.remove(&metric.id).unwrap_or_default();
+            }
+        }
+        Err(e) => {
+            tracing::error!(
+                "Failed to resolve drill links for dashboard {}: {}",
+                dashboard_id,
+                e
+            );
+        }
+    }
+
     // Query individual permissions for this dashboard
     let individual_permissions_query = asset_permissions::table
         .inner_join(users::table.on(users::id.eq(asset_permissions::identity_id)))
@@ -326,6 +378,7 @@ pub async fn get_dashboard_handler(
         .map(|v| Version {
             version_number: v.version_number,
             updated_at: v.updated_at,
+            restored_from: v.restored_from.clone(),
         })
         .collect();
 
@@ -417,35 +470,58 @@ pub async fn get_dashboard_handler(
         public_enabled_by: public_enabled_by_user,
         // Version information
         versions,
+        active_tab,
+        deferred_metric_ids,
     })
 }
 
 fn parse_dashboard_config(content: &Value) -> Result<DashboardConfig> {
-    let rows = content
-        .get("rows")
-        .ok_or_else(|| anyhow!("Missing rows in dashboard content"))?
-        .as_array()
+    let rows = parse_dashboard_rows(
+        content
+            .get("rows")
+            .ok_or_else(|| anyhow!("Missing rows in dashboard content"))?,
+    )?;
+
+    let tabs = match content.get("tabs") {
+        Some(tabs) => tabs
+            .as_array()
+            .ok_or_else(|| anyhow!("Tabs is not an array"))?
+            .iter()
+            .map(|tab| {
+                let field = |key: &str| {
+                    tab.get(key)
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                        .ok_or_else(|| anyhow!("Missing {} in tab", key))
+                };
+                Ok(DashboardTab {
+                    id: field("id")?,
+                    name: field("name")?,
+                    rows: parse_dashboard_rows(tab.get("rows").unwrap_or(&Value::Null))?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?,
+        None => Vec::new(),
+    };
+
+    Ok(DashboardConfig { rows, tabs })
+}
+
+fn parse_dashboard_rows(rows: &Value) -> Result<Vec<DashboardRow>> {
+    if rows.is_null() {
+        return Ok(Vec::new());
+    }
+
+    rows.as_array()
         .ok_or_else(|| anyhow!("Rows is not an array"))?
         .iter()
         .enumerate()
         .map(|(index, row)| {
-            let items = row
-                .get("items")
-                .ok_or_else(|| anyhow!("Missing items in row"))?
-                .as_array()
-                .ok_or_else(|| anyhow!("Items is not an array"))?
-                .iter()
-                .map(|item| {
-                    Ok(DashboardRowItem {
-                        id: item
-                            .get("id")
-                            .ok_or_else(|| anyhow!("Missing id in item"))?
-                            .as_str()
-                            .ok_or_else(|| anyhow!("Id is not a string"))?
-                            .to_string(),
-                    })
-                })
-                .collect::<Result<Vec<_>>>()?;
+            let items: Vec<DashboardRowItem> = deserialize_row_items(
+                row.get("items")
+                    .ok_or_else(|| anyhow!("Missing items in row"))?,
+            )
+            .map_err(|e| anyhow!("Invalid items in row: {}", e))?;
 
             // Extract column_sizes from the row if available
             let column_sizes = row.get("columnSizes").and_then(|sizes| {
@@ -461,14 +537,19 @@ fn parse_dashboard_config(content: &Value) -> Result<DashboardConfig> {
                 .get("rowHeight")
                 .and_then(|height| height.as_u64().map(|h| h as u32));
 
+            // Row ids are unique across tabs; older rows fall back to their position
+            let id = row
+                .get("id")
+                .and_then(Value::as_u64)
+                .filter(|id| *id > 0)
+                .unwrap_or(index as u64 + 1);
+
             Ok(DashboardRow {
-                id: (index + 1).to_string(),
+                id: id.to_string(),
                 items,
                 row_height,
                 column_sizes,
             })
         })
-        .collect::<Result<Vec<_>>>()?;
-
-    Ok(DashboardConfig { rows })
+        .collect()
 }
@@ -0,0 +1,273 @@
+use std::collections::{HashMap, HashSet};
+
+use agents::tools::file_tools::common::process_metric_file;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use database::enums::{AssetPermissionRole, AssetType, IdentityType};
+use database::helpers::metric_sql::data_source_dialect;
+use database::models::{
+    AssetPermission, DashboardFile, DashboardTemplate, MetricFileToDashboardFile,
+    MetricFileToDataset,
+};
+use database::organization::get_user_organization_id;
+use database::pool::get_pg_pool;
+use database::schema::{
+    asset_permissions, dashboard_files, dashboard_templates, data_sources, metric_files,
+    metric_files_to_dashboard_files, metric_files_to_datasets,
+};
+use database::types::{TemplatePlaceholderKind, VersionHistory};
+use dataset_security::get_permissioned_datasets;
+use diesel::{insert_into, ExpressionMethods, QueryDsl};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use futures::future::join_all;
+use middleware::AuthenticatedUser;
+use serde::{Deserialize, Serialize};
+use sql_analyzer::{remap_tables, TableMapping, WarehouseDialect};
+use uuid::Uuid;
+
+use super::{get_dashboard_handler, BusterDashboardResponse};
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct DashboardTemplateInstantiateRequest {
+    /// Defaults to the name of the dashboard the template was saved from
+    pub name: Option<String>,
+    /// Placeholder values by placeholder name: data source ids for data
+    /// source placeholders, names for schema and table placeholders.
+    /// Placeholders left out keep the value the template was saved with.
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
+
+/// Creates a dashboard and new copies of its metrics from a template. Each
+/// metric's table references are remapped to the placeholder values and its
+/// SQL checked against the target data source's datasets; nothing is
+/// created unless every metric passes.
+pub async fn instantiate_dashboard_template_handler(
+    template_id: Uuid,
+    user: &AuthenticatedUser,
+    request: DashboardTemplateInstantiateRequest,
+) -> Result<BusterDashboardResponse> {
+    let organization_id = get_user_organization_id(&user.id)
+        .await?
+        .ok_or_else(|| anyhow!("User does not belong to any organization"))?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let template = dashboard_templates::table
+        .filter(dashboard_templates::id.eq(template_id))
+        .filter(dashboard_templates::organization_id.eq(organization_id))
+        .filter(dashboard_templates::deleted_at.is_null())
+        .first::<DashboardTemplate>(&mut conn)
+        .await
+        .map_err(|_| anyhow!("Dashboard template not found"))?;
+    let content = template.content;
+    content.validate()?;
+
+    let values = content.resolve(&request.values)?;
+
+    // Metrics can only be created on data sources with datasets the user may
+    // query, as when the agent creates them
+    let permitted_data_sources: HashSet<Uuid> = get_permissioned_datasets(&user.id, 0, 10000)
+        .await?
+        .into_iter()
+        .map(|dataset| dataset.data_source_id)
+        .collect();
+
+    let mut data_source_ids: HashMap<String, Uuid> = HashMap::new();
+    let mut dialects: HashMap<String, WarehouseDialect> = HashMap::new();
+    for placeholder in &content.placeholders {
+        if placeholder.kind != TemplatePlaceholderKind::DataSource {
+            continue;
+        }
+        let value = &values[&placeholder.name];
+        let id = Uuid::parse_str(value).map_err(|_| {
+            anyhow!(
+                "Placeholder '{}' must be a data source id, got '{}'",
+                placeholder.name,
+                value
+            )
+        })?;
+        let found = data_sources::table
+            .filter(data_sources::id.eq(id))
+            .filter(data_sources::organization_id.eq(organization_id))
+            .filter(data_sources::deleted_at.is_null())
+            .select(data_sources::id)
+            .first::<Uuid>(&mut conn)
+            .await;
+        if found.is_err() {
+            return Err(anyhow!(
+                "Placeholder '{}': data source {} was not found",
+                placeholder.name,
+                id
+            ));
+        }
+        if !permitted_data_sources.contains(&id) {
+            return Err(anyhow!(
+                "Placeholder '{}': you don't have access to any datasets on data source {}",
+                placeholder.name,
+                id
+            ));
+        }
+        data_source_ids.insert(placeholder.name.clone(), id);
+        dialects.insert(placeholder.name.clone(), data_source_dialect(&id).await?);
+    }
+
+    let mapping = TableMapping {
+        schemas: content.renames(TemplatePlaceholderKind::Schema, &values),
+        tables: content.renames(TemplatePlaceholderKind::Table, &values),
+    };
+
+    let dashboard_id = Uuid::new_v4();
+    let metric_ids: HashMap<Uuid, Uuid> = content
+        .metrics
+        .iter()
+        .map(|metric| (metric.id, Uuid::new_v4()))
+        .collect();
+    let (mut dashboard_yml, metric_ymls) = content.with_ids(&metric_ids, dashboard_id);
+
+    // Remap and validate every metric before creating anything
+    let instance = dashboard_id.to_string();
+    let results = join_all(content.metrics.iter().zip(metric_ymls).map(
+        |(template_metric, mut metric_yml)| {
+            let mapping = mapping.clone();
+            let instance = instance.clone();
+            let data_source_id = data_source_ids[&template_metric.data_source];
+            let dialect = dialects[&template_metric.data_source];
+            let id = metric_ids[&template_metric.id];
+            async move {
+                let name = metric_yml.name.clone();
+                metric_yml.sql = remap_tables(metric_yml.sql.clone(), mapping, dialect)
+                    .await
+                    .map_err(|e| format!("{}: {}", name, e))?;
+                let yml = serde_yaml::to_string(&metric_yml).map_err(|e| e.to_string())?;
+                let (mut metric_file, _, _, _, dataset_ids) =
+                    process_metric_file(instance, name.clone(), yml, data_source_id, &user.id)
+                        .await
+                        .map_err(|e| format!("{}: {}", name, e))?;
+                metric_file.id = id;
+                Ok::<_, String>((metric_file, dataset_ids))
+            }
+        },
+    ))
+    .await;
+
+    let mut metric_files_to_insert = Vec::new();
+    let mut dataset_links = Vec::new();
+    let mut errors = Vec::new();
+    for result in results {
+        match result {
+            Ok((metric_file, dataset_ids)) => {
+                dataset_links.extend(dataset_ids.into_iter().map(|dataset_id| {
+                    MetricFileToDataset {
+                        metric_file_id: metric_file.id,
+                        dataset_id,
+                        metric_version_number: 1,
+                        created_at: Utc::now(),
+                    }
+                }));
+                metric_files_to_insert.push(metric_file);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(anyhow!(
+            "The template doesn't fit the target data source:\n- {}",
+            errors.join("\n- ")
+        ));
+    }
+
+    if let Some(name) = request.name {
+        dashboard_yml.name = name;
+    }
+    dashboard_yml.validate()?;
+
+    let now = Utc::now();
+    let owner = |asset_id: Uuid, asset_type: AssetType| AssetPermission {
+        identity_id: user.id,
+        identity_type: IdentityType::User,
+        asset_id,
+        asset_type,
+        role: AssetPermissionRole::Owner,
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+        created_by: user.id,
+        updated_by: user.id,
+    };
+
+    let mut permissions: Vec<AssetPermission> = metric_files_to_insert
+        .iter()
+        .map(|metric_file| owner(metric_file.id, AssetType::MetricFile))
+        .collect();
+    permissions.push(owner(dashboard_id, AssetType::DashboardFile));
+
+    let dashboard_links: Vec<MetricFileToDashboardFile> = metric_files_to_insert
+        .iter()
+        .map(|metric_file| MetricFileToDashboardFile {
+            metric_file_id: metric_file.id,
+            dashboard_file_id: dashboard_id,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            created_by: user.id,
+        })
+        .collect();
+
+    let dashboard_file = DashboardFile {
+        id: dashboard_id,
+        name: dashboard_yml.name.clone(),
+        file_name: format!("{}.yml", dashboard_yml.name),
+        content: dashboard_yml.clone(),
+        filter: None,
+        organization_id,
+        created_by: user.id,
+        created_at: now,
+        updated_at: now,
+        deleted_at: None,
+        publicly_accessible: false,
+        publicly_enabled_by: None,
+        public_expiry_date: None,
+        version_history: VersionHistory::new(1, dashboard_yml),
+        public_password: None,
+    };
+
+    // Create everything or nothing
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        async move {
+            if !metric_files_to_insert.is_empty() {
+                insert_into(metric_files::table)
+                    .values(&metric_files_to_insert)
+                    .execute(conn)
+                    .await?;
+            }
+            if !dataset_links.is_empty() {
+                insert_into(metric_files_to_datasets::table)
+                    .values(&dataset_links)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            insert_into(dashboard_files::table)
+                .values(&dashboard_file)
+                .execute(conn)
+                .await?;
+            insert_into(asset_permissions::table)
+                .values(&permissions)
+                .execute(conn)
+                .await?;
+            if !dashboard_links.is_empty() {
+                insert_into(metric_files_to_dashboard_files::table)
+                    .values(&dashboard_links)
+                    .execute(conn)
+                    .await?;
+            }
+            Ok(())
+        }
+        .scope_boxed()
+    })
+    .await?;
+
+    get_dashboard_handler(&dashboard_id, user, None, None).await
+}
@@ -0,0 +1,31 @@
+use anyhow::{anyhow, Result};
+use database::models::DashboardTemplate;
+use database::organization::get_user_organization_id;
+use database::pool::get_pg_pool;
+use database::schema::dashboard_templates;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::AuthenticatedUser;
+
+use super::BusterDashboardTemplate;
+
+/// Lists the dashboard templates saved in the user's organization, newest
+/// first.
+pub async fn list_dashboard_templates_handler(
+    user: &AuthenticatedUser,
+) -> Result<Vec<BusterDashboardTemplate>> {
+    let organization_id = get_user_organization_id(&user.id)
+        .await?
+        .ok_or_else(|| anyhow!("User does not belong to any organization"))?;
+
+    let mut conn = get_pg_pool().get().await?;
+
+    let templates = dashboard_templates::table
+        .filter(dashboard_templates::organization_id.eq(organization_id))
+        .filter(dashboard_templates::deleted_at.is_null())
+        .order(dashboard_templates::created_at.desc())
+        .load::<DashboardTemplate>(&mut conn)
+        .await?;
+
+    Ok(templates.into_iter().map(Into::into).collect())
+}
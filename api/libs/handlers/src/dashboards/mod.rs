@@ -1,14 +1,24 @@
 mod create_dashboard_handler;
+mod create_dashboard_template_handler;
 mod delete_dashboard_handler;
+mod diff_dashboard_versions_handler;
 mod get_dashboard_handler;
+mod instantiate_dashboard_template_handler;
 mod list_dashboard_handler;
+mod list_dashboard_templates_handler;
+mod restore_dashboard_version_handler;
 mod update_dashboard_handler;
 mod types;
 pub mod sharing;
 
 pub use create_dashboard_handler::*;
+pub use create_dashboard_template_handler::*;
 pub use delete_dashboard_handler::*;
+pub use diff_dashboard_versions_handler::*;
 pub use get_dashboard_handler::*;
+pub use instantiate_dashboard_template_handler::*;
 pub use list_dashboard_handler::*;
+pub use list_dashboard_templates_handler::*;
+pub use restore_dashboard_version_handler::*;
 pub use update_dashboard_handler::*;
 pub use types::*;
\ No newline at end of file
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use super::{update_dashboard_handler, BusterDashboardResponse, DashboardUpdateRequest};
+
+/// Restores a dashboard to an earlier version by adding that version's
+/// content as a new version. The new version records who restored it and why.
+pub async fn restore_dashboard_version_handler(
+    dashboard_id: Uuid,
+    user: &AuthenticatedUser,
+    version_number: i32,
+    reason: Option<String>,
+) -> Result<BusterDashboardResponse> {
+    let request = DashboardUpdateRequest {
+        restore_to_version: Some(version_number),
+        restore_reason: reason,
+        ..Default::default()
+    };
+    update_dashboard_handler(dashboard_id, request, user).await
+}
@@ -2,6 +2,9 @@ use std::collections::HashMap;
 
 use chrono::{DateTime, Utc};
 use database::enums::{AssetPermissionRole, Verification};
+use database::models::DashboardTemplate;
+use database::types::dashboard_yml::deserialize_row_items;
+use database::types::TemplatePlaceholder;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -19,6 +22,33 @@ pub struct BusterDashboardListItem {
     pub is_shared: bool,
 }
 
+/// A saved dashboard template, with the placeholders filled in when it's
+/// instantiated
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BusterDashboardTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub placeholders: Vec<TemplatePlaceholder>,
+    pub metric_count: usize,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<DashboardTemplate> for BusterDashboardTemplate {
+    fn from(template: DashboardTemplate) -> Self {
+        BusterDashboardTemplate {
+            id: template.id,
+            name: template.name,
+            description: template.description,
+            metric_count: template.content.metrics.len(),
+            placeholders: template.content.placeholders,
+            created_by: template.created_by,
+            created_at: template.created_at,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct DashboardMember {
     pub avatar_url: Option<String>,
@@ -49,6 +79,20 @@ pub struct BusterDashboardResponse {
     pub public_enabled_by: Option<String>,
     // Versioning field
     pub versions: Vec<Version>,
+    /// Tab whose metrics are in `metrics`; none when the dashboard has no tabs
+    pub active_tab: Option<String>,
+    /// Metrics on the dashboard left out of `metrics`: those on other tabs
+    /// and in collapsed sections. Request them with `tab` or `expanded`.
+    pub deferred_metric_ids: Vec<Uuid>,
+}
+
+/// Which part of a dashboard to load metrics for
+#[derive(Debug, Clone, Default)]
+pub struct DashboardView {
+    /// Tab id; defaults to the first tab
+    pub tab: Option<String>,
+    /// Row ids of collapsed section dividers to load as if opened
+    pub expanded_sections: Vec<u32>,
 }
 
 // Note: This extends BusterShare but omits certain fields
@@ -79,12 +123,23 @@ pub struct DashboardCollection {
 #[serde(rename_all = "camelCase")]
 pub struct DashboardConfig {
     pub rows: Vec<DashboardRow>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tabs: Vec<DashboardTab>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardTab {
+    pub id: String,
+    pub name: String,
+    pub rows: Vec<DashboardRow>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct DashboardRow {
     pub id: String,
+    #[serde(deserialize_with = "deserialize_row_items")]
     pub items: Vec<DashboardRowItem>,
     #[serde(alias = "rowHeight", skip_serializing_if = "Option::is_none")]
     pub row_height: Option<u32>,
@@ -92,8 +147,22 @@ pub struct DashboardRow {
     pub column_sizes: Option<Vec<u32>>,
 }
 
+/// A tile in a dashboard row. Items sent without a `type` are metric tiles.
 #[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct DashboardRowItem {
-    pub id: String,
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum DashboardRowItem {
+    Metric {
+        id: String,
+    },
+    Markdown {
+        content: String,
+    },
+    Divider {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        #[serde(default)]
+        collapsible: bool,
+        #[serde(default)]
+        collapsed: bool,
+    },
 }
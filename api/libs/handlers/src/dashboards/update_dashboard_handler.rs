@@ -5,8 +5,8 @@ use database::helpers::dashboard_files::fetch_dashboard_file_with_permission;
 use database::models::MetricFileToDashboardFile;
 use database::pool::get_pg_pool;
 use database::schema::{dashboard_files, metric_files_to_dashboard_files};
-use database::types::dashboard_yml::{DashboardYml, Row, RowItem};
-use database::types::VersionHistory;
+use database::types::dashboard_yml::{DashboardTab, DashboardYml, Row, RowItem};
+use database::types::{VersionHistory, VersionRestore};
 use diesel::{ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
 use middleware::AuthenticatedUser;
@@ -14,7 +14,9 @@ use serde::{Deserialize, Serialize};
 use sharing::check_permission_access;
 use uuid::Uuid;
 
-use super::{get_dashboard_handler, BusterDashboardResponse, DashboardConfig};
+use super::{
+    get_dashboard_handler, BusterDashboardResponse, DashboardConfig, DashboardRow, DashboardRowItem,
+};
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct DashboardUpdateRequest {
@@ -37,6 +39,8 @@ pub struct DashboardUpdateRequest {
     pub update_version: Option<bool>,
     /// Version to restore (optional) - when provided, other update parameters are ignored
     pub restore_to_version: Option<i32>,
+    /// Why the version is being restored, kept in the version history
+    pub restore_reason: Option<String>,
 }
 
 /// Updates an existing dashboard by ID
@@ -105,6 +109,8 @@ pub async fn update_dashboard_handler(
         name: "New Dashboard".to_string(),
         description: None,
         rows: Vec::new(),
+        tabs: Vec::new(),
+        filters: Vec::new(),
     };
 
     let mut current_version_history: VersionHistory = dashboard_files::table
@@ -161,30 +167,19 @@ pub async fn update_dashboard_handler(
 
             // Update config if provided - reconcile DashboardConfig with DashboardYml
             if let Some(config) = request.config {
-                // Convert DashboardConfig to DashboardYml rows
-                let mut new_rows = Vec::new();
-
-                for dashboard_row in config.rows {
-                    let mut row_items = Vec::new();
-
-                    for item in dashboard_row.items {
-                        // Try to parse the item.id as UUID
-                        if let Ok(metric_id) = Uuid::parse_str(&item.id) {
-                            row_items.push(RowItem { id: metric_id });
-                        } else {
-                            return Err(anyhow!("Invalid metric ID format: {}", item.id));
-                        }
-                    }
-
-                    new_rows.push(Row {
-                        items: row_items,
-                        row_height: dashboard_row.row_height,
-                        column_sizes: dashboard_row.column_sizes.unwrap_or_default(),
-                        id: dashboard_row.id.parse::<u32>().unwrap_or(0),
-                    });
-                }
-
-                dashboard_yml.rows = new_rows;
+                dashboard_yml.rows = config_rows_to_yml(config.rows)?;
+                dashboard_yml.tabs = config
+                    .tabs
+                    .into_iter()
+                    .map(|tab| {
+                        Ok(DashboardTab {
+                            id: tab.id,
+                            name: tab.name,
+                            rows: config_rows_to_yml(tab.rows)?,
+                        })
+                    })
+                    .collect::<Result<_>>()?;
+                dashboard_yml.assign_row_ids();
                 has_changes = true;
             }
         }
@@ -202,10 +197,21 @@ pub async fn update_dashboard_handler(
     // Only add a new version if has_changes and should_update_version
     if has_changes {
         if should_update_version {
-            current_version_history.add_version(
-                next_version,
-                database::types::VersionContent::DashboardYml(dashboard_yml.clone()),
-            );
+            match request.restore_to_version {
+                Some(version_number) => current_version_history.add_restored_version(
+                    next_version,
+                    dashboard_yml.clone(),
+                    VersionRestore {
+                        version_number,
+                        restored_by: user.id,
+                        reason: request.restore_reason.clone(),
+                    },
+                ),
+                None => current_version_history.add_version(
+                    next_version,
+                    database::types::VersionContent::DashboardYml(dashboard_yml.clone()),
+                ),
+            }
         } else {
             // Overwrite the current version instead of creating a new one
             current_version_history.update_latest_version(
@@ -311,19 +317,47 @@ pub async fn update_dashboard_handler(
     get_dashboard_handler(&dashboard_id, user, None, None).await
 }
 
-/// Extract metric IDs from dashboard content
-fn extract_metric_ids_from_dashboard(dashboard: &DashboardYml) -> Vec<Uuid> {
-    let mut metric_ids = Vec::new();
+/// Converts DashboardConfig rows to DashboardYml rows. Rows without a
+/// numeric id are left at 0 for `assign_row_ids` to fill in.
+fn config_rows_to_yml(rows: Vec<DashboardRow>) -> Result<Vec<Row>> {
+    let mut new_rows = Vec::new();
+
+    for dashboard_row in rows {
+        let mut row_items = Vec::new();
 
-    // Iterate through all rows and collect unique metric IDs
-    for row in &dashboard.rows {
-        for item in &row.items {
-            metric_ids.push(item.id);
+        for item in dashboard_row.items {
+            row_items.push(match item {
+                DashboardRowItem::Metric { id } => match Uuid::parse_str(&id) {
+                    Ok(metric_id) => RowItem::Metric { id: metric_id },
+                    Err(_) => return Err(anyhow!("Invalid metric ID format: {}", id)),
+                },
+                DashboardRowItem::Markdown { content } => RowItem::Markdown { content },
+                DashboardRowItem::Divider {
+                    title,
+                    collapsible,
+                    collapsed,
+                } => RowItem::Divider {
+                    title,
+                    collapsible,
+                    collapsed,
+                },
+            });
         }
+
+        new_rows.push(Row {
+            items: row_items,
+            row_height: dashboard_row.row_height,
+            column_sizes: dashboard_row.column_sizes.unwrap_or_default(),
+            id: dashboard_row.id.parse::<u32>().unwrap_or(0),
+        });
     }
 
-    // Return unique metric IDs
-    metric_ids
+    Ok(new_rows)
+}
+
+/// Extract metric IDs from dashboard content
+fn extract_metric_ids_from_dashboard(dashboard: &DashboardYml) -> Vec<Uuid> {
+    dashboard.metric_ids().collect()
 }
 
 /// Update associations between a dashboard and its metrics
@@ -421,6 +455,8 @@ mod tests {
                 name: "Empty Dashboard".to_string(),
                 description: None,
                 rows: Vec::new(),
+                tabs: Vec::new(),
+                filters: Vec::new(),
             }),
         );
 
@@ -429,11 +465,13 @@ mod tests {
             name: "Original Dashboard".to_string(),
             description: Some("Original description".to_string()),
             rows: vec![Row {
-                items: vec![RowItem { id: Uuid::new_v4() }],
+                items: vec![RowItem::Metric { id: Uuid::new_v4() }],
                 row_height: Some(300),
                 column_sizes: vec![12],
                 id: 1,
             }],
+            tabs: Vec::new(),
+            filters: Vec::new(),
         };
 
         // Version 2 content
@@ -443,20 +481,22 @@ mod tests {
             rows: vec![
                 Row {
                     items: vec![
-                        RowItem { id: Uuid::new_v4() },
-                        RowItem { id: Uuid::new_v4() },
+                        RowItem::Metric { id: Uuid::new_v4() },
+                        RowItem::Metric { id: Uuid::new_v4() },
                     ],
                     row_height: Some(400),
                     column_sizes: vec![6, 6],
                     id: 1,
                 },
                 Row {
-                    items: vec![RowItem { id: Uuid::new_v4() }],
+                    items: vec![RowItem::Metric { id: Uuid::new_v4() }],
                     row_height: Some(300),
                     column_sizes: vec![12],
                     id: 2,
                 },
             ],
+            tabs: Vec::new(),
+            filters: Vec::new(),
         };
 
         // Add versions to history
@@ -526,18 +566,20 @@ mod tests {
             description: Some("Test Description".to_string()),
             rows: vec![
                 Row {
-                    items: vec![RowItem { id: uuid1 }, RowItem { id: uuid2 }],
+                    items: vec![RowItem::Metric { id: uuid1 }, RowItem::Metric { id: uuid2 }],
                     row_height: Some(400),
                     column_sizes: vec![6, 6],
                     id: 1,
                 },
                 Row {
-                    items: vec![RowItem { id: uuid3 }],
+                    items: vec![RowItem::Metric { id: uuid3 }],
                     row_height: Some(300),
                     column_sizes: vec![12],
                     id: 2,
                 },
             ],
+            tabs: Vec::new(),
+            filters: Vec::new(),
         };
 
         // Extract metric IDs
@@ -3,21 +3,23 @@
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use diesel::{upsert::excluded, ExpressionMethods, QueryDsl};
-use diesel_async::RunQueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
 use serde::Deserialize; // Added for DeployDatasetsRequest if it's moved or redefined here
 use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use tracing::{error, info, warn};
 
 // Types from this crate's parent (handlers) -> Corrected to super
-use super::types::{ValidationError, ValidationResult, DeployDatasetsRequest}; // Added DeployDatasetsRequest
+use super::types::{ValidationError, ValidationResult, DeployDatasetsRequest, DeployDatasetsColumnsRequest, DeployDatasetsOutcome}; // Added DeployDatasetsRequest
+use super::impact::analyze_deploy_impact;
+use super::semantic::validate_semantic_models;
 
 // Corrected to use the `database` crate directly as per Cargo.toml
 use database::{{
     enums::DatasetType,
-    models::{{DataSource, Dataset}},
+    models::{{DataSource, Dataset, DatasetColumn}},
     pool::get_pg_pool,
-    schema::{{data_sources, datasets}},
+    schema::{{data_sources, dataset_columns, datasets}},
 }};
 
 // TODO: Define or import necessary structs like DeployDatasetsRequest, DataSource, Dataset, etc.
@@ -32,8 +34,82 @@ pub async fn deploy_datasets_handler_core(
     user_id: &Uuid,
     organization_id: Uuid, // Pass organization_id directly
     requests: Vec<DeployDatasetsRequest>, // This now uses the imported DeployDatasetsRequest
+    force: bool, // Deploy even if the impact check finds metrics that would break
     // conn: &mut AsyncPgConnection, // Or get a connection from a pool passed in/accessible globally
-) -> Result<Vec<ValidationResult>> {
+) -> Result<DeployDatasetsOutcome> {
+    // --- SEMANTIC MODEL VALIDATION ---
+    let mut semantic_errors = validate_semantic_models(&requests);
+    if !semantic_errors.is_empty() {
+        let invalid_models = semantic_errors.len();
+        warn!(
+            "Blocking deployment: {} model(s) failed semantic validation",
+            invalid_models
+        );
+        let results = requests
+            .iter()
+            .map(|req| {
+                let mut validation = ValidationResult::new(
+                    req.name.clone(),
+                    req.data_source_name.clone(),
+                    req.schema.clone(),
+                );
+                match semantic_errors.remove(&req.name) {
+                    Some(errors) => errors.into_iter().for_each(|e| validation.add_error(e)),
+                    None => validation.add_error(ValidationError::semantic_validation_error(
+                        format!(
+                            "Deployment blocked: {} other model(s) failed semantic validation.",
+                            invalid_models
+                        ),
+                        None,
+                    )),
+                }
+                validation
+            })
+            .collect();
+        return Ok(DeployDatasetsOutcome {
+            results,
+            impact: Default::default(),
+            blocked: true,
+        });
+    }
+    // --- END SEMANTIC MODEL VALIDATION ---
+
+    // --- PRE-DEPLOY IMPACT CHECK ---
+    let impact = analyze_deploy_impact(organization_id, &requests).await?;
+    if !impact.is_empty() {
+        if !force {
+            warn!(
+                "Blocking deployment: {} metric(s) reference removed datasets or columns",
+                impact.impacted_metrics.len()
+            );
+            let results = requests
+                .iter()
+                .map(|req| {
+                    let mut validation = ValidationResult::new(
+                        req.name.clone(),
+                        req.data_source_name.clone(),
+                        req.schema.clone(),
+                    );
+                    validation.add_error(ValidationError::deploy_impact_error(format!(
+                        "Deployment blocked: {} metric(s) reference datasets or columns this deployment removes. Deploy with force (`buster deploy --force`) to deploy anyway.",
+                        impact.impacted_metrics.len()
+                    )));
+                    validation
+                })
+                .collect();
+            return Ok(DeployDatasetsOutcome {
+                results,
+                impact,
+                blocked: true,
+            });
+        }
+        warn!(
+            "Forcing deployment that breaks {} metric(s)",
+            impact.impacted_metrics.len()
+        );
+    }
+    // --- END PRE-DEPLOY IMPACT CHECK ---
+
     // Temporary: Get a connection. This needs to be replaced with proper DB connection management.
     let mut conn = database::pool::get_pg_pool().get().await?; // This path is incorrect from here
 
@@ -200,6 +276,21 @@ pub async fn deploy_datasets_handler_core(
                 Ok(num_upserted) => {
                     info!("Successfully upserted {} datasets for data source '{}'", num_upserted, data_source_name);
                     // Success is already marked for validation results, no change needed here unless specific counts matter.
+
+                    // The next deploy's impact check and metric validation
+                    // read the columns from here
+                    for dataset in &datasets_to_upsert {
+                        let Some(req) = group.iter().find(|req| req.name == dataset.name) else {
+                            continue;
+                        };
+                        if let Err(e) = upsert_dataset_columns(&mut conn, dataset.id, &req.columns).await {
+                            error!("Failed to upsert columns for dataset '{}': {}", dataset.name, e);
+                            if let Some(validation_result) = results.iter_mut().find(|r| r.model_name == dataset.name && r.data_source_name == data_source_name && r.schema == dataset.schema) {
+                                validation_result.success = false;
+                                validation_result.add_error(ValidationError::internal_error(format!("Failed to upsert dataset columns: {}", e)));
+                            }
+                        }
+                    }
                 }
                 Err(e) => {
                     error!("Failed to bulk upsert datasets for data_source_id '{}': {}", data_source.id, e);
@@ -212,8 +303,7 @@ pub async fn deploy_datasets_handler_core(
                     }
                 }
             };
-            // Column processing is skipped as per requirements.
-            // Stored values sync job logic is also skipped as it depends on columns.
+            // Stored values sync job logic is skipped as per requirements.
         } else {
             info!("No datasets to upsert for data source '{}'", data_source_name);
         }
@@ -293,7 +383,81 @@ pub async fn deploy_datasets_handler_core(
     }
     // --- END SOFT DELETION LOGIC ---
 
-    Ok(results)
+    Ok(DeployDatasetsOutcome {
+        results,
+        impact,
+        blocked: false,
+    })
+}
+
+/// Writes a deployed model's columns to `dataset_columns` and soft-deletes
+/// the ones it no longer defines. Stored values settings of existing columns
+/// are left alone.
+async fn upsert_dataset_columns(
+    conn: &mut AsyncPgConnection,
+    dataset_id: Uuid,
+    columns: &[DeployDatasetsColumnsRequest],
+) -> Result<()> {
+    let now = Utc::now();
+
+    let mut names = HashSet::new();
+    let rows: Vec<DatasetColumn> = columns
+        .iter()
+        .filter(|col| names.insert(col.name.as_str()))
+        .map(|col| DatasetColumn {
+            id: Uuid::new_v4(),
+            dataset_id,
+            name: col.name.clone(),
+            type_: col.type_.clone().unwrap_or_else(|| "text".to_string()),
+            description: Some(col.description.clone()),
+            nullable: true,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+            stored_values: Some(col.searchable),
+            stored_values_status: None,
+            stored_values_error: None,
+            stored_values_count: None,
+            stored_values_last_synced: None,
+            semantic_type: col.semantic_type.clone(),
+            dim_type: col.type_.clone(),
+            expr: col.expr.clone(),
+        })
+        .collect();
+
+    if !rows.is_empty() {
+        diesel::insert_into(dataset_columns::table)
+            .values(&rows)
+            .on_conflict((dataset_columns::dataset_id, dataset_columns::name))
+            .do_update()
+            .set((
+                dataset_columns::type_.eq(excluded(dataset_columns::type_)),
+                dataset_columns::description.eq(excluded(dataset_columns::description)),
+                dataset_columns::semantic_type.eq(excluded(dataset_columns::semantic_type)),
+                dataset_columns::dim_type.eq(excluded(dataset_columns::dim_type)),
+                dataset_columns::expr.eq(excluded(dataset_columns::expr)),
+                dataset_columns::updated_at.eq(now),
+                dataset_columns::deleted_at.eq(None as Option<DateTime<Utc>>),
+            ))
+            .execute(conn)
+            .await?;
+    }
+
+    let names: Vec<String> = names.into_iter().map(String::from).collect();
+    diesel::update(
+        dataset_columns::table
+            .filter(dataset_columns::dataset_id.eq(dataset_id))
+            .filter(dataset_columns::name.ne_all(&names))
+            .filter(dataset_columns::deleted_at.is_null()),
+    )
+    .set((
+        dataset_columns::deleted_at.eq(now),
+        dataset_columns::updated_at.eq(now),
+    ))
+    .execute(conn)
+    .await?;
+
+    Ok(())
 }
 
 // IMPORTANT: The database interaction parts (marked with // Incorrect path)
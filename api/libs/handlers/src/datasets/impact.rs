@@ -0,0 +1,273 @@
+use anyhow::Result;
+use database::{
+    helpers::metric_sql::metric_query_sql,
+    pool::get_pg_pool,
+    schema::{
+        dashboard_files, data_sources, dataset_columns, datasets, metric_files,
+        metric_files_to_dashboard_files, metric_files_to_datasets,
+    },
+    types::MetricYml,
+};
+use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl};
+use diesel_async::RunQueryDsl;
+use sql_analyzer::{analyze_query, types::TableKind};
+use std::collections::{HashMap, HashSet};
+use tracing::warn;
+use uuid::Uuid;
+
+use super::types::{DeployDatasetsRequest, DeployImpactReport, ImpactedDashboard, ImpactedMetric};
+
+/// What a deployment changes about an existing dataset.
+struct DatasetChange {
+    name: String,
+    /// The dataset is not part of the deployment and will be soft-deleted
+    removed: bool,
+    /// Lowercased names of columns that are no longer defined by the model
+    removed_columns: HashSet<String>,
+}
+
+/// Lists the metrics and dashboards that a deployment would break.
+///
+/// A deployment breaks a metric when its SQL reads from a dataset that the
+/// deployment soft-deletes, or references a column the redeployed model no
+/// longer defines (renaming a column shows up as removing the old name).
+/// The previous definition is read from `dataset_columns`, which every
+/// deploy rewrites for the models it deploys. Candidate metrics come from
+/// `metric_files_to_datasets`; each metric's current SQL is then analyzed
+/// to find the columns it actually uses.
+pub async fn analyze_deploy_impact(
+    organization_id: Uuid,
+    requests: &[DeployDatasetsRequest],
+) -> Result<DeployImpactReport> {
+    let mut conn = get_pg_pool().get().await?;
+
+    let mut requests_by_data_source: HashMap<(&str, &str), Vec<&DeployDatasetsRequest>> =
+        HashMap::new();
+    for req in requests {
+        requests_by_data_source
+            .entry((req.data_source_name.as_str(), req.env.as_str()))
+            .or_default()
+            .push(req);
+    }
+
+    let mut changes: HashMap<Uuid, DatasetChange> = HashMap::new();
+
+    for ((data_source_name, env), group) in requests_by_data_source {
+        // Unknown data sources are reported by the deployment itself
+        let data_source_id = match data_sources::table
+            .filter(data_sources::name.eq(data_source_name))
+            .filter(data_sources::env.eq(env))
+            .filter(data_sources::organization_id.eq(organization_id))
+            .filter(data_sources::deleted_at.is_null())
+            .select(data_sources::id)
+            .first::<Uuid>(&mut conn)
+            .await
+        {
+            Ok(id) => id,
+            Err(_) => continue,
+        };
+
+        let active_datasets = datasets::table
+            .filter(datasets::data_source_id.eq(data_source_id))
+            .filter(datasets::deleted_at.is_null())
+            .select((datasets::id, datasets::database_name))
+            .load::<(Uuid, String)>(&mut conn)
+            .await?;
+
+        let requests_by_name: HashMap<&str, &DeployDatasetsRequest> =
+            group.iter().map(|req| (req.name.as_str(), *req)).collect();
+
+        let redeployed_ids: Vec<Uuid> = active_datasets
+            .iter()
+            .filter(|(_, name)| requests_by_name.contains_key(name.as_str()))
+            .map(|(id, _)| *id)
+            .collect();
+
+        let mut existing_columns: HashMap<Uuid, Vec<String>> = HashMap::new();
+        if !redeployed_ids.is_empty() {
+            let columns = dataset_columns::table
+                .filter(dataset_columns::dataset_id.eq_any(&redeployed_ids))
+                .filter(dataset_columns::deleted_at.is_null())
+                .select((dataset_columns::dataset_id, dataset_columns::name))
+                .load::<(Uuid, String)>(&mut conn)
+                .await?;
+            for (dataset_id, name) in columns {
+                existing_columns.entry(dataset_id).or_default().push(name);
+            }
+        }
+
+        for (dataset_id, name) in active_datasets {
+            let Some(req) = requests_by_name.get(name.as_str()) else {
+                changes.insert(
+                    dataset_id,
+                    DatasetChange {
+                        name,
+                        removed: true,
+                        removed_columns: HashSet::new(),
+                    },
+                );
+                continue;
+            };
+
+            // A column survives if the new model still defines it by name or
+            // exposes it as the expression of a renamed column
+            let deployed_columns: HashSet<String> = req
+                .columns
+                .iter()
+                .flat_map(|col| std::iter::once(&col.name).chain(col.expr.as_ref()))
+                .map(|name| name.trim().to_lowercase())
+                .collect();
+
+            let removed_columns: HashSet<String> = existing_columns
+                .remove(&dataset_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|name| name.to_lowercase())
+                .filter(|name| !deployed_columns.contains(name))
+                .collect();
+
+            if !removed_columns.is_empty() {
+                changes.insert(
+                    dataset_id,
+                    DatasetChange {
+                        name,
+                        removed: false,
+                        removed_columns,
+                    },
+                );
+            }
+        }
+    }
+
+    if changes.is_empty() {
+        return Ok(DeployImpactReport::default());
+    }
+
+    let changed_ids: Vec<Uuid> = changes.keys().copied().collect();
+    let mut linked_metrics = metric_files_to_datasets::table
+        .inner_join(
+            metric_files::table.on(metric_files::id.eq(metric_files_to_datasets::metric_file_id)),
+        )
+        .filter(metric_files_to_datasets::dataset_id.eq_any(&changed_ids))
+        .filter(metric_files::organization_id.eq(organization_id))
+        .filter(metric_files::deleted_at.is_null())
+        .select((
+            metric_files::id,
+            metric_files::name,
+            metric_files::content,
+            metric_files::data_source_id,
+            metric_files_to_datasets::dataset_id,
+        ))
+        .load::<(Uuid, String, MetricYml, Uuid, Uuid)>(&mut conn)
+        .await?;
+
+    // The join table keeps a row per metric version
+    linked_metrics.sort_by_key(|(metric_id, _, _, _, dataset_id)| (*metric_id, *dataset_id));
+    linked_metrics.dedup_by_key(|(metric_id, _, _, _, dataset_id)| (*metric_id, *dataset_id));
+
+    let mut impacted_metrics = Vec::new();
+    for (metric_id, metric_name, content, data_source_id, dataset_id) in linked_metrics {
+        let change = &changes[&dataset_id];
+
+        // Parameterized SQL only parses once its placeholders are bound, and
+        // the time range's column is referenced like any other
+        let analysis = match metric_query_sql(&content, &HashMap::new(), &data_source_id).await {
+            Ok(sql) => analyze_query(sql).await.map_err(anyhow::Error::from),
+            Err(e) => Err(e),
+        };
+        let referenced_columns = match analysis {
+            Ok(summary) => {
+                let tables: Vec<_> = summary
+                    .tables
+                    .into_iter()
+                    .filter(|t| {
+                        t.kind == TableKind::Base
+                            && t.table_identifier.eq_ignore_ascii_case(&change.name)
+                    })
+                    .collect();
+                // Older versions of the metric may have used the dataset
+                if tables.is_empty() {
+                    continue;
+                }
+                Some(
+                    tables
+                        .into_iter()
+                        .flat_map(|t| t.columns)
+                        .map(|col| col.to_lowercase())
+                        .collect::<HashSet<_>>(),
+                )
+            }
+            Err(e) => {
+                warn!(
+                    "Could not analyze SQL of metric {} during deploy impact check: {}",
+                    metric_id, e
+                );
+                None
+            }
+        };
+
+        let mut missing_columns: Vec<String> = match &referenced_columns {
+            Some(columns) => change
+                .removed_columns
+                .intersection(columns)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+        missing_columns.sort();
+
+        // A metric whose SQL can't be analyzed is only reported when its
+        // whole dataset goes away
+        if !change.removed && missing_columns.is_empty() {
+            continue;
+        }
+
+        impacted_metrics.push(ImpactedMetric {
+            metric_id,
+            metric_name,
+            dataset_name: change.name.clone(),
+            dataset_removed: change.removed,
+            missing_columns,
+            dashboards: Vec::new(),
+        });
+    }
+
+    if impacted_metrics.is_empty() {
+        return Ok(DeployImpactReport::default());
+    }
+
+    let metric_ids: Vec<Uuid> = impacted_metrics.iter().map(|m| m.metric_id).collect();
+    let dashboards = metric_files_to_dashboard_files::table
+        .inner_join(
+            dashboard_files::table
+                .on(dashboard_files::id.eq(metric_files_to_dashboard_files::dashboard_file_id)),
+        )
+        .filter(metric_files_to_dashboard_files::metric_file_id.eq_any(&metric_ids))
+        .filter(metric_files_to_dashboard_files::deleted_at.is_null())
+        .filter(dashboard_files::deleted_at.is_null())
+        .select((
+            metric_files_to_dashboard_files::metric_file_id,
+            dashboard_files::id,
+            dashboard_files::name,
+        ))
+        .load::<(Uuid, Uuid, String)>(&mut conn)
+        .await?;
+
+    let mut dashboards_by_metric: HashMap<Uuid, Vec<ImpactedDashboard>> = HashMap::new();
+    for (metric_id, id, name) in dashboards {
+        dashboards_by_metric
+            .entry(metric_id)
+            .or_default()
+            .push(ImpactedDashboard { id, name });
+    }
+    for metric in &mut impacted_metrics {
+        if let Some(dashboards) = dashboards_by_metric.get(&metric.metric_id) {
+            metric.dashboards = dashboards.clone();
+        }
+    }
+
+    impacted_metrics
+        .sort_by(|a, b| (&a.dataset_name, &a.metric_name).cmp(&(&b.dataset_name, &b.metric_name)));
+
+    Ok(DeployImpactReport { impacted_metrics })
+}
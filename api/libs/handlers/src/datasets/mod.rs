@@ -1,2 +1,4 @@
 pub mod deploy;
-pub mod types; 
\ No newline at end of file
+pub mod impact;
+pub mod semantic;
+pub mod types;
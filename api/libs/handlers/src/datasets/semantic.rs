@@ -0,0 +1,80 @@
+use semantic_layer::models::{Dimension, Model};
+use semantic_layer::validate::{validate_models, SourcedModel};
+use std::collections::HashMap;
+
+use super::types::{DeployDatasetsRequest, ValidationError};
+
+/// Validates the semantic models of a deployment as a single graph.
+///
+/// Each request's `yml_file` holds the model it was generated from. Requests
+/// without one (or whose YAML can't be parsed) still take part as a model
+/// whose dimensions are the request's columns, so that entities pointing at
+/// them resolve. Errors are returned keyed by request name.
+pub fn validate_semantic_models(
+    requests: &[DeployDatasetsRequest],
+) -> HashMap<String, Vec<ValidationError>> {
+    let mut errors: HashMap<String, Vec<ValidationError>> = HashMap::new();
+
+    let models: Vec<Model> = requests
+        .iter()
+        .map(|req| match req.yml_file.as_deref() {
+            Some(yml) => match serde_yaml::from_str::<Model>(yml) {
+                Ok(model) => model,
+                Err(e) => {
+                    errors.entry(req.name.clone()).or_default().push(
+                        ValidationError::semantic_validation_error(
+                            format!("Model YAML could not be parsed: {}", e),
+                            None,
+                        ),
+                    );
+                    model_from_request(req)
+                }
+            },
+            None => model_from_request(req),
+        })
+        .collect();
+
+    let sourced: Vec<SourcedModel> = models
+        .iter()
+        .map(|model| SourcedModel { file: None, model })
+        .collect();
+
+    for error in validate_models(&sourced) {
+        let message = match &error.field {
+            Some(field) => format!("{}: {}", field, error.message),
+            None => error.message.clone(),
+        };
+        errors
+            .entry(error.model.clone())
+            .or_default()
+            .push(ValidationError::semantic_validation_error(message, error.field));
+    }
+
+    errors
+}
+
+fn model_from_request(req: &DeployDatasetsRequest) -> Model {
+    Model {
+        name: req.name.clone(),
+        description: Some(req.description.clone()),
+        data_source_name: Some(req.data_source_name.clone()),
+        database: req.database.clone(),
+        schema: Some(req.schema.clone()),
+        dimensions: req
+            .columns
+            .iter()
+            .map(|col| Dimension {
+                name: col.name.clone(),
+                description: Some(col.description.clone()),
+                type_: col.type_.clone(),
+                searchable: col.searchable,
+                options: None,
+                time: None,
+            })
+            .collect(),
+        measures: Vec::new(),
+        metrics: Vec::new(),
+        filters: Vec::new(),
+        relationships: Vec::new(),
+    }
+}
@@ -54,6 +54,22 @@ impl ValidationError {
         }
     }
 
+    pub fn semantic_validation_error(message: String, location: Option<String>) -> Self {
+        Self {
+            code: "SEMANTIC_VALIDATION".to_string(),
+            message,
+            location,
+        }
+    }
+
+    pub fn deploy_impact_error(message: String) -> Self {
+        Self {
+            code: "DEPLOY_IMPACT".to_string(),
+            message,
+            location: None,
+        }
+    }
+
     // Add other factory methods if they were used (e.g., table_not_found, column_not_found)
     // fn table_not_found(table_name: &str) -> Self {
     //     Self {
@@ -72,6 +88,49 @@ impl ValidationError {
 }
 // --- End Local Struct Definitions --- 
 
+// --- Deploy Impact Analysis ---
+
+/// Metrics and dashboards that a deployment would break
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DeployImpactReport {
+    pub impacted_metrics: Vec<ImpactedMetric>,
+}
+
+impl DeployImpactReport {
+    pub fn is_empty(&self) -> bool {
+        self.impacted_metrics.is_empty()
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImpactedMetric {
+    pub metric_id: Uuid,
+    pub metric_name: String,
+    pub dataset_name: String,
+    /// The dataset is missing from the deployment and would be removed
+    pub dataset_removed: bool,
+    /// Columns the metric uses that the redeployed model no longer defines
+    pub missing_columns: Vec<String>,
+    pub dashboards: Vec<ImpactedDashboard>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImpactedDashboard {
+    pub id: Uuid,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DeployDatasetsOutcome {
+    pub results: Vec<ValidationResult>,
+    pub impact: DeployImpactReport,
+    /// True when nothing was deployed because the impact check found broken
+    /// metrics and the deploy was not forced
+    pub blocked: bool,
+}
+
+// --- End Deploy Impact Analysis ---
+
 // --- Dataset Deployment Specific Request/Response Structs ---
 
 #[derive(Debug, Deserialize, Clone, Serialize)] // Added Serialize for potential use, Clone for handler
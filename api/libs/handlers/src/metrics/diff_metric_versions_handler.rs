@@ -0,0 +1,43 @@
+use anyhow::{anyhow, Result};
+use database::types::{MetricVersionDiff, MetricYml};
+use middleware::AuthenticatedUser;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::metrics::get_metric_handler;
+
+/// Differences between two versions of a metric
+#[derive(Debug, Serialize)]
+pub struct MetricVersionDiffResponse {
+    pub metric_id: Uuid,
+    pub from_version: i32,
+    pub to_version: i32,
+    #[serde(flatten)]
+    pub diff: MetricVersionDiff,
+}
+
+/// Compares two versions of a metric: changed SQL lines, chart config
+/// fields and other top-level fields. Either version may be older.
+pub async fn diff_metric_versions_handler(
+    metric_id: &Uuid,
+    user: &AuthenticatedUser,
+    from_version: i32,
+    to_version: i32,
+) -> Result<MetricVersionDiffResponse> {
+    // Loading each version through the get handler applies its permission checks
+    let from = get_metric_handler(metric_id, user, Some(from_version), None).await?;
+    let to = get_metric_handler(metric_id, user, Some(to_version), None).await?;
+
+    let parse = |file: &str| {
+        serde_yaml::from_str::<MetricYml>(file)
+            .map_err(|e| anyhow!("Failed to parse metric version: {}", e))
+    };
+    let diff = MetricVersionDiff::between(&parse(&from.file)?, &parse(&to.file)?)?;
+
+    Ok(MetricVersionDiffResponse {
+        metric_id: *metric_id,
+        from_version,
+        to_version,
+        diff,
+    })
+}
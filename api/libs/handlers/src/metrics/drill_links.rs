@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use database::models::{DashboardFile, MetricFile};
+use database::types::{DrillLink, DrillTarget};
+use middleware::AuthenticatedUser;
+use sharing::{fetch_filterable_dashboard_files, fetch_viewable_metric_files};
+use uuid::Uuid;
+
+use crate::metrics::types::MetricDrillLink;
+
+/// Resolves a metric's drill links for a viewer. Links are left out when the
+/// viewer can't open the target, or when the target no longer has the
+/// parameter or filter the clicked value is bound to. A dashboard target
+/// opens with a filter set, so it needs a role that can filter it.
+pub async fn resolve_drill_links(
+    drills: &[DrillLink],
+    user: &AuthenticatedUser,
+) -> Result<Vec<MetricDrillLink>> {
+    if drills.is_empty() {
+        return Ok(Vec::new());
+    }
+    let (metrics, dashboards) = fetch_targets(drills.iter(), user).await?;
+    Ok(viewable_links(drills, &metrics, &dashboards))
+}
+
+/// Resolves the drill links of several metrics, keyed by metric id, as
+/// [`resolve_drill_links`] does. Every target is fetched in one go.
+pub async fn resolve_drill_links_for_metrics(
+    drills: &HashMap<Uuid, Vec<DrillLink>>,
+    user: &AuthenticatedUser,
+) -> Result<HashMap<Uuid, Vec<MetricDrillLink>>> {
+    if drills.values().all(|drills| drills.is_empty()) {
+        return Ok(HashMap::new());
+    }
+    let (metrics, dashboards) = fetch_targets(drills.values().flatten(), user).await?;
+    Ok(drills
+        .iter()
+        .map(|(id, drills)| (*id, viewable_links(drills, &metrics, &dashboards)))
+        .collect())
+}
+
+/// The drill targets the viewer can open, among those the links point at
+async fn fetch_targets(
+    drills: impl Iterator<Item = &DrillLink>,
+    user: &AuthenticatedUser,
+) -> Result<(Vec<MetricFile>, Vec<DashboardFile>)> {
+    let mut metric_ids: Vec<Uuid> = Vec::new();
+    let mut dashboard_ids: Vec<Uuid> = Vec::new();
+    for drill in drills {
+        match &drill.target {
+            DrillTarget::Metric { id, .. } => metric_ids.push(*id),
+            DrillTarget::Dashboard { id, .. } => dashboard_ids.push(*id),
+        }
+    }
+    metric_ids.sort();
+    metric_ids.dedup();
+    dashboard_ids.sort();
+    dashboard_ids.dedup();
+
+    tokio::try_join!(
+        fetch_viewable_metric_files(&metric_ids, user),
+        fetch_filterable_dashboard_files(&dashboard_ids, user)
+    )
+}
+
+fn viewable_links(
+    drills: &[DrillLink],
+    metrics: &[MetricFile],
+    dashboards: &[DashboardFile],
+) -> Vec<MetricDrillLink> {
+    drills
+        .iter()
+        .filter_map(|drill| {
+            let target_name = match &drill.target {
+                DrillTarget::Metric { id, parameter } => {
+                    let metric = metrics.iter().find(|metric| metric.id == *id)?;
+                    metric
+                        .content
+                        .parameters
+                        .iter()
+                        .any(|p| &p.name == parameter)
+                        .then(|| metric.name.clone())?
+                }
+                DrillTarget::Dashboard { id, filter } => {
+                    let dashboard = dashboards.iter().find(|dashboard| dashboard.id == *id)?;
+                    dashboard
+                        .content
+                        .filters
+                        .iter()
+                        .any(|f| &f.id == filter)
+                        .then(|| dashboard.name.clone())?
+                }
+            };
+
+            Some(MetricDrillLink {
+                link: drill.clone(),
+                target_name,
+            })
+        })
+        .collect()
+}
@@ -1,21 +1,35 @@
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use database::{
+    helpers::dashboard_files::fetch_dashboard_file_with_permission,
+    helpers::metric_sql::{data_source_dialect, metric_comparison_sql, metric_query_sql},
     pool::get_pg_pool,
     schema::{dashboard_files, metric_files, metric_files_to_dashboard_files},
-    types::{data_metadata::DataMetadata, MetricYml},
+    types::{
+        dashboard_yml::{DashboardFilter, DashboardFilterType, DashboardYml},
+        data_metadata::DataMetadata,
+        ChartConfig, Comparison, MetricYml, TimeRange,
+    },
 };
 use diesel::{BoolExpressionMethods, ExpressionMethods, JoinOnDsl, QueryDsl};
 use diesel_async::RunQueryDsl;
 use indexmap::IndexMap;
 use middleware::AuthenticatedUser;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sharing::{check_permission_access, FILTER_ROLES};
+use sql_analyzer::{apply_column_filters, ColumnFilter, ColumnFilterCondition, FilterValue};
+use std::collections::HashMap;
 use uuid::Uuid;
 
 use query_engine::data_types::DataType;
 
+use crate::metrics::pivot::{pivot_table, PivotTable};
 use crate::metrics::{get_metric_for_dashboard_handler, get_metric_handler, BusterMetric};
 
+/// Rows the query engine returns when the request sets no limit
+const DEFAULT_ROW_LIMIT: i64 = 5000;
+
 /// Request structure for the get_metric_data handler
 #[derive(Debug, Deserialize)]
 pub struct GetMetricDataRequest {
@@ -23,6 +37,17 @@ pub struct GetMetricDataRequest {
     pub version_number: Option<i32>,
     pub limit: Option<i64>,
     pub password: Option<String>,
+    /// Dashboard the metric is being viewed on, required when `filters` is set
+    pub dashboard_id: Option<Uuid>,
+    /// Values for the dashboard's filters, keyed by filter id. A null value
+    /// leaves that filter unapplied.
+    #[serde(default)]
+    pub filters: HashMap<String, Value>,
+    /// Values for the metric's parameters, keyed by name, such as the
+    /// clicked value a drill link binds. Parameters without a value use
+    /// their default.
+    #[serde(default)]
+    pub parameters: HashMap<String, Value>,
 }
 
 /// Structure for the metric data response
@@ -31,6 +56,25 @@ pub struct MetricDataResponse {
     pub metric_id: Uuid,
     pub data: Vec<IndexMap<String, DataType>>,
     pub data_metadata: DataMetadata,
+    /// The metric over its comparison period, when it declares one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comparison: Option<ComparisonSeries>,
+    /// `data` cross-tabulated, for pivot charts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pivot: Option<PivotTable>,
+}
+
+/// Rows of the metric's comparison period, lined up with the current period
+#[derive(Debug, Serialize)]
+pub struct ComparisonSeries {
+    /// e.g. "Same period last year"
+    pub label: String,
+    pub start: Option<NaiveDate>,
+    /// Inclusive
+    pub end: Option<NaiveDate>,
+    /// One entry per row of `data`: the comparison row for the same bucket,
+    /// or null when the comparison period has none
+    pub data: Vec<Option<IndexMap<String, DataType>>>,
 }
 
 /// Handler to retrieve both the metric definition and its associated data
@@ -143,7 +187,46 @@ pub async fn get_metric_data_handler(
             return Err(anyhow!("Failed to parse metric definition: {}", parse_err));
         }
     };
-    let sql = metric_yml.sql;
+    // Parameters are bound and the time range applied first, since filters
+    // are applied to the parsed SQL
+    let sql = metric_query_sql(&metric_yml, &request.parameters, &metric.data_source_id)
+        .await
+        .map_err(|e| anyhow!("Invalid metric parameters or time range: {}", e))?;
+
+    let comparison_query =
+        metric_comparison_sql(&metric_yml, &request.parameters, &metric.data_source_id)
+            .await
+            .map_err(|e| anyhow!("Invalid metric comparison: {}", e))?;
+
+    let filters = if request.filters.is_empty() {
+        Vec::new()
+    } else {
+        let dashboard_id = request
+            .dashboard_id
+            .ok_or_else(|| anyhow!("A dashboard_id is required to apply dashboard filters"))?;
+        let dashboard = fetch_filterable_dashboard(&dashboard_id, &user).await?;
+        let filters = column_filters_for_metric(&dashboard, &request.metric_id, &request.filters)?;
+        tracing::debug!(
+            metric_id = %request.metric_id,
+            dashboard_id = %dashboard_id,
+            filter_count = filters.len(),
+            "Applying dashboard filters to metric SQL"
+        );
+        filters
+    };
+    // Filters are written in the data source's SQL dialect, looked up only
+    // when there are filters to apply
+    let dialect = if filters.is_empty() {
+        None
+    } else {
+        Some(data_source_dialect(&metric.data_source_id).await?)
+    };
+    let sql = match dialect {
+        Some(dialect) => apply_column_filters(sql, filters.clone(), dialect)
+            .await
+            .map_err(|e| anyhow!("Failed to apply dashboard filters: {}", e))?,
+        None => sql,
+    };
 
     // --- USE DIRECT DATA SOURCE ID ---
     let data_source_id = metric.data_source_id; // Already a Uuid
@@ -166,11 +249,19 @@ pub async fn get_metric_data_handler(
         .map_err(|e| anyhow!("Error retrieving cached metadata: {}", e))?;
     tracing::debug!("Cached metadata found: {}", cached_metadata.is_some());
 
+    // Pivot totals are computed from the returned rows, so pivot charts fetch
+    // one row past the limit to tell whether the limit cut any off
+    let row_limit = request.limit.unwrap_or(DEFAULT_ROW_LIMIT);
+    let query_limit = match &metric_yml.chart_config {
+        ChartConfig::Pivot(_) => Some(row_limit + 1),
+        _ => request.limit,
+    };
+
     // Execute the query to get the metric data
-    let query_result = match query_engine::data_source_query_routes::query_engine::query_engine(
+    let mut query_result = match query_engine::data_source_query_routes::query_engine::query_engine(
         &data_source_id, // Use the direct ID
         &sql,
-        request.limit,
+        query_limit,
     )
     .await
     {
@@ -190,6 +281,11 @@ pub async fn get_metric_data_handler(
             return Err(anyhow!("Error executing metric query: {}", e));
         }
     };
+    let truncated = query_result.data.len() as i64 > row_limit;
+    if truncated {
+        query_result.data.truncate(row_limit as usize);
+        query_result.metadata.row_count = row_limit;
+    }
 
     // Determine which metadata to use
     let final_metadata = if let Some(metadata) = cached_metadata {
@@ -215,6 +311,61 @@ pub async fn get_metric_data_handler(
         query_result.metadata.clone()
     };
 
+    let comparison = match (
+        &metric_yml.comparison,
+        &metric_yml.time_range,
+        comparison_query,
+    ) {
+        (Some(comparison), Some(time_range), Some(query)) => {
+            // Filters on the time range's own column would keep the comparison
+            // period's rows out, so they only apply to the current period
+            let filters: Vec<ColumnFilter> = filters
+                .into_iter()
+                .filter(|filter| filter.column != time_range.column)
+                .collect();
+            let sql = match dialect {
+                Some(dialect) if !filters.is_empty() => {
+                    apply_column_filters(query.sql, filters, dialect)
+                        .await
+                        .map_err(|e| anyhow!("Failed to apply dashboard filters: {}", e))?
+                }
+                _ => query.sql,
+            };
+            let comparison_result =
+                query_engine::data_source_query_routes::query_engine::query_engine(
+                    &data_source_id,
+                    &sql,
+                    request.limit,
+                )
+                .await
+                .map_err(|e| anyhow!("Error executing comparison query: {}", e))?;
+            tracing::debug!(
+                metric_id = %request.metric_id,
+                rows = comparison_result.data.len(),
+                "Executed metric comparison query"
+            );
+
+            Some(ComparisonSeries {
+                label: comparison.describe(),
+                start: query.start,
+                end: query.end.and_then(|end| end.pred_opt()),
+                data: align_comparison(
+                    comparison,
+                    time_range,
+                    &metric_yml.chart_config,
+                    &query_result.data,
+                    comparison_result.data,
+                ),
+            })
+        }
+        _ => None,
+    };
+
+    let pivot = match &metric_yml.chart_config {
+        ChartConfig::Pivot(config) => Some(pivot_table(config, &query_result.data, truncated)),
+        _ => None,
+    };
+
     // Construct and return the response
     tracing::info!(
         "Successfully retrieved data for metric {}. Returning response.",
@@ -224,5 +375,404 @@ pub async fn get_metric_data_handler(
         metric_id: request.metric_id,
         data: query_result.data,
         data_metadata: final_metadata,
+        comparison,
+        pivot,
     })
 }
+
+/// Pairs each row of the current period with the comparison row for the same
+/// bucket, by moving comparison buckets forward onto the current period.
+/// Rows are matched on the bucket date and the chart's other dimension
+/// columns, so each category or series is compared with itself.
+fn align_comparison(
+    comparison: &Comparison,
+    time_range: &TimeRange,
+    chart_config: &ChartConfig,
+    data: &[IndexMap<String, DataType>],
+    comparison_rows: Vec<IndexMap<String, DataType>>,
+) -> Vec<Option<IndexMap<String, DataType>>> {
+    let dimensions: Vec<&str> = chart_config
+        .dimension_columns()
+        .into_iter()
+        .filter(|column| !column.eq_ignore_ascii_case(&comparison.column_id))
+        .collect();
+    let series = |row: &IndexMap<String, DataType>| {
+        let values: Vec<Option<&DataType>> = dimensions
+            .iter()
+            .map(|column| column_value(row, column))
+            .collect();
+        serde_json::to_string(&values).unwrap_or_default()
+    };
+
+    let mut by_bucket = HashMap::new();
+    for row in comparison_rows {
+        if let Some(bucket) = bucket_date(&row, &comparison.column_id)
+            .and_then(|date| comparison.align(time_range, date))
+        {
+            by_bucket.entry((bucket, series(&row))).or_insert(row);
+        }
+    }
+
+    data.iter()
+        .map(|row| {
+            bucket_date(row, &comparison.column_id)
+                .and_then(|date| by_bucket.get(&(date, series(row))).cloned())
+        })
+        .collect()
+}
+
+/// A row's value in a column. Warehouses may return the column name in
+/// upper case.
+fn column_value<'a>(row: &'a IndexMap<String, DataType>, column: &str) -> Option<&'a DataType> {
+    row.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(column))
+        .map(|(_, value)| value)
+}
+
+/// The date in a row's bucket column. Warehouses may return dates as text.
+fn bucket_date(row: &IndexMap<String, DataType>, column: &str) -> Option<NaiveDate> {
+    match column_value(row, column)? {
+        DataType::Date(date) => *date,
+        DataType::Timestamp(timestamp) => timestamp.map(|t| t.date()),
+        DataType::Timestamptz(timestamp) => timestamp.map(|t| t.date_naive()),
+        DataType::Text(Some(text)) | DataType::Char(Some(text)) | DataType::Unknown(Some(text)) => {
+            NaiveDate::parse_from_str(text.get(..10)?, "%Y-%m-%d").ok()
+        }
+        _ => None,
+    }
+}
+
+/// Loads a dashboard's content if the user may filter it. Filtering needs
+/// `CanFilter` or higher; `CanView` only shows the dashboard as saved.
+async fn fetch_filterable_dashboard(
+    dashboard_id: &Uuid,
+    user: &AuthenticatedUser,
+) -> Result<DashboardYml> {
+    let dashboard = fetch_dashboard_file_with_permission(dashboard_id, &user.id)
+        .await?
+        .ok_or_else(|| anyhow!("Dashboard not found"))?;
+
+    let can_filter = check_permission_access(
+        dashboard.permission,
+        FILTER_ROLES,
+        dashboard.dashboard_file.organization_id,
+        &user.organizations,
+    );
+
+    if !can_filter {
+        return Err(anyhow!(
+            "You don't have permission to filter this dashboard"
+        ));
+    }
+
+    Ok(dashboard.dashboard_file.content)
+}
+
+/// Turns submitted filter values into predicates on the columns the
+/// dashboard binds for this metric. Filters that don't bind the metric, and
+/// filters with a null or empty value, are skipped.
+fn column_filters_for_metric(
+    dashboard: &DashboardYml,
+    metric_id: &Uuid,
+    values: &HashMap<String, Value>,
+) -> Result<Vec<ColumnFilter>> {
+    if let Some(unknown) = values
+        .keys()
+        .find(|id| !dashboard.filters.iter().any(|filter| &filter.id == *id))
+    {
+        return Err(anyhow!("Dashboard has no filter '{}'", unknown));
+    }
+
+    let mut column_filters = Vec::new();
+    for filter in &dashboard.filters {
+        let Some(column) = filter.columns.get(metric_id) else {
+            continue;
+        };
+        let Some(value) = values.get(&filter.id).filter(|value| !value.is_null()) else {
+            continue;
+        };
+        if let Some(condition) = filter_condition(filter, value)? {
+            column_filters.push(ColumnFilter {
+                column: column.clone(),
+                condition,
+            });
+        }
+    }
+
+    Ok(column_filters)
+}
+
+fn filter_condition(
+    filter: &DashboardFilter,
+    value: &Value,
+) -> Result<Option<ColumnFilterCondition>> {
+    let invalid = |expected: &str| {
+        anyhow!(
+            "Invalid value for filter '{}': expected {}, got {}",
+            filter.id,
+            expected,
+            value
+        )
+    };
+
+    match filter.filter_type {
+        DashboardFilterType::Select => {
+            let value = option_value(filter, value).ok_or_else(|| invalid("a string or number"))?;
+            Ok(Some(ColumnFilterCondition::Equals { value }))
+        }
+        DashboardFilterType::MultiSelect => {
+            let values = value
+                .as_array()
+                .ok_or_else(|| invalid("an array"))?
+                .iter()
+                .map(|value| {
+                    option_value(filter, value).ok_or_else(|| invalid("strings or numbers"))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Ok((!values.is_empty()).then_some(ColumnFilterCondition::In { values }))
+        }
+        DashboardFilterType::DateRange => {
+            let date = |key: &str| -> Result<Option<NaiveDate>> {
+                match value.get(key) {
+                    None | Some(Value::Null) => Ok(None),
+                    Some(Value::String(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                        .map(Some)
+                        .map_err(|_| invalid("dates formatted as YYYY-MM-DD")),
+                    Some(_) => Err(invalid("dates formatted as YYYY-MM-DD")),
+                }
+            };
+            if !value.is_object() {
+                return Err(invalid("an object with start and end dates"));
+            }
+            let start = date("start")?;
+            // The end date is inclusive, so compare timestamps against the following midnight
+            let end = date("end")?.and_then(|end| end.succ_opt());
+            if start.is_none() && end.is_none() {
+                return Ok(None);
+            }
+            let as_value =
+                |date: NaiveDate| FilterValue::String(date.format("%Y-%m-%d").to_string());
+            Ok(Some(ColumnFilterCondition::Range {
+                min: start.map(as_value),
+                max: end.map(as_value),
+                max_inclusive: false,
+            }))
+        }
+        DashboardFilterType::NumberRange => {
+            let number = |key: &str| -> Result<Option<f64>> {
+                match value.get(key) {
+                    None | Some(Value::Null) => Ok(None),
+                    Some(Value::Number(n)) => Ok(n.as_f64()),
+                    Some(_) => Err(invalid("numeric min and max")),
+                }
+            };
+            if !value.is_object() {
+                return Err(invalid("an object with min and max"));
+            }
+            let (min, max) = (number("min")?, number("max")?);
+            if min.is_none() && max.is_none() {
+                return Ok(None);
+            }
+            Ok(Some(ColumnFilterCondition::Range {
+                min: min.map(FilterValue::Number),
+                max: max.map(FilterValue::Number),
+                max_inclusive: true,
+            }))
+        }
+    }
+}
+
+/// A select value, checked against the filter's options when it has any
+fn option_value(filter: &DashboardFilter, value: &Value) -> Option<FilterValue> {
+    let (text, filter_value) = match value {
+        Value::String(s) => (s.clone(), FilterValue::String(s.clone())),
+        Value::Number(n) => (n.to_string(), FilterValue::Number(n.as_f64()?)),
+        _ => return None,
+    };
+    (filter.options.is_empty() || filter.options.contains(&text)).then_some(filter_value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::types::{ComparisonPeriod, TimePeriod, TimeUnit};
+    use serde_json::json;
+
+    fn dashboard(metric_id: Uuid) -> DashboardYml {
+        let yml = format!(
+            r#"
+name: Sales
+rows:
+  - id: 1
+    items:
+      - id: {metric_id}
+    columnSizes: [12]
+filters:
+  - id: period
+    name: Order date
+    type: dateRange
+    columns:
+      {metric_id}: created_at
+  - id: region
+    name: Region
+    type: multiSelect
+    options: [EU, US]
+    columns:
+      {metric_id}: region
+  - id: amount
+    name: Amount
+    type: numberRange
+    columns:
+      {metric_id}: o.amount
+"#
+        );
+        DashboardYml::new(yml).unwrap()
+    }
+
+    #[test]
+    fn test_column_filters_for_metric() {
+        let metric_id = Uuid::new_v4();
+        let values = HashMap::from([
+            (
+                "period".to_string(),
+                json!({ "start": "2024-01-01", "end": "2024-01-31" }),
+            ),
+            ("region".to_string(), json!(["EU"])),
+            ("amount".to_string(), Value::Null),
+        ]);
+
+        let filters =
+            column_filters_for_metric(&dashboard(metric_id), &metric_id, &values).unwrap();
+
+        assert_eq!(
+            filters,
+            vec![
+                ColumnFilter {
+                    column: "created_at".to_string(),
+                    condition: ColumnFilterCondition::Range {
+                        min: Some(FilterValue::String("2024-01-01".to_string())),
+                        max: Some(FilterValue::String("2024-02-01".to_string())),
+                        max_inclusive: false,
+                    },
+                },
+                ColumnFilter {
+                    column: "region".to_string(),
+                    condition: ColumnFilterCondition::In {
+                        values: vec![FilterValue::String("EU".to_string())],
+                    },
+                },
+            ]
+        );
+
+        // Metrics the dashboard doesn't bind are left unfiltered
+        assert!(
+            column_filters_for_metric(&dashboard(metric_id), &Uuid::new_v4(), &values)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_invalid_filter_values_are_rejected() {
+        let metric_id = Uuid::new_v4();
+        let dashboard = dashboard(metric_id);
+
+        for (id, value) in [
+            ("region", json!(["APAC"])),
+            ("period", json!({ "start": "01/01/2024" })),
+            ("amount", json!({ "min": "10" })),
+            ("unknown", json!("x")),
+        ] {
+            let values = HashMap::from([(id.to_string(), value)]);
+            assert!(
+                column_filters_for_metric(&dashboard, &metric_id, &values).is_err(),
+                "expected '{}' to be rejected",
+                id
+            );
+        }
+    }
+
+    #[test]
+    fn test_align_comparison() {
+        let time_range = TimeRange {
+            column: "created_at".to_string(),
+            period: TimePeriod::Previous {
+                unit: TimeUnit::Quarter,
+            },
+        };
+        let comparison = Comparison {
+            column_id: "month".to_string(),
+            period: ComparisonPeriod::SamePeriodLastYear,
+        };
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let row = |month: DataType, revenue: i64| {
+            IndexMap::from([
+                ("MONTH".to_string(), month),
+                ("REVENUE".to_string(), DataType::Int8(Some(revenue))),
+            ])
+        };
+
+        let data = vec![
+            row(DataType::Date(Some(date("2024-01-01"))), 10),
+            row(DataType::Date(Some(date("2024-02-01"))), 20),
+            row(DataType::Date(Some(date("2024-03-01"))), 30),
+        ];
+        let comparison_rows = vec![
+            row(DataType::Text(Some("2023-03-01T00:00:00".to_string())), 3),
+            row(DataType::Date(Some(date("2023-01-01"))), 1),
+        ];
+
+        let chart = |yml: &str| serde_yaml::from_str::<ChartConfig>(yml).unwrap();
+        let metric_chart =
+            chart("selectedChartType: metric\nmetricColumnId: revenue\ncolumnLabelFormats: {}\n");
+        let revenue = |aligned: &[Option<IndexMap<String, DataType>>]| -> Vec<Option<i64>> {
+            aligned
+                .iter()
+                .map(|row| match row.as_ref().map(|row| &row["REVENUE"]) {
+                    Some(DataType::Int8(revenue)) => *revenue,
+                    _ => None,
+                })
+                .collect()
+        };
+
+        let aligned = align_comparison(
+            &comparison,
+            &time_range,
+            &metric_chart,
+            &data,
+            comparison_rows,
+        );
+        assert_eq!(revenue(&aligned), vec![Some(1), None, Some(3)]);
+
+        // Rows split by a category line up with the same category's row
+        let region_row = |month: &str, region: &str, revenue: i64| {
+            let mut row = row(DataType::Date(Some(date(month))), revenue);
+            row.insert(
+                "REGION".to_string(),
+                DataType::Text(Some(region.to_string())),
+            );
+            row
+        };
+        let line_chart = chart(
+            "selectedChartType: line\nbarAndLineAxis:\n  x: [month]\n  y: [revenue]\n  category: [region]\ncolumnLabelFormats: {}\n",
+        );
+        let data = vec![
+            region_row("2024-01-01", "EU", 10),
+            region_row("2024-01-01", "US", 20),
+            region_row("2024-01-01", "APAC", 30),
+        ];
+        let comparison_rows = vec![
+            region_row("2023-01-01", "US", 2),
+            region_row("2023-01-01", "EU", 1),
+        ];
+
+        let aligned = align_comparison(
+            &comparison,
+            &time_range,
+            &line_chart,
+            &data,
+            comparison_rows,
+        );
+        assert_eq!(revenue(&aligned), vec![Some(1), Some(2), None]);
+    }
+}
@@ -197,6 +197,7 @@ pub async fn get_metric_for_dashboard_handler(
         .map(|v| Version {
             version_number: v.version_number,
             updated_at: v.updated_at,
+            restored_from: v.restored_from.clone(),
         })
         .collect();
 
@@ -299,5 +300,7 @@ pub async fn get_metric_for_dashboard_handler(
         public_expiry_date: None,   // Default value
         public_enabled_by: None,    // Default value
         public_password: None,      // Default value
+        drill_links: vec![],        // No viewer here; the caller resolves them
+        drills: resolved_content_for_yaml.drills,
     })
 } 
\ No newline at end of file
@@ -16,7 +16,7 @@ use database::schema::{
 };
 use sharing::check_permission_access;
 
-use super::Version;
+use super::{resolve_drill_links, Version};
 
 #[derive(Queryable)]
 struct DatasetInfo {
@@ -322,6 +322,7 @@ pub async fn get_metric_handler(
         .map(|v| Version {
             version_number: v.version_number,
             updated_at: v.updated_at,
+            restored_from: v.restored_from.clone(),
         })
         .collect();
 
@@ -386,6 +387,19 @@ pub async fn get_metric_handler(
         }
     };
 
+    // Only expose drill links whose targets the viewer can open
+    let drill_links = match resolve_drill_links(&resolved_content_for_yaml.drills, user).await {
+        Ok(drill_links) => drill_links,
+        Err(e) => {
+            tracing::error!(
+                "Failed to resolve drill links for metric {}: {}",
+                metric_id,
+                e
+            );
+            vec![]
+        }
+    };
+
     // Convert AssetPermissionInfo to BusterShareIndividual
     let individual_permissions = match individual_permissions_query {
         Ok(permissions) => {
@@ -441,5 +455,7 @@ pub async fn get_metric_handler(
         public_expiry_date: metric_file.public_expiry_date, // Not versioned
         public_enabled_by: public_enabled_by_user, // Not versioned
         public_password: metric_file.public_password, // Not versioned
+        drill_links, // Resolved for this viewer
+        drills: resolved_content_for_yaml.drills,
     })
 }
@@ -1,8 +1,12 @@
 pub mod bulk_update_metrics_handler;
 pub mod delete_metric_handler;
+pub mod diff_metric_versions_handler;
+pub mod drill_links;
 pub mod get_metric_data_handler;
 pub mod get_metric_handler;
 pub mod list_metrics_handler;
+pub mod pivot;
+pub mod restore_metric_version_handler;
 pub mod sharing;
 pub mod types;
 pub mod update_metric_handler;
@@ -11,15 +15,19 @@ pub mod get_metric_for_dashboard_handler;
 // Re-export specific items from handlers
 pub use bulk_update_metrics_handler::*;
 pub use delete_metric_handler::*;
+pub use diff_metric_versions_handler::*;
+pub use drill_links::*;
 pub use get_metric_handler::*;
 pub use list_metrics_handler::*;
+pub use pivot::*;
+pub use restore_metric_version_handler::*;
 pub use update_metric_handler::*;
 pub use get_metric_for_dashboard_handler::get_metric_for_dashboard_handler;
 
 // For get_metric_data_handler, only export the handler functions and request types
 // but not the types that conflict with types.rs
 pub use get_metric_data_handler::{
-    get_metric_data_handler, GetMetricDataRequest, MetricDataResponse,
+    get_metric_data_handler, ComparisonSeries, GetMetricDataRequest, MetricDataResponse,
 };
 
 // Re-export types and sharing
@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+
+use database::types::{ColumnLabelFormat, PivotAggregate, PivotChartConfig};
+use indexmap::IndexMap;
+use query_engine::data_types::DataType;
+use serde::Serialize;
+use serde_json::Value;
+
+/// A pivot chart's query result, cross-tabulated by the server
+#[derive(Debug, Serialize, Clone)]
+pub struct PivotTable {
+    /// Generated columns, shown after the row dimensions
+    pub columns: Vec<PivotColumn>,
+    pub rows: Vec<PivotRow>,
+    /// Generated columns left out by `pivotMaxColumns`. Totals still cover
+    /// them.
+    pub truncated_columns: usize,
+    /// Whether the query hit its row limit, so cells and totals only cover
+    /// the rows returned
+    pub partial: bool,
+    /// `columnLabelFormats` for the generated columns, taken from their
+    /// value column
+    pub column_label_formats: IndexMap<String, ColumnLabelFormat>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PivotColumn {
+    pub id: String,
+    /// Values of the column dimensions, empty for a total column
+    pub dimensions: Vec<DataType>,
+    pub value_column: String,
+    pub aggregate: PivotAggregate,
+    pub total: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PivotRow {
+    pub kind: PivotRowKind,
+    /// Values of the row dimensions. Subtotal rows only have the levels they
+    /// group by, and the grand total has none.
+    pub dimensions: Vec<DataType>,
+    /// One per generated column; null where no rows fall in the cell
+    pub values: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PivotRowKind {
+    Row,
+    Subtotal,
+    GrandTotal,
+}
+
+/// Running totals for one value in one cell
+#[derive(Debug, Default, Clone)]
+struct Accumulator {
+    count: usize,
+    numbers: usize,
+    sum: f64,
+    min: Option<f64>,
+    max: Option<f64>,
+}
+
+impl Accumulator {
+    fn add(&mut self, value: &DataType) {
+        if matches!(serde_json::to_value(value), Ok(Value::Null)) {
+            return;
+        }
+        self.count += 1;
+        if let Some(number) = number(value) {
+            self.numbers += 1;
+            self.sum += number;
+            self.min = Some(self.min.map_or(number, |min| min.min(number)));
+            self.max = Some(self.max.map_or(number, |max| max.max(number)));
+        }
+    }
+
+    fn result(&self, aggregate: PivotAggregate) -> Option<f64> {
+        match aggregate {
+            PivotAggregate::Count => Some(self.count as f64),
+            PivotAggregate::Sum => (self.numbers > 0).then_some(self.sum),
+            PivotAggregate::Average => (self.numbers > 0).then(|| self.sum / self.numbers as f64),
+            PivotAggregate::Min => self.min,
+            PivotAggregate::Max => self.max,
+        }
+    }
+}
+
+/// Pivots `data` as the chart config declares. Rows and columns keep the
+/// order their dimension values first appear in, and every cell, subtotal
+/// and total is aggregated from the underlying rows. `partial` marks `data`
+/// as cut short by the query's row limit.
+pub fn pivot_table(
+    config: &PivotChartConfig,
+    data: &[IndexMap<String, DataType>],
+    partial: bool,
+) -> PivotTable {
+    let axis = &config.pivot_axis;
+    let depth = axis.rows.len();
+
+    let mut column_keys: IndexMap<String, Vec<DataType>> = IndexMap::new();
+    for row in data {
+        let values = dimension_values(row, &axis.columns);
+        column_keys.entry(group_key(&values)).or_insert(values);
+    }
+    let kept_keys = match axis.values.len() {
+        0 => 0,
+        count => column_keys.len().min(config.max_columns() as usize / count),
+    };
+    let truncated_columns = (column_keys.len() - kept_keys) * axis.values.len();
+
+    let mut columns = Vec::new();
+    let mut cell_columns = Vec::new();
+    for (index, (_, dimensions)) in column_keys.iter().take(kept_keys).enumerate() {
+        for (value_index, value) in axis.values.iter().enumerate() {
+            let id = if axis.columns.is_empty() {
+                value.column_id.clone()
+            } else {
+                let labels: Vec<String> = dimensions.iter().map(label).collect();
+                format!("{}__{}", value.column_id, labels.join("__"))
+            };
+            columns.push(PivotColumn {
+                id,
+                dimensions: dimensions.clone(),
+                value_column: value.column_id.clone(),
+                aggregate: value.aggregate,
+                total: false,
+            });
+            cell_columns.push((Some(index), value_index));
+        }
+    }
+    if config.show_grand_totals() && !axis.columns.is_empty() {
+        for (value_index, value) in axis.values.iter().enumerate() {
+            columns.push(PivotColumn {
+                id: format!("{}__total", value.column_id),
+                dimensions: Vec::new(),
+                value_column: value.column_id.clone(),
+                aggregate: value.aggregate,
+                total: true,
+            });
+            cell_columns.push((None, value_index));
+        }
+    }
+
+    // Row groups are keyed by how many row dimensions they cover: `depth`
+    // for a row, fewer for a subtotal and zero for the grand total
+    let mut levels = vec![depth];
+    if config.show_subtotals() {
+        levels.extend(1..depth);
+    }
+    if config.show_grand_totals() && depth > 0 {
+        levels.push(0);
+    }
+
+    let mut leaves: IndexMap<String, Vec<DataType>> = IndexMap::new();
+    let mut first_seen: HashMap<(usize, String), usize> = HashMap::new();
+    let mut cells: HashMap<(usize, String, Option<usize>), Vec<Accumulator>> = HashMap::new();
+    for row in data {
+        let dimensions = dimension_values(row, &axis.rows);
+        for level in 1..=depth {
+            let seen = first_seen.len();
+            first_seen
+                .entry((level, group_key(&dimensions[..level])))
+                .or_insert(seen);
+        }
+        let column = column_keys
+            .get_index_of(&group_key(&dimension_values(row, &axis.columns)))
+            .filter(|index| *index < kept_keys);
+
+        for &level in &levels {
+            let group = group_key(&dimensions[..level]);
+            // Every row counts toward the total column, truncated or not
+            for column in std::iter::once(None).chain(column.map(Some)) {
+                let accumulators = cells
+                    .entry((level, group.clone(), column))
+                    .or_insert_with(|| vec![Accumulator::default(); axis.values.len()]);
+                for (accumulator, value) in accumulators.iter_mut().zip(&axis.values) {
+                    if let Some((_, cell)) = find_column(row, &value.column_id) {
+                        accumulator.add(cell);
+                    }
+                }
+            }
+        }
+        leaves.entry(group_key(&dimensions)).or_insert(dimensions);
+    }
+
+    // Sort rows so each group's rows sit together, in first-seen order
+    let order = |dimensions: &[DataType]| -> Vec<usize> {
+        (1..=depth)
+            .map(|level| first_seen[&(level, group_key(&dimensions[..level]))])
+            .collect()
+    };
+    let mut leaves: Vec<Vec<DataType>> = leaves.into_values().collect();
+    leaves.sort_by_key(|dimensions| order(dimensions));
+
+    let row_values = |level: usize, dimensions: &[DataType]| -> Vec<Option<f64>> {
+        let group = group_key(dimensions);
+        cell_columns
+            .iter()
+            .map(|(column, value_index)| {
+                let accumulators = cells.get(&(level, group.clone(), *column))?;
+                accumulators[*value_index].result(axis.values[*value_index].aggregate)
+            })
+            .collect()
+    };
+
+    let mut rows = Vec::new();
+    for (index, dimensions) in leaves.iter().enumerate() {
+        rows.push(PivotRow {
+            kind: PivotRowKind::Row,
+            dimensions: dimensions.clone(),
+            values: row_values(depth, dimensions),
+        });
+        if !config.show_subtotals() {
+            continue;
+        }
+        // Close each group this row is the last of, innermost first
+        let next = leaves.get(index + 1);
+        for level in (1..depth).rev() {
+            if next.is_some_and(|next| next[..level] == dimensions[..level]) {
+                break;
+            }
+            rows.push(PivotRow {
+                kind: PivotRowKind::Subtotal,
+                dimensions: dimensions[..level].to_vec(),
+                values: row_values(level, &dimensions[..level]),
+            });
+        }
+    }
+    if config.show_grand_totals() && depth > 0 {
+        rows.push(PivotRow {
+            kind: PivotRowKind::GrandTotal,
+            dimensions: Vec::new(),
+            values: row_values(0, &[]),
+        });
+    }
+
+    let formats = &config.base.column_label_formats;
+    let column_label_formats = columns
+        .iter()
+        .filter(|column| column.id != column.value_column)
+        .filter_map(|column| {
+            // A count is a number whatever the column it counts holds
+            let format = match column.aggregate {
+                PivotAggregate::Count => ColumnLabelFormat::new_number(),
+                _ => formats.get(&column.value_column)?.clone(),
+            };
+            Some((column.id.clone(), format))
+        })
+        .collect();
+
+    PivotTable {
+        columns,
+        rows,
+        truncated_columns,
+        partial,
+        column_label_formats,
+    }
+}
+
+/// Warehouses may return column names in upper case
+fn find_column<'a>(
+    row: &'a IndexMap<String, DataType>,
+    column: &str,
+) -> Option<(&'a String, &'a DataType)> {
+    row.iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(column))
+}
+
+fn dimension_values(row: &IndexMap<String, DataType>, columns: &[String]) -> Vec<DataType> {
+    columns
+        .iter()
+        .map(|column| find_column(row, column).map_or(DataType::Null, |(_, value)| value.clone()))
+        .collect()
+}
+
+fn group_key(values: &[DataType]) -> String {
+    serde_json::to_string(values).unwrap_or_default()
+}
+
+fn label(value: &DataType) -> String {
+    match serde_json::to_value(value) {
+        Ok(Value::String(text)) => text,
+        Ok(value) => value.to_string(),
+        Err(_) => String::new(),
+    }
+}
+
+fn number(value: &DataType) -> Option<f64> {
+    match value {
+        DataType::Int2(Some(n)) => Some(f64::from(*n)),
+        DataType::Int4(Some(n)) => Some(f64::from(*n)),
+        DataType::Int8(Some(n)) => Some(*n as f64),
+        DataType::Float4(Some(n)) => Some(f64::from(*n)),
+        DataType::Float8(Some(n)) => Some(*n),
+        DataType::Decimal(Some(n)) => n.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::types::{ChartConfig, MetricYml};
+
+    fn config(extra: &str) -> PivotChartConfig {
+        let yml = format!(
+            r#"name: Revenue by Region
+sql: SELECT region, country, quarter, revenue, order_id FROM orders
+chartConfig:
+  selectedChartType: pivot
+  pivotAxis:
+    rows: [region, country]
+    columns: [quarter]
+    values:
+      - columnId: revenue
+      - columnId: order_id
+        aggregate: count
+  columnLabelFormats:
+    revenue: {{ columnType: number, style: currency }}
+{}"#,
+            extra
+        );
+        match MetricYml::new(yml).unwrap().chart_config {
+            ChartConfig::Pivot(config) => config,
+            _ => panic!("Expected pivot chart config"),
+        }
+    }
+
+    fn data() -> Vec<IndexMap<String, DataType>> {
+        [
+            ("EU", "DE", "Q1", 10),
+            ("EU", "FR", "Q1", 5),
+            ("US", "US", "Q1", 20),
+            ("EU", "DE", "Q2", 7),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(id, (region, country, quarter, revenue))| {
+            let text = |s: &str| DataType::Text(Some(s.to_string()));
+            IndexMap::from([
+                ("REGION".to_string(), text(region)),
+                ("COUNTRY".to_string(), text(country)),
+                ("QUARTER".to_string(), text(quarter)),
+                ("REVENUE".to_string(), DataType::Int8(Some(revenue))),
+                ("ORDER_ID".to_string(), DataType::Int4(Some(id as i32))),
+            ])
+        })
+        .collect()
+    }
+
+    /// A row as its dimensions and values, with `-` for an empty cell
+    fn cells(row: &PivotRow) -> String {
+        let dimensions: Vec<String> = row.dimensions.iter().map(label).collect();
+        let values: Vec<String> = row
+            .values
+            .iter()
+            .map(|value| value.map_or("-".to_string(), |v| v.to_string()))
+            .collect();
+        format!("{} | {}", dimensions.join(" "), values.join(" "))
+    }
+
+    #[test]
+    fn test_pivot_table_with_subtotals_and_totals() {
+        let pivot = pivot_table(&config(""), &data(), false);
+
+        let ids: Vec<&str> = pivot.columns.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "revenue__Q1",
+                "order_id__Q1",
+                "revenue__Q2",
+                "order_id__Q2",
+                "revenue__total",
+                "order_id__total",
+            ]
+        );
+
+        let rows: Vec<(PivotRowKind, String)> = pivot
+            .rows
+            .iter()
+            .map(|row| (row.kind, cells(row)))
+            .collect();
+        let (row, subtotal, total) = (
+            PivotRowKind::Row,
+            PivotRowKind::Subtotal,
+            PivotRowKind::GrandTotal,
+        );
+        assert_eq!(
+            rows,
+            vec![
+                (row, "EU DE | 10 1 7 1 17 2".to_string()),
+                (row, "EU FR | 5 1 - - 5 1".to_string()),
+                (subtotal, "EU | 15 2 7 1 22 3".to_string()),
+                (row, "US US | 20 1 - - 20 1".to_string()),
+                (subtotal, "US | 20 1 - - 20 1".to_string()),
+                (total, " | 35 3 7 1 42 4".to_string()),
+            ]
+        );
+
+        assert_eq!(pivot.column_label_formats["revenue__Q2"].style, "currency");
+        assert_eq!(
+            pivot.column_label_formats["order_id__total"].style,
+            "number"
+        );
+        assert_eq!(pivot.truncated_columns, 0);
+        assert!(!pivot.partial);
+    }
+
+    #[test]
+    fn test_pivot_table_column_cap() {
+        let pivot = pivot_table(
+            &config("  pivotMaxColumns: 2\n  pivotShowSubtotals: false\n"),
+            &data(),
+            true,
+        );
+
+        let ids: Vec<&str> = pivot.columns.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "revenue__Q1",
+                "order_id__Q1",
+                "revenue__total",
+                "order_id__total"
+            ]
+        );
+        assert_eq!(pivot.truncated_columns, 2);
+        assert!(pivot.partial);
+        assert!(pivot
+            .rows
+            .iter()
+            .all(|row| row.kind != PivotRowKind::Subtotal));
+        // Totals still cover the columns that were cut
+        let grand_total = pivot.rows.last().unwrap();
+        assert_eq!(grand_total.kind, PivotRowKind::GrandTotal);
+        assert_eq!(grand_total.values[2], Some(42.0));
+    }
+}
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::metrics::{update_metric_handler, BusterMetric, UpdateMetricRequest};
+
+/// Restores a metric to an earlier version by adding that version's content
+/// as a new version. The new version records who restored it and why.
+pub async fn restore_metric_version_handler(
+    metric_id: &Uuid,
+    user: &AuthenticatedUser,
+    version_number: i32,
+    reason: Option<String>,
+) -> Result<BusterMetric> {
+    let request = UpdateMetricRequest {
+        restore_to_version: Some(version_number),
+        restore_reason: reason,
+        ..Default::default()
+    };
+    update_metric_handler(metric_id, user, request).await
+}
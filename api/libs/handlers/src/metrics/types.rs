@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use database::{enums::{AssetPermissionRole, Verification}, types::{ChartConfig, DataMetadata}};
+use database::{enums::{AssetPermissionRole, Verification}, types::{ChartConfig, DataMetadata, DrillLink, VersionRestore}};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::collections::HashMap;
@@ -16,6 +16,8 @@ pub struct Dataset {
 pub struct Version {
     pub version_number: i32,
     pub updated_at: DateTime<Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restored_from: Option<VersionRestore>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -61,6 +63,20 @@ pub struct BusterMetric {
     pub public_enabled_by: Option<String>,
     pub publicly_accessible: bool,
     pub public_password: Option<String>,
+    /// Drill links whose targets the viewer can open
+    pub drill_links: Vec<MetricDrillLink>,
+    /// The drill links as the metric declares them, before they're resolved
+    /// for a viewer
+    #[serde(skip)]
+    pub drills: Vec<DrillLink>,
+}
+
+/// A drill link along with the name of the metric or dashboard it opens
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetricDrillLink {
+    #[serde(flatten)]
+    pub link: DrillLink,
+    pub target_name: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
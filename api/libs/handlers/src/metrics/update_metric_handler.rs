@@ -3,15 +3,16 @@ use chrono::{DateTime, Utc};
 use database::{
     enums::{AssetPermissionRole, AssetType, IdentityType, Verification},
     helpers::metric_files::fetch_metric_file_with_permissions,
-    models::{Dataset, MetricFile, MetricFileToDataset},
+    helpers::metric_sql::metric_query_sql,
+    models::{MetricFile, MetricFileToDataset},
     pool::get_pg_pool,
-    schema::{datasets, metric_files, metric_files_to_datasets},
+    schema::{metric_files, metric_files_to_datasets},
     types::{
-        ColumnLabelFormat, ColumnMetaData, ColumnType, DataMetadata, MetricYml, SimpleType,
-        VersionContent, VersionHistory,
+        metric_yml::TimeRange, ColumnLabelFormat, ColumnMetaData, ColumnType, DataMetadata,
+        MetricYml, SimpleType, VersionContent, VersionHistory, VersionRestore,
     },
 };
-use dataset_security::has_all_datasets_access;
+use dataset_security::sql_references::{format_reference_errors, resolve_sql_references};
 use diesel::{insert_into, AsChangeset, ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
 use indexmap;
@@ -20,7 +21,8 @@ use query_engine::data_source_query_routes::query_engine::query_engine;
 use serde_json::Value;
 use sharing::check_permission_access;
 use sql_analyzer::{analyze_query, types::TableKind};
-use tracing::{debug, error, warn};
+use std::collections::HashMap;
+use tracing::{debug, error};
 use uuid::Uuid;
 
 /// Recursively merges two JSON objects.
@@ -67,12 +69,15 @@ pub struct UpdateMetricRequest {
     pub description: Option<String>,
     pub chart_config: Option<Value>,
     pub time_frame: Option<String>,
+    pub time_range: Option<TimeRange>,
     #[serde(alias = "status")]
     pub verification: Option<database::enums::Verification>,
     pub file: Option<String>,
     pub sql: Option<String>,
     pub update_version: Option<bool>,
     pub restore_to_version: Option<i32>,
+    /// Why the version is being restored, kept in the version history
+    pub restore_reason: Option<String>,
 }
 
 /// Handler to update a metric by ID
@@ -147,6 +152,13 @@ pub async fn update_metric_handler(
     let mut current_version_history = current_metric_file_record.version_history.clone();
     let data_source_id: Option<Uuid> = Some(current_metric_file_record.data_source_id); // Start with existing
 
+    // Name or description edits shouldn't be blocked by an existing chart config
+    let changes_chart = request.chart_config.is_some()
+        || request.sql.is_some()
+        || request.time_range.is_some()
+        || request.file.is_some()
+        || request.restore_to_version.is_some();
+
     // --- Determine Content to Save ---
     let mut final_content: MetricYml = if let Some(version_number) = request.restore_to_version {
         // ... restore logic (doesn't change dataset handling directly here) ...
@@ -173,6 +185,13 @@ pub async fn update_metric_handler(
             let merged_config = merge_json_objects(existing_config, chart_config)?;
             current_content.chart_config = serde_json::from_value(merged_config)?;
         }
+        if let Some(time_range) = request.time_range.clone() {
+            // A label that described the old range would now be wrong
+            if request.time_frame.is_none() {
+                current_content.time_frame = time_range.describe();
+            }
+            current_content.time_range = Some(time_range);
+        }
         if let Some(time_frame) = request.time_frame {
             current_content.time_frame = time_frame;
         }
@@ -189,65 +208,55 @@ pub async fn update_metric_handler(
     // --- SQL Validation, Dataset ID Extraction, and Metadata Calculation ---
     let mut data_metadata: Option<DataMetadata> = current_metric_file_record.data_metadata; // Default to existing
     let mut validated_dataset_ids: Vec<Uuid> = Vec::new(); // Store validated IDs for association
-    let requires_revalidation =
-        request.sql.is_some() || request.file.is_some() || request.restore_to_version.is_some();
+    let requires_revalidation = request.sql.is_some()
+        || request.time_range.is_some()
+        || request.file.is_some()
+        || request.restore_to_version.is_some();
 
     if requires_revalidation {
+        // Parameterized SQL is validated with every parameter at its default
+        let sql = match data_source_id {
+            Some(ds_id) => metric_query_sql(&final_content, &HashMap::new(), &ds_id).await?,
+            None => final_content.sql.clone(),
+        };
+
         // 1. Analyze SQL to get table names
-        let analysis_result = analyze_query(final_content.sql.clone()).await?;
-        let table_names: Vec<String> = analysis_result
+        let analysis_result = analyze_query(sql.clone()).await?;
+        let has_base_tables = analysis_result
             .tables
-            .into_iter()
-            .filter(|t| t.kind == TableKind::Base)
-            .map(|t| t.table_identifier.clone())
-            .collect();
+            .iter()
+            .any(|t| t.kind == TableKind::Base);
 
-        if !table_names.is_empty() {
+        if has_base_tables {
             // Need a data source ID to find datasets. If it wasn't set before, error out.
             let ds_id = data_source_id.ok_or_else(|| {
                 anyhow!("Cannot validate SQL without a data source ID associated with the metric")
             })?;
 
-            // 2. Find corresponding Datasets
-            let found_datasets = datasets::table
-                .filter(datasets::data_source_id.eq(ds_id))
-                .filter(datasets::name.eq_any(&table_names))
-                .filter(datasets::deleted_at.is_null())
-                .load::<Dataset>(&mut conn)
-                .await?;
-
-            let current_dataset_ids: Vec<Uuid> = found_datasets.iter().map(|ds| ds.id).collect();
-
-            // 3. Check Permissions
-            if !current_dataset_ids.is_empty() {
-                if !has_all_datasets_access(&user.id, &current_dataset_ids).await? {
-                    bail!("Permission denied: User {} does not have access to one or more datasets required by the updated query: {:?}", user.id, table_names);
-                }
-                validated_dataset_ids = current_dataset_ids; // Store these IDs for association
-            } else {
-                warn!("Tables {:?} mentioned in updated query not found as datasets for data source {}", table_names, ds_id);
-                // Decide if this is an error or not. For now, allow update but associate no datasets.
-                validated_dataset_ids.clear();
+            // 2. Resolve tables and columns against datasets the user may query
+            let resolved = resolve_sql_references(&analysis_result, &ds_id, &user.id).await?;
+            if !resolved.errors.is_empty() {
+                bail!(
+                    "Updated SQL references unknown or inaccessible datasets or columns:\n{}",
+                    format_reference_errors(&resolved.errors)
+                );
             }
 
+            // 3. Store the resolved dataset IDs for association
+            validated_dataset_ids = resolved.dataset_ids;
+
             // 4. Execute Query for Metadata (using the same data_source_id)
-            match query_engine(&ds_id, &final_content.sql, Some(100)).await {
+            match query_engine(&ds_id, &sql, Some(100)).await {
                 Ok(query_result) => {
                     data_metadata = Some(query_result.metadata.clone());
                     // Update column formats based on new metadata
                     // ... (existing format update logic using query_result.metadata) ...
                     let default_formats_map: indexmap::IndexMap<String, ColumnLabelFormat> =
-                        ColumnLabelFormat::generate_formats_from_metadata(&query_result.metadata);
-                    let base_chart_config = match &mut final_content.chart_config {
-                        // ... cases ...
-                        database::types::ChartConfig::Bar(config) => &mut config.base,
-                        database::types::ChartConfig::Line(config) => &mut config.base,
-                        database::types::ChartConfig::Scatter(config) => &mut config.base,
-                        database::types::ChartConfig::Pie(config) => &mut config.base,
-                        database::types::ChartConfig::Combo(config) => &mut config.base,
-                        database::types::ChartConfig::Metric(config) => &mut config.base,
-                        database::types::ChartConfig::Table(config) => &mut config.base,
-                    };
+                        ColumnLabelFormat::generate_formats_for_chart(
+                            &query_result.metadata,
+                            &final_content.chart_config,
+                        );
+                    let base_chart_config = final_content.chart_config.base_mut();
                     let existing_formats_map = base_chart_config.column_label_formats.clone();
                     base_chart_config.column_settings = None; // Clear old settings
                     if let Some(trendlines) = &mut base_chart_config.trendlines {
@@ -293,6 +302,13 @@ pub async fn update_metric_handler(
             .map_err(|e| anyhow!("Failed to fetch current dataset associations: {}", e))?;
     }
 
+    // --- Chart Config Validation ---
+    if changes_chart {
+        final_content
+            .validate(data_metadata.as_ref())
+            .map_err(|e| anyhow!("Invalid chart config: {}", e))?;
+    }
+
     // --- Version History Update ---
     let next_version;
     let should_update_version = request.update_version.unwrap_or(true);
@@ -300,7 +316,18 @@ pub async fn update_metric_handler(
         next_version = current_version_history
             .get_latest_version()
             .map_or(1, |v| v.version_number + 1);
-        current_version_history.add_version(next_version, final_content.clone());
+        match request.restore_to_version {
+            Some(version_number) => current_version_history.add_restored_version(
+                next_version,
+                final_content.clone(),
+                VersionRestore {
+                    version_number,
+                    restored_by: user.id,
+                    reason: request.restore_reason.clone(),
+                },
+            ),
+            None => current_version_history.add_version(next_version, final_content.clone()),
+        }
     } else {
         next_version = current_version_history.get_version_number(); // Keep current version number
         current_version_history.update_latest_version(final_content.clone());
@@ -407,11 +434,13 @@ mod tests {
                 "config": { "showLegend": true }
             })),
             time_frame: Some("daily".to_string()),
+            time_range: None,
             verification: Some(database::enums::Verification::NotRequested),
             file: None,
             sql: None,
             update_version: None,
             restore_to_version: None,
+            restore_reason: None,
         };
 
         // Verify the request fields are properly structured
@@ -438,10 +467,12 @@ mod tests {
             description: Some("This should be ignored".to_string()),
             chart_config: None,
             time_frame: None,
+            time_range: None,
             verification: None,
             file: None,
             sql: None,
             update_version: None,
+            restore_reason: None,
         };
 
         // Verify the request fields are properly structured
@@ -34,6 +34,7 @@ pub async fn post_organization_handler(
         updated_at: now,
         deleted_at: None,
         payment_required: true,
+        timezone: "UTC".to_string(),
     };
 
     insert_into(organizations::table)
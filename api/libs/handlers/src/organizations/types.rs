@@ -4,10 +4,13 @@ use uuid::Uuid;
 #[derive(Serialize, Deserialize, Clone)]
 pub struct UpdateOrganizationRequest {
     pub name: Option<String>,
+    /// IANA time zone name, e.g. "America/New_York"
+    pub timezone: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct OrganizationResponse {
     pub id: Uuid,
     pub name: String,
+    pub timezone: String,
 }
\ No newline at end of file
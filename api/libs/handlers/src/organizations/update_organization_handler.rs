@@ -1,5 +1,6 @@
 use anyhow::{anyhow, Result};
-use diesel::{update, ExpressionMethods};
+use chrono_tz::Tz;
+use diesel::{update, ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
 use uuid::Uuid;
 
@@ -27,24 +28,42 @@ pub async fn update_organization_handler(
         return Err(anyhow!("User is not a workspace admin"));
     }
 
+    if let Some(timezone) = payload.timezone.as_ref() {
+        if timezone.parse::<Tz>().is_err() {
+            return Err(anyhow!("Invalid timezone: {}", timezone));
+        }
+    }
+
     let mut conn = get_pg_pool().get().await?;
 
     // Update organization name if provided
-    let name = if let Some(name) = payload.name.as_ref() {
+    if let Some(name) = payload.name.as_ref() {
         update(organizations::table)
             .filter(organizations::id.eq(organization_id))
             .set(organizations::name.eq(name))
             .execute(&mut conn)
             .await?;
-        name.clone()
-    } else {
-        // This should never happen if we validate at the API level
-        return Err(anyhow!("Name is required for update"));
-    };
+    }
+
+    // Update organization timezone if provided
+    if let Some(timezone) = payload.timezone.as_ref() {
+        update(organizations::table)
+            .filter(organizations::id.eq(organization_id))
+            .set(organizations::timezone.eq(timezone))
+            .execute(&mut conn)
+            .await?;
+    }
+
+    let (name, timezone) = organizations::table
+        .filter(organizations::id.eq(organization_id))
+        .select((organizations::name, organizations::timezone))
+        .first::<(String, String)>(&mut conn)
+        .await?;
 
     // Return updated organization
     Ok(OrganizationResponse {
         id: organization_id,
         name,
+        timezone,
     })
 }
\ No newline at end of file
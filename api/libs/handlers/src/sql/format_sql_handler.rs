@@ -0,0 +1,33 @@
+use anyhow::Result;
+use middleware::types::AuthenticatedUser;
+use serde::{Deserialize, Serialize};
+use sql_analyzer::{fingerprint_query, format_sql};
+
+#[derive(Debug, Deserialize)]
+pub struct FormatSqlRequest {
+    pub sql: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FormatSqlResponse {
+    pub formatted_sql: String,
+    /// Stable across literal values, whitespace and identifier case, for
+    /// grouping query history and spotting duplicate metrics
+    pub fingerprint: String,
+    pub normalized_sql: String,
+}
+
+/// Pretty-prints SQL for the SQL editor and returns its fingerprint.
+pub async fn format_sql_handler(
+    request: FormatSqlRequest,
+    _user: &AuthenticatedUser,
+) -> Result<FormatSqlResponse> {
+    let formatted_sql = format_sql(request.sql.clone()).await?;
+    let fingerprint = fingerprint_query(request.sql).await?;
+
+    Ok(FormatSqlResponse {
+        formatted_sql,
+        fingerprint: fingerprint.fingerprint,
+        normalized_sql: fingerprint.normalized_sql,
+    })
+}
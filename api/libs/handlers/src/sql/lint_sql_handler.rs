@@ -0,0 +1,87 @@
+use anyhow::{anyhow, Result};
+use database::{
+    pool::get_pg_pool,
+    schema::{data_sources, dataset_columns, datasets},
+};
+use dataset_security::get_permissioned_datasets;
+use diesel::{ExpressionMethods, JoinOnDsl, OptionalExtension, QueryDsl};
+use diesel_async::RunQueryDsl;
+use middleware::types::AuthenticatedUser;
+use serde::{Deserialize, Serialize};
+use sql_analyzer::{lint_sql, LintDiagnostic, LintOptions};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct LintSqlRequest {
+    pub sql: String,
+    /// When provided, dataset column counts from this data source are used
+    /// to flag `SELECT *` on wide tables
+    pub data_source_id: Option<Uuid>,
+    /// Tables the caller knows to be large, used to flag unbounded sorts
+    #[serde(default)]
+    pub large_tables: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LintSqlResponse {
+    pub diagnostics: Vec<LintDiagnostic>,
+}
+
+/// Lints SQL for the SQL editor, returning the same diagnostics the agent
+/// receives before saving a metric.
+pub async fn lint_sql_handler(
+    request: LintSqlRequest,
+    user: &AuthenticatedUser,
+) -> Result<LintSqlResponse> {
+    let mut options = LintOptions {
+        large_tables: request.large_tables.into_iter().collect(),
+        ..Default::default()
+    };
+
+    if let Some(data_source_id) = request.data_source_id {
+        let mut conn = get_pg_pool().get().await?;
+
+        let organization_id = data_sources::table
+            .filter(data_sources::id.eq(data_source_id))
+            .filter(data_sources::deleted_at.is_null())
+            .select(data_sources::organization_id)
+            .first::<Uuid>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| anyhow!("Data source not found"))?;
+        if !user
+            .organizations
+            .iter()
+            .any(|org| org.id == organization_id)
+        {
+            return Err(anyhow!(
+                "You don't have permission to access this data source"
+            ));
+        }
+
+        // Only datasets the user may query contribute column counts
+        let permitted_ids: Vec<Uuid> = get_permissioned_datasets(&user.id, 0, 10000)
+            .await?
+            .into_iter()
+            .filter(|ds| ds.data_source_id == data_source_id)
+            .map(|ds| ds.id)
+            .collect();
+
+        let dataset_names = datasets::table
+            .inner_join(dataset_columns::table.on(dataset_columns::dataset_id.eq(datasets::id)))
+            .filter(datasets::id.eq_any(&permitted_ids))
+            .filter(datasets::deleted_at.is_null())
+            .filter(dataset_columns::deleted_at.is_null())
+            .select(datasets::name)
+            .load::<String>(&mut conn)
+            .await?;
+
+        for name in dataset_names {
+            *options.table_column_counts.entry(name).or_insert(0) += 1;
+        }
+    }
+
+    let diagnostics = lint_sql(request.sql, options).await?;
+
+    Ok(LintSqlResponse { diagnostics })
+}
@@ -0,0 +1,5 @@
+mod format_sql_handler;
+mod lint_sql_handler;
+
+pub use format_sql_handler::{format_sql_handler, FormatSqlRequest, FormatSqlResponse};
+pub use lint_sql_handler::{lint_sql_handler, LintSqlRequest, LintSqlResponse};
@@ -27,6 +27,8 @@ async fn create_test_metric(
         description: Some(format!("Test metric description for {}", name)),
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
+        time_range: None,
+        comparison: None,
         chart_config: ChartConfig::Bar(BarLineChartConfig {
             base: BaseChartConfig {
                 column_label_formats: indexmap::IndexMap::new(),
@@ -56,6 +58,8 @@ async fn create_test_metric(
             line_group_type: None,
         }),
         dataset_ids: Vec::new(),
+        parameters: Vec::new(),
+        drills: Vec::new(),
     };
     
     let metric_file = MetricFile {
@@ -42,6 +42,7 @@ impl TestSetup {
             updated_at: chrono::Utc::now(),
             deleted_at: None,
             payment_required: false,
+            timezone: "UTC".to_string(),
         };
         
         // Create user with specified role
@@ -141,8 +142,12 @@ async fn test_update_metric_status() -> Result<()> {
         description: Some(format!("Test metric description for {}", test_id)),
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
+        time_range: None,
+        comparison: None,
         chart_config: create_default_chart_config(),
         dataset_ids: vec![],
+        parameters: Vec::new(),
+        drills: Vec::new(),
     };
     
     // Initial verification status
@@ -343,8 +348,12 @@ async fn test_update_metric_status_unauthorized() -> Result<()> {
         description: Some(format!("Test metric description for {}", test_id)),
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
+        time_range: None,
+        comparison: None,
         chart_config: create_default_chart_config(),
         dataset_ids: vec![],
+        parameters: Vec::new(),
+        drills: Vec::new(),
     };
     
     // Initial verification status
@@ -510,8 +519,12 @@ async fn test_update_metric_status_null_value() -> Result<()> {
         description: Some(format!("Test metric description for {}", test_id)),
         sql: "SELECT * FROM test".to_string(),
         time_frame: "last 30 days".to_string(),
+        time_range: None,
+        comparison: None,
         chart_config: create_default_chart_config(),
         dataset_ids: vec![],
+        parameters: Vec::new(),
+        drills: Vec::new(),
     };
     
     // Initial verification status - set to Verified for this test
@@ -0,0 +1,997 @@
+use std::fmt;
+
+use crate::models::{Aggregation, Measure, Metric, MetricType, Model, SemanticLayerSpec, TimeGrain};
+
+/// SQL dialects the compiler can target
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Postgres,
+    Redshift,
+    Snowflake,
+    BigQuery,
+    Databricks,
+    MySql,
+    SqlServer,
+}
+
+impl Dialect {
+    /// Maps a data source type (e.g. `postgres`, `supabase`, `mariadb`) to
+    /// the dialect its queries are written in.
+    pub fn from_data_source_type(data_source_type: &str) -> Option<Self> {
+        match data_source_type {
+            "postgres" | "supabase" => Some(Dialect::Postgres),
+            "redshift" => Some(Dialect::Redshift),
+            "snowflake" => Some(Dialect::Snowflake),
+            "bigquery" => Some(Dialect::BigQuery),
+            "databricks" => Some(Dialect::Databricks),
+            "mysql" | "mariadb" => Some(Dialect::MySql),
+            "sqlserver" => Some(Dialect::SqlServer),
+            _ => None,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Dialect::Postgres => "postgres",
+            Dialect::Redshift => "redshift",
+            Dialect::Snowflake => "snowflake",
+            Dialect::BigQuery => "bigquery",
+            Dialect::Databricks => "databricks",
+            Dialect::MySql => "mysql",
+            Dialect::SqlServer => "sqlserver",
+        }
+    }
+
+    fn percentile(&self, expr: &str, percentile: f64) -> Result<String, CompileError> {
+        match self {
+            Dialect::Postgres | Dialect::Redshift | Dialect::Snowflake => Ok(format!(
+                "PERCENTILE_CONT({}) WITHIN GROUP (ORDER BY {})",
+                percentile, expr
+            )),
+            Dialect::Databricks => Ok(format!("PERCENTILE({}, {})", expr, percentile)),
+            // BigQuery only has exact percentiles as window functions
+            Dialect::BigQuery => Ok(format!(
+                "APPROX_QUANTILES({}, 100)[OFFSET({})]",
+                expr,
+                (percentile * 100.0).round() as u32
+            )),
+            Dialect::MySql | Dialect::SqlServer => Err(CompileError::Unsupported {
+                dialect: *self,
+                feature: "percentile aggregations".to_string(),
+            }),
+        }
+    }
+
+    fn median(&self, expr: &str) -> Result<String, CompileError> {
+        match self {
+            Dialect::Redshift | Dialect::Snowflake | Dialect::Databricks => {
+                Ok(format!("MEDIAN({})", expr))
+            }
+            _ => self.percentile(expr, 0.5),
+        }
+    }
+
+    /// Divides as floating point, yielding NULL instead of failing when the
+    /// denominator is zero.
+    fn divide(&self, numerator: &str, denominator: &str) -> String {
+        match self {
+            Dialect::BigQuery => format!("SAFE_DIVIDE({}, {})", numerator, denominator),
+            // MySQL division already returns a decimal
+            Dialect::MySql => format!("{} / NULLIF({}, 0)", numerator, denominator),
+            Dialect::Postgres | Dialect::Redshift => format!(
+                "CAST({} AS DOUBLE PRECISION) / NULLIF({}, 0)",
+                numerator, denominator
+            ),
+            Dialect::Databricks => {
+                format!("CAST({} AS DOUBLE) / NULLIF({}, 0)", numerator, denominator)
+            }
+            Dialect::Snowflake | Dialect::SqlServer => {
+                format!("CAST({} AS FLOAT) / NULLIF({}, 0)", numerator, denominator)
+            }
+        }
+    }
+
+    /// Truncates a date or timestamp to the start of its calendar period.
+    /// Weeks start on Monday; fiscal grains are treated as starting in
+    /// January.
+    pub fn date_trunc(&self, grain: TimeGrain, expr: &str) -> String {
+        self.time_bucket(grain, expr, 1)
+    }
+
+    /// Truncates a date or timestamp to the start of its period, where fiscal
+    /// quarters and years start in `fiscal_year_start_month` (1-12).
+    ///
+    /// A fiscal period is bucketed by shifting the date back to a calendar
+    /// year that starts in January, truncating, and shifting the result
+    /// forward again, so the bucket is the first day of the fiscal period.
+    pub fn time_bucket(&self, grain: TimeGrain, expr: &str, fiscal_year_start_month: u32) -> String {
+        let calendar_grain = match grain {
+            TimeGrain::FiscalQuarter => TimeGrain::Quarter,
+            TimeGrain::FiscalYear => TimeGrain::Year,
+            _ => return self.calendar_trunc(grain, expr),
+        };
+        let offset = fiscal_year_start_month.clamp(1, 12) as i32 - 1;
+        if offset == 0 {
+            return self.calendar_trunc(calendar_grain, expr);
+        }
+        let shifted = self.add_months(expr, -offset);
+        self.add_months(&self.calendar_trunc(calendar_grain, &shifted), offset)
+    }
+
+    fn calendar_trunc(&self, grain: TimeGrain, expr: &str) -> String {
+        let unit = match grain {
+            TimeGrain::Day => "day",
+            TimeGrain::Week => "week",
+            TimeGrain::Month => "month",
+            TimeGrain::Quarter | TimeGrain::FiscalQuarter => "quarter",
+            TimeGrain::Year | TimeGrain::FiscalYear => "year",
+        };
+        match self {
+            Dialect::Postgres | Dialect::Redshift | Dialect::Snowflake | Dialect::Databricks => {
+                format!("DATE_TRUNC('{}', {})", unit, expr)
+            }
+            Dialect::BigQuery => {
+                let part = match grain {
+                    TimeGrain::Week => "WEEK(MONDAY)".to_string(),
+                    _ => unit.to_uppercase(),
+                };
+                format!("DATE_TRUNC({}, {})", expr, part)
+            }
+            // DATETRUNC(week, ...) starts weeks on the session's @@DATEFIRST,
+            // so step back to Monday whatever day that is
+            Dialect::SqlServer if grain == TimeGrain::Week => format!(
+                "DATEADD(day, -((DATEPART(weekday, {}) + @@DATEFIRST - 2) % 7), CAST({} AS date))",
+                expr, expr
+            ),
+            Dialect::SqlServer => format!("DATETRUNC({}, {})", unit, expr),
+            Dialect::MySql => match unit {
+                "day" => format!("DATE({})", expr),
+                "week" => format!(
+                    "DATE_SUB(DATE({}), INTERVAL WEEKDAY({}) DAY)",
+                    expr, expr
+                ),
+                "month" => format!("DATE_FORMAT({}, '%Y-%m-01')", expr),
+                "quarter" => format!(
+                    "MAKEDATE(YEAR({}), 1) + INTERVAL (QUARTER({}) - 1) QUARTER",
+                    expr, expr
+                ),
+                _ => format!("DATE_FORMAT({}, '%Y-01-01')", expr),
+            },
+        }
+    }
+
+    fn add_months(&self, expr: &str, months: i32) -> String {
+        match self {
+            Dialect::Postgres | Dialect::Redshift => {
+                format!("({} + INTERVAL '{} month')", expr, months)
+            }
+            Dialect::Snowflake | Dialect::SqlServer => {
+                format!("DATEADD(month, {}, {})", months, expr)
+            }
+            // DATE_ADD only takes dates and TIMESTAMP_ADD has no MONTH part,
+            // so dates and timestamps alike go through DATETIME
+            Dialect::BigQuery => format!(
+                "DATETIME_ADD(CAST({} AS DATETIME), INTERVAL {} MONTH)",
+                expr, months
+            ),
+            Dialect::MySql => format!("DATE_ADD({}, INTERVAL {} MONTH)", expr, months),
+            Dialect::Databricks => format!("ADD_MONTHS({}, {})", expr, months),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompileError {
+    UnknownModel(String),
+    /// A name that is not a dimension, measure or metric of the model
+    UnknownField { model: String, name: String },
+    InvalidMeasure { measure: String, message: String },
+    InvalidMetric { metric: String, message: String },
+    InvalidDimension { dimension: String, message: String },
+    /// Metrics that reference each other, in reference order
+    CircularMetric(Vec<String>),
+    Unsupported { dialect: Dialect, feature: String },
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileError::UnknownModel(model) => write!(f, "Model `{}` is not defined", model),
+            CompileError::UnknownField { model, name } => write!(
+                f,
+                "`{}` is not a dimension, measure or metric of model `{}`",
+                name, model
+            ),
+            CompileError::InvalidMeasure { measure, message } => {
+                write!(f, "Measure `{}`: {}", measure, message)
+            }
+            CompileError::InvalidMetric { metric, message } => {
+                write!(f, "Metric `{}`: {}", metric, message)
+            }
+            CompileError::InvalidDimension { dimension, message } => {
+                write!(f, "Dimension `{}`: {}", dimension, message)
+            }
+            CompileError::CircularMetric(path) => {
+                write!(f, "Metrics reference each other: {}", path.join(" -> "))
+            }
+            CompileError::Unsupported { dialect, feature } => {
+                write!(f, "{} does not support {}", dialect.name(), feature)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+/// Metrics to compute for one model, grouped by some of its dimensions
+#[derive(Debug, Clone, Default)]
+pub struct MetricQuery {
+    pub model: String,
+    /// Names of measures or metrics
+    pub metrics: Vec<String>,
+    /// Dimension names. Time dimensions can be bucketed with a grain suffix,
+    /// e.g. `order_date__month` or `order_date__fiscal_quarter`.
+    pub dimensions: Vec<String>,
+    pub limit: Option<u64>,
+}
+
+/// Compiles a measure to its aggregate expression, e.g. `SUM(amount)`.
+pub fn compile_measure(model: &Model, measure: &str, dialect: Dialect) -> Result<String, CompileError> {
+    let measure = model
+        .measures
+        .iter()
+        .find(|m| m.name == measure)
+        .ok_or_else(|| unknown_field(model, measure))?;
+    aggregate(measure, dialect)
+}
+
+/// Compiles a metric to a single select expression.
+///
+/// Ratio and difference metrics are built from the compiled expressions of
+/// their operands, which may be measures or other metrics. Cumulative
+/// metrics depend on how the query is grouped, so they can only be compiled
+/// with [`compile_query`].
+pub fn compile_metric(model: &Model, metric: &str, dialect: Dialect) -> Result<String, CompileError> {
+    Compiler::new(model, dialect, None).metric(metric)
+}
+
+/// Compiles a query selecting measures and metrics of a model, grouped by
+/// the requested dimensions.
+///
+/// # Examples
+/// ```
+/// use semantic_layer::compile::{compile_query, Dialect, MetricQuery};
+/// use semantic_layer::models::SemanticLayerSpec;
+///
+/// let spec: SemanticLayerSpec = serde_yaml::from_str(r#"
+/// models:
+///   - name: orders
+///     schema: sales
+///     dimensions:
+///       - name: region
+///     measures:
+///       - name: revenue
+///         expr: amount
+///         agg: sum
+///       - name: order_count
+///         expr: id
+///         agg: count_distinct
+///     metrics:
+///       - name: average_order_value
+///         type: ratio
+///         numerator: revenue
+///         denominator: order_count
+/// "#).unwrap();
+///
+/// let sql = compile_query(
+///     &spec,
+///     &MetricQuery {
+///         model: "orders".to_string(),
+///         metrics: vec!["average_order_value".to_string()],
+///         dimensions: vec!["region".to_string()],
+///         limit: None,
+///     },
+///     Dialect::BigQuery,
+/// ).unwrap();
+/// assert_eq!(
+///     sql,
+///     "SELECT region, SAFE_DIVIDE(SUM(amount), COUNT(DISTINCT id)) AS average_order_value FROM sales.orders GROUP BY region"
+/// );
+/// ```
+pub fn compile_query(
+    spec: &SemanticLayerSpec,
+    query: &MetricQuery,
+    dialect: Dialect,
+) -> Result<String, CompileError> {
+    let model = spec
+        .models
+        .iter()
+        .find(|m| m.name == query.model)
+        .ok_or_else(|| CompileError::UnknownModel(query.model.clone()))?;
+
+    let group_by = query
+        .dimensions
+        .iter()
+        .map(|name| resolve_dimension(model, name, dialect))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut compiler = Compiler::new(model, dialect, Some(&group_by));
+    let mut select: Vec<String> = group_by
+        .iter()
+        .map(|column| {
+            if column.sql == column.name {
+                column.name.clone()
+            } else {
+                format!("{} AS {}", column.sql, column.name)
+            }
+        })
+        .collect();
+    for name in &query.metrics {
+        select.push(format!("{} AS {}", compiler.field(name, false)?, name));
+    }
+
+    let top = match (dialect, query.limit) {
+        (Dialect::SqlServer, Some(limit)) => format!("TOP {} ", limit),
+        _ => String::new(),
+    };
+    let mut sql = format!("SELECT {}{} FROM {}", top, select.join(", "), table_name(model));
+    if !group_by.is_empty() {
+        let group_by: Vec<&str> = group_by.iter().map(|column| column.sql.as_str()).collect();
+        sql.push_str(&format!(" GROUP BY {}", group_by.join(", ")));
+    }
+    if let (false, Some(limit)) = (dialect == Dialect::SqlServer, query.limit) {
+        sql.push_str(&format!(" LIMIT {}", limit));
+    }
+    Ok(sql)
+}
+
+/// A dimension a query groups by
+struct GroupColumn {
+    /// The requested name, used as the column alias
+    name: String,
+    /// The model dimension it is computed from
+    dimension: String,
+    sql: String,
+}
+
+/// Resolves a requested dimension, which is either a dimension name or a
+/// time dimension with a `__grain` suffix.
+fn resolve_dimension(model: &Model, name: &str, dialect: Dialect) -> Result<GroupColumn, CompileError> {
+    if model.dimensions.iter().any(|d| d.name == name) {
+        return Ok(GroupColumn {
+            name: name.to_string(),
+            dimension: name.to_string(),
+            sql: name.to_string(),
+        });
+    }
+
+    let (dimension, grain) = name
+        .rsplit_once("__")
+        .and_then(|(base, grain)| {
+            let dimension = model.dimensions.iter().find(|d| d.name == base)?;
+            Some((dimension, TimeGrain::parse(grain)?))
+        })
+        .ok_or_else(|| unknown_field(model, name))?;
+
+    let invalid = |message: String| CompileError::InvalidDimension {
+        dimension: dimension.name.clone(),
+        message,
+    };
+    let time = dimension
+        .time
+        .as_ref()
+        .ok_or_else(|| invalid("only time dimensions can be bucketed by a grain".to_string()))?;
+    if !time.supports(grain) {
+        return Err(invalid(match grain {
+            TimeGrain::FiscalQuarter | TimeGrain::FiscalYear => {
+                "fiscal grains require `fiscal_year_start_month`".to_string()
+            }
+            _ => format!("grain `{}` is not one of its `grains`", grain.as_str()),
+        }));
+    }
+
+    Ok(GroupColumn {
+        name: name.to_string(),
+        dimension: dimension.name.clone(),
+        sql: dialect.time_bucket(grain, &dimension.name, time.fiscal_start()),
+    })
+}
+
+struct Compiler<'a> {
+    model: &'a Model,
+    dialect: Dialect,
+    /// Dimensions the query groups by; `None` outside of a query
+    group_by: Option<&'a [GroupColumn]>,
+    /// Metrics being compiled, to detect cycles
+    stack: Vec<String>,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(model: &'a Model, dialect: Dialect, group_by: Option<&'a [GroupColumn]>) -> Self {
+        Self {
+            model,
+            dialect,
+            group_by,
+            stack: Vec::new(),
+        }
+    }
+
+    /// Compiles a measure or metric. Metrics used as operands are
+    /// parenthesized so that they bind as a unit.
+    fn field(&mut self, name: &str, operand: bool) -> Result<String, CompileError> {
+        if let Some(measure) = self.model.measures.iter().find(|m| m.name == name) {
+            return aggregate(measure, self.dialect);
+        }
+        let sql = self.metric(name)?;
+        Ok(if operand { format!("({})", sql) } else { sql })
+    }
+
+    fn metric(&mut self, name: &str) -> Result<String, CompileError> {
+        let metric = self
+            .model
+            .metrics
+            .iter()
+            .find(|m| m.name == name)
+            .ok_or_else(|| unknown_field(self.model, name))?;
+
+        if self.stack.iter().any(|m| m == name) {
+            let mut path = self.stack.clone();
+            path.push(name.to_string());
+            return Err(CompileError::CircularMetric(path));
+        }
+        self.stack.push(name.to_string());
+
+        let sql = match metric.type_ {
+            MetricType::Expression => {
+                if metric.expr.trim().is_empty() {
+                    return Err(invalid_metric(metric, "`expr` is required"));
+                }
+                metric.expr.clone()
+            }
+            MetricType::Ratio => {
+                let numerator = self.field(required(metric, &metric.numerator, "numerator")?, true)?;
+                let denominator =
+                    self.field(required(metric, &metric.denominator, "denominator")?, true)?;
+                self.dialect.divide(&numerator, &denominator)
+            }
+            MetricType::Difference => {
+                let minuend = self.field(required(metric, &metric.minuend, "minuend")?, true)?;
+                let subtrahend =
+                    self.field(required(metric, &metric.subtrahend, "subtrahend")?, true)?;
+                format!("{} - {}", minuend, subtrahend)
+            }
+            MetricType::Cumulative => self.cumulative(metric)?,
+        };
+
+        self.stack.pop();
+        Ok(sql)
+    }
+
+    /// A running total over the periods of the time dimension, computed as a
+    /// window over the grouped rows. `window` counts rows, so it assumes every
+    /// period has a row.
+    fn cumulative(&mut self, metric: &Metric) -> Result<String, CompileError> {
+        let measure_name = required(metric, &metric.measure, "measure")?;
+        let measure = self
+            .model
+            .measures
+            .iter()
+            .find(|m| m.name == measure_name)
+            .ok_or_else(|| {
+                invalid_metric(
+                    metric,
+                    &format!("`{}` is not a measure of model `{}`", measure_name, self.model.name),
+                )
+            })?;
+        if !measure.agg.is_some_and(|agg| agg.is_additive()) {
+            return Err(invalid_metric(
+                metric,
+                &format!(
+                    "measure `{}` must use a `sum` or `count` aggregation to be accumulated",
+                    measure.name
+                ),
+            ));
+        }
+
+        let time_dimension = required(metric, &metric.time_dimension, "time_dimension")?;
+        let (time_column, other_columns): (Vec<&GroupColumn>, Vec<&GroupColumn>) = self
+            .group_by
+            .unwrap_or_default()
+            .iter()
+            .partition(|column| column.dimension == time_dimension);
+        let Some(time_column) = time_column.first() else {
+            return Err(invalid_metric(
+                metric,
+                &format!("the query must group by its time dimension `{}`", time_dimension),
+            ));
+        };
+
+        let mut partition: Vec<String> = other_columns.iter().map(|c| c.sql.clone()).collect();
+        if let Some(grain) = metric.grain_to_date {
+            let time = self
+                .model
+                .dimensions
+                .iter()
+                .find(|d| d.name == time_dimension)
+                .and_then(|d| d.time.as_ref());
+            let fiscal_start = match (grain, time) {
+                (TimeGrain::FiscalQuarter | TimeGrain::FiscalYear, Some(time))
+                    if time.fiscal_year_start_month.is_some() =>
+                {
+                    time.fiscal_start()
+                }
+                (TimeGrain::FiscalQuarter | TimeGrain::FiscalYear, _) => {
+                    return Err(invalid_metric(
+                        metric,
+                        &format!(
+                            "fiscal `grain_to_date` requires `fiscal_year_start_month` on `{}`",
+                            time_dimension
+                        ),
+                    ))
+                }
+                _ => 1,
+            };
+            partition.push(self.dialect.time_bucket(grain, &time_column.sql, fiscal_start));
+        }
+
+        let frame = match metric.window {
+            Some(0) => return Err(invalid_metric(metric, "`window` must be at least 1")),
+            Some(window) => format!("ROWS BETWEEN {} PRECEDING AND CURRENT ROW", window - 1),
+            None => "ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW".to_string(),
+        };
+
+        let partition = if partition.is_empty() {
+            String::new()
+        } else {
+            format!("PARTITION BY {} ", partition.join(", "))
+        };
+
+        Ok(format!(
+            "SUM({}) OVER ({}ORDER BY {} {})",
+            aggregate(measure, self.dialect)?,
+            partition,
+            time_column.sql,
+            frame
+        ))
+    }
+}
+
+fn aggregate(measure: &Measure, dialect: Dialect) -> Result<String, CompileError> {
+    let expr = measure.expr.as_deref().unwrap_or(&measure.name);
+    let agg = measure.agg.ok_or_else(|| CompileError::InvalidMeasure {
+        measure: measure.name.clone(),
+        message: "`agg` is required to aggregate a measure".to_string(),
+    })?;
+
+    match agg {
+        Aggregation::Sum => Ok(format!("SUM({})", expr)),
+        Aggregation::Count => Ok(format!("COUNT({})", expr)),
+        Aggregation::CountDistinct => Ok(format!("COUNT(DISTINCT {})", expr)),
+        Aggregation::Avg => Ok(format!("AVG({})", expr)),
+        Aggregation::Min => Ok(format!("MIN({})", expr)),
+        Aggregation::Max => Ok(format!("MAX({})", expr)),
+        Aggregation::Median => dialect.median(expr),
+        Aggregation::Percentile => match measure.percentile {
+            Some(p) if p > 0.0 && p < 1.0 => dialect.percentile(expr, p),
+            _ => Err(CompileError::InvalidMeasure {
+                measure: measure.name.clone(),
+                message: "`percentile` must be between 0 and 1".to_string(),
+            }),
+        },
+    }
+}
+
+fn table_name(model: &Model) -> String {
+    [model.database.as_deref(), model.schema.as_deref(), Some(model.name.as_str())]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn required<'m>(
+    metric: &Metric,
+    value: &'m Option<String>,
+    field: &str,
+) -> Result<&'m str, CompileError> {
+    value
+        .as_deref()
+        .ok_or_else(|| invalid_metric(metric, &format!("`{}` is required", field)))
+}
+
+fn invalid_metric(metric: &Metric, message: &str) -> CompileError {
+    CompileError::InvalidMetric {
+        metric: metric.name.clone(),
+        message: message.to_string(),
+    }
+}
+
+fn unknown_field(model: &Model, name: &str) -> CompileError {
+    CompileError::UnknownField {
+        model: model.name.clone(),
+        name: name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r#"
+models:
+  - name: orders
+    database: analytics
+    schema: sales
+    dimensions:
+      - name: region
+      - name: order_date
+        time:
+          fiscal_year_start_month: 4
+      - name: shipped_at
+        time:
+          grains: [day, month]
+    measures:
+      - name: revenue
+        expr: amount
+        agg: sum
+      - name: refunds
+        expr: refund_amount
+        agg: sum
+      - name: orders
+        expr: id
+        agg: count_distinct
+      - name: median_amount
+        expr: amount
+        agg: median
+      - name: p90_amount
+        expr: amount
+        agg: percentile
+        percentile: 0.9
+      - name: average_amount
+        expr: amount
+        agg: avg
+      - name: amount
+    metrics:
+      - name: net_revenue
+        type: difference
+        minuend: revenue
+        subtrahend: refunds
+      - name: net_revenue_per_order
+        type: ratio
+        numerator: net_revenue
+        denominator: orders
+      - name: running_revenue
+        type: cumulative
+        measure: revenue
+        time_dimension: order_date
+      - name: trailing_7_day_revenue
+        type: cumulative
+        measure: revenue
+        time_dimension: order_date
+        window: 7
+      - name: month_to_date_revenue
+        type: cumulative
+        measure: revenue
+        time_dimension: order_date
+        grain_to_date: month
+      - name: fiscal_ytd_revenue
+        type: cumulative
+        measure: revenue
+        time_dimension: order_date
+        grain_to_date: fiscal_year
+      - name: running_average
+        type: cumulative
+        measure: average_amount
+        time_dimension: order_date
+      - name: loop_a
+        type: ratio
+        numerator: loop_b
+        denominator: orders
+      - name: loop_b
+        type: difference
+        minuend: loop_a
+        subtrahend: refunds
+      - name: large_orders
+        expr: COUNT(CASE WHEN amount > 100 THEN 1 END)
+"#;
+
+    fn spec() -> SemanticLayerSpec {
+        serde_yaml::from_str(SPEC).expect("valid spec")
+    }
+
+    fn query(metrics: &[&str], dimensions: &[&str]) -> MetricQuery {
+        MetricQuery {
+            model: "orders".to_string(),
+            metrics: metrics.iter().map(|m| m.to_string()).collect(),
+            dimensions: dimensions.iter().map(|d| d.to_string()).collect(),
+            limit: None,
+        }
+    }
+
+    #[test]
+    fn test_compile_measures() {
+        let spec = spec();
+        let model = &spec.models[0];
+        assert_eq!(compile_measure(model, "revenue", Dialect::Postgres).unwrap(), "SUM(amount)");
+        assert_eq!(
+            compile_measure(model, "orders", Dialect::Postgres).unwrap(),
+            "COUNT(DISTINCT id)"
+        );
+        assert_eq!(
+            compile_measure(model, "average_amount", Dialect::MySql).unwrap(),
+            "AVG(amount)"
+        );
+        assert!(matches!(
+            compile_measure(model, "amount", Dialect::Postgres),
+            Err(CompileError::InvalidMeasure { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compile_median_and_percentile_per_dialect() {
+        let spec = spec();
+        let model = &spec.models[0];
+        assert_eq!(
+            compile_measure(model, "median_amount", Dialect::Postgres).unwrap(),
+            "PERCENTILE_CONT(0.5) WITHIN GROUP (ORDER BY amount)"
+        );
+        assert_eq!(
+            compile_measure(model, "median_amount", Dialect::Snowflake).unwrap(),
+            "MEDIAN(amount)"
+        );
+        assert_eq!(
+            compile_measure(model, "p90_amount", Dialect::Databricks).unwrap(),
+            "PERCENTILE(amount, 0.9)"
+        );
+        assert_eq!(
+            compile_measure(model, "p90_amount", Dialect::BigQuery).unwrap(),
+            "APPROX_QUANTILES(amount, 100)[OFFSET(90)]"
+        );
+        assert!(matches!(
+            compile_measure(model, "p90_amount", Dialect::MySql),
+            Err(CompileError::Unsupported { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compile_derived_metrics() {
+        let spec = spec();
+        let model = &spec.models[0];
+        assert_eq!(
+            compile_metric(model, "net_revenue", Dialect::Postgres).unwrap(),
+            "SUM(amount) - SUM(refund_amount)"
+        );
+        assert_eq!(
+            compile_metric(model, "net_revenue_per_order", Dialect::Postgres).unwrap(),
+            "CAST((SUM(amount) - SUM(refund_amount)) AS DOUBLE PRECISION) / NULLIF(COUNT(DISTINCT id), 0)"
+        );
+        assert_eq!(
+            compile_metric(model, "net_revenue_per_order", Dialect::BigQuery).unwrap(),
+            "SAFE_DIVIDE((SUM(amount) - SUM(refund_amount)), COUNT(DISTINCT id))"
+        );
+        assert_eq!(
+            compile_metric(model, "large_orders", Dialect::Postgres).unwrap(),
+            "COUNT(CASE WHEN amount > 100 THEN 1 END)"
+        );
+    }
+
+    #[test]
+    fn test_circular_metrics() {
+        let spec = spec();
+        let err = compile_metric(&spec.models[0], "loop_a", Dialect::Postgres).unwrap_err();
+        assert_eq!(
+            err,
+            CompileError::CircularMetric(vec![
+                "loop_a".to_string(),
+                "loop_b".to_string(),
+                "loop_a".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_compile_query() {
+        let spec = spec();
+        let sql = compile_query(
+            &spec,
+            &query(&["revenue", "net_revenue_per_order"], &["region"]),
+            Dialect::Snowflake,
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT region, SUM(amount) AS revenue, CAST((SUM(amount) - SUM(refund_amount)) AS FLOAT) / NULLIF(COUNT(DISTINCT id), 0) AS net_revenue_per_order FROM analytics.sales.orders GROUP BY region"
+        );
+    }
+
+    #[test]
+    fn test_compile_query_limit_per_dialect() {
+        let spec = spec();
+        let mut q = query(&["revenue"], &["region"]);
+        q.limit = Some(10);
+        assert_eq!(
+            compile_query(&spec, &q, Dialect::SqlServer).unwrap(),
+            "SELECT TOP 10 region, SUM(amount) AS revenue FROM analytics.sales.orders GROUP BY region"
+        );
+        assert_eq!(
+            compile_query(&spec, &q, Dialect::Postgres).unwrap(),
+            "SELECT region, SUM(amount) AS revenue FROM analytics.sales.orders GROUP BY region LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_compile_cumulative_metrics() {
+        let spec = spec();
+        let sql = compile_query(
+            &spec,
+            &query(&["running_revenue"], &["region", "order_date"]),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT region, order_date, SUM(SUM(amount)) OVER (PARTITION BY region ORDER BY order_date ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS running_revenue FROM analytics.sales.orders GROUP BY region, order_date"
+        );
+
+        let sql = compile_query(
+            &spec,
+            &query(&["trailing_7_day_revenue"], &["order_date"]),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        assert!(sql.contains(
+            "SUM(SUM(amount)) OVER (ORDER BY order_date ROWS BETWEEN 6 PRECEDING AND CURRENT ROW)"
+        ));
+
+        let sql = compile_query(
+            &spec,
+            &query(&["month_to_date_revenue"], &["order_date"]),
+            Dialect::BigQuery,
+        )
+        .unwrap();
+        assert!(sql.contains("PARTITION BY DATE_TRUNC(order_date, MONTH) ORDER BY order_date"));
+    }
+
+    #[test]
+    fn test_cumulative_metric_errors() {
+        let spec = spec();
+        let model = &spec.models[0];
+
+        // Needs the query's grouping to be compiled
+        assert!(matches!(
+            compile_metric(model, "running_revenue", Dialect::Postgres),
+            Err(CompileError::InvalidMetric { .. })
+        ));
+        assert!(matches!(
+            compile_query(&spec, &query(&["running_revenue"], &["region"]), Dialect::Postgres),
+            Err(CompileError::InvalidMetric { .. })
+        ));
+        // Averages can't be summed across periods
+        assert!(matches!(
+            compile_query(&spec, &query(&["running_average"], &["order_date"]), Dialect::Postgres),
+            Err(CompileError::InvalidMetric { .. })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_names() {
+        let spec = spec();
+        assert_eq!(
+            compile_query(&spec, &query(&["revenue"], &["country"]), Dialect::Postgres),
+            Err(CompileError::UnknownField {
+                model: "orders".to_string(),
+                name: "country".to_string()
+            })
+        );
+        let mut q = query(&["revenue"], &[]);
+        q.model = "customers".to_string();
+        assert_eq!(
+            compile_query(&spec, &q, Dialect::Postgres),
+            Err(CompileError::UnknownModel("customers".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_compile_time_grains() {
+        let spec = spec();
+        assert_eq!(
+            compile_query(&spec, &query(&["revenue"], &["order_date__month"]), Dialect::Postgres)
+                .unwrap(),
+            "SELECT DATE_TRUNC('month', order_date) AS order_date__month, SUM(amount) AS revenue FROM analytics.sales.orders GROUP BY DATE_TRUNC('month', order_date)"
+        );
+        assert_eq!(
+            compile_query(
+                &spec,
+                &query(&["revenue"], &["order_date__fiscal_quarter"]),
+                Dialect::Snowflake
+            )
+            .unwrap(),
+            "SELECT DATEADD(month, 3, DATE_TRUNC('quarter', DATEADD(month, -3, order_date))) AS order_date__fiscal_quarter, SUM(amount) AS revenue FROM analytics.sales.orders GROUP BY DATEADD(month, 3, DATE_TRUNC('quarter', DATEADD(month, -3, order_date)))"
+        );
+    }
+
+    #[test]
+    fn test_compile_time_grain_errors() {
+        let spec = spec();
+        // Not a time dimension
+        assert!(matches!(
+            compile_query(&spec, &query(&["revenue"], &["region__month"]), Dialect::Postgres),
+            Err(CompileError::InvalidDimension { .. })
+        ));
+        // Grain not listed in `grains`
+        assert!(matches!(
+            compile_query(&spec, &query(&["revenue"], &["shipped_at__week"]), Dialect::Postgres),
+            Err(CompileError::InvalidDimension { .. })
+        ));
+        // No fiscal calendar configured
+        assert!(matches!(
+            compile_query(&spec, &query(&["revenue"], &["shipped_at__fiscal_year"]), Dialect::Postgres),
+            Err(CompileError::InvalidDimension { .. })
+        ));
+        // Unknown grain
+        assert!(matches!(
+            compile_query(&spec, &query(&["revenue"], &["order_date__fortnight"]), Dialect::Postgres),
+            Err(CompileError::UnknownField { .. })
+        ));
+    }
+
+    #[test]
+    fn test_compile_cumulative_metric_at_grain() {
+        let spec = spec();
+        let sql = compile_query(
+            &spec,
+            &query(&["fiscal_ytd_revenue"], &["order_date__month"]),
+            Dialect::Postgres,
+        )
+        .unwrap();
+        assert_eq!(
+            sql,
+            "SELECT DATE_TRUNC('month', order_date) AS order_date__month, SUM(SUM(amount)) OVER (PARTITION BY (DATE_TRUNC('year', (DATE_TRUNC('month', order_date) + INTERVAL '-3 month')) + INTERVAL '3 month') ORDER BY DATE_TRUNC('month', order_date) ROWS BETWEEN UNBOUNDED PRECEDING AND CURRENT ROW) AS fiscal_ytd_revenue FROM analytics.sales.orders GROUP BY DATE_TRUNC('month', order_date)"
+        );
+    }
+
+    #[test]
+    fn test_fiscal_time_buckets_per_dialect() {
+        assert_eq!(
+            Dialect::BigQuery.time_bucket(TimeGrain::FiscalYear, "created_at", 7),
+            "DATETIME_ADD(CAST(DATE_TRUNC(DATETIME_ADD(CAST(created_at AS DATETIME), INTERVAL -6 MONTH), YEAR) AS DATETIME), INTERVAL 6 MONTH)"
+        );
+        assert_eq!(
+            Dialect::Databricks.time_bucket(TimeGrain::FiscalQuarter, "created_at", 2),
+            "ADD_MONTHS(DATE_TRUNC('quarter', ADD_MONTHS(created_at, -1)), 1)"
+        );
+        assert_eq!(
+            Dialect::SqlServer.time_bucket(TimeGrain::FiscalYear, "created_at", 10),
+            "DATEADD(month, 9, DATETRUNC(year, DATEADD(month, -9, created_at)))"
+        );
+        // A fiscal year starting in January is the calendar year
+        assert_eq!(
+            Dialect::Postgres.time_bucket(TimeGrain::FiscalYear, "created_at", 1),
+            "DATE_TRUNC('year', created_at)"
+        );
+    }
+
+    #[test]
+    fn test_date_trunc_per_dialect() {
+        assert_eq!(
+            Dialect::Postgres.date_trunc(TimeGrain::Quarter, "created_at"),
+            "DATE_TRUNC('quarter', created_at)"
+        );
+        assert_eq!(
+            Dialect::BigQuery.date_trunc(TimeGrain::Week, "created_at"),
+            "DATE_TRUNC(created_at, WEEK(MONDAY))"
+        );
+        assert_eq!(
+            Dialect::SqlServer.date_trunc(TimeGrain::Month, "created_at"),
+            "DATETRUNC(month, created_at)"
+        );
+        assert_eq!(
+            Dialect::SqlServer.date_trunc(TimeGrain::Week, "created_at"),
+            "DATEADD(day, -((DATEPART(weekday, created_at) + @@DATEFIRST - 2) % 7), CAST(created_at AS date))"
+        );
+        assert_eq!(
+            Dialect::MySql.date_trunc(TimeGrain::Month, "created_at"),
+            "DATE_FORMAT(created_at, '%Y-%m-01')"
+        );
+    }
+}
@@ -0,0 +1,1441 @@
+//! Import of LookML views and explores.
+//!
+//! Views become models named after the table in their `sql_table_name` (or
+//! after the view when it has none), with the schema and database taken from
+//! the qualified name. Dimensions and dimension groups whose SQL resolves to
+//! a column become dimensions, measures become measures with a typed `agg`,
+//! and `type: number` measures become `ratio`, `difference` or expression
+//! metrics. Explore joins become entities on the model they are joined from.
+//!
+//! `${TABLE}`, `${field}`, `${view.field}` and `${view.SQL_TABLE_NAME}` are
+//! resolved while converting. Derived tables, Liquid, filtered measures,
+//! explore-level filters and anything else without an equivalent are returned
+//! as warnings for review.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{is_identifier, Import, ImportWarning};
+use crate::models::{
+    Aggregation, Dimension, Measure, Metric, MetricType, Model, Relationship, TimeDimension,
+    TimeGrain,
+};
+use crate::validate::SUPPORTED_CARDINALITIES;
+
+/// A parsed `.lkml` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LookmlFile {
+    pub nodes: Vec<Node>,
+}
+
+impl LookmlFile {
+    pub fn parse(src: &str) -> Result<Self, ParseError> {
+        let mut parser = Parser {
+            chars: src.chars().collect(),
+            pos: 0,
+            line: 1,
+        };
+        Ok(Self {
+            nodes: parser.nodes(false)?,
+        })
+    }
+}
+
+/// A `key: value` pair, e.g. `type: sum` or `view: orders { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub key: String,
+    /// Name of a named block such as `dimension: amount { ... }`
+    pub name: Option<String>,
+    pub value: Value,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Scalar(String),
+    /// A value terminated by `;;`, such as `sql` and `sql_on`
+    Sql(String),
+    List(Vec<String>),
+    Block(Vec<Node>),
+}
+
+impl Node {
+    pub fn block(&self) -> &[Node] {
+        match &self.value {
+            Value::Block(nodes) => nodes,
+            _ => &[],
+        }
+    }
+}
+
+fn find<'n>(nodes: &'n [Node], key: &str) -> Option<&'n Node> {
+    nodes.iter().find(|n| n.key == key)
+}
+
+fn text<'n>(nodes: &'n [Node], key: &str) -> Option<&'n str> {
+    match &find(nodes, key)?.value {
+        Value::Scalar(s) | Value::Sql(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn list<'n>(nodes: &'n [Node], key: &str) -> Option<&'n [String]> {
+    match &find(nodes, key)?.value {
+        Value::List(items) => Some(items),
+        _ => None,
+    }
+}
+
+fn is_yes(nodes: &[Node], key: &str) -> bool {
+    text(nodes, key) == Some("yes")
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '#' {
+                while self.peek().is_some_and(|c| c != '\n') {
+                    self.bump();
+                }
+            } else if c.is_whitespace() {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn nodes(&mut self, in_block: bool) -> Result<Vec<Node>, ParseError> {
+        let mut nodes = Vec::new();
+        loop {
+            self.skip_trivia();
+            match self.peek() {
+                None if in_block => return self.error("unclosed `{`"),
+                None => return Ok(nodes),
+                Some('}') if in_block => {
+                    self.bump();
+                    return Ok(nodes);
+                }
+                Some('}') => return self.error("unexpected `}`"),
+                Some(_) => {}
+            }
+
+            let key = self.word();
+            if key.is_empty() {
+                return self.error(format!(
+                    "expected a key, found `{}`",
+                    self.peek().unwrap_or(' ')
+                ));
+            }
+            self.skip_trivia();
+            if self.bump() != Some(':') {
+                return self.error(format!("expected `:` after `{}`", key));
+            }
+            let (name, value) = self.value(&key)?;
+            nodes.push(Node { key, name, value });
+        }
+    }
+
+    fn value(&mut self, key: &str) -> Result<(Option<String>, Value), ParseError> {
+        if key.starts_with("sql") || key.ends_with("_sql") || key == "html" || key == "expression" {
+            return Ok((None, Value::Sql(self.until_semicolons()?)));
+        }
+        self.skip_trivia();
+        match self.peek() {
+            Some('{') => {
+                self.bump();
+                Ok((None, Value::Block(self.nodes(true)?)))
+            }
+            Some('[') => {
+                self.bump();
+                Ok((None, Value::List(self.list()?)))
+            }
+            Some('"') => Ok((None, Value::Scalar(self.string()?))),
+            Some(_) => {
+                let word = self.word();
+                if word.is_empty() {
+                    return self.error(format!("expected a value for `{}`", key));
+                }
+                self.skip_trivia();
+                if self.peek() == Some('{') {
+                    self.bump();
+                    return Ok((Some(word), Value::Block(self.nodes(true)?)));
+                }
+                Ok((None, Value::Scalar(word)))
+            }
+            None => self.error(format!("expected a value for `{}`", key)),
+        }
+    }
+
+    fn word(&mut self) -> String {
+        let mut word = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, '{' | '}' | '[' | ']' | ',' | ':' | '"' | '#') {
+                break;
+            }
+            word.push(c);
+            self.bump();
+        }
+        word
+    }
+
+    fn string(&mut self) -> Result<String, ParseError> {
+        self.bump(); // Opening quote
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.bump() {
+                    Some(c) => s.push(c),
+                    None => return self.error("unterminated string"),
+                },
+                Some(c) => s.push(c),
+                None => return self.error("unterminated string"),
+            }
+        }
+    }
+
+    fn until_semicolons(&mut self) -> Result<String, ParseError> {
+        let start_line = self.line;
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some(';') if self.peek() == Some(';') => {
+                    self.bump();
+                    return Ok(s.trim().to_string());
+                }
+                Some(c) => s.push(c),
+                None => {
+                    return Err(ParseError {
+                        line: start_line,
+                        message: "SQL is not terminated with `;;`".to_string(),
+                    })
+                }
+            }
+        }
+    }
+
+    /// Items of `[a, "b", c: "d"]`; `key: value` pairs are kept as written.
+    fn list(&mut self) -> Result<Vec<String>, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            self.skip_trivia();
+            let mut item = match self.peek() {
+                Some(']') => {
+                    self.bump();
+                    return Ok(items);
+                }
+                Some('"') => self.string()?,
+                Some(_) => self.word(),
+                None => return self.error("unclosed `[`"),
+            };
+            self.skip_trivia();
+            if self.peek() == Some(':') {
+                self.bump();
+                self.skip_trivia();
+                let value = if self.peek() == Some('"') {
+                    self.string()?
+                } else {
+                    self.word()
+                };
+                item = format!("{}: {}", item, value);
+                self.skip_trivia();
+            }
+            if item.is_empty() {
+                return self.error("expected a list item");
+            }
+            items.push(item);
+            match self.peek() {
+                Some(',') => {
+                    self.bump();
+                }
+                Some(']') => {}
+                _ => return self.error("expected `,` or `]` in list"),
+            }
+        }
+    }
+}
+
+/// Converts the views and explores of a LookML project.
+pub fn import_lookml(files: &[LookmlFile]) -> Import {
+    let mut importer = Importer {
+        warnings: Vec::new(),
+        models: Vec::new(),
+        views: HashMap::new(),
+        view_order: Vec::new(),
+        explores: Vec::new(),
+        fiscal_month_offset: None,
+    };
+    for file in files {
+        importer.collect(&file.nodes);
+    }
+    for name in importer.view_order.clone() {
+        importer.declare_model(name);
+    }
+    for name in importer.view_order.clone() {
+        importer.convert_fields(name);
+    }
+    for explore in importer.explores.clone() {
+        importer.convert_explore(explore);
+    }
+
+    Import {
+        models: importer.models,
+        warnings: importer.warnings,
+    }
+}
+
+struct View<'a> {
+    name: &'a str,
+    dimensions: HashMap<&'a str, &'a [Node]>,
+    groups: HashMap<&'a str, &'a [Node]>,
+    measures: Vec<(&'a str, &'a [Node])>,
+    block: &'a [Node],
+    /// Index of the view's model, once declared
+    model: Option<usize>,
+    /// What `${TABLE}` stands for
+    table: String,
+}
+
+impl View<'_> {
+    fn measure(&self, name: &str) -> Option<&[Node]> {
+        self.measures
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, def)| *def)
+    }
+}
+
+struct Importer<'a> {
+    warnings: Vec<ImportWarning>,
+    models: Vec<Model>,
+    views: HashMap<&'a str, View<'a>>,
+    view_order: Vec<&'a str>,
+    explores: Vec<&'a Node>,
+    fiscal_month_offset: Option<u32>,
+}
+
+const MAX_REFERENCE_DEPTH: usize = 16;
+
+/// Timeframes that are row-level attributes rather than grains
+const IGNORED_TIMEFRAMES: &[&str] = &["raw", "time", "date"];
+
+impl<'a> Importer<'a> {
+    fn warn(
+        &mut self,
+        construct: &'static str,
+        name: impl Into<String>,
+        message: impl Into<String>,
+    ) {
+        self.warnings
+            .push(ImportWarning::new(construct, name, message));
+    }
+
+    fn collect(&mut self, nodes: &'a [Node]) {
+        for node in nodes {
+            let construct = match node.key.as_str() {
+                "view" => "view",
+                "explore" => "explore",
+                "fiscal_month_offset" => {
+                    if let Value::Scalar(offset) = &node.value {
+                        self.fiscal_month_offset = offset.parse().ok();
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let Some(name) = node.name.as_deref() else {
+                self.warn(
+                    construct,
+                    &node.key,
+                    "block has no name; it was not imported",
+                );
+                continue;
+            };
+            if name.starts_with('+') {
+                self.warn(
+                    construct,
+                    name,
+                    "refinements are not supported; the refinement was not imported",
+                );
+                continue;
+            }
+            if find(node.block(), "extends").is_some() {
+                self.warn(
+                    construct,
+                    name,
+                    "`extends` is not supported; inherited definitions were not imported",
+                );
+            }
+            if construct == "explore" {
+                self.explores.push(node);
+                continue;
+            }
+            if self.views.contains_key(name) {
+                self.warn(
+                    "view",
+                    name,
+                    "view is defined more than once; only the first definition was imported",
+                );
+                continue;
+            }
+
+            let block = node.block();
+            let named = |key: &'static str| {
+                block
+                    .iter()
+                    .filter(move |n| n.key == key)
+                    .filter_map(|n| Some((n.name.as_deref()?, n.block())))
+            };
+            self.views.insert(
+                name,
+                View {
+                    name,
+                    dimensions: named("dimension").collect(),
+                    groups: named("dimension_group").collect(),
+                    measures: named("measure").collect(),
+                    block,
+                    model: None,
+                    table: name.to_string(),
+                },
+            );
+            self.view_order.push(name);
+        }
+    }
+
+    /// Creates the view's model from its table, before any fields are
+    /// resolved, so references to other views can use their model names.
+    fn declare_model(&mut self, view_name: &'a str) {
+        let block = self.views[view_name].block;
+        let mut model_name = view_name.to_string();
+        let (mut database, mut schema) = (None, None);
+        let mut table = view_name.to_string();
+
+        if let Some(sql_table_name) = text(block, "sql_table_name") {
+            if has_liquid(sql_table_name) {
+                self.warn(
+                    "view",
+                    view_name,
+                    format!(
+                        "Liquid in `sql_table_name: {}` is not supported; the model is named after the view",
+                        sql_table_name
+                    ),
+                );
+            } else {
+                let mut parts: Vec<String> = sql_table_name
+                    .split('.')
+                    .map(|part| {
+                        part.trim()
+                            .trim_matches(|c| matches!(c, '"' | '`' | '[' | ']'))
+                            .to_string()
+                    })
+                    .collect();
+                model_name = parts.pop().unwrap_or_default();
+                schema = parts.pop();
+                database = parts.pop();
+                table = sql_table_name.to_string();
+            }
+        } else if let Some(derived) = find(block, "derived_table") {
+            let message = match text(derived.block(), "sql") {
+                Some(sql) => format!(
+                    "derived tables are not supported; materialize this SQL as `{}` before querying the model: {}",
+                    view_name, sql
+                ),
+                None => format!(
+                    "native derived tables are not supported; materialize the table as `{}` before querying the model",
+                    view_name
+                ),
+            };
+            self.warn("view", view_name, message);
+        }
+
+        if self.models.iter().any(|m| m.name == model_name) {
+            self.warn(
+                "view",
+                view_name,
+                format!(
+                    "another view already uses the table `{}`; the view was not imported",
+                    model_name
+                ),
+            );
+            return;
+        }
+
+        let view = self.views.get_mut(view_name).expect("view was collected");
+        view.table = table;
+        view.model = Some(self.models.len());
+        self.models.push(Model {
+            name: model_name,
+            description: text(block, "description").map(str::to_string),
+            data_source_name: None,
+            database,
+            schema,
+            dimensions: Vec::new(),
+            measures: Vec::new(),
+            metrics: Vec::new(),
+            filters: Vec::new(),
+            relationships: Vec::new(),
+        });
+    }
+
+    fn convert_fields(&mut self, view_name: &'a str) {
+        let view = &self.views[view_name];
+        let Some(model) = view.model else {
+            return;
+        };
+
+        let mut dimensions: Vec<Dimension> = Vec::new();
+        let mut warnings = Vec::new();
+        let mut push = |dimension: Dimension| {
+            // Several LookML dimensions often read the same column
+            if !dimensions.iter().any(|d| d.name == dimension.name) {
+                dimensions.push(dimension);
+            }
+        };
+
+        for node in view.block {
+            let Some(name) = node.name.as_deref() else {
+                continue;
+            };
+            let def = node.block();
+            let qualified = format!("{}.{}", view_name, name);
+            match node.key.as_str() {
+                "dimension" => match self.convert_dimension(view, name, def) {
+                    Ok(dimension) => push(dimension),
+                    Err(message) => {
+                        warnings.push(ImportWarning::new("dimension", qualified, message))
+                    }
+                },
+                "dimension_group" => {
+                    match self.convert_dimension_group(view, name, def, &mut warnings) {
+                        Ok(dimension) => push(dimension),
+                        Err(message) => {
+                            warnings.push(ImportWarning::new("dimension_group", qualified, message))
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let mut measures = Vec::new();
+        let mut metrics = Vec::new();
+        for (name, def) in &view.measures {
+            let qualified = format!("{}.{}", view_name, name);
+            let result = if text(def, "type") == Some("number") {
+                self.convert_number_measure(view, name, def)
+                    .map(|m| metrics.push(m))
+            } else {
+                self.convert_measure(view, name, def)
+                    .map(|m| measures.push(m))
+            };
+            if let Err(message) = result {
+                warnings.push(ImportWarning::new("measure", qualified, message));
+            }
+        }
+
+        self.warnings.extend(warnings);
+        let model = &mut self.models[model];
+        model.dimensions = dimensions;
+        model.measures = measures;
+        model.metrics = metrics;
+    }
+
+    fn convert_dimension(
+        &self,
+        view: &View,
+        name: &str,
+        def: &[Node],
+    ) -> Result<Dimension, String> {
+        let type_ = text(def, "type").unwrap_or("string");
+        let (type_, time) = match type_ {
+            "string" | "zipcode" => ("string", None),
+            "number" => ("number", None),
+            "yesno" => ("boolean", None),
+            "date" | "date_time" | "date_raw" => (
+                "date",
+                Some(TimeDimension {
+                    grains: TimeGrain::calendar_grains(),
+                    fiscal_year_start_month: None,
+                }),
+            ),
+            other => return Err(format!("`type: {}` dimensions are not supported", other)),
+        };
+        let column = self.column(view, name)?;
+        Ok(Dimension {
+            name: column,
+            description: description(def),
+            type_: Some(type_.to_string()),
+            searchable: false,
+            options: None,
+            time,
+        })
+    }
+
+    fn convert_dimension_group(
+        &self,
+        view: &View,
+        name: &str,
+        def: &[Node],
+        warnings: &mut Vec<ImportWarning>,
+    ) -> Result<Dimension, String> {
+        let type_ = text(def, "type").unwrap_or("time");
+        if type_ != "time" {
+            return Err(format!(
+                "`type: {}` dimension groups are not supported",
+                type_
+            ));
+        }
+        let column = self.column(view, name)?;
+
+        let mut grains = Vec::new();
+        let mut fiscal = false;
+        let mut unmapped = Vec::new();
+        let timeframes = list(def, "timeframes").unwrap_or_default();
+        for timeframe in timeframes {
+            let grain = match timeframe.as_str() {
+                "date" => Some(TimeGrain::Day),
+                other => TimeGrain::parse(other),
+            };
+            match grain {
+                Some(TimeGrain::FiscalQuarter | TimeGrain::FiscalYear) => fiscal = true,
+                Some(grain) if !grains.contains(&grain) => grains.push(grain),
+                Some(_) => {}
+                None if IGNORED_TIMEFRAMES.contains(&timeframe.as_str()) => {}
+                None => unmapped.push(timeframe.as_str()),
+            }
+        }
+        if !unmapped.is_empty() {
+            warnings.push(ImportWarning::new(
+                "dimension_group",
+                format!("{}.{}", view.name, name),
+                format!(
+                    "timeframes `{}` have no equivalent grain",
+                    unmapped.join(", ")
+                ),
+            ));
+        }
+        if grains.is_empty() {
+            grains = TimeGrain::calendar_grains();
+        }
+        grains.sort_by_key(|g| TimeGrain::calendar_grains().iter().position(|c| c == g));
+
+        Ok(Dimension {
+            name: column,
+            description: description(def),
+            type_: Some("timestamp".to_string()),
+            searchable: false,
+            options: None,
+            time: Some(TimeDimension {
+                grains,
+                fiscal_year_start_month: fiscal
+                    .then(|| self.fiscal_month_offset.unwrap_or(0) % 12 + 1),
+            }),
+        })
+    }
+
+    fn convert_measure(&self, view: &View, name: &str, def: &[Node]) -> Result<Measure, String> {
+        if find(def, "filters").is_some() {
+            return Err("filtered measures are not supported".to_string());
+        }
+        let type_ = text(def, "type").unwrap_or("count");
+        let mut percentile = None;
+        let agg = match type_ {
+            "count" => Aggregation::Count,
+            "count_distinct" => Aggregation::CountDistinct,
+            "sum" => Aggregation::Sum,
+            "average" => Aggregation::Avg,
+            "min" => Aggregation::Min,
+            "max" => Aggregation::Max,
+            "median" => Aggregation::Median,
+            "percentile" => {
+                let value: f64 = text(def, "percentile")
+                    .and_then(|p| p.parse().ok())
+                    .ok_or("percentile measure has no numeric `percentile`")?;
+                percentile = Some(value / 100.0);
+                Aggregation::Percentile
+            }
+            other => return Err(format!("`type: {}` measures are not supported", other)),
+        };
+        let expr = match (agg, text(def, "sql")) {
+            (Aggregation::Count, _) => "*".to_string(),
+            (_, Some(sql)) => self.substitute(view, sql, 0, false)?,
+            (_, None) => return Err(format!("`type: {}` measure has no `sql`", type_)),
+        };
+        Ok(Measure {
+            name: name.to_string(),
+            description: description(def),
+            type_: None,
+            expr: (expr != name).then_some(expr),
+            agg: Some(agg),
+            percentile,
+        })
+    }
+
+    /// `type: number` measures combine other measures. Plain ratios and
+    /// differences keep the references; other expressions are inlined.
+    fn convert_number_measure(
+        &self,
+        view: &View,
+        name: &str,
+        def: &[Node],
+    ) -> Result<Metric, String> {
+        let sql = text(def, "sql").ok_or("`type: number` measure has no `sql`")?;
+        let mut metric = Metric {
+            name: name.to_string(),
+            expr: String::new(),
+            description: description(def),
+            args: Vec::new(),
+            type_: MetricType::Expression,
+            numerator: None,
+            denominator: None,
+            minuend: None,
+            subtrahend: None,
+            measure: None,
+            time_dimension: None,
+            window: None,
+            grain_to_date: None,
+        };
+
+        if let Some((left, op, right)) = binary_measure_expression(sql) {
+            if view.measure(left).is_some() && view.measure(right).is_some() {
+                let (left, right) = (Some(left.to_string()), Some(right.to_string()));
+                if op == '/' {
+                    metric.type_ = MetricType::Ratio;
+                    metric.numerator = left;
+                    metric.denominator = right;
+                } else {
+                    metric.type_ = MetricType::Difference;
+                    metric.minuend = left;
+                    metric.subtrahend = right;
+                }
+                return Ok(metric);
+            }
+        }
+
+        metric.expr = self.substitute(view, sql, 0, true)?;
+        Ok(metric)
+    }
+
+    /// The column a dimension reads, or an error if its SQL is an expression.
+    fn column(&self, view: &View, name: &str) -> Result<String, String> {
+        let sql = self.dimension_sql(view, name, 0)?;
+        if is_identifier(&sql) {
+            Ok(sql)
+        } else {
+            Err(format!(
+                "SQL `{}` is an expression; only columns can be imported",
+                sql
+            ))
+        }
+    }
+
+    fn dimension_sql(&self, view: &View, field: &str, depth: usize) -> Result<String, String> {
+        let def = view.dimensions.get(field).copied().or_else(|| {
+            // `${created_date}` refers to the `created` dimension group
+            view.groups
+                .iter()
+                .find(|(group, _)| {
+                    field
+                        .strip_prefix(**group)
+                        .is_some_and(|rest| rest.is_empty() || rest.starts_with('_'))
+                })
+                .map(|(_, def)| *def)
+        });
+        let Some(def) = def else {
+            return Err(format!(
+                "`{}` is not a dimension of view `{}`",
+                field, view.name
+            ));
+        };
+        let default_sql = format!("${{TABLE}}.{}", field);
+        let sql = text(def, "sql").unwrap_or(&default_sql);
+        self.substitute(view, sql, depth + 1, false)
+    }
+
+    fn measure_sql(&self, view: &View, field: &str, depth: usize) -> Result<String, String> {
+        let def = view
+            .measure(field)
+            .ok_or_else(|| format!("`{}` is not a measure", field))?;
+        if text(def, "type") == Some("number") {
+            let sql =
+                text(def, "sql").ok_or_else(|| format!("measure `{}` has no `sql`", field))?;
+            return self.substitute(view, sql, depth + 1, true);
+        }
+        let measure = self.convert_measure(view, field, def)?;
+        let expr = measure.expr.as_deref().unwrap_or(field);
+        match measure.agg {
+            Some(Aggregation::Sum) => Ok(format!("SUM({})", expr)),
+            Some(Aggregation::Count) => Ok(format!("COUNT({})", expr)),
+            Some(Aggregation::CountDistinct) => Ok(format!("COUNT(DISTINCT {})", expr)),
+            Some(Aggregation::Avg) => Ok(format!("AVG({})", expr)),
+            Some(Aggregation::Min) => Ok(format!("MIN({})", expr)),
+            Some(Aggregation::Max) => Ok(format!("MAX({})", expr)),
+            _ => Err(format!(
+                "`{}` can't be inlined because its aggregation differs between databases",
+                field
+            )),
+        }
+    }
+
+    /// Resolves LookML substitutions in `sql`.
+    fn substitute(
+        &self,
+        view: &View,
+        sql: &str,
+        depth: usize,
+        measures: bool,
+    ) -> Result<String, String> {
+        if depth > MAX_REFERENCE_DEPTH {
+            return Err("references are circular or nested too deeply".to_string());
+        }
+        if has_liquid(sql) {
+            return Err("Liquid templating is not supported".to_string());
+        }
+
+        let mut out = String::new();
+        let mut rest = sql;
+        while let Some(start) = rest.find("${") {
+            out.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find('}').ok_or("unterminated `${`")?;
+            let reference = after[..end].trim();
+            rest = &after[end + 1..];
+
+            let replacement = match reference.split_once('.') {
+                None if reference == "TABLE" => match rest.strip_prefix('.') {
+                    // `${TABLE}.amount` is just the column
+                    Some(column) => {
+                        rest = column;
+                        String::new()
+                    }
+                    None => view.table.clone(),
+                },
+                None if measures && view.measure(reference).is_some() => {
+                    wrap(self.measure_sql(view, reference, depth)?)
+                }
+                None => wrap(self.dimension_sql(view, reference, depth)?),
+                Some((other, "SQL_TABLE_NAME")) => self.view(other)?.table.clone(),
+                Some((other, field)) if other == view.name => {
+                    wrap(self.dimension_sql(view, field, depth)?)
+                }
+                Some((other, field)) => {
+                    let other_view = self.view(other)?;
+                    let column = self.dimension_sql(other_view, field, depth)?;
+                    if !is_identifier(&column) {
+                        return Err(format!(
+                            "`${{{}}}` is an expression in another view",
+                            reference
+                        ));
+                    }
+                    let model = other_view
+                        .model
+                        .ok_or_else(|| format!("view `{}` was not imported", other))?;
+                    format!("{}.{}", self.models[model].name, column)
+                }
+            };
+            out.push_str(&replacement);
+        }
+        out.push_str(rest);
+        Ok(unquote(out.trim()))
+    }
+
+    fn view(&self, name: &str) -> Result<&View<'a>, String> {
+        self.views
+            .get(name)
+            .ok_or_else(|| format!("view `{}` is not defined", name))
+    }
+
+    fn convert_explore(&mut self, explore: &'a Node) {
+        let explore_name = explore.name.as_deref().unwrap_or_default();
+        let block = explore.block();
+        for key in [
+            "sql_always_where",
+            "sql_always_having",
+            "always_filter",
+            "conditionally_filter",
+            "access_filter",
+        ] {
+            if find(block, key).is_some() {
+                self.warn(
+                    "explore",
+                    explore_name,
+                    format!(
+                        "`{}` is not supported; apply the filter in queries by hand",
+                        key
+                    ),
+                );
+            }
+        }
+
+        let base = text(block, "view_name")
+            .or_else(|| text(block, "from"))
+            .unwrap_or(explore_name);
+        let joins: Vec<&Node> = block.iter().filter(|n| n.key == "join").collect();
+        let mut aliases: HashMap<&str, &str> = HashMap::from([(explore_name, base), (base, base)]);
+        for join in &joins {
+            if let Some(alias) = join.name.as_deref() {
+                aliases.insert(alias, text(join.block(), "from").unwrap_or(alias));
+            }
+        }
+
+        for join in joins {
+            let Some(alias) = join.name.as_deref() else {
+                continue;
+            };
+            let qualified = format!("{}.{}", explore_name, alias);
+            if let Err(message) = self.convert_join(base, alias, join.block(), &aliases) {
+                self.warn("join", qualified, message);
+            }
+        }
+    }
+
+    fn convert_join(
+        &mut self,
+        base: &str,
+        alias: &str,
+        def: &[Node],
+        aliases: &HashMap<&str, &str>,
+    ) -> Result<(), String> {
+        let join_type = match text(def, "type").unwrap_or("left_outer") {
+            "left_outer" => "LEFT",
+            "inner" => "INNER",
+            "full_outer" => "FULL",
+            other => return Err(format!("`type: {}` joins are not supported", other)),
+        };
+        let cardinality = text(def, "relationship")
+            .unwrap_or("many_to_one")
+            .replace('_', "-");
+        if !SUPPORTED_CARDINALITIES.contains(&cardinality.as_str()) {
+            return Err(format!("relationship `{}` is not supported", cardinality));
+        }
+
+        // Each side of the join as (alias, LookML field)
+        let (parent, child) = if let Some(sql_on) = text(def, "sql_on") {
+            let (left, right) = join_condition(sql_on).ok_or_else(|| {
+                format!(
+                    "`sql_on: {}` is not a single equality between two fields",
+                    sql_on
+                )
+            })?;
+            match (left.0 == alias, right.0 == alias) {
+                (false, true) => (left, right),
+                (true, false) => (right, left),
+                _ => {
+                    return Err(format!(
+                        "`sql_on: {}` must compare `{}` with another view",
+                        sql_on, alias
+                    ))
+                }
+            }
+        } else if let Some(foreign_key) = text(def, "foreign_key") {
+            let view = self.view(aliases.get(alias).copied().unwrap_or(alias))?;
+            let primary_key = view
+                .dimensions
+                .iter()
+                .find(|(_, d)| is_yes(d, "primary_key"))
+                .map(|(name, _)| *name)
+                .ok_or_else(|| {
+                    format!(
+                        "`foreign_key` joins need a `primary_key` dimension on `{}`",
+                        view.name
+                    )
+                })?;
+            ((base, foreign_key), (alias, primary_key))
+        } else {
+            return Err("join has no `sql_on` or `foreign_key`".to_string());
+        };
+        let sql_where = text(def, "sql_where").is_some();
+
+        let resolve = |(side_alias, field): (&str, &str)| -> Result<(usize, String), String> {
+            let view_name = aliases
+                .get(side_alias)
+                .copied()
+                .ok_or_else(|| format!("`{}` is not joined in this explore", side_alias))?;
+            let view = self.view(view_name)?;
+            let model = view
+                .model
+                .ok_or_else(|| format!("view `{}` was not imported", view_name))?;
+            Ok((model, self.column(view, field)?))
+        };
+        let (parent_model, primary_key) = resolve(parent)?;
+        let (child_model, foreign_key) = resolve(child)?;
+
+        let relationship = Relationship {
+            name: self.models[child_model].name.clone(),
+            primary_key,
+            foreign_key,
+            type_: Some(join_type.to_string()),
+            cardinality: Some(cardinality),
+            description: None,
+        };
+        let existing = self.models[parent_model]
+            .relationships
+            .iter()
+            .find(|r| r.name == relationship.name);
+        match existing {
+            Some(r)
+                if r.primary_key == relationship.primary_key
+                    && r.foreign_key == relationship.foreign_key => {}
+            Some(_) => {
+                return Err(format!(
+                    "`{}` already joins `{}` on other keys; only the first join was imported",
+                    self.models[parent_model].name, relationship.name
+                ))
+            }
+            None => self.models[parent_model].relationships.push(relationship),
+        }
+
+        if sql_where {
+            return Err(
+                "`sql_where` is not supported; the join was imported without it".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn description(def: &[Node]) -> Option<String> {
+    text(def, "description")
+        .or_else(|| text(def, "label"))
+        .map(str::to_string)
+}
+
+fn has_liquid(sql: &str) -> bool {
+    sql.contains("{%") || sql.contains("{{")
+}
+
+fn unquote(s: &str) -> String {
+    let s = s.trim();
+    for (open, close) in [('"', '"'), ('`', '`'), ('[', ']')] {
+        if let Some(inner) = s.strip_prefix(open).and_then(|s| s.strip_suffix(close)) {
+            if is_identifier(inner) {
+                return inner.to_string();
+            }
+        }
+    }
+    s.to_string()
+}
+
+fn wrap(sql: String) -> String {
+    if is_identifier(&sql)
+        || sql.ends_with(')') && sql.find('(').is_some_and(|i| is_identifier(&sql[..i]))
+    {
+        sql
+    } else {
+        format!("({})", sql)
+    }
+}
+
+/// Splits `${a} / ${b}`, `${a} / NULLIF(${b}, 0)` or `${a} - ${b}`.
+fn binary_measure_expression(sql: &str) -> Option<(&str, char, &str)> {
+    let reference = |s: &str| -> Option<(usize, usize)> {
+        let start = s.find("${")? + 2;
+        let end = start + s[start..].find('}')?;
+        Some((start, end))
+    };
+    let (l_start, l_end) = reference(sql)?;
+    if !sql[..l_start - 2].trim().is_empty() {
+        return None;
+    }
+    let rest = sql[l_end + 1..].trim_start();
+    let op = rest.chars().next().filter(|c| matches!(c, '/' | '-'))?;
+    let right = rest[1..].trim();
+    let offset = sql.len() - right.len();
+    let (r_start, r_end) = reference(right)?;
+
+    let before = right[..r_start - 2].replace(' ', "").to_uppercase();
+    let after = right[r_end + 1..].replace(' ', "");
+    let plain = before.is_empty() && after.is_empty();
+    let nullif = op == '/' && before == "NULLIF(" && after == ",0)";
+    if !(plain || nullif) {
+        return None;
+    }
+    let (left, right) = (&sql[l_start..l_end], &sql[offset + r_start..offset + r_end]);
+    (is_identifier(left) && is_identifier(right)).then_some((left, op, right))
+}
+
+/// A `(view, field)` pair from `${view.field}`
+type QualifiedField<'s> = (&'s str, &'s str);
+
+/// Splits `${a.x} = ${b.y}` into `(a, x)` and `(b, y)`.
+fn join_condition(sql_on: &str) -> Option<(QualifiedField<'_>, QualifiedField<'_>)> {
+    let (left, right) = sql_on.split_once('=')?;
+    Some((qualified_field(left)?, qualified_field(right)?))
+}
+
+fn qualified_field(side: &str) -> Option<QualifiedField<'_>> {
+    let inner = side.trim().strip_prefix("${")?.strip_suffix('}')?;
+    let (view, field) = inner.trim().split_once('.')?;
+    (is_identifier(view) && is_identifier(field)).then_some((view, field))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::{validate_models, SourcedModel};
+
+    const ORDERS_VIEW: &str = r#"
+# Orders, one row per order
+view: orders {
+  sql_table_name: analytics.public.orders ;;
+  description: "All orders"
+
+  dimension: id {
+    primary_key: yes
+    type: number
+    sql: ${TABLE}.id ;;
+  }
+  dimension: user_id {
+    type: number
+    sql: ${TABLE}."user_id" ;;
+  }
+  dimension: amount {
+    type: number
+    sql: ${TABLE}.amount ;;
+  }
+  dimension: status {
+    label: "Order Status"
+    sql: ${TABLE}.status ;;
+  }
+  dimension: is_large {
+    type: yesno
+    sql: ${amount} > 100 ;;
+  }
+  dimension_group: created {
+    type: time
+    timeframes: [raw, date, week, month, fiscal_quarter, hour_of_day]
+    sql: ${TABLE}.created_at ;;
+  }
+
+  measure: count {
+    type: count
+  }
+  measure: total_revenue {
+    type: sum
+    sql: ${amount} ;;
+  }
+  measure: p90_amount {
+    type: percentile
+    percentile: 90
+    sql: ${amount} ;;
+  }
+  measure: aov {
+    type: number
+    sql: ${total_revenue} / NULLIF(${count}, 0) ;;
+  }
+  measure: net {
+    type: number
+    sql: ${total_revenue} - ${count} ;;
+  }
+  measure: revenue_per_user {
+    type: number
+    sql: 1.0 * ${total_revenue} / NULLIF(COUNT(DISTINCT ${user_id}), 0) ;;
+  }
+  measure: completed_orders {
+    type: count
+    filters: [status: "complete"]
+  }
+}
+"#;
+
+    const MODEL_FILE: &str = r#"
+connection: "warehouse"
+fiscal_month_offset: 3
+include: "*.view.lkml"
+
+view: users {
+  sql_table_name: `analytics.public.users` ;;
+  dimension: id {
+    primary_key: yes
+    sql: ${TABLE}.id ;;
+  }
+  dimension: region {}
+}
+
+view: user_facts {
+  derived_table: {
+    sql: SELECT user_id, COUNT(*) AS orders FROM ${orders.SQL_TABLE_NAME} GROUP BY 1 ;;
+  }
+  dimension: user_id {
+    primary_key: yes
+  }
+}
+
+explore: orders {
+  always_filter: {
+    filters: [orders.status: "complete"]
+  }
+  join: users {
+    type: left_outer
+    relationship: many_to_one
+    sql_on: ${orders.user_id} = ${users.id} ;;
+  }
+  join: user_facts {
+    relationship: one_to_one
+    foreign_key: user_id
+  }
+  join: buyers {
+    from: users
+    sql_on: ${buyers.region} = ${orders.status} ;;
+  }
+  join: everything {
+    from: users
+    type: cross
+  }
+}
+"#;
+
+    fn import() -> Import {
+        import_lookml(&[
+            LookmlFile::parse(ORDERS_VIEW).unwrap(),
+            LookmlFile::parse(MODEL_FILE).unwrap(),
+        ])
+    }
+
+    #[test]
+    fn test_parse_lookml() {
+        let file = LookmlFile::parse(ORDERS_VIEW).unwrap();
+        let view = &file.nodes[0];
+        assert_eq!(view.key, "view");
+        assert_eq!(view.name.as_deref(), Some("orders"));
+        assert_eq!(
+            text(view.block(), "sql_table_name"),
+            Some("analytics.public.orders")
+        );
+        assert_eq!(text(view.block(), "description"), Some("All orders"));
+
+        let created = view
+            .block()
+            .iter()
+            .find(|n| n.name.as_deref() == Some("created"))
+            .unwrap();
+        assert_eq!(
+            list(created.block(), "timeframes").unwrap(),
+            [
+                "raw",
+                "date",
+                "week",
+                "month",
+                "fiscal_quarter",
+                "hour_of_day"
+            ]
+        );
+        let completed = view
+            .block()
+            .iter()
+            .find(|n| n.name.as_deref() == Some("completed_orders"))
+            .unwrap();
+        assert_eq!(
+            list(completed.block(), "filters").unwrap(),
+            ["status: complete"]
+        );
+
+        let error =
+            LookmlFile::parse("view: orders {\n  dimension: id {\n    sql: ${TABLE}.id\n  }\n}")
+                .unwrap_err();
+        assert_eq!(error.line, 3);
+        assert!(error.message.contains(";;"));
+        let error = LookmlFile::parse("view: orders {\n  dimension id {}\n}").unwrap_err();
+        assert_eq!(error.to_string(), "line 2: expected `:` after `dimension`");
+        assert!(LookmlFile::parse("view: orders {").is_err());
+    }
+
+    #[test]
+    fn test_import_views() {
+        let import = import();
+        let orders = &import.models[0];
+        assert_eq!(orders.name, "orders");
+        assert_eq!(orders.database.as_deref(), Some("analytics"));
+        assert_eq!(orders.schema.as_deref(), Some("public"));
+        assert_eq!(orders.description.as_deref(), Some("All orders"));
+
+        let dimension_names: Vec<&str> =
+            orders.dimensions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            dimension_names,
+            ["id", "user_id", "amount", "status", "created_at"]
+        );
+        let status = &orders.dimensions[3];
+        assert_eq!(status.description.as_deref(), Some("Order Status"));
+        let created = orders.dimensions[4].time.as_ref().unwrap();
+        assert_eq!(
+            created.grains,
+            [TimeGrain::Day, TimeGrain::Week, TimeGrain::Month]
+        );
+        assert_eq!(created.fiscal_year_start_month, Some(4));
+
+        let measure = |name: &str| orders.measures.iter().find(|m| m.name == name).unwrap();
+        assert_eq!(measure("count").expr.as_deref(), Some("*"));
+        assert_eq!(measure("count").agg, Some(Aggregation::Count));
+        assert_eq!(measure("total_revenue").expr.as_deref(), Some("amount"));
+        assert_eq!(measure("p90_amount").percentile, Some(0.9));
+
+        let metric = |name: &str| orders.metrics.iter().find(|m| m.name == name).unwrap();
+        assert_eq!(metric("aov").type_, MetricType::Ratio);
+        assert_eq!(metric("aov").numerator.as_deref(), Some("total_revenue"));
+        assert_eq!(metric("aov").denominator.as_deref(), Some("count"));
+        assert_eq!(metric("net").type_, MetricType::Difference);
+        assert_eq!(
+            metric("revenue_per_user").expr,
+            "1.0 * SUM(amount) / NULLIF(COUNT(DISTINCT user_id), 0)"
+        );
+
+        let users = &import.models[1];
+        assert_eq!(users.name, "users");
+        assert_eq!(users.schema.as_deref(), Some("public"));
+        assert_eq!(users.dimensions.len(), 2);
+        assert_eq!(import.models[2].name, "user_facts");
+    }
+
+    #[test]
+    fn test_import_explore_joins() {
+        let import = import();
+        let joins = &import.models[0].relationships;
+        assert_eq!(joins.len(), 2);
+
+        assert_eq!(joins[0].name, "users");
+        assert_eq!(joins[0].primary_key, "user_id");
+        assert_eq!(joins[0].foreign_key, "id");
+        assert_eq!(joins[0].type_.as_deref(), Some("LEFT"));
+        assert_eq!(joins[0].cardinality.as_deref(), Some("many-to-one"));
+
+        assert_eq!(joins[1].name, "user_facts");
+        assert_eq!(joins[1].primary_key, "user_id");
+        assert_eq!(joins[1].foreign_key, "user_id");
+        assert_eq!(joins[1].cardinality.as_deref(), Some("one-to-one"));
+
+        let sourced: Vec<SourcedModel> = import
+            .models
+            .iter()
+            .map(|model| SourcedModel { file: None, model })
+            .collect();
+        assert_eq!(validate_models(&sourced), Vec::new());
+    }
+
+    #[test]
+    fn test_import_review_report() {
+        let import = import();
+        let messages: Vec<String> = import.warnings.iter().map(|w| w.to_string()).collect();
+        let has = |needle: &str| messages.iter().any(|m| m.contains(needle));
+
+        assert!(has("view `user_facts`: derived tables are not supported; materialize this SQL as `user_facts`"));
+        assert!(has("FROM ${orders.SQL_TABLE_NAME}"));
+        assert!(has(
+            "dimension `orders.is_large`: SQL `amount > 100` is an expression"
+        ));
+        assert!(has(
+            "dimension_group `orders.created`: timeframes `hour_of_day` have no equivalent grain"
+        ));
+        assert!(has(
+            "measure `orders.completed_orders`: filtered measures are not supported"
+        ));
+        assert!(has("explore `orders`: `always_filter` is not supported"));
+        assert!(has(
+            "join `orders.buyers`: `orders` already joins `users` on other keys"
+        ));
+        assert!(has(
+            "join `orders.everything`: `type: cross` joins are not supported"
+        ));
+        assert_eq!(messages.len(), 7, "{:#?}", messages);
+    }
+
+    #[test]
+    fn test_substitution_edge_cases() {
+        let import = import_lookml(&[LookmlFile::parse(
+            r#"
+view: events {
+  sql_table_name: {{ _user_attributes['schema'] }}.events ;;
+  dimension: id { sql: ${id} ;; }
+  dimension: kind { sql: {% if x %} a {% endif %} ;; }
+  measure: p50 {
+    type: median
+    sql: ${TABLE}.duration ;;
+  }
+  measure: scaled {
+    type: number
+    sql: ${p50} * 2 ;;
+  }
+}
+view: +events {
+  dimension: extra {}
+}
+"#,
+        )
+        .unwrap()]);
+        let messages: Vec<String> = import.warnings.iter().map(|w| w.to_string()).collect();
+        let has = |needle: &str| messages.iter().any(|m| m.contains(needle));
+
+        assert_eq!(import.models[0].name, "events");
+        assert!(has("view `events`: Liquid in `sql_table_name"));
+        assert!(has("dimension `events.id`: references are circular"));
+        assert!(has(
+            "dimension `events.kind`: Liquid templating is not supported"
+        ));
+        assert!(has("measure `events.scaled`: `p50` can't be inlined"));
+        assert!(has("view `+events`: refinements are not supported"));
+        assert_eq!(
+            import.models[0].measures[0].expr.as_deref(),
+            Some("duration")
+        );
+    }
+
+    #[test]
+    fn test_binary_measure_expression() {
+        assert_eq!(
+            binary_measure_expression("${a} / ${b}"),
+            Some(("a", '/', "b"))
+        );
+        assert_eq!(
+            binary_measure_expression("${a}/NULLIF(${b}, 0)"),
+            Some(("a", '/', "b"))
+        );
+        assert_eq!(
+            binary_measure_expression("${a} - ${b}"),
+            Some(("a", '-', "b"))
+        );
+        assert_eq!(binary_measure_expression("${a} - NULLIF(${b}, 0)"), None);
+        assert_eq!(binary_measure_expression("2 * ${a} / ${b}"), None);
+        assert_eq!(binary_measure_expression("${a} / ${b} * 100"), None);
+    }
+}
@@ -0,0 +1,1195 @@
+//! Import of dbt MetricFlow `semantic_models:` and `metrics:` definitions.
+//!
+//! Each semantic model becomes one [`Model`] named after the dbt model it
+//! `ref`s, so imported files line up with the ones `buster generate` builds
+//! from the dbt catalog. Entities become key dimensions, and foreign entities
+//! become `many-to-one` entities pointing at the model whose primary entity
+//! shares their name. Metrics are attached to the model that owns the
+//! measures they are built on:
+//!
+//! - `simple` metrics become a copy of their measure under the metric's name,
+//!   or nothing when both already share a name.
+//! - `ratio` metrics become `ratio` metrics.
+//! - `derived` metrics of the form `a - b` or `a / b` become `difference` and
+//!   `ratio` metrics.
+//! - `cumulative` metrics become `cumulative` metrics over the measure's
+//!   `agg_time_dimension`.
+//!
+//! Filters, offsets, conversion metrics and anything else without an
+//! equivalent are reported as warnings rather than dropped silently.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Deserializer};
+use serde_yaml::Value;
+
+use super::{is_identifier, Import, ImportWarning};
+use crate::models::{
+    Aggregation, Dimension, Measure, Metric, MetricType, Model, Relationship, TimeDimension,
+    TimeGrain,
+};
+
+/// The MetricFlow parts of a dbt properties file. Other top-level keys
+/// (`models:`, `sources:`, ...) are ignored.
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricFlowFile {
+    #[serde(default)]
+    pub semantic_models: Vec<SemanticModel>,
+    #[serde(default)]
+    pub metrics: Vec<MetricDef>,
+}
+
+impl MetricFlowFile {
+    pub fn from_yaml(yaml: &str) -> Result<Self, serde_yaml::Error> {
+        if yaml.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(yaml)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.semantic_models.is_empty() && self.metrics.is_empty()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SemanticModel {
+    pub name: String,
+    pub description: Option<String>,
+    pub model: Option<String>, // e.g. `ref('orders')`
+    pub defaults: Option<SemanticModelDefaults>,
+    #[serde(default)]
+    pub entities: Vec<EntityDef>,
+    #[serde(default)]
+    pub dimensions: Vec<DimensionDef>,
+    #[serde(default)]
+    pub measures: Vec<MeasureDef>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SemanticModelDefaults {
+    pub agg_time_dimension: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EntityDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String, // primary, unique, foreign or natural
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub expr: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DimensionDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: String, // categorical or time
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub expr: Option<String>,
+    pub description: Option<String>,
+    pub type_params: Option<DimensionTypeParams>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DimensionTypeParams {
+    pub time_granularity: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MeasureDef {
+    pub name: String,
+    pub agg: String,
+    #[serde(default, deserialize_with = "scalar_string")]
+    pub expr: Option<String>,
+    pub description: Option<String>,
+    pub agg_params: Option<AggParams>,
+    pub agg_time_dimension: Option<String>,
+    pub non_additive_dimension: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AggParams {
+    pub percentile: Option<f64>,
+    #[serde(default)]
+    pub use_discrete_percentile: bool,
+    #[serde(default)]
+    pub use_approximate_percentile: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MetricDef {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_: Option<String>, // simple, ratio, derived, cumulative or conversion
+    pub description: Option<String>,
+    pub label: Option<String>,
+    #[serde(default)]
+    pub type_params: MetricTypeParams,
+    pub filter: Option<Value>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MetricTypeParams {
+    pub measure: Option<MetricInput>,
+    pub numerator: Option<MetricInput>,
+    pub denominator: Option<MetricInput>,
+    pub expr: Option<String>,
+    #[serde(default)]
+    pub metrics: Vec<MetricInput>,
+    pub window: Option<String>,
+    pub grain_to_date: Option<String>,
+    pub cumulative_type_params: Option<CumulativeTypeParams>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CumulativeTypeParams {
+    pub window: Option<String>,
+    pub grain_to_date: Option<String>,
+    pub period_agg: Option<String>,
+}
+
+/// A measure or metric used as a metric input, written either as a bare
+/// name or as a mapping with per-input options.
+#[derive(Debug, Deserialize)]
+#[serde(from = "RawMetricInput")]
+pub struct MetricInput {
+    pub name: String,
+    pub alias: Option<String>,
+    pub filter: Option<Value>,
+    pub offset_window: Option<String>,
+    pub offset_to_grain: Option<String>,
+    pub fill_nulls_with: Option<Value>,
+    pub join_to_timespine: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+#[allow(clippy::large_enum_variant)] // Only lives for the duration of deserialization
+enum RawMetricInput {
+    Name(String),
+    Detailed {
+        name: String,
+        alias: Option<String>,
+        filter: Option<Value>,
+        offset_window: Option<String>,
+        offset_to_grain: Option<String>,
+        fill_nulls_with: Option<Value>,
+        #[serde(default)]
+        join_to_timespine: bool,
+    },
+}
+
+impl From<RawMetricInput> for MetricInput {
+    fn from(raw: RawMetricInput) -> Self {
+        match raw {
+            RawMetricInput::Name(name) => MetricInput {
+                name,
+                alias: None,
+                filter: None,
+                offset_window: None,
+                offset_to_grain: None,
+                fill_nulls_with: None,
+                join_to_timespine: false,
+            },
+            RawMetricInput::Detailed {
+                name,
+                alias,
+                filter,
+                offset_window,
+                offset_to_grain,
+                fill_nulls_with,
+                join_to_timespine,
+            } => MetricInput {
+                name,
+                alias,
+                filter,
+                offset_window,
+                offset_to_grain,
+                fill_nulls_with,
+                join_to_timespine,
+            },
+        }
+    }
+}
+
+/// Accepts `expr: 1` as well as `expr: "1"`.
+fn scalar_string<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<Value>::deserialize(deserializer)? {
+        Some(Value::String(s)) => Some(s),
+        Some(Value::Number(n)) => Some(n.to_string()),
+        Some(Value::Bool(b)) => Some(b.to_string()),
+        _ => None,
+    })
+}
+
+/// Converts the semantic models and metrics of a set of dbt properties files.
+///
+/// Returns one model per semantic model, in input order, so callers can map
+/// models back to the file that declared them.
+pub fn import_metricflow(files: &[MetricFlowFile]) -> Import {
+    let semantic_models: Vec<&SemanticModel> =
+        files.iter().flat_map(|f| f.semantic_models.iter()).collect();
+    let metrics: Vec<&MetricDef> = files.iter().flat_map(|f| f.metrics.iter()).collect();
+
+    let mut importer = Importer {
+        warnings: Vec::new(),
+        models: Vec::new(),
+        measures: HashMap::new(),
+        metrics: metrics.iter().map(|m| (m.name.as_str(), *m)).collect(),
+        converted: HashMap::new(),
+        in_progress: HashSet::new(),
+    };
+
+    for semantic_model in &semantic_models {
+        importer.convert_semantic_model(semantic_model);
+    }
+    importer.convert_entities(&semantic_models);
+    for metric in &metrics {
+        importer.convert_metric(metric);
+    }
+
+    Import {
+        models: importer.models,
+        warnings: importer.warnings,
+    }
+}
+
+#[derive(Clone, Copy)]
+struct ImportedMeasure<'a> {
+    model: usize,
+    def: &'a MeasureDef,
+    time_dimension: Option<&'a str>,
+}
+
+struct Importer<'a> {
+    warnings: Vec<ImportWarning>,
+    models: Vec<Model>,
+    measures: HashMap<&'a str, ImportedMeasure<'a>>,
+    metrics: HashMap<&'a str, &'a MetricDef>,
+    /// Model each metric was imported into, or `None` if it was skipped
+    converted: HashMap<&'a str, Option<usize>>,
+    in_progress: HashSet<&'a str>,
+}
+
+impl<'a> Importer<'a> {
+    fn warn(&mut self, construct: &'static str, name: impl Into<String>, message: impl Into<String>) {
+        self.warnings.push(ImportWarning::new(construct, name, message));
+    }
+
+    fn convert_semantic_model(&mut self, semantic_model: &'a SemanticModel) {
+        let name = semantic_model
+            .model
+            .as_deref()
+            .and_then(ref_target)
+            .unwrap_or_else(|| semantic_model.name.clone());
+        let index = self.models.len();
+
+        let mut dimensions = Vec::new();
+        for dim in &semantic_model.dimensions {
+            let qualified = format!("{}.{}", name, dim.name);
+            if let Some(expr) = dim.expr.as_deref().filter(|e| *e != dim.name) {
+                self.warn(
+                    "dimension",
+                    &qualified,
+                    format!(
+                        "`expr: {}` is not supported on dimensions; imported as the column `{}`",
+                        expr, dim.name
+                    ),
+                );
+            }
+            let time = match dim.type_.to_lowercase().as_str() {
+                "time" => {
+                    let granularity = dim
+                        .type_params
+                        .as_ref()
+                        .and_then(|p| p.time_granularity.as_deref());
+                    Some(TimeDimension {
+                        grains: self.grains_from(&qualified, granularity),
+                        fiscal_year_start_month: None,
+                    })
+                }
+                "categorical" => None,
+                other => {
+                    self.warn(
+                        "dimension",
+                        &qualified,
+                        format!("unsupported dimension type `{}`; imported as categorical", other),
+                    );
+                    None
+                }
+            };
+            dimensions.push(Dimension {
+                name: dim.name.clone(),
+                description: dim.description.clone(),
+                type_: None,
+                searchable: false,
+                options: None,
+                time,
+            });
+        }
+
+        // Entity columns are the join keys, so they must be fields of the model
+        for entity in &semantic_model.entities {
+            let column = entity.expr.as_deref().unwrap_or(&entity.name);
+            if !is_identifier(column) {
+                continue; // Reported when joins are resolved
+            }
+            if !dimensions.iter().any(|d: &Dimension| d.name == column) {
+                dimensions.push(Dimension {
+                    name: column.to_string(),
+                    description: entity.description.clone(),
+                    type_: None,
+                    searchable: false,
+                    options: None,
+                    time: None,
+                });
+            }
+        }
+
+        let default_time_dimension = semantic_model
+            .defaults
+            .as_ref()
+            .and_then(|d| d.agg_time_dimension.as_deref());
+        let mut measures = Vec::new();
+        for def in &semantic_model.measures {
+            measures.push(self.convert_measure(&format!("{}.{}", name, def.name), def));
+            self.measures.insert(
+                def.name.as_str(),
+                ImportedMeasure {
+                    model: index,
+                    def,
+                    time_dimension: def.agg_time_dimension.as_deref().or(default_time_dimension),
+                },
+            );
+        }
+
+        self.models.push(Model {
+            name,
+            description: semantic_model.description.clone(),
+            data_source_name: None,
+            database: None,
+            schema: None,
+            dimensions,
+            measures,
+            metrics: Vec::new(),
+            filters: Vec::new(),
+            relationships: Vec::new(),
+        });
+    }
+
+    fn grains_from(&mut self, dimension: &str, granularity: Option<&str>) -> Vec<TimeGrain> {
+        let calendar = TimeGrain::calendar_grains();
+        let Some(granularity) = granularity else {
+            return calendar;
+        };
+        match TimeGrain::parse(granularity) {
+            Some(grain) => match calendar.iter().position(|g| *g == grain) {
+                Some(start) => calendar[start..].to_vec(),
+                None => calendar,
+            },
+            None => {
+                self.warn(
+                    "dimension",
+                    dimension,
+                    format!(
+                        "time granularity `{}` is not supported; imported with day and coarser grains",
+                        granularity
+                    ),
+                );
+                calendar
+            }
+        }
+    }
+
+    fn convert_measure(&mut self, qualified: &str, def: &MeasureDef) -> Measure {
+        let mut expr = def.expr.clone().filter(|e| *e != def.name);
+        let mut percentile = None;
+        let agg = match def.agg.to_lowercase().as_str() {
+            "sum" => Some(Aggregation::Sum),
+            "count" => Some(Aggregation::Count),
+            "count_distinct" => Some(Aggregation::CountDistinct),
+            "average" | "avg" => Some(Aggregation::Avg),
+            "min" => Some(Aggregation::Min),
+            "max" => Some(Aggregation::Max),
+            "median" => Some(Aggregation::Median),
+            "sum_boolean" => {
+                let column = expr.take().unwrap_or_else(|| def.name.clone());
+                expr = Some(format!("CASE WHEN {} THEN 1 ELSE 0 END", column));
+                Some(Aggregation::Sum)
+            }
+            "percentile" => {
+                let params = def.agg_params.as_ref();
+                percentile = params.and_then(|p| p.percentile);
+                if percentile.is_none() {
+                    self.warn("measure", qualified, "percentile measure has no `agg_params.percentile`");
+                }
+                if params.is_some_and(|p| p.use_discrete_percentile || p.use_approximate_percentile) {
+                    self.warn(
+                        "measure",
+                        qualified,
+                        "discrete and approximate percentiles are not supported; imported as an exact continuous percentile",
+                    );
+                }
+                Some(Aggregation::Percentile)
+            }
+            other => {
+                self.warn(
+                    "measure",
+                    qualified,
+                    format!("unsupported aggregation `{}`; imported without an aggregation", other),
+                );
+                None
+            }
+        };
+        if def.non_additive_dimension.is_some() {
+            self.warn(
+                "measure",
+                qualified,
+                "`non_additive_dimension` is not supported; imported as a fully additive measure",
+            );
+        }
+
+        Measure {
+            name: def.name.clone(),
+            description: def.description.clone(),
+            type_: None,
+            expr,
+            agg,
+            percentile,
+        }
+    }
+
+    fn convert_entities(&mut self, semantic_models: &[&'a SemanticModel]) {
+        // Entity name -> (model index, key column) for entities that identify a row
+        let mut keys: HashMap<&str, (usize, &str)> = HashMap::new();
+        for (index, semantic_model) in semantic_models.iter().enumerate() {
+            for entity in &semantic_model.entities {
+                if matches!(entity.type_.to_lowercase().as_str(), "primary" | "unique" | "natural") {
+                    let column = entity.expr.as_deref().unwrap_or(&entity.name);
+                    keys.entry(entity.name.as_str()).or_insert((index, column));
+                }
+            }
+        }
+
+        for (index, semantic_model) in semantic_models.iter().enumerate() {
+            for entity in &semantic_model.entities {
+                let qualified = format!("{}.{}", self.models[index].name, entity.name);
+                let column = entity.expr.as_deref().unwrap_or(&entity.name);
+                if !is_identifier(column) {
+                    self.warn(
+                        "entity",
+                        &qualified,
+                        format!("`expr: {}` is not a column; the entity was not imported", column),
+                    );
+                    continue;
+                }
+                if entity.type_.to_lowercase() != "foreign" {
+                    continue;
+                }
+                let Some(&(target, target_column)) = keys.get(entity.name.as_str()) else {
+                    self.warn(
+                        "entity",
+                        &qualified,
+                        "no semantic model declares this entity as primary, unique or natural; the join was not imported",
+                    );
+                    continue;
+                };
+                let relationship = Relationship {
+                    name: self.models[target].name.clone(),
+                    primary_key: column.to_string(),
+                    foreign_key: target_column.to_string(),
+                    type_: None,
+                    cardinality: Some("many-to-one".to_string()),
+                    description: entity.description.clone(),
+                };
+                self.models[index].relationships.push(relationship);
+            }
+        }
+    }
+
+    /// Imports a metric once, after the metrics it is built on. Returns the
+    /// model it was attached to.
+    fn convert_metric(&mut self, def: &'a MetricDef) -> Option<usize> {
+        if let Some(converted) = self.converted.get(def.name.as_str()) {
+            return *converted;
+        }
+        if !self.in_progress.insert(def.name.as_str()) {
+            self.warn("metric", &def.name, "metric references itself");
+            return None;
+        }
+        let converted = self.convert_metric_uncached(def);
+        self.in_progress.remove(def.name.as_str());
+        self.converted.insert(def.name.as_str(), converted);
+        converted
+    }
+
+    fn convert_metric_uncached(&mut self, def: &'a MetricDef) -> Option<usize> {
+        if def.filter.is_some() {
+            self.warn("metric", &def.name, "metric filters are not supported; the metric was not imported");
+            return None;
+        }
+        let metric_type = def.type_.as_deref().unwrap_or_default().to_lowercase();
+        match metric_type.as_str() {
+            "simple" => self.convert_simple(def),
+            "ratio" => self.convert_ratio(def),
+            "derived" => self.convert_derived(def),
+            "cumulative" => self.convert_cumulative(def),
+            "conversion" => {
+                self.warn("metric", &def.name, "conversion metrics are not supported; the metric was not imported");
+                None
+            }
+            "" => {
+                self.warn(
+                    "metric",
+                    &def.name,
+                    "metric has no `type` (legacy dbt metrics are not supported); the metric was not imported",
+                );
+                None
+            }
+            other => {
+                self.warn(
+                    "metric",
+                    &def.name,
+                    format!("unsupported metric type `{}`; the metric was not imported", other),
+                );
+                None
+            }
+        }
+    }
+
+    /// Rejects input options that change what an input computes. Options
+    /// that only affect empty periods are imported without their effect.
+    fn check_input(&mut self, metric: &str, input: &MetricInput) -> bool {
+        if input.filter.is_some() {
+            self.warn(
+                "metric",
+                metric,
+                format!("filter on input `{}` is not supported; the metric was not imported", input.name),
+            );
+            return false;
+        }
+        if input.offset_window.is_some() || input.offset_to_grain.is_some() {
+            self.warn(
+                "metric",
+                metric,
+                format!("offset on input `{}` is not supported; the metric was not imported", input.name),
+            );
+            return false;
+        }
+        if input.fill_nulls_with.is_some() || input.join_to_timespine {
+            self.warn(
+                "metric",
+                metric,
+                format!(
+                    "`fill_nulls_with` and `join_to_timespine` on input `{}` are not supported; empty periods stay empty",
+                    input.name
+                ),
+            );
+        }
+        true
+    }
+
+    fn measure_input(&mut self, def: &MetricDef, input: Option<&MetricInput>) -> Option<ImportedMeasure<'a>> {
+        let Some(input) = input else {
+            self.warn("metric", &def.name, "metric has no `measure`; the metric was not imported");
+            return None;
+        };
+        if !self.check_input(&def.name, input) {
+            return None;
+        }
+        let measure = self.measures.get(input.name.as_str()).copied();
+        if measure.is_none() {
+            self.warn(
+                "metric",
+                &def.name,
+                format!("measure `{}` is not defined; the metric was not imported", input.name),
+            );
+        }
+        measure
+    }
+
+    fn metric_input(&mut self, def: &MetricDef, input: &MetricInput) -> Option<usize> {
+        if !self.check_input(&def.name, input) {
+            return None;
+        }
+        let Some(input_def) = self.metrics.get(input.name.as_str()).copied() else {
+            self.warn(
+                "metric",
+                &def.name,
+                format!("metric `{}` is not defined; the metric was not imported", input.name),
+            );
+            return None;
+        };
+        let model = self.convert_metric(input_def);
+        if model.is_none() {
+            self.warn(
+                "metric",
+                &def.name,
+                format!("input metric `{}` was not imported, so neither was this metric", input.name),
+            );
+        }
+        model
+    }
+
+    /// Converts metric inputs and checks that they all live on one model,
+    /// since compiled metrics can't span models.
+    fn same_model(&mut self, def: &MetricDef, inputs: &[&MetricInput]) -> Option<usize> {
+        let mut model: Option<usize> = None;
+        for input in inputs {
+            let input_model = self.metric_input(def, input)?;
+            match model {
+                Some(m) if m != input_model => {
+                    self.warn(
+                        "metric",
+                        &def.name,
+                        format!(
+                            "inputs come from both `{}` and `{}`; metrics spanning models are not supported",
+                            self.models[m].name, self.models[input_model].name
+                        ),
+                    );
+                    return None;
+                }
+                _ => model = Some(input_model),
+            }
+        }
+        if model.is_none() {
+            self.warn("metric", &def.name, "metric has no inputs; the metric was not imported");
+        }
+        model
+    }
+
+    fn convert_simple(&mut self, def: &'a MetricDef) -> Option<usize> {
+        let measure = self.measure_input(def, def.type_params.measure.as_ref())?;
+        // Measures are queryable by name, so a metric that shares its
+        // measure's name needs nothing more; otherwise the measure is
+        // repeated under the metric's name.
+        if def.name != measure.def.name {
+            let measures = &mut self.models[measure.model].measures;
+            let source = measures.iter().find(|m| m.name == measure.def.name)?;
+            let copy = Measure {
+                name: def.name.clone(),
+                description: def
+                    .description
+                    .clone()
+                    .or_else(|| def.label.clone())
+                    .or_else(|| source.description.clone()),
+                type_: source.type_.clone(),
+                expr: source.expr.clone(),
+                agg: source.agg,
+                percentile: source.percentile,
+            };
+            measures.push(copy);
+        }
+        Some(measure.model)
+    }
+
+    fn convert_ratio(&mut self, def: &'a MetricDef) -> Option<usize> {
+        let params = &def.type_params;
+        let (Some(numerator), Some(denominator)) = (params.numerator.as_ref(), params.denominator.as_ref())
+        else {
+            self.warn(
+                "metric",
+                &def.name,
+                "ratio metric needs a `numerator` and a `denominator`; the metric was not imported",
+            );
+            return None;
+        };
+        let model = self.same_model(def, &[numerator, denominator])?;
+        self.models[model].metrics.push(Metric {
+            numerator: Some(numerator.name.clone()),
+            denominator: Some(denominator.name.clone()),
+            ..metric(def, MetricType::Ratio)
+        });
+        Some(model)
+    }
+
+    fn convert_derived(&mut self, def: &'a MetricDef) -> Option<usize> {
+        let params = &def.type_params;
+        let Some(expr) = params.expr.as_deref() else {
+            self.warn("metric", &def.name, "derived metric has no `expr`; the metric was not imported");
+            return None;
+        };
+        // Inputs are referred to in `expr` by alias when they have one
+        let input = |reference: &str| {
+            params
+                .metrics
+                .iter()
+                .find(|i| i.alias.as_deref().unwrap_or(&i.name) == reference)
+        };
+        let operands = binary_expression(expr).and_then(|(left, op, right)| Some((input(left)?, op, input(right)?)));
+        let Some((left, op, right)) = operands else {
+            self.warn(
+                "metric",
+                &def.name,
+                format!(
+                    "only `a - b` and `a / b` derived metrics are supported, not `{}`; the metric was not imported",
+                    expr
+                ),
+            );
+            return None;
+        };
+
+        let model = self.same_model(def, &[left, right])?;
+        let (left, right) = (Some(left.name.clone()), Some(right.name.clone()));
+        self.models[model].metrics.push(match op {
+            '-' => Metric {
+                minuend: left,
+                subtrahend: right,
+                ..metric(def, MetricType::Difference)
+            },
+            _ => Metric {
+                numerator: left,
+                denominator: right,
+                ..metric(def, MetricType::Ratio)
+            },
+        });
+        Some(model)
+    }
+
+    fn convert_cumulative(&mut self, def: &'a MetricDef) -> Option<usize> {
+        let params = &def.type_params;
+        let nested = params.cumulative_type_params.as_ref();
+        let window = params
+            .window
+            .as_deref()
+            .or_else(|| nested.and_then(|p| p.window.as_deref()));
+        let grain_to_date = params
+            .grain_to_date
+            .as_deref()
+            .or_else(|| nested.and_then(|p| p.grain_to_date.as_deref()));
+        if nested.and_then(|p| p.period_agg.as_deref()).is_some() {
+            self.warn(
+                "metric",
+                &def.name,
+                "`period_agg` is not supported; totals are computed at the queried grain",
+            );
+        }
+
+        let measure = self.measure_input(def, params.measure.as_ref())?;
+        if !matches!(measure.def.agg.to_lowercase().as_str(), "sum" | "count" | "sum_boolean") {
+            self.warn(
+                "metric",
+                &def.name,
+                format!(
+                    "cumulative totals need a `sum` or `count` measure, but `{}` uses `{}`; the metric was not imported",
+                    measure.def.name, measure.def.agg
+                ),
+            );
+            return None;
+        }
+        let Some(time_dimension) = measure.time_dimension else {
+            self.warn(
+                "metric",
+                &def.name,
+                format!(
+                    "measure `{}` has no `agg_time_dimension`; the metric was not imported",
+                    measure.def.name
+                ),
+            );
+            return None;
+        };
+
+        let window = match window {
+            Some(window) => {
+                let Some((count, grain)) = parse_window(window) else {
+                    self.warn(
+                        "metric",
+                        &def.name,
+                        format!("window `{}` is not supported; the metric was not imported", window),
+                    );
+                    return None;
+                };
+                self.warn(
+                    "metric",
+                    &def.name,
+                    format!(
+                        "window `{}` is imported as the last {} periods; query `{}__{}` for the same result",
+                        window,
+                        count,
+                        time_dimension,
+                        grain.as_str()
+                    ),
+                );
+                Some(count)
+            }
+            None => None,
+        };
+        let grain_to_date = match grain_to_date {
+            Some(grain) => match TimeGrain::parse(grain) {
+                Some(grain) => Some(grain),
+                None => {
+                    self.warn(
+                        "metric",
+                        &def.name,
+                        format!("`grain_to_date: {}` is not supported; the metric was not imported", grain),
+                    );
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        self.models[measure.model].metrics.push(Metric {
+            measure: Some(measure.def.name.clone()),
+            time_dimension: Some(time_dimension.to_string()),
+            window,
+            grain_to_date,
+            ..metric(def, MetricType::Cumulative)
+        });
+        Some(measure.model)
+    }
+}
+
+fn metric(def: &MetricDef, type_: MetricType) -> Metric {
+    Metric {
+        name: def.name.clone(),
+        expr: String::new(),
+        description: def.description.clone().or_else(|| def.label.clone()),
+        args: Vec::new(),
+        type_,
+        numerator: None,
+        denominator: None,
+        minuend: None,
+        subtrahend: None,
+        measure: None,
+        time_dimension: None,
+        window: None,
+        grain_to_date: None,
+    }
+}
+
+/// The model name in `ref('orders')`, `ref("orders")` or `ref('pkg', 'orders')`.
+fn ref_target(model: &str) -> Option<String> {
+    let inner = model
+        .trim()
+        .trim_start_matches("{{")
+        .trim_end_matches("}}")
+        .trim()
+        .strip_prefix("ref(")?
+        .strip_suffix(')')?;
+    let name = inner.rsplit(',').next()?.trim().trim_matches(|c| c == '\'' || c == '"');
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+/// Splits `a - b` or `a / b` into its operands.
+fn binary_expression(expr: &str) -> Option<(&str, char, &str)> {
+    let op_index = expr.find(['-', '/'])?;
+    let (left, right) = (expr[..op_index].trim(), expr[op_index + 1..].trim());
+    let op = expr[op_index..].chars().next()?;
+    (is_identifier(left) && is_identifier(right)).then_some((left, op, right))
+}
+
+/// Parses windows such as `7 days` or `1 month`.
+fn parse_window(window: &str) -> Option<(u32, TimeGrain)> {
+    let mut parts = window.split_whitespace();
+    let count = parts.next()?.parse().ok()?;
+    let unit = parts.next()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    let grain = TimeGrain::parse(unit.strip_suffix('s').unwrap_or(unit))?;
+    Some((count, grain))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::{validate_models, SourcedModel};
+
+    const PROJECT: &str = r#"
+semantic_models:
+  - name: orders
+    description: One row per order
+    model: ref('fct_orders')
+    defaults:
+      agg_time_dimension: ordered_at
+    entities:
+      - name: order_id
+        type: primary
+      - name: customer
+        type: foreign
+        expr: customer_id
+    dimensions:
+      - name: ordered_at
+        type: time
+        type_params:
+          time_granularity: day
+      - name: status
+        type: categorical
+    measures:
+      - name: order_total
+        agg: sum
+        expr: amount
+      - name: order_count
+        agg: sum
+        expr: 1
+      - name: median_order
+        agg: median
+        expr: amount
+      - name: is_returned
+        agg: sum_boolean
+  - name: customers
+    model: "{{ ref('dim_customers') }}"
+    entities:
+      - name: customer
+        type: primary
+        expr: id
+    dimensions:
+      - name: region
+        type: categorical
+
+metrics:
+  - name: revenue
+    label: Revenue
+    type: simple
+    type_params:
+      measure: order_total
+  - name: order_count
+    type: simple
+    type_params:
+      measure: order_count
+  - name: aov
+    type: ratio
+    type_params:
+      numerator: revenue
+      denominator: order_count
+  - name: revenue_less_orders
+    type: derived
+    type_params:
+      expr: revenue - orders
+      metrics:
+        - revenue
+        - name: order_count
+          alias: orders
+  - name: revenue_with_tax
+    type: derived
+    type_params:
+      expr: revenue * 1.2 + order_count
+      metrics:
+        - revenue
+        - order_count
+  - name: revenue_7d
+    type: cumulative
+    type_params:
+      measure: order_total
+      window: 7 days
+  - name: revenue_mtd
+    type: cumulative
+    type_params:
+      measure:
+        name: order_total
+      grain_to_date: month
+"#;
+
+    fn import(yaml: &str) -> Import {
+        import_metricflow(&[MetricFlowFile::from_yaml(yaml).unwrap()])
+    }
+
+    fn find_metric<'a>(model: &'a Model, name: &str) -> &'a Metric {
+        model.metrics.iter().find(|m| m.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_import_semantic_models() {
+        let import = import(PROJECT);
+        let orders = &import.models[0];
+        let customers = &import.models[1];
+
+        assert_eq!(orders.name, "fct_orders");
+        assert_eq!(customers.name, "dim_customers");
+
+        let ordered_at = orders.dimensions.iter().find(|d| d.name == "ordered_at").unwrap();
+        assert_eq!(ordered_at.time.as_ref().unwrap().grains, TimeGrain::calendar_grains());
+        assert!(orders.dimensions.iter().any(|d| d.name == "order_id"));
+        assert!(orders.dimensions.iter().any(|d| d.name == "customer_id"));
+        assert!(customers.dimensions.iter().any(|d| d.name == "id"));
+
+        let order_total = orders.measures.iter().find(|m| m.name == "order_total").unwrap();
+        assert_eq!(order_total.expr.as_deref(), Some("amount"));
+        assert_eq!(order_total.agg, Some(Aggregation::Sum));
+        let is_returned = orders.measures.iter().find(|m| m.name == "is_returned").unwrap();
+        assert_eq!(
+            is_returned.expr.as_deref(),
+            Some("CASE WHEN is_returned THEN 1 ELSE 0 END")
+        );
+
+        assert_eq!(orders.relationships.len(), 1);
+        let join = &orders.relationships[0];
+        assert_eq!(join.name, "dim_customers");
+        assert_eq!(join.primary_key, "customer_id");
+        assert_eq!(join.foreign_key, "id");
+        assert_eq!(join.cardinality.as_deref(), Some("many-to-one"));
+    }
+
+    #[test]
+    fn test_import_metrics() {
+        let import = import(PROJECT);
+        let orders = &import.models[0];
+
+        let revenue = orders.measures.iter().find(|m| m.name == "revenue").unwrap();
+        assert_eq!(revenue.expr.as_deref(), Some("amount"));
+        assert_eq!(revenue.agg, Some(Aggregation::Sum));
+        assert_eq!(revenue.description.as_deref(), Some("Revenue"));
+        // Shares its measure's name, so the measure is used directly
+        assert_eq!(orders.measures.iter().filter(|m| m.name == "order_count").count(), 1);
+        assert!(!orders.metrics.iter().any(|m| m.name == "order_count"));
+
+        let aov = find_metric(orders, "aov");
+        assert_eq!(aov.type_, MetricType::Ratio);
+        assert_eq!(aov.numerator.as_deref(), Some("revenue"));
+        assert_eq!(aov.denominator.as_deref(), Some("order_count"));
+
+        let difference = find_metric(orders, "revenue_less_orders");
+        assert_eq!(difference.type_, MetricType::Difference);
+        assert_eq!(difference.minuend.as_deref(), Some("revenue"));
+        assert_eq!(difference.subtrahend.as_deref(), Some("order_count"));
+
+        let rolling = find_metric(orders, "revenue_7d");
+        assert_eq!(rolling.type_, MetricType::Cumulative);
+        assert_eq!(rolling.measure.as_deref(), Some("order_total"));
+        assert_eq!(rolling.time_dimension.as_deref(), Some("ordered_at"));
+        assert_eq!(rolling.window, Some(7));
+        assert_eq!(find_metric(orders, "revenue_mtd").grain_to_date, Some(TimeGrain::Month));
+
+        assert!(!orders.metrics.iter().any(|m| m.name == "revenue_with_tax"));
+        let messages: Vec<String> = import.warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "metric `revenue_with_tax`: only `a - b` and `a / b` derived metrics are supported, not `revenue * 1.2 + order_count`; the metric was not imported",
+                "metric `revenue_7d`: window `7 days` is imported as the last 7 periods; query `ordered_at__day` for the same result",
+            ]
+        );
+
+        let sourced: Vec<SourcedModel> = import
+            .models
+            .iter()
+            .map(|model| SourcedModel { file: None, model })
+            .collect();
+        assert_eq!(validate_models(&sourced), Vec::new());
+    }
+
+    #[test]
+    fn test_import_reports_unsupported_constructs() {
+        let import = import(
+            r#"
+semantic_models:
+  - name: orders
+    model: ref('orders')
+    entities:
+      - name: customer
+        type: foreign
+        expr: customer_id
+      - name: composite
+        type: unique
+        expr: "id || '-' || line"
+    dimensions:
+      - name: is_large
+        type: categorical
+        expr: amount > 100
+    measures:
+      - name: amount_p90
+        agg: percentile
+        expr: amount
+        agg_params:
+          percentile: 0.9
+          use_discrete_percentile: true
+      - name: balance
+        agg: max
+        non_additive_dimension:
+          name: ordered_at
+metrics:
+  - name: large_orders
+    type: simple
+    type_params:
+      measure: amount_p90
+    filter: "{{ Dimension('order__is_large') }}"
+  - name: p90
+    type: simple
+    type_params:
+      measure: amount_p90
+  - name: growth
+    type: derived
+    type_params:
+      expr: p90 - p90_last_month
+      metrics:
+        - p90
+        - name: p90
+          alias: p90_last_month
+          offset_window: 1 month
+  - name: signup_conversion
+    type: conversion
+  - name: legacy
+    calculation_method: sum
+"#,
+        );
+        let messages: Vec<String> = import.warnings.iter().map(|w| w.to_string()).collect();
+        let has = |needle: &str| messages.iter().any(|m| m.contains(needle));
+
+        assert!(has("dimension `orders.is_large`: `expr: amount > 100` is not supported"));
+        assert!(has("entity `orders.customer`: no semantic model declares this entity"));
+        assert!(has("entity `orders.composite`: `expr: id || '-' || line` is not a column"));
+        assert!(has("measure `orders.amount_p90`: discrete and approximate percentiles"));
+        assert!(has("measure `orders.balance`: `non_additive_dimension` is not supported"));
+        assert!(has("metric `large_orders`: metric filters are not supported"));
+        assert!(has("metric `growth`: offset on input `p90`"));
+        assert!(has("metric `signup_conversion`: conversion metrics are not supported"));
+        assert!(has("metric `legacy`: metric has no `type`"));
+
+        assert!(import.models[0].metrics.is_empty());
+        assert!(import.models[0].relationships.is_empty());
+        // `p90` repeats the percentile measure under its own name
+        let p90 = import.models[0].measures.iter().find(|m| m.name == "p90").unwrap();
+        assert_eq!(p90.percentile, Some(0.9));
+    }
+
+    #[test]
+    fn test_import_across_files() {
+        let models = MetricFlowFile::from_yaml(
+            r#"
+version: 2
+models:
+  - name: orders
+    columns:
+      - name: amount
+semantic_models:
+  - name: orders
+    model: ref('orders')
+    measures:
+      - name: order_total
+        agg: sum
+        expr: amount
+"#,
+        )
+        .unwrap();
+        let metrics = MetricFlowFile::from_yaml(
+            r#"
+metrics:
+  - name: revenue
+    type: simple
+    type_params:
+      measure: order_total
+"#,
+        )
+        .unwrap();
+        let empty = MetricFlowFile::from_yaml("").unwrap();
+        assert!(empty.is_empty());
+
+        let import = import_metricflow(&[models, metrics, empty]);
+        assert!(import.warnings.is_empty(), "{:?}", import.warnings);
+        assert!(import.models[0].measures.iter().any(|m| m.name == "revenue"));
+    }
+
+    #[test]
+    fn test_ref_target() {
+        assert_eq!(ref_target("ref('orders')").as_deref(), Some("orders"));
+        assert_eq!(ref_target("{{ ref(\"orders\") }}").as_deref(), Some("orders"));
+        assert_eq!(ref_target("ref('jaffle', 'orders')").as_deref(), Some("orders"));
+        assert_eq!(ref_target("orders"), None);
+    }
+}
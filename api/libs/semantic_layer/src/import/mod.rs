@@ -0,0 +1,52 @@
+//! Converters from other semantic layer formats into [`Model`]s.
+//!
+//! Importers never fail on constructs they can't represent. They convert
+//! what they can and describe everything else as an [`ImportWarning`], so
+//! callers can show users exactly what needs attention by hand.
+
+pub mod lookml;
+pub mod metricflow;
+
+use std::fmt;
+
+use crate::models::Model;
+
+/// The models produced by an import, plus everything that couldn't be
+/// carried over exactly.
+#[derive(Debug, Default)]
+pub struct Import {
+    pub models: Vec<Model>,
+    pub warnings: Vec<ImportWarning>,
+}
+
+/// A construct that was skipped or only approximated during an import.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportWarning {
+    /// What kind of definition this is about, e.g. `metric` or `dimension`
+    pub construct: &'static str,
+    /// Name of the definition, qualified by its model where it has one
+    pub name: String,
+    pub message: String,
+}
+
+impl ImportWarning {
+    fn new(construct: &'static str, name: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            construct,
+            name: name.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ImportWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} `{}`: {}", self.construct, self.name, self.message)
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
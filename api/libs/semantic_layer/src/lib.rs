@@ -1,2 +1,4 @@
+pub mod compile;
+pub mod import;
 pub mod models;
-// Placeholder for semantic_layer library code
+pub mod validate;
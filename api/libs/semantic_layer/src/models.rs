@@ -1,11 +1,27 @@
+use schemars::{schema::RootSchema, schema_for, JsonSchema};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct SemanticLayerSpec {
     pub models: Vec<Model>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+/// A model file: either a `models:` list or a single model.
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
+#[serde(untagged)]
+pub enum ModelFile {
+    Spec(SemanticLayerSpec),
+    Model(Box<Model>),
+}
+
+impl ModelFile {
+    /// JSON Schema for model files, for editors to validate and autocomplete against.
+    pub fn json_schema() -> RootSchema {
+        schema_for!(ModelFile)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Model {
     pub name: String,
     pub description: Option<String>,
@@ -27,7 +43,7 @@ pub struct Model {
     pub relationships: Vec<Relationship>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Dimension {
     pub name: String,
     pub description: Option<String>,
@@ -36,26 +52,192 @@ pub struct Dimension {
     #[serde(default)] // Default to false if 'searchable' is missing
     pub searchable: bool,
     pub options: Option<Vec<String>>, // Default to None if 'options' is missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time: Option<TimeDimension>, // Present on date and timestamp dimensions
+}
+
+impl Dimension {
+    pub fn is_time(&self) -> bool {
+        self.time.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, PartialEq)]
+pub struct TimeDimension {
+    #[serde(default = "TimeGrain::calendar_grains")]
+    pub grains: Vec<TimeGrain>, // Grains the dimension may be bucketed by
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiscal_year_start_month: Option<u32>, // 1-12; enables fiscal grains
+}
+
+impl TimeDimension {
+    /// The month the fiscal year starts in, January unless configured
+    pub fn fiscal_start(&self) -> u32 {
+        self.fiscal_year_start_month.unwrap_or(1)
+    }
+
+    pub fn supports(&self, grain: TimeGrain) -> bool {
+        match grain {
+            TimeGrain::FiscalQuarter | TimeGrain::FiscalYear => {
+                self.fiscal_year_start_month.is_some()
+            }
+            _ => self.grains.contains(&grain),
+        }
+    }
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Measure {
     pub name: String,
     pub description: Option<String>,
     #[serde(rename = "type")]
     pub type_: Option<String>, // 'type' is optional according to spec
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expr: Option<String>, // Expression to aggregate; defaults to the column named `name`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agg: Option<Aggregation>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percentile: Option<f64>, // Required when `agg` is `percentile`, between 0 and 1
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Aggregation {
+    Sum,
+    Count,
+    CountDistinct,
+    Avg,
+    Min,
+    Max,
+    Median,
+    Percentile,
+}
+
+impl Aggregation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Aggregation::Sum => "sum",
+            Aggregation::Count => "count",
+            Aggregation::CountDistinct => "count_distinct",
+            Aggregation::Avg => "avg",
+            Aggregation::Min => "min",
+            Aggregation::Max => "max",
+            Aggregation::Median => "median",
+            Aggregation::Percentile => "percentile",
+        }
+    }
+
+    /// Whether partial results can be summed, which running totals rely on
+    pub fn is_additive(&self) -> bool {
+        matches!(self, Aggregation::Sum | Aggregation::Count)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricType {
+    /// A raw SQL expression in `expr`
+    #[default]
+    Expression,
+    /// `numerator / denominator`
+    Ratio,
+    /// `minuend - subtrahend`
+    Difference,
+    /// A running total of `measure` ordered by `time_dimension`
+    Cumulative,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+impl MetricType {
+    pub fn is_expression(&self) -> bool {
+        *self == MetricType::Expression
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeGrain {
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+    /// A quarter of a fiscal year; requires `fiscal_year_start_month`
+    FiscalQuarter,
+    /// A fiscal year; requires `fiscal_year_start_month`
+    FiscalYear,
+}
+
+impl TimeGrain {
+    /// Parses a grain name, including the chart `xAxisTimeInterval` values.
+    pub fn parse(grain: &str) -> Option<Self> {
+        match grain.to_lowercase().as_str() {
+            "day" => Some(TimeGrain::Day),
+            "week" => Some(TimeGrain::Week),
+            "month" => Some(TimeGrain::Month),
+            "quarter" => Some(TimeGrain::Quarter),
+            "year" => Some(TimeGrain::Year),
+            "fiscal_quarter" | "fiscalquarter" => Some(TimeGrain::FiscalQuarter),
+            "fiscal_year" | "fiscalyear" => Some(TimeGrain::FiscalYear),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeGrain::Day => "day",
+            TimeGrain::Week => "week",
+            TimeGrain::Month => "month",
+            TimeGrain::Quarter => "quarter",
+            TimeGrain::Year => "year",
+            TimeGrain::FiscalQuarter => "fiscal_quarter",
+            TimeGrain::FiscalYear => "fiscal_year",
+        }
+    }
+
+    /// Day through year, the grains a time dimension supports by default
+    pub fn calendar_grains() -> Vec<TimeGrain> {
+        vec![
+            TimeGrain::Day,
+            TimeGrain::Week,
+            TimeGrain::Month,
+            TimeGrain::Quarter,
+            TimeGrain::Year,
+        ]
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Metric {
     pub name: String,
-    pub expr: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub expr: String, // Required for `expression` metrics
     pub description: Option<String>,
     #[serde(default)] // Changed from Option<Vec<Argument>>
     pub args: Vec<Argument>,
+    #[serde(rename = "type", default, skip_serializing_if = "MetricType::is_expression")]
+    pub type_: MetricType,
+    // Measure or metric names used by `ratio` metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numerator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub denominator: Option<String>,
+    // Measure or metric names used by `difference` metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minuend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subtrahend: Option<String>,
+    // Settings for `cumulative` metrics
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub measure: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_dimension: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<u32>, // Number of periods to sum over; all time if omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grain_to_date: Option<TimeGrain>, // Restart the total at the start of each period
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Filter {
     pub name: String,
     pub expr: String,
@@ -64,7 +246,7 @@ pub struct Filter {
     pub args: Vec<Argument>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Argument {
     pub name: String,
     #[serde(rename = "type")]
@@ -72,7 +254,7 @@ pub struct Argument {
     pub description: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, JsonSchema, PartialEq)]
 pub struct Relationship {
     pub name: String,
     pub primary_key: String,
@@ -238,4 +420,66 @@ models:
         assert_eq!(logins_model.metrics.len(), 0); // Default empty vec
         assert_eq!(logins_model.relationships.len(), 1);
     }
+
+    #[test]
+    fn test_deserialize_typed_measures_and_metrics() {
+        let yaml_content = r#"
+name: orders
+measures:
+  - name: revenue
+    expr: amount
+    agg: sum
+  - name: p90_amount
+    expr: amount
+    agg: percentile
+    percentile: 0.9
+metrics:
+  - name: large_orders
+    expr: COUNT(CASE WHEN amount > 100 THEN 1 END)
+  - name: month_to_date_revenue
+    type: cumulative
+    measure: revenue
+    time_dimension: order_date
+    grain_to_date: month
+"#;
+
+        let model: Model = serde_yaml::from_str(yaml_content).unwrap();
+        assert_eq!(model.measures[0].agg, Some(Aggregation::Sum));
+        assert_eq!(model.measures[0].expr.as_deref(), Some("amount"));
+        assert_eq!(model.measures[1].agg, Some(Aggregation::Percentile));
+        assert_eq!(model.measures[1].percentile, Some(0.9));
+        assert_eq!(model.metrics[0].type_, MetricType::Expression);
+        assert_eq!(model.metrics[1].type_, MetricType::Cumulative);
+        assert_eq!(model.metrics[1].grain_to_date, Some(TimeGrain::Month));
+
+        // Round-trips without emitting unset fields
+        let yaml = serde_yaml::to_string(&model.metrics[0]).unwrap();
+        assert!(!yaml.contains("type"));
+        assert!(!yaml.contains("numerator"));
+        let unknown_agg = serde_yaml::from_str::<Measure>("name: x\nagg: total");
+        assert!(unknown_agg.is_err());
+    }
+
+    #[test]
+    fn test_model_file_json_schema() {
+        let schema = serde_yaml::to_value(ModelFile::json_schema()).unwrap();
+        let variants = schema["anyOf"].as_sequence().unwrap();
+        assert_eq!(variants.len(), 2);
+
+        let model = &schema["definitions"]["Model"];
+        let required: Vec<&str> = model["required"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(required, ["name"]);
+        assert!(model["properties"]["entities"].is_mapping());
+        assert!(model["properties"].get("relationships").is_none());
+
+        let aggregations = schema["definitions"]["Aggregation"]["enum"].as_sequence().unwrap();
+        assert!(aggregations.contains(&serde_yaml::Value::from("count_distinct")));
+        let time = &schema["definitions"]["TimeDimension"]["properties"]["grains"];
+        assert_eq!(time["default"].as_sequence().unwrap().len(), 5);
+    }
 }
@@ -0,0 +1,1223 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::ops::ControlFlow;
+
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, FunctionArguments, Query, Visit, Visitor,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+use crate::models::{
+    Aggregation, Argument, Metric, MetricType, Model, SemanticLayerSpec, TimeGrain,
+};
+
+/// Argument types that filters and metrics may declare
+pub const SUPPORTED_ARGUMENT_TYPES: &[&str] = &[
+    "string", "integer", "number", "float", "boolean", "date", "timestamp",
+];
+
+/// Join types an entity may request
+pub const SUPPORTED_JOIN_TYPES: &[&str] = &["LEFT", "INNER", "RIGHT", "FULL"];
+
+/// Cardinalities an entity may declare
+pub const SUPPORTED_CARDINALITIES: &[&str] =
+    &["one-to-one", "one-to-many", "many-to-one", "many-to-many"];
+
+/// Date and time parts that functions such as `DATEADD(day, ...)` or
+/// `DATE_TRUNC(week, ...)` take as bare words rather than fields.
+const DATE_PARTS: &[&str] = &[
+    "year", "years", "yy", "yyyy", "quarter", "quarters", "qq", "q", "month", "months", "mm",
+    "m", "week", "weeks", "wk", "ww", "isoweek", "day", "days", "dd", "d", "dayofweek", "dow",
+    "dayofyear", "doy", "weekday", "dw", "hour", "hours", "hh", "minute", "minutes", "mi", "n",
+    "second", "seconds", "ss", "s", "millisecond", "milliseconds", "ms", "microsecond",
+    "microseconds", "mcs", "nanosecond", "nanoseconds", "ns", "epoch", "isoyear",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// A name is empty
+    MissingName,
+    /// Two models, or two fields of the same kind in a model, share a name
+    DuplicateName,
+    /// An entity points at a model that is not defined
+    UnknownModel,
+    /// An entity key or expression references a field the model does not define
+    UnknownField,
+    /// An argument, join type or cardinality is not one of the supported values
+    UnsupportedValue,
+    /// An expression uses a `{placeholder}` without declaring it in `args`
+    UndeclaredArgument,
+    /// A field required by a measure's aggregation or a metric's type is missing
+    MissingField,
+    /// Derived metrics that are defined in terms of each other
+    CircularReference,
+}
+
+/// A problem found while validating a semantic layer, with enough context to
+/// locate it: the file it came from (when known), the model, and the path of
+/// the offending field within the model (e.g. `metrics.total_revenue.expr`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    pub kind: ValidationErrorKind,
+    pub file: Option<String>,
+    pub model: String,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file)?;
+        }
+        write!(f, "model `{}`", self.model)?;
+        if let Some(field) = &self.field {
+            write!(f, ", field `{}`", field)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+/// A model together with the file it was read from
+#[derive(Debug, Clone, Copy)]
+pub struct SourcedModel<'a> {
+    pub file: Option<&'a str>,
+    pub model: &'a Model,
+}
+
+/// Validates every model in a spec read from a single file.
+///
+/// See [`validate_models`] for the checks performed.
+pub fn validate_spec(spec: &SemanticLayerSpec, file: Option<&str>) -> Vec<ValidationError> {
+    let models: Vec<SourcedModel> = spec
+        .models
+        .iter()
+        .map(|model| SourcedModel { file, model })
+        .collect();
+    validate_models(&models)
+}
+
+/// Validates a set of models as a single graph.
+///
+/// Each model is checked on its own for empty and duplicate names, argument
+/// types, join types and cardinalities. Entities are then resolved across
+/// the whole set: the related model must exist, `primary_key` must be a
+/// field of the current model and `foreign_key` a field of the related one.
+/// Metric and filter expressions may only reference the model's own fields
+/// and metrics, their declared arguments, and `model.field` columns of the
+/// model itself or of models it has entities for.
+///
+/// All errors are returned rather than stopping at the first one.
+pub fn validate_models(models: &[SourcedModel]) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+
+    let mut models_by_name: HashMap<&str, &SourcedModel> = HashMap::new();
+    for sourced in models {
+        let name = sourced.model.name.as_str();
+        if name.trim().is_empty() {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingName,
+                Some("name".to_string()),
+                "Model name is empty.".to_string(),
+            ));
+            continue;
+        }
+        if let Some(existing) = models_by_name.get(name) {
+            let location = existing
+                .file
+                .map(|file| format!(" (first defined in {})", file))
+                .unwrap_or_default();
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::DuplicateName,
+                Some("name".to_string()),
+                format!("Model `{}` is defined more than once{}.", name, location),
+            ));
+            continue;
+        }
+        models_by_name.insert(name, sourced);
+    }
+
+    for sourced in models {
+        if sourced.model.name.trim().is_empty() {
+            continue;
+        }
+        validate_model(sourced, &models_by_name, &mut errors);
+    }
+
+    errors
+}
+
+fn validate_model(
+    sourced: &SourcedModel,
+    models_by_name: &HashMap<&str, &SourcedModel>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let model = sourced.model;
+
+    check_names(sourced, "dimensions", model.dimensions.iter().map(|d| &d.name), errors);
+    check_names(sourced, "measures", model.measures.iter().map(|m| &m.name), errors);
+    check_names(sourced, "metrics", model.metrics.iter().map(|m| &m.name), errors);
+    check_names(sourced, "filters", model.filters.iter().map(|f| &f.name), errors);
+    check_names(sourced, "entities", model.relationships.iter().map(|r| &r.name), errors);
+
+    for measure in &model.measures {
+        if model.dimensions.iter().any(|d| d.name == measure.name) {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::DuplicateName,
+                Some(format!("measures.{}", measure.name)),
+                format!(
+                    "`{}` is defined as both a dimension and a measure.",
+                    measure.name
+                ),
+            ));
+        }
+    }
+
+    for entity in &model.relationships {
+        let field = format!("entities.{}", entity.name);
+
+        if let Some(type_) = &entity.type_ {
+            if !SUPPORTED_JOIN_TYPES.contains(&type_.to_uppercase().as_str()) {
+                errors.push(error(
+                    sourced,
+                    ValidationErrorKind::UnsupportedValue,
+                    Some(format!("{}.type", field)),
+                    format!(
+                        "Unsupported join type `{}`. Expected one of: {}.",
+                        type_,
+                        SUPPORTED_JOIN_TYPES.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        if let Some(cardinality) = &entity.cardinality {
+            if !SUPPORTED_CARDINALITIES.contains(&cardinality.to_lowercase().as_str()) {
+                errors.push(error(
+                    sourced,
+                    ValidationErrorKind::UnsupportedValue,
+                    Some(format!("{}.cardinality", field)),
+                    format!(
+                        "Unsupported cardinality `{}`. Expected one of: {}.",
+                        cardinality,
+                        SUPPORTED_CARDINALITIES.join(", ")
+                    ),
+                ));
+            }
+        }
+
+        if !has_field(model, &entity.primary_key) {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::UnknownField,
+                Some(format!("{}.primary_key", field)),
+                format!(
+                    "Primary key `{}` is not a dimension or measure of model `{}`.",
+                    entity.primary_key, model.name
+                ),
+            ));
+        }
+
+        match models_by_name.get(entity.name.as_str()) {
+            Some(related) => {
+                if !has_field(related.model, &entity.foreign_key) {
+                    errors.push(error(
+                        sourced,
+                        ValidationErrorKind::UnknownField,
+                        Some(format!("{}.foreign_key", field)),
+                        format!(
+                            "Foreign key `{}` is not a dimension or measure of model `{}`.",
+                            entity.foreign_key, entity.name
+                        ),
+                    ));
+                }
+            }
+            None => errors.push(error(
+                sourced,
+                ValidationErrorKind::UnknownModel,
+                Some(field),
+                format!("Entity references model `{}`, which is not defined.", entity.name),
+            )),
+        }
+    }
+
+    for dimension in &model.dimensions {
+        let Some(time) = &dimension.time else {
+            continue;
+        };
+        let field = format!("dimensions.{}.time", dimension.name);
+        if let Some(month) = time.fiscal_year_start_month {
+            if !(1..=12).contains(&month) {
+                errors.push(error(
+                    sourced,
+                    ValidationErrorKind::UnsupportedValue,
+                    Some(format!("{}.fiscal_year_start_month", field)),
+                    format!("`fiscal_year_start_month` must be between 1 and 12, got {}.", month),
+                ));
+            }
+        }
+        if time.grains.is_empty() {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingField,
+                Some(format!("{}.grains", field)),
+                "A time dimension needs at least one grain.".to_string(),
+            ));
+        }
+        let lists_fiscal_grain = time
+            .grains
+            .iter()
+            .any(|g| matches!(g, TimeGrain::FiscalQuarter | TimeGrain::FiscalYear));
+        if lists_fiscal_grain && time.fiscal_year_start_month.is_none() {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingField,
+                Some(format!("{}.fiscal_year_start_month", field)),
+                "Fiscal grains require `fiscal_year_start_month`.".to_string(),
+            ));
+        }
+    }
+
+    for measure in &model.measures {
+        let field = format!("measures.{}.percentile", measure.name);
+        match (measure.agg, measure.percentile) {
+            (Some(Aggregation::Percentile), None) => errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingField,
+                Some(field),
+                "`percentile` is required when `agg` is `percentile`.".to_string(),
+            )),
+            (Some(Aggregation::Percentile), Some(p)) if p <= 0.0 || p >= 1.0 => errors.push(error(
+                sourced,
+                ValidationErrorKind::UnsupportedValue,
+                Some(field),
+                format!("`percentile` must be between 0 and 1, got {}.", p),
+            )),
+            (agg, Some(_)) if agg != Some(Aggregation::Percentile) => errors.push(error(
+                sourced,
+                ValidationErrorKind::UnsupportedValue,
+                Some(field),
+                "`percentile` only applies when `agg` is `percentile`.".to_string(),
+            )),
+            _ => {}
+        }
+    }
+
+    for metric in &model.metrics {
+        let field = format!("metrics.{}", metric.name);
+        check_args(sourced, &field, &metric.args, errors);
+        match metric.type_ {
+            MetricType::Expression if metric.expr.trim().is_empty() => errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingField,
+                Some(format!("{}.expr", field)),
+                "`expr` is required for expression metrics.".to_string(),
+            )),
+            MetricType::Expression => check_expression(
+                sourced,
+                &field,
+                &metric.expr,
+                &metric.args,
+                models_by_name,
+                errors,
+            ),
+            _ => check_derived_metric(sourced, &field, metric, errors),
+        }
+    }
+    check_metric_cycles(sourced, errors);
+
+    for filter in &model.filters {
+        let field = format!("filters.{}", filter.name);
+        check_args(sourced, &field, &filter.args, errors);
+        check_expression(sourced, &field, &filter.expr, &filter.args, models_by_name, errors);
+    }
+}
+
+/// Checks the fields a ratio, difference or cumulative metric is built from.
+fn check_derived_metric(
+    sourced: &SourcedModel,
+    field: &str,
+    metric: &Metric,
+    errors: &mut Vec<ValidationError>,
+) {
+    let model = sourced.model;
+    let operands: &[(&str, &Option<String>)] = match metric.type_ {
+        MetricType::Ratio => &[
+            ("numerator", &metric.numerator),
+            ("denominator", &metric.denominator),
+        ],
+        MetricType::Difference => &[
+            ("minuend", &metric.minuend),
+            ("subtrahend", &metric.subtrahend),
+        ],
+        MetricType::Cumulative => &[
+            ("measure", &metric.measure),
+            ("time_dimension", &metric.time_dimension),
+        ],
+        MetricType::Expression => &[],
+    };
+
+    for (name, value) in operands {
+        let operand_field = Some(format!("{}.{}", field, name));
+        let Some(value) = value else {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingField,
+                operand_field,
+                format!("`{}` is required for {} metrics.", name, metric_type_name(metric.type_)),
+            ));
+            continue;
+        };
+
+        let problem = match *name {
+            "measure" => match model.measures.iter().find(|m| &m.name == value) {
+                None => Some((
+                    ValidationErrorKind::UnknownField,
+                    format!("`{}` is not a measure of model `{}`.", value, model.name),
+                )),
+                Some(measure) if !measure.agg.is_some_and(|agg| agg.is_additive()) => Some((
+                    ValidationErrorKind::UnsupportedValue,
+                    format!(
+                        "Measure `{}` must use a `sum` or `count` aggregation to be accumulated.",
+                        value
+                    ),
+                )),
+                Some(_) => None,
+            },
+            "time_dimension" => match model.dimensions.iter().find(|d| &d.name == value) {
+                None => Some((
+                    ValidationErrorKind::UnknownField,
+                    format!("`{}` is not a dimension of model `{}`.", value, model.name),
+                )),
+                Some(dimension) if !dimension.is_time() => Some((
+                    ValidationErrorKind::UnsupportedValue,
+                    format!("`{}` is not a time dimension.", value),
+                )),
+                Some(dimension) => {
+                    let fiscal = matches!(
+                        metric.grain_to_date,
+                        Some(TimeGrain::FiscalQuarter | TimeGrain::FiscalYear)
+                    );
+                    let has_fiscal_calendar = dimension
+                        .time
+                        .as_ref()
+                        .is_some_and(|t| t.fiscal_year_start_month.is_some());
+                    (fiscal && !has_fiscal_calendar).then(|| {
+                        (
+                            ValidationErrorKind::MissingField,
+                            format!(
+                                "A fiscal `grain_to_date` requires `fiscal_year_start_month` on `{}`.",
+                                value
+                            ),
+                        )
+                    })
+                }
+            },
+            _ => (!model.measures.iter().any(|m| &m.name == value)
+                && !model.metrics.iter().any(|m| &m.name == value))
+            .then(|| {
+                (
+                    ValidationErrorKind::UnknownField,
+                    format!("`{}` is not a measure or metric of model `{}`.", value, model.name),
+                )
+            }),
+        };
+
+        if let Some((kind, message)) = problem {
+            errors.push(error(sourced, kind, operand_field, message));
+        }
+    }
+
+    if metric.window == Some(0) {
+        errors.push(error(
+            sourced,
+            ValidationErrorKind::UnsupportedValue,
+            Some(format!("{}.window", field)),
+            "`window` must be at least 1.".to_string(),
+        ));
+    }
+}
+
+fn metric_type_name(type_: MetricType) -> &'static str {
+    match type_ {
+        MetricType::Expression => "expression",
+        MetricType::Ratio => "ratio",
+        MetricType::Difference => "difference",
+        MetricType::Cumulative => "cumulative",
+    }
+}
+
+/// Reports each metric that can reach itself through ratio and difference
+/// operands.
+fn check_metric_cycles(sourced: &SourcedModel, errors: &mut Vec<ValidationError>) {
+    let metrics: HashMap<&str, &Metric> = sourced
+        .model
+        .metrics
+        .iter()
+        .map(|m| (m.name.as_str(), m))
+        .collect();
+    let operands = |metric: &Metric| -> Vec<String> {
+        [&metric.numerator, &metric.denominator, &metric.minuend, &metric.subtrahend]
+            .into_iter()
+            .flatten()
+            .filter(|name| metrics.contains_key(name.as_str()))
+            .cloned()
+            .collect()
+    };
+
+    for metric in &sourced.model.metrics {
+        let mut stack = vec![(metric.name.clone(), vec![metric.name.clone()])];
+        let mut visited = HashSet::new();
+        while let Some((name, path)) = stack.pop() {
+            for next in operands(metrics[name.as_str()]) {
+                if next == metric.name {
+                    let mut path = path.clone();
+                    path.push(next);
+                    errors.push(error(
+                        sourced,
+                        ValidationErrorKind::CircularReference,
+                        Some(format!("metrics.{}", metric.name)),
+                        format!("Metrics reference each other: {}.", path.join(" -> ")),
+                    ));
+                    stack.clear();
+                    break;
+                }
+                if visited.insert(next.clone()) {
+                    let mut path = path.clone();
+                    path.push(next.clone());
+                    stack.push((next, path));
+                }
+            }
+        }
+    }
+}
+
+fn check_names<'a>(
+    sourced: &SourcedModel,
+    section: &str,
+    names: impl Iterator<Item = &'a String>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut seen = HashSet::new();
+    for name in names {
+        if name.trim().is_empty() {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::MissingName,
+                Some(section.to_string()),
+                format!("An entry in `{}` has an empty name.", section),
+            ));
+        } else if !seen.insert(name.as_str()) {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::DuplicateName,
+                Some(format!("{}.{}", section, name)),
+                format!("`{}` is defined more than once in `{}`.", name, section),
+            ));
+        }
+    }
+}
+
+fn check_args(
+    sourced: &SourcedModel,
+    field: &str,
+    args: &[Argument],
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut seen = HashSet::new();
+    for arg in args {
+        if !seen.insert(arg.name.as_str()) {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::DuplicateName,
+                Some(format!("{}.args.{}", field, arg.name)),
+                format!("Argument `{}` is declared more than once.", arg.name),
+            ));
+        }
+        if !SUPPORTED_ARGUMENT_TYPES.contains(&arg.type_.to_lowercase().as_str()) {
+            errors.push(error(
+                sourced,
+                ValidationErrorKind::UnsupportedValue,
+                Some(format!("{}.args.{}.type", field, arg.name)),
+                format!(
+                    "Unsupported argument type `{}`. Expected one of: {}.",
+                    arg.type_,
+                    SUPPORTED_ARGUMENT_TYPES.join(", ")
+                ),
+            ));
+        }
+    }
+}
+
+fn check_expression(
+    sourced: &SourcedModel,
+    field: &str,
+    expr: &str,
+    args: &[Argument],
+    models_by_name: &HashMap<&str, &SourcedModel>,
+    errors: &mut Vec<ValidationError>,
+) {
+    let model = sourced.model;
+    let expr_field = Some(format!("{}.expr", field));
+
+    for reference in scan_expression(expr) {
+        match reference {
+            ExprReference::Placeholder(name) => {
+                if !args.iter().any(|arg| arg.name == name) {
+                    errors.push(error(
+                        sourced,
+                        ValidationErrorKind::UndeclaredArgument,
+                        expr_field.clone(),
+                        format!("Placeholder `{{{}}}` is not declared in `args`.", name),
+                    ));
+                }
+            }
+            ExprReference::Field(name) => {
+                let known = has_field(model, &name)
+                    || model.metrics.iter().any(|m| m.name == name)
+                    || args.iter().any(|arg| arg.name == name);
+                if !known {
+                    errors.push(error(
+                        sourced,
+                        ValidationErrorKind::UnknownField,
+                        expr_field.clone(),
+                        format!(
+                            "`{}` is not a dimension, measure or metric of model `{}`.",
+                            name, model.name
+                        ),
+                    ));
+                }
+            }
+            ExprReference::Qualified(model_name, name) => {
+                let related = model_name == model.name
+                    || model.relationships.iter().any(|r| r.name == model_name);
+                if !related {
+                    errors.push(error(
+                        sourced,
+                        ValidationErrorKind::UnknownModel,
+                        expr_field.clone(),
+                        format!(
+                            "`{}.{}` references model `{}`, which is not `{}` or one of its entities.",
+                            model_name, name, model_name, model.name
+                        ),
+                    ));
+                    continue;
+                }
+                // Unknown entity models are reported on the entity itself
+                if let Some(target) = models_by_name.get(model_name.as_str()) {
+                    if !has_field(target.model, &name)
+                        && !target.model.metrics.iter().any(|m| m.name == name)
+                    {
+                        errors.push(error(
+                            sourced,
+                            ValidationErrorKind::UnknownField,
+                            expr_field.clone(),
+                            format!(
+                                "`{}` is not a dimension, measure or metric of model `{}`.",
+                                name, model_name
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn has_field(model: &Model, name: &str) -> bool {
+    model.dimensions.iter().any(|d| d.name == name) || model.measures.iter().any(|m| m.name == name)
+}
+
+fn error(
+    sourced: &SourcedModel,
+    kind: ValidationErrorKind,
+    field: Option<String>,
+    message: String,
+) -> ValidationError {
+    ValidationError {
+        kind,
+        file: sourced.file.map(str::to_string),
+        model: sourced.model.name.clone(),
+        field,
+        message,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum ExprReference {
+    /// `{name}`
+    Placeholder(String),
+    /// A bare, unquoted column name
+    Field(String),
+    /// `model.field`
+    Qualified(String, String),
+}
+
+/// Extracts the names an expression refers to from its parsed form, so
+/// keywords, function names, literals, cast targets and the parts of
+/// `EXTRACT` or `INTERVAL` are never mistaken for fields. Date parts passed
+/// bare to a function and anything inside subqueries are skipped too. An
+/// expression that doesn't parse only has its placeholders checked; the
+/// warehouse reports its syntax errors when the model is queried.
+fn scan_expression(expr: &str) -> Vec<ExprReference> {
+    let (sql, placeholders) = replace_placeholders(expr);
+
+    let mut collector = ReferenceCollector::default();
+    let parsed = Parser::new(&GenericDialect)
+        .try_with_sql(&sql)
+        .and_then(|mut parser| parser.parse_expr());
+    if let Ok(parsed) = parsed {
+        let _ = parsed.visit(&mut collector);
+    }
+
+    let mut references = collector.references;
+    references.extend(placeholders.into_iter().map(ExprReference::Placeholder));
+    references
+}
+
+/// Swaps each `{name}` placeholder outside string literals for `NULL` so the
+/// expression parses, returning the placeholder names in order.
+fn replace_placeholders(expr: &str) -> (String, Vec<String>) {
+    let mut sql = String::with_capacity(expr.len());
+    let mut names = Vec::new();
+    let mut quote: Option<char> = None;
+    let mut chars = expr.chars();
+
+    while let Some(c) = chars.next() {
+        match quote {
+            // A doubled quote closes and reopens, which leaves it open
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '\'' || c == '"' => quote = Some(c),
+            None if c == '{' => {
+                let name: String = chars.by_ref().take_while(|n| *n != '}').collect();
+                names.push(name.trim().to_string());
+                sql.push_str("NULL");
+                continue;
+            }
+            None => {}
+        }
+        sql.push(c);
+    }
+
+    (sql, names)
+}
+
+#[derive(Default)]
+struct ReferenceCollector {
+    references: Vec<ExprReference>,
+    query_depth: usize,
+    /// Function arguments that name a date part rather than a field
+    date_parts: Vec<*const Expr>,
+}
+
+impl Visitor for ReferenceCollector {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.query_depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.query_depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if self.query_depth > 0 {
+            return ControlFlow::Continue(());
+        }
+
+        match expr {
+            Expr::Function(function) => {
+                if let FunctionArguments::List(list) = &function.args {
+                    for arg in &list.args {
+                        if let FunctionArg::Unnamed(FunctionArgExpr::Expr(
+                            arg @ Expr::Identifier(ident),
+                        )) = arg
+                        {
+                            if ident.quote_style.is_none()
+                                && DATE_PARTS.contains(&ident.value.to_lowercase().as_str())
+                            {
+                                self.date_parts.push(arg as *const Expr);
+                            }
+                        }
+                    }
+                }
+            }
+            // Quoted words may be string literals, depending on the dialect
+            Expr::Identifier(ident)
+                if ident.quote_style.is_none()
+                    && !self.date_parts.contains(&(expr as *const Expr)) =>
+            {
+                self.references
+                    .push(ExprReference::Field(ident.value.clone()));
+            }
+            Expr::CompoundIdentifier(idents) if idents.len() == 2 => {
+                self.references.push(ExprReference::Qualified(
+                    idents[0].value.clone(),
+                    idents[1].value.clone(),
+                ));
+            }
+            _ => {}
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> SemanticLayerSpec {
+        serde_yaml::from_str(yaml).expect("valid YAML")
+    }
+
+    const VALID_SPEC: &str = r#"
+models:
+  - name: culture
+    dimensions:
+      - name: cultureid
+      - name: name
+    measures:
+      - name: revenue
+    filters:
+      - name: active_subscribed_customer
+        expr: logins.login_count > {threshold} AND subscriptions.subscription_status = 'active'
+        args:
+          - name: threshold
+            type: integer
+    metrics:
+      - name: total_revenue
+        expr: SUM(revenue)
+      - name: revenue_per_login
+        expr: total_revenue / NULLIF(SUM(logins.login_count), 0)::float
+    entities:
+      - name: logins
+        primary_key: cultureid
+        foreign_key: cultureid
+        type: LEFT
+        cardinality: one-to-many
+      - name: subscriptions
+        primary_key: cultureid
+        foreign_key: cultureid
+        cardinality: one-to-one
+  - name: logins
+    dimensions:
+      - name: cultureid
+    measures:
+      - name: login_count
+    entities:
+      - name: culture
+        primary_key: cultureid
+        foreign_key: cultureid
+        cardinality: many-to-one
+  - name: subscriptions
+    dimensions:
+      - name: cultureid
+      - name: subscription_status
+"#;
+
+    #[test]
+    fn test_valid_spec_has_no_errors() {
+        let spec = parse(VALID_SPEC);
+        let errors = validate_spec(&spec, Some("models.yml"));
+        assert!(errors.is_empty(), "unexpected errors: {:?}", errors);
+    }
+
+    #[test]
+    fn test_entity_to_unknown_model() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: customer_id
+    entities:
+      - name: customers
+        primary_key: customer_id
+        foreign_key: id
+"#,
+        );
+        let errors = validate_spec(&spec, Some("orders.yml"));
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ValidationErrorKind::UnknownModel);
+        assert_eq!(errors[0].field.as_deref(), Some("entities.customers"));
+        assert_eq!(
+            errors[0].to_string(),
+            "orders.yml: model `orders`, field `entities.customers`: Entity references model `customers`, which is not defined."
+        );
+    }
+
+    #[test]
+    fn test_entity_keys_must_exist() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: customer_id
+    entities:
+      - name: customers
+        primary_key: cust_id
+        foreign_key: customer_key
+  - name: customers
+    dimensions:
+      - name: id
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_deref().unwrap()).collect();
+        assert_eq!(
+            fields,
+            vec!["entities.customers.primary_key", "entities.customers.foreign_key"]
+        );
+        assert!(errors.iter().all(|e| e.kind == ValidationErrorKind::UnknownField));
+    }
+
+    #[test]
+    fn test_metric_referencing_unknown_measure() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    measures:
+      - name: amount
+    metrics:
+      - name: total
+        expr: SUM(amuont)
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ValidationErrorKind::UnknownField);
+        assert_eq!(errors[0].field.as_deref(), Some("metrics.total.expr"));
+        assert!(errors[0].message.contains("`amuont`"));
+    }
+
+    #[test]
+    fn test_metric_referencing_unrelated_model() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    measures:
+      - name: amount
+    metrics:
+      - name: total
+        expr: SUM(amount) + SUM(refunds.amount)
+  - name: refunds
+    measures:
+      - name: amount
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].kind, ValidationErrorKind::UnknownModel);
+        assert!(errors[0].message.contains("`refunds.amount`"));
+    }
+
+    #[test]
+    fn test_metric_referencing_unknown_entity_column() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: id
+    metrics:
+      - name: refunded
+        expr: COUNT(refunds.refund_total)
+    entities:
+      - name: refunds
+        primary_key: id
+        foreign_key: order_id
+  - name: refunds
+    dimensions:
+      - name: order_id
+    measures:
+      - name: amount
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("`refund_total`"));
+        assert!(errors[0].message.contains("`refunds`"));
+    }
+
+    #[test]
+    fn test_unsupported_argument_type_and_undeclared_placeholder() {
+        let spec = parse(
+            r#"
+models:
+  - name: logins
+    measures:
+      - name: login_count
+    filters:
+      - name: frequent
+        expr: login_count > {threshold} AND login_count < {ceiling}
+        args:
+          - name: threshold
+            type: int64
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].kind, ValidationErrorKind::UnsupportedValue);
+        assert_eq!(
+            errors[0].field.as_deref(),
+            Some("filters.frequent.args.threshold.type")
+        );
+        assert_eq!(errors[1].kind, ValidationErrorKind::UndeclaredArgument);
+        assert!(errors[1].message.contains("{ceiling}"));
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: status
+      - name: status
+      - name: amount
+    measures:
+      - name: amount
+  - name: orders
+"#,
+        );
+        let errors = validate_spec(&spec, Some("orders.yml"));
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_deref().unwrap()).collect();
+        assert_eq!(fields, vec!["name", "dimensions.status", "measures.amount"]);
+        assert!(errors.iter().all(|e| e.kind == ValidationErrorKind::DuplicateName));
+        assert!(errors[0].message.contains("first defined in orders.yml"));
+    }
+
+    #[test]
+    fn test_unsupported_join_type_and_cardinality() {
+        let spec = parse(
+            r#"
+models:
+  - name: a
+    dimensions:
+      - name: id
+    entities:
+      - name: b
+        primary_key: id
+        foreign_key: id
+        type: OUTER
+        cardinality: one-to-few
+  - name: b
+    dimensions:
+      - name: id
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_deref().unwrap()).collect();
+        assert_eq!(fields, vec!["entities.b.type", "entities.b.cardinality"]);
+    }
+
+    #[test]
+    fn test_models_from_different_files() {
+        let orders = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: customer_id
+    entities:
+      - name: customers
+        primary_key: customer_id
+        foreign_key: id
+"#,
+        );
+        let customers = parse(
+            r#"
+models:
+  - name: customers
+    dimensions:
+      - name: id
+"#,
+        );
+        let models = vec![
+            SourcedModel {
+                file: Some("orders.yml"),
+                model: &orders.models[0],
+            },
+            SourcedModel {
+                file: Some("customers.yml"),
+                model: &customers.models[0],
+            },
+        ];
+        assert!(validate_models(&models).is_empty());
+        assert_eq!(validate_spec(&orders, Some("orders.yml")).len(), 1);
+    }
+
+    #[test]
+    fn test_derived_metrics() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: order_date
+        time: {}
+    measures:
+      - name: revenue
+        agg: sum
+      - name: average_amount
+        agg: avg
+      - name: p95
+        agg: percentile
+    metrics:
+      - name: revenue_per_order
+        type: ratio
+        numerator: revenue
+        denominator: order_count
+      - name: net
+        type: difference
+        minuend: revenue
+      - name: running_average
+        type: cumulative
+        measure: average_amount
+        time_dimension: created_at
+        window: 0
+      - name: running_revenue
+        type: cumulative
+        measure: revenue
+        time_dimension: order_date
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_deref().unwrap()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "measures.p95.percentile",
+                "metrics.revenue_per_order.denominator",
+                "metrics.net.subtrahend",
+                "metrics.running_average.measure",
+                "metrics.running_average.time_dimension",
+                "metrics.running_average.window",
+            ]
+        );
+        assert_eq!(errors[2].kind, ValidationErrorKind::MissingField);
+        assert_eq!(errors[2].message, "`subtrahend` is required for difference metrics.");
+        assert_eq!(errors[3].kind, ValidationErrorKind::UnsupportedValue);
+    }
+
+    #[test]
+    fn test_time_dimensions() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    dimensions:
+      - name: order_date
+        time:
+          grains: [month, fiscal_quarter]
+      - name: shipped_at
+        time:
+          fiscal_year_start_month: 13
+      - name: region
+    measures:
+      - name: revenue
+        agg: sum
+    metrics:
+      - name: by_region
+        type: cumulative
+        measure: revenue
+        time_dimension: region
+      - name: fiscal_ytd
+        type: cumulative
+        measure: revenue
+        time_dimension: order_date
+        grain_to_date: fiscal_year
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        let fields: Vec<_> = errors.iter().map(|e| e.field.as_deref().unwrap()).collect();
+        assert_eq!(
+            fields,
+            vec![
+                "dimensions.order_date.time.fiscal_year_start_month",
+                "dimensions.shipped_at.time.fiscal_year_start_month",
+                "metrics.by_region.time_dimension",
+                "metrics.fiscal_ytd.time_dimension",
+            ]
+        );
+        assert_eq!(errors[2].message, "`region` is not a time dimension.");
+    }
+
+    #[test]
+    fn test_circular_metrics() {
+        let spec = parse(
+            r#"
+models:
+  - name: orders
+    measures:
+      - name: revenue
+        agg: sum
+    metrics:
+      - name: a
+        type: ratio
+        numerator: b
+        denominator: revenue
+      - name: b
+        type: difference
+        minuend: a
+        subtrahend: revenue
+"#,
+        );
+        let errors = validate_spec(&spec, None);
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(|e| e.kind == ValidationErrorKind::CircularReference));
+        assert_eq!(errors[0].message, "Metrics reference each other: a -> b -> a.");
+    }
+
+    #[test]
+    fn test_scan_expression() {
+        assert_eq!(
+            scan_expression(
+                "CASE WHEN o.status = 'it''s done' THEN CAST(amount AS decimal) ELSE 0 END + {bonus}::numeric"
+            ),
+            vec![
+                ExprReference::Qualified("o".to_string(), "status".to_string()),
+                ExprReference::Field("amount".to_string()),
+                ExprReference::Placeholder("bonus".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_expression_skips_date_parts_and_keywords() {
+        assert_eq!(
+            scan_expression(
+                "EXTRACT(YEAR FROM created_at) + DATEDIFF(day, created_at, CURRENT_DATE) \
+                 + DATE_PART('dow', shipped_at) + (updated_at - INTERVAL '1' MONTH IS NULL)::int"
+            ),
+            vec![
+                ExprReference::Field("created_at".to_string()),
+                ExprReference::Field("created_at".to_string()),
+                ExprReference::Field("shipped_at".to_string()),
+                ExprReference::Field("updated_at".to_string()),
+            ]
+        );
+        assert_eq!(
+            scan_expression("DATEADD(week, {offset}, o.ordered_at)"),
+            vec![
+                ExprReference::Qualified("o".to_string(), "ordered_at".to_string()),
+                ExprReference::Placeholder("offset".to_string()),
+            ]
+        );
+    }
+}
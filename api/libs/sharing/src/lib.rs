@@ -5,6 +5,7 @@ pub mod remove_asset_permissions;
 pub mod types;
 pub mod user_lookup;
 pub mod asset_access_checks;
+pub mod viewable_assets;
 
 #[cfg(test)]
 pub mod tests;
@@ -20,3 +21,7 @@ pub use types::{
 };
 pub use user_lookup::find_user_by_email;
 pub use asset_access_checks::check_permission_access;
+pub use viewable_assets::{
+    fetch_filterable_dashboard_files, fetch_viewable_dashboard_files, fetch_viewable_metric_files,
+    FILTER_ROLES,
+};
@@ -0,0 +1,134 @@
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use database::enums::AssetPermissionRole;
+use database::helpers::dashboard_files::fetch_dashboard_files_with_permissions;
+use database::helpers::metric_files::fetch_metric_files_with_permissions;
+use database::models::{DashboardFile, MetricFile};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::check_permission_access;
+
+const VIEW_ROLES: &[AssetPermissionRole] = &[
+    AssetPermissionRole::CanView,
+    AssetPermissionRole::CanFilter,
+    AssetPermissionRole::CanEdit,
+    AssetPermissionRole::FullAccess,
+    AssetPermissionRole::Owner,
+];
+
+/// Roles that can view a dashboard with its filters set, which a view-only
+/// share can't
+pub const FILTER_ROLES: &[AssetPermissionRole] = &[
+    AssetPermissionRole::CanFilter,
+    AssetPermissionRole::CanEdit,
+    AssetPermissionRole::FullAccess,
+    AssetPermissionRole::Owner,
+];
+
+/// Loads the metric files among `ids` that the user can view, through an
+/// asset permission, an admin role in the file's organization, or a public
+/// link that hasn't expired and has no password. Other ids are left out.
+pub async fn fetch_viewable_metric_files(
+    ids: &[Uuid],
+    user: &AuthenticatedUser,
+) -> Result<Vec<MetricFile>> {
+    let files = fetch_metric_files_with_permissions(ids, &user.id).await?;
+
+    Ok(files
+        .into_iter()
+        .filter(|file| {
+            let metric_file = &file.metric_file;
+            check_permission_access(
+                file.permission,
+                VIEW_ROLES,
+                metric_file.organization_id,
+                &user.organizations,
+            ) || is_open_public_link(
+                metric_file.publicly_accessible,
+                metric_file.public_expiry_date,
+                metric_file.public_password.as_deref(),
+            )
+        })
+        .map(|file| file.metric_file)
+        .collect())
+}
+
+/// Loads the dashboard files among `ids` that the user can view, on the same
+/// terms as `fetch_viewable_metric_files`
+pub async fn fetch_viewable_dashboard_files(
+    ids: &[Uuid],
+    user: &AuthenticatedUser,
+) -> Result<Vec<DashboardFile>> {
+    let files = fetch_dashboard_files_with_permissions(ids, &user.id).await?;
+
+    Ok(files
+        .into_iter()
+        .filter(|file| {
+            let dashboard_file = &file.dashboard_file;
+            check_permission_access(
+                file.permission,
+                VIEW_ROLES,
+                dashboard_file.organization_id,
+                &user.organizations,
+            ) || is_open_public_link(
+                dashboard_file.publicly_accessible,
+                dashboard_file.public_expiry_date,
+                dashboard_file.public_password.as_deref(),
+            )
+        })
+        .map(|file| file.dashboard_file)
+        .collect())
+}
+
+/// Loads the dashboard files among `ids` whose filters the user can set,
+/// through one of `FILTER_ROLES` or an admin role in the file's organization.
+/// Public links don't count, since they only grant viewing.
+pub async fn fetch_filterable_dashboard_files(
+    ids: &[Uuid],
+    user: &AuthenticatedUser,
+) -> Result<Vec<DashboardFile>> {
+    let files = fetch_dashboard_files_with_permissions(ids, &user.id).await?;
+
+    Ok(files
+        .into_iter()
+        .filter(|file| {
+            check_permission_access(
+                file.permission,
+                FILTER_ROLES,
+                file.dashboard_file.organization_id,
+                &user.organizations,
+            )
+        })
+        .map(|file| file.dashboard_file)
+        .collect())
+}
+
+/// A public link anyone can open without a password
+fn is_open_public_link(
+    publicly_accessible: bool,
+    public_expiry_date: Option<DateTime<Utc>>,
+    public_password: Option<&str>,
+) -> bool {
+    publicly_accessible
+        && public_password.is_none()
+        && public_expiry_date.map_or(true, |expiry| expiry > Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_open_public_link() {
+        let tomorrow = Some(Utc::now() + Duration::days(1));
+        let yesterday = Some(Utc::now() - Duration::days(1));
+
+        assert!(is_open_public_link(true, None, None));
+        assert!(is_open_public_link(true, tomorrow, None));
+        assert!(!is_open_public_link(true, yesterday, None));
+        assert!(!is_open_public_link(true, None, Some("secret")));
+        assert!(!is_open_public_link(false, None, None));
+    }
+}
@@ -0,0 +1,234 @@
+use crate::errors::SqlAnalyzerError;
+use crate::parameters::string_literal;
+use crate::types::{ColumnFilter, ColumnFilterCondition, FilterValue, WarehouseDialect};
+use sqlparser::ast::{BinaryOperator, Expr, Ident, Query, SetExpr, Statement, Value};
+use sqlparser::parser::{Parser, ParserOptions};
+
+/// Adds column predicates to the outermost `SELECT` of a query.
+///
+/// Each filter becomes a predicate built directly in the AST, with its values
+/// as literals, and all of them are `AND`-ed onto the existing `WHERE` clause.
+/// The query is parsed in the syntax of `dialect`, its own string literals
+/// are written back as they were, and filter values are escaped for that
+/// warehouse.
+/// Column names must be plain or dot-qualified identifiers. Queries whose
+/// outermost body is a set operation (`UNION`, `INTERSECT`, `EXCEPT`) are
+/// rejected, since there is no single `WHERE` clause to extend.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{
+///     apply_column_filters, ColumnFilter, ColumnFilterCondition, FilterValue, WarehouseDialect,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let filters = vec![ColumnFilter {
+///         column: "region".to_string(),
+///         condition: ColumnFilterCondition::Equals { value: FilterValue::String("EU".to_string()) },
+///     }];
+///     let sql = apply_column_filters(
+///         "SELECT region, SUM(amount) FROM sales GROUP BY region".to_string(),
+///         filters,
+///         WarehouseDialect::Postgres,
+///     )
+///     .await?;
+///     assert_eq!(sql, "SELECT region, SUM(amount) FROM sales WHERE region = 'EU' GROUP BY region");
+///     Ok(())
+/// }
+/// ```
+pub async fn apply_column_filters(
+    sql: String,
+    filters: Vec<ColumnFilter>,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    tokio::task::spawn_blocking(move || apply_filters(&sql, &filters, dialect))
+        .await
+        .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))?
+}
+
+fn apply_filters(
+    sql: &str,
+    filters: &[ColumnFilter],
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    // Literals are kept unescaped so they print back in the query's own syntax
+    let mut statements = Parser::new(dialect.parser_dialect().as_ref())
+        .with_options(ParserOptions::new().with_unescape(false))
+        .try_with_sql(sql)?
+        .parse_statements()?;
+    if statements.len() != 1 {
+        return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+            "expected a single query, found {} statements",
+            statements.len()
+        )));
+    }
+    if filters.is_empty() {
+        return Ok(sql.to_string());
+    }
+
+    let predicates = filters
+        .iter()
+        .map(|filter| filter_predicate(filter, dialect))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    match &mut statements[0] {
+        Statement::Query(query) => add_predicates(query, predicates)?,
+        other => {
+            return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+                "filters can only be applied to queries, found: {}",
+                other
+            )))
+        }
+    }
+
+    Ok(statements[0].to_string())
+}
+
+fn add_predicates(query: &mut Query, predicates: Vec<Expr>) -> Result<(), SqlAnalyzerError> {
+    match query.body.as_mut() {
+        SetExpr::Select(select) => {
+            let mut conditions = predicates.into_iter();
+            let mut selection = match select.selection.take() {
+                // Parenthesize so an existing `a OR b` keeps its meaning
+                Some(existing) => Expr::Nested(Box::new(existing)),
+                None => match conditions.next() {
+                    Some(first) => first,
+                    None => return Ok(()),
+                },
+            };
+            for condition in conditions {
+                selection = and(selection, condition);
+            }
+            select.selection = Some(selection);
+            Ok(())
+        }
+        SetExpr::Query(inner) => add_predicates(inner, predicates),
+        SetExpr::SetOperation { op, .. } => Err(SqlAnalyzerError::UnsupportedStatement(format!(
+            "filters can't be applied to a {} query; wrap it in a subquery",
+            op
+        ))),
+        other => Err(SqlAnalyzerError::UnsupportedStatement(format!(
+            "filters can't be applied to: {}",
+            other
+        ))),
+    }
+}
+
+fn filter_predicate(
+    filter: &ColumnFilter,
+    dialect: WarehouseDialect,
+) -> Result<Expr, SqlAnalyzerError> {
+    let column = column_expr(&filter.column)?;
+
+    match &filter.condition {
+        ColumnFilterCondition::Equals { value } => {
+            Ok(binary(column, BinaryOperator::Eq, literal(value, dialect)?))
+        }
+        ColumnFilterCondition::In { values } => {
+            if values.is_empty() {
+                return Err(SqlAnalyzerError::InvalidParameter(format!(
+                    "filter on '{}' has no values",
+                    filter.column
+                )));
+            }
+            Ok(Expr::InList {
+                expr: Box::new(column),
+                list: values
+                    .iter()
+                    .map(|value| literal(value, dialect))
+                    .collect::<Result<_, _>>()?,
+                negated: false,
+            })
+        }
+        ColumnFilterCondition::Range {
+            min,
+            max,
+            max_inclusive,
+        } => {
+            let lower = min
+                .as_ref()
+                .map(|min| {
+                    Ok::<_, SqlAnalyzerError>(binary(
+                        column.clone(),
+                        BinaryOperator::GtEq,
+                        literal(min, dialect)?,
+                    ))
+                })
+                .transpose()?;
+            let upper_op = if *max_inclusive {
+                BinaryOperator::LtEq
+            } else {
+                BinaryOperator::Lt
+            };
+            let upper = max
+                .as_ref()
+                .map(|max| {
+                    Ok::<_, SqlAnalyzerError>(binary(
+                        column.clone(),
+                        upper_op,
+                        literal(max, dialect)?,
+                    ))
+                })
+                .transpose()?;
+
+            match (lower, upper) {
+                (Some(lower), Some(upper)) => Ok(and(lower, upper)),
+                (Some(bound), None) | (None, Some(bound)) => Ok(bound),
+                (None, None) => Err(SqlAnalyzerError::InvalidParameter(format!(
+                    "range filter on '{}' has no bounds",
+                    filter.column
+                ))),
+            }
+        }
+    }
+}
+
+fn column_expr(column: &str) -> Result<Expr, SqlAnalyzerError> {
+    let parts: Vec<&str> = column.split('.').collect();
+    let valid = parts.iter().all(|part| {
+        let mut chars = part.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if !valid {
+        return Err(SqlAnalyzerError::InvalidParameter(format!(
+            "'{}' is not a valid column name",
+            column
+        )));
+    }
+
+    let mut idents: Vec<Ident> = parts.into_iter().map(Ident::new).collect();
+    Ok(if idents.len() == 1 {
+        Expr::Identifier(idents.remove(0))
+    } else {
+        Expr::CompoundIdentifier(idents)
+    })
+}
+
+fn literal(value: &FilterValue, dialect: WarehouseDialect) -> Result<Expr, SqlAnalyzerError> {
+    match value {
+        FilterValue::Number(n) if n.is_finite() => {
+            Ok(Expr::Value(Value::Number(n.to_string(), false)))
+        }
+        FilterValue::Number(n) => Err(SqlAnalyzerError::InvalidParameter(format!(
+            "{} is not a valid filter value",
+            n
+        ))),
+        // Placeholders print verbatim, so the literal keeps the dialect's own
+        // escaping rather than sqlparser's
+        FilterValue::String(s) => Ok(Expr::Value(Value::Placeholder(string_literal(s, dialect)))),
+    }
+}
+
+fn binary(left: Expr, op: BinaryOperator, right: Expr) -> Expr {
+    Expr::BinaryOp {
+        left: Box::new(left),
+        op,
+        right: Box::new(right),
+    }
+}
+
+fn and(left: Expr, right: Expr) -> Expr {
+    binary(left, BinaryOperator::And, right)
+}
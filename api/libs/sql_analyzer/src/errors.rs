@@ -29,6 +29,9 @@ pub enum SqlAnalyzerError {
     #[error("Invalid expression: {0}")]
     InvalidExpression(String),
 
+    #[error("Fan-out join detected: {0}")]
+    FanOutJoin(String),
+
     #[error("Substitution error: {0}")]
     SubstitutionError(String),
 
@@ -43,4 +46,10 @@ impl From<sqlparser::parser::ParserError> for SqlAnalyzerError {
     fn from(err: sqlparser::parser::ParserError) -> Self {
         SqlAnalyzerError::ParseError(err.to_string())
     }
-}
\ No newline at end of file
+}
+
+impl From<sqlparser::tokenizer::TokenizerError> for SqlAnalyzerError {
+    fn from(err: sqlparser::tokenizer::TokenizerError) -> Self {
+        SqlAnalyzerError::ParseError(err.to_string())
+    }
+}
@@ -0,0 +1,440 @@
+use crate::errors::SqlAnalyzerError;
+use crate::types::{Cardinality, FanOutWarning, SemanticLayer, ValidationMode};
+use anyhow::Result;
+use sqlparser::ast::{
+    BinaryOperator, DuplicateTreatment, Expr, Function, FunctionArg, FunctionArgExpr,
+    FunctionArguments, JoinConstraint, JoinOperator, Query, Select, SelectItem, SetExpr, Statement,
+    TableFactor, TableWithJoins, Visit, Visitor,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::ops::ControlFlow;
+
+/// Aggregates whose result changes when input rows are duplicated.
+/// MIN and MAX are unaffected by duplication, and COUNT(DISTINCT ...) is skipped.
+const DUPLICATE_SENSITIVE_AGGREGATES: &[&str] = &["sum", "avg", "count", "mean"];
+
+/// Detects measures aggregated across joins that duplicate their rows.
+///
+/// Relationship cardinalities from the semantic layer decide which side of a
+/// join is the "many" side. Summing a column from the "one" side of a
+/// one-to-many (or either side of a many-to-many) join double-counts it.
+///
+/// In `ValidationMode::Strict` any fan-out is an error; in
+/// `ValidationMode::Flexible` the fan-outs are returned as warnings.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{detect_fan_out_joins, Cardinality, Relationship, SemanticLayer, ValidationMode};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let mut semantic_layer = SemanticLayer::new();
+///     semantic_layer.add_relationship(Relationship {
+///         from_table: "orders".to_string(),
+///         from_column: "id".to_string(),
+///         to_table: "order_items".to_string(),
+///         to_column: "order_id".to_string(),
+///         cardinality: Some(Cardinality::OneToMany),
+///     });
+///
+///     let sql = "SELECT SUM(o.shipping_cost) FROM orders o JOIN order_items i ON o.id = i.order_id";
+///     let warnings = detect_fan_out_joins(sql.to_string(), semantic_layer, ValidationMode::Flexible).await?;
+///     for warning in warnings {
+///         println!("{}\n{}", warning.message, warning.suggestion);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn detect_fan_out_joins(
+    sql: String,
+    semantic_layer: SemanticLayer,
+    mode: ValidationMode,
+) -> Result<Vec<FanOutWarning>, SqlAnalyzerError> {
+    let warnings = tokio::task::spawn_blocking(move || {
+        let ast = Parser::parse_sql(&GenericDialect, &sql)?;
+        Ok::<_, SqlAnalyzerError>(find_fan_outs(&ast, &semantic_layer))
+    })
+    .await
+    .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))??;
+
+    if mode == ValidationMode::Strict && !warnings.is_empty() {
+        return Err(SqlAnalyzerError::FanOutJoin(
+            warnings
+                .iter()
+                .map(|w| format!("{}\n{}", w.message, w.suggestion))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ));
+    }
+
+    Ok(warnings)
+}
+
+/// Finds fan-out aggregates in already parsed statements.
+pub(crate) fn find_fan_outs(statements: &[Statement], semantic_layer: &SemanticLayer) -> Vec<FanOutWarning> {
+    let mut detector = FanOutDetector {
+        semantic_layer,
+        warnings: Vec::new(),
+    };
+    for stmt in statements {
+        let _ = stmt.visit(&mut detector);
+    }
+    detector.warnings
+}
+
+/// A qualified column reference as (qualifier, column).
+type ColumnRef = (String, String);
+
+/// A join edge between two relations in the same SELECT, keyed by alias.
+#[derive(Debug, Clone)]
+struct JoinEdge {
+    left: String,
+    right: String,
+    left_key: String,
+    right_key: String,
+    /// Cardinality read from `left` to `right`
+    cardinality: Cardinality,
+}
+
+struct FanOutDetector<'a> {
+    semantic_layer: &'a SemanticLayer,
+    warnings: Vec<FanOutWarning>,
+}
+
+impl Visitor for FanOutDetector<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        let mut selects = Vec::new();
+        collect_selects(&query.body, &mut selects);
+        for select in selects {
+            self.check_select(select);
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl FanOutDetector<'_> {
+    fn check_select(&mut self, select: &Select) {
+        // alias (or table name) -> table name
+        let mut relations: HashMap<String, String> = HashMap::new();
+        let mut conditions: Vec<&Expr> = Vec::new();
+        for table_with_joins in &select.from {
+            collect_relations(table_with_joins, &mut relations, &mut conditions);
+        }
+        if relations.len() < 2 {
+            return;
+        }
+        if let Some(selection) = &select.selection {
+            conditions.push(selection);
+        }
+
+        let edges = self.join_edges(&relations, &conditions);
+        if edges.is_empty() {
+            return;
+        }
+
+        let mut aggregates = Vec::new();
+        for item in &select.projection {
+            if let SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } = item {
+                collect_aggregates(expr, &mut aggregates);
+            }
+        }
+        if let Some(having) = &select.having {
+            collect_aggregates(having, &mut aggregates);
+        }
+
+        let mut reported = HashSet::new();
+        for (measure, function_name, qualifier, column) in aggregates {
+            let Some(table) = relations.get(&qualifier) else {
+                continue;
+            };
+            let Some((edge, fanned_by, cardinality)) = find_fan_out(&qualifier, &edges) else {
+                continue;
+            };
+            if !reported.insert(measure.clone()) {
+                continue;
+            }
+
+            let fanned_table = relations.get(&fanned_by).cloned().unwrap_or(fanned_by);
+            let key = if edge.left == qualifier {
+                edge.left_key.clone()
+            } else if edge.right == qualifier {
+                edge.right_key.clone()
+            } else {
+                // The fan-out happens further along the join path; the
+                // relationship key of the measure's own table is unknown here
+                "<join key>".to_string()
+            };
+            let cte_name = format!("{}_agg", table);
+            let output_column = format!("{}_{}", column, function_name);
+
+            self.warnings.push(FanOutWarning {
+                table: table.clone(),
+                measure: measure.clone(),
+                fanned_out_by: fanned_table.clone(),
+                cardinality,
+                message: format!(
+                    "`{}` aggregates `{}` across a join to `{}`, which repeats each `{}` row once per matching `{}` row and inflates the result",
+                    measure, table, fanned_table, table, fanned_table
+                ),
+                suggestion: format!(
+                    "Pre-aggregate `{}` in a CTE before joining, e.g. WITH {} AS (SELECT {}, {}({}) AS {} FROM {} GROUP BY {}) and join to {} on {}",
+                    table,
+                    cte_name,
+                    key,
+                    function_name.to_uppercase(),
+                    column,
+                    output_column,
+                    table,
+                    key,
+                    cte_name,
+                    key
+                ),
+            });
+        }
+    }
+
+    /// Builds join edges from equality predicates between qualified columns
+    /// of tables that have a relationship with a known cardinality.
+    fn join_edges(&self, relations: &HashMap<String, String>, conditions: &[&Expr]) -> Vec<JoinEdge> {
+        let mut equalities = Vec::new();
+        for condition in conditions {
+            collect_equalities(condition, &mut equalities);
+        }
+
+        let mut edges = Vec::new();
+        for ((left, left_key), (right, right_key)) in equalities {
+            let (Some(left_table), Some(right_table)) = (relations.get(&left), relations.get(&right)) else {
+                continue;
+            };
+            if left == right {
+                continue;
+            }
+            if let Some(cardinality) = self
+                .semantic_layer
+                .relationship_cardinality(left_table, right_table)
+            {
+                edges.push(JoinEdge {
+                    left,
+                    right,
+                    left_key,
+                    right_key,
+                    cardinality,
+                });
+            }
+        }
+        edges
+    }
+}
+
+/// Walks the join graph from `start` and returns the first edge traversed in
+/// a "to-many" direction, along with the relation on its many side and the
+/// cardinality read in the direction of travel.
+fn find_fan_out<'e>(
+    start: &str,
+    edges: &'e [JoinEdge],
+) -> Option<(&'e JoinEdge, String, Cardinality)> {
+    let mut visited = HashSet::from([start.to_string()]);
+    let mut queue = VecDeque::from([start.to_string()]);
+
+    while let Some(current) = queue.pop_front() {
+        for edge in edges {
+            let (next, cardinality) = if edge.left == current {
+                (&edge.right, edge.cardinality)
+            } else if edge.right == current {
+                (&edge.left, edge.cardinality.reversed())
+            } else {
+                continue;
+            };
+            if visited.contains(next) {
+                continue;
+            }
+            if cardinality.fans_out() {
+                return Some((edge, next.clone(), cardinality));
+            }
+            visited.insert(next.clone());
+            queue.push_back(next.clone());
+        }
+    }
+
+    None
+}
+
+fn collect_selects<'a>(body: &'a SetExpr, out: &mut Vec<&'a Select>) {
+    match body {
+        SetExpr::Select(select) => out.push(select),
+        SetExpr::SetOperation { left, right, .. } => {
+            collect_selects(left, out);
+            collect_selects(right, out);
+        }
+        _ => {}
+    }
+}
+
+fn collect_relations<'a>(
+    table_with_joins: &'a TableWithJoins,
+    relations: &mut HashMap<String, String>,
+    conditions: &mut Vec<&'a Expr>,
+) {
+    collect_factor(&table_with_joins.relation, relations, conditions);
+    for join in &table_with_joins.joins {
+        collect_factor(&join.relation, relations, conditions);
+        match &join.join_operator {
+            JoinOperator::Inner(JoinConstraint::On(expr))
+            | JoinOperator::LeftOuter(JoinConstraint::On(expr))
+            | JoinOperator::RightOuter(JoinConstraint::On(expr))
+            | JoinOperator::FullOuter(JoinConstraint::On(expr)) => conditions.push(expr),
+            _ => {}
+        }
+    }
+}
+
+fn collect_factor<'a>(
+    factor: &'a TableFactor,
+    relations: &mut HashMap<String, String>,
+    conditions: &mut Vec<&'a Expr>,
+) {
+    match factor {
+        TableFactor::Table { name, alias, .. } => {
+            let table = name.0.last().map(|i| i.value.clone()).unwrap_or_default();
+            let key = alias
+                .as_ref()
+                .map(|a| a.name.value.clone())
+                .unwrap_or_else(|| table.clone());
+            relations.insert(key, table);
+        }
+        TableFactor::NestedJoin {
+            table_with_joins, ..
+        } => collect_relations(table_with_joins, relations, conditions),
+        _ => {}
+    }
+}
+
+/// Collects `a.x = b.y` predicates joined by AND as ((a, x), (b, y)).
+fn collect_equalities(expr: &Expr, out: &mut Vec<(ColumnRef, ColumnRef)>) {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            collect_equalities(left, out);
+            collect_equalities(right, out);
+        }
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::Eq,
+            right,
+        } => {
+            if let (Some(l), Some(r)) = (qualified_column(left), qualified_column(right)) {
+                out.push((l, r));
+            }
+        }
+        Expr::Nested(inner) => collect_equalities(inner, out),
+        _ => {}
+    }
+}
+
+fn qualified_column(expr: &Expr) -> Option<ColumnRef> {
+    match expr {
+        Expr::CompoundIdentifier(idents) if idents.len() >= 2 => Some((
+            idents[idents.len() - 2].value.clone(),
+            idents[idents.len() - 1].value.clone(),
+        )),
+        Expr::Nested(inner) => qualified_column(inner),
+        _ => None,
+    }
+}
+
+/// Collects duplicate-sensitive aggregates over a single qualified column as
+/// (aggregate text, function name, qualifier, column).
+fn collect_aggregates(expr: &Expr, out: &mut Vec<(String, String, String, String)>) {
+    struct AggregateCollector<'o> {
+        out: &'o mut Vec<(String, String, String, String)>,
+        query_depth: usize,
+    }
+
+    impl Visitor for AggregateCollector<'_> {
+        type Break = ();
+
+        fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+            self.query_depth += 1;
+            ControlFlow::Continue(())
+        }
+
+        fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+            self.query_depth -= 1;
+            ControlFlow::Continue(())
+        }
+
+        fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+            if self.query_depth > 0 {
+                return ControlFlow::Continue(());
+            }
+            if let Expr::Function(function) = expr {
+                if let Some((name, qualifier, column)) = duplicate_sensitive_aggregate(function) {
+                    self.out.push((expr.to_string(), name, qualifier, column));
+                }
+            }
+            ControlFlow::Continue(())
+        }
+    }
+
+    let _ = expr.visit(&mut AggregateCollector {
+        out,
+        query_depth: 0,
+    });
+}
+
+fn duplicate_sensitive_aggregate(function: &Function) -> Option<(String, String, String)> {
+    if function.over.is_some() {
+        return None;
+    }
+    let name = function.name.0.last()?.value.to_lowercase();
+    if !DUPLICATE_SENSITIVE_AGGREGATES.contains(&name.as_str()) {
+        return None;
+    }
+
+    let FunctionArguments::List(list) = &function.args else {
+        return None;
+    };
+    if list.duplicate_treatment == Some(DuplicateTreatment::Distinct) {
+        return None;
+    }
+
+    let mut columns = Vec::new();
+    for arg in &list.args {
+        if let FunctionArg::Unnamed(FunctionArgExpr::Expr(arg_expr)) = arg {
+            collect_qualified_columns(arg_expr, &mut columns);
+        }
+    }
+
+    // Only attribute the measure when every column comes from one relation
+    let (qualifier, column) = columns.first()?.clone();
+    if columns.iter().any(|(q, _)| *q != qualifier) {
+        return None;
+    }
+    Some((name, qualifier, column))
+}
+
+fn collect_qualified_columns(expr: &Expr, out: &mut Vec<(String, String)>) {
+    struct ColumnCollector<'o> {
+        out: &'o mut Vec<(String, String)>,
+    }
+
+    impl Visitor for ColumnCollector<'_> {
+        type Break = ();
+
+        fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+            if let Some(column) = qualified_column(expr) {
+                self.out.push(column);
+            }
+            ControlFlow::Continue(())
+        }
+    }
+
+    let _ = expr.visit(&mut ColumnCollector { out });
+}
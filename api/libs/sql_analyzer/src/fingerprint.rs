@@ -0,0 +1,193 @@
+use crate::errors::SqlAnalyzerError;
+use crate::types::QueryFingerprint;
+use sha2::{Digest, Sha256};
+use sqlparser::ast::{
+    BinaryOperator, Expr, Ident, ObjectName, Query, SelectItem, SetExpr, Statement, TableFactor,
+    Value, VisitMut, VisitorMut,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use std::ops::ControlFlow;
+
+/// Placeholder that replaces every literal in a normalized query
+const LITERAL_PLACEHOLDER: &str = "?";
+
+/// Produces a canonical fingerprint for a SQL query.
+///
+/// Two queries share a fingerprint when they differ only in:
+/// - literal values (strings, numbers, booleans, and `IN` lists of literals)
+/// - whitespace and keyword case
+/// - the case of unquoted identifiers
+/// - the order of operands of `AND`, `OR`, `=` and `<>`
+///
+/// The returned `normalized_sql` is the query the fingerprint is computed
+/// from, which is useful for grouping query history by shape.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::fingerprint_query;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let a = fingerprint_query("SELECT id FROM users WHERE status = 'active' AND age > 21".to_string()).await?;
+///     let b = fingerprint_query("select ID from USERS where age > 30 and status = 'new'".to_string()).await?;
+///     assert_eq!(a.fingerprint, b.fingerprint);
+///     Ok(())
+/// }
+/// ```
+pub async fn fingerprint_query(sql: String) -> Result<QueryFingerprint, SqlAnalyzerError> {
+    tokio::task::spawn_blocking(move || {
+        let normalized_sql = normalize_sql(&sql)?;
+        let fingerprint = Sha256::digest(normalized_sql.as_bytes())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        Ok(QueryFingerprint {
+            fingerprint,
+            normalized_sql,
+        })
+    })
+    .await
+    .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))?
+}
+
+/// Parses and normalizes SQL, joining multiple statements with `; `.
+pub(crate) fn normalize_sql(sql: &str) -> Result<String, SqlAnalyzerError> {
+    let mut statements = Parser::parse_sql(&GenericDialect, sql)?;
+
+    let mut normalizer = Normalizer;
+    for statement in &mut statements {
+        let _ = statement.visit(&mut normalizer);
+    }
+
+    Ok(statements
+        .iter()
+        .map(Statement::to_string)
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+struct Normalizer;
+
+impl VisitorMut for Normalizer {
+    type Break = ();
+
+    fn post_visit_query(&mut self, query: &mut Query) -> ControlFlow<Self::Break> {
+        if let Some(with) = &mut query.with {
+            for cte in &mut with.cte_tables {
+                normalize_ident(&mut cte.alias.name);
+            }
+        }
+        normalize_select_aliases(&mut query.body);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_relation(&mut self, relation: &mut ObjectName) -> ControlFlow<Self::Break> {
+        relation.0.iter_mut().for_each(normalize_ident);
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        if let TableFactor::Table { alias: Some(alias), .. }
+        | TableFactor::Derived { alias: Some(alias), .. } = table_factor
+        {
+            normalize_ident(&mut alias.name);
+            alias.columns.iter_mut().for_each(|c| normalize_ident(&mut c.name));
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Children are visited before `post_visit_expr`, so operands are already
+    // normalized when a parent expression is sorted or collapsed.
+    fn post_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Value(value) if !matches!(value, Value::Null | Value::Placeholder(_)) => {
+                *value = Value::Placeholder(LITERAL_PLACEHOLDER.to_string());
+            }
+            Expr::TypedString { value, .. } => *value = LITERAL_PLACEHOLDER.to_string(),
+            Expr::Identifier(ident) => normalize_ident(ident),
+            Expr::CompoundIdentifier(idents) => idents.iter_mut().for_each(normalize_ident),
+            Expr::Function(function) => function.name.0.iter_mut().for_each(normalize_ident),
+            Expr::InList { list, .. } if list.iter().all(is_placeholder) => {
+                list.truncate(1);
+            }
+            Expr::BinaryOp {
+                op: op @ (BinaryOperator::And | BinaryOperator::Or),
+                ..
+            } => {
+                let op = op.clone();
+                let mut operands = Vec::new();
+                flatten(std::mem::replace(expr, Expr::Value(Value::Null)), &op, &mut operands);
+                operands.sort_by_cached_key(operand_key);
+                *expr = operands
+                    .into_iter()
+                    .reduce(|left, right| Expr::BinaryOp {
+                        left: Box::new(left),
+                        op: op.clone(),
+                        right: Box::new(right),
+                    })
+                    .expect("a binary operation has at least two operands");
+            }
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::Eq | BinaryOperator::NotEq,
+                right,
+            } if operand_key(left) > operand_key(right) => std::mem::swap(left, right),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// Lowercases unquoted identifiers; quoted identifiers are case-sensitive.
+fn normalize_ident(ident: &mut Ident) {
+    if ident.quote_style.is_none() {
+        ident.value = ident.value.to_lowercase();
+    }
+}
+
+fn normalize_select_aliases(body: &mut SetExpr) {
+    match body {
+        SetExpr::Select(select) => {
+            for item in &mut select.projection {
+                if let SelectItem::ExprWithAlias { alias, .. } = item {
+                    normalize_ident(alias);
+                }
+            }
+        }
+        SetExpr::SetOperation { left, right, .. } => {
+            normalize_select_aliases(left);
+            normalize_select_aliases(right);
+        }
+        _ => {}
+    }
+}
+
+/// Orders operands by their text, with literals after columns so that
+/// `? = col` reads as `col = ?`.
+fn operand_key(expr: &Expr) -> (bool, String) {
+    (is_placeholder(expr), expr.to_string())
+}
+
+fn is_placeholder(expr: &Expr) -> bool {
+    matches!(expr, Expr::Value(Value::Placeholder(p)) if p == LITERAL_PLACEHOLDER)
+}
+
+/// Flattens a chain of the same associative operator into its operands.
+fn flatten(expr: Expr, op: &BinaryOperator, out: &mut Vec<Expr>) {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: inner,
+            right,
+        } if inner == *op => {
+            flatten(*left, op, out);
+            flatten(*right, op, out);
+        }
+        other => out.push(other),
+    }
+}
@@ -0,0 +1,273 @@
+use crate::errors::SqlAnalyzerError;
+use sqlparser::ast::{
+    BinaryOperator, Cte, Expr, GroupByExpr, Query, Select, SetExpr, Statement, TableFactor,
+    TableWithJoins,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+
+const INDENT: &str = "  ";
+
+/// Pretty-prints SQL with one clause per line.
+///
+/// Select items, `GROUP BY` and `ORDER BY` expressions go on their own
+/// indented lines, top-level `AND` conditions in `WHERE`/`HAVING` are split
+/// one per line, and CTEs and derived tables are formatted recursively.
+/// Keywords are uppercased; identifiers and literals are left as written.
+/// Constructs the formatter does not lay out are printed on a single line.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::format_sql;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let formatted = format_sql("select id, name from users where active = true".to_string()).await?;
+///     assert_eq!(formatted, "SELECT\n  id,\n  name\nFROM users\nWHERE\n  active = true");
+///     Ok(())
+/// }
+/// ```
+pub async fn format_sql(sql: String) -> Result<String, SqlAnalyzerError> {
+    tokio::task::spawn_blocking(move || {
+        let statements = Parser::parse_sql(&GenericDialect, &sql)?;
+        Ok(statements
+            .iter()
+            .map(format_statement)
+            .collect::<Vec<_>>()
+            .join(";\n\n"))
+    })
+    .await
+    .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))?
+}
+
+fn format_statement(statement: &Statement) -> String {
+    let single_line = statement.to_string();
+    // Re-indenting would change string literals that span lines
+    if single_line.contains('\n') {
+        return single_line;
+    }
+
+    match statement {
+        Statement::Query(query) => format_query(query),
+        _ => single_line,
+    }
+}
+
+fn format_query(query: &Query) -> String {
+    let has_unformatted_clauses = !query.limit_by.is_empty()
+        || query.fetch.is_some()
+        || !query.locks.is_empty()
+        || query.for_clause.is_some()
+        || query.settings.is_some()
+        || query.format_clause.is_some()
+        || query
+            .order_by
+            .as_ref()
+            .is_some_and(|order_by| order_by.interpolate.is_some());
+    if has_unformatted_clauses {
+        return query.to_string();
+    }
+
+    let mut lines = Vec::new();
+
+    if let Some(with) = &query.with {
+        lines.push(if with.recursive { "WITH RECURSIVE" } else { "WITH" }.to_string());
+        let ctes = with
+            .cte_tables
+            .iter()
+            .map(|cte| indent(&format_cte(cte)))
+            .collect::<Vec<_>>();
+        lines.push(ctes.join(",\n"));
+    }
+
+    lines.push(format_set_expr(&query.body));
+
+    if let Some(order_by) = &query.order_by {
+        lines.push("ORDER BY".to_string());
+        lines.push(list(order_by.exprs.iter().map(ToString::to_string)));
+    }
+    if let Some(limit) = &query.limit {
+        lines.push(format!("LIMIT {}", limit));
+    }
+    if let Some(offset) = &query.offset {
+        lines.push(offset.to_string());
+    }
+
+    lines.join("\n")
+}
+
+fn format_cte(cte: &Cte) -> String {
+    if cte.from.is_some() || cte.materialized.is_some() {
+        return cte.to_string();
+    }
+    format!("{} AS (\n{}\n)", cte.alias, indent(&format_query(&cte.query)))
+}
+
+fn format_set_expr(body: &SetExpr) -> String {
+    match body {
+        SetExpr::Select(select) => format_select(select),
+        SetExpr::Query(query) => format!("(\n{}\n)", indent(&format_query(query))),
+        SetExpr::SetOperation {
+            op,
+            set_quantifier,
+            left,
+            right,
+        } => {
+            let quantifier = set_quantifier.to_string();
+            let operator = if quantifier.is_empty() {
+                op.to_string()
+            } else {
+                format!("{} {}", op, quantifier)
+            };
+            format!(
+                "{}\n{}\n{}",
+                format_set_expr(left),
+                operator,
+                format_set_expr(right)
+            )
+        }
+        other => other.to_string(),
+    }
+}
+
+fn format_select(select: &Select) -> String {
+    let has_unformatted_clauses = select.top.is_some()
+        || select.into.is_some()
+        || !select.lateral_views.is_empty()
+        || select.prewhere.is_some()
+        || !select.cluster_by.is_empty()
+        || !select.distribute_by.is_empty()
+        || !select.sort_by.is_empty()
+        || !select.named_window.is_empty()
+        || select.value_table_mode.is_some()
+        || select.connect_by.is_some();
+    if has_unformatted_clauses {
+        return select.to_string();
+    }
+
+    let mut lines = Vec::new();
+
+    lines.push(match &select.distinct {
+        Some(distinct) => format!("SELECT {}", distinct),
+        None => "SELECT".to_string(),
+    });
+    lines.push(list(select.projection.iter().map(ToString::to_string)));
+
+    if !select.from.is_empty() {
+        let from = select
+            .from
+            .iter()
+            .map(format_table_with_joins)
+            .collect::<Vec<_>>()
+            .join(",\n");
+        lines.push(format!("FROM {}", from));
+    }
+
+    if let Some(selection) = &select.selection {
+        lines.push("WHERE".to_string());
+        lines.push(conditions(selection));
+    }
+
+    match &select.group_by {
+        GroupByExpr::Expressions(exprs, modifiers) if modifiers.is_empty() => {
+            if !exprs.is_empty() {
+                lines.push("GROUP BY".to_string());
+                lines.push(list(exprs.iter().map(ToString::to_string)));
+            }
+        }
+        group_by => lines.push(group_by.to_string()),
+    }
+
+    if let Some(having) = &select.having {
+        lines.push("HAVING".to_string());
+        lines.push(conditions(having));
+    }
+
+    if let Some(qualify) = &select.qualify {
+        lines.push("QUALIFY".to_string());
+        lines.push(conditions(qualify));
+    }
+
+    lines.join("\n")
+}
+
+fn format_table_with_joins(table_with_joins: &TableWithJoins) -> String {
+    let mut lines = vec![format_table_factor(&table_with_joins.relation)];
+    for join in &table_with_joins.joins {
+        lines.push(join.to_string().trim_start().to_string());
+    }
+    lines.join("\n")
+}
+
+fn format_table_factor(factor: &TableFactor) -> String {
+    match factor {
+        TableFactor::Derived {
+            lateral,
+            subquery,
+            alias,
+        } => {
+            let mut formatted = format!(
+                "{}(\n{}\n)",
+                if *lateral { "LATERAL " } else { "" },
+                indent(&format_query(subquery))
+            );
+            if let Some(alias) = alias {
+                formatted.push_str(&format!(" AS {}", alias));
+            }
+            formatted
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Splits top-level `AND` conditions onto their own indented lines.
+fn conditions(expr: &Expr) -> String {
+    let mut operands = Vec::new();
+    split_and(expr, &mut operands);
+    operands
+        .iter()
+        .enumerate()
+        .map(|(i, operand)| {
+            if i == 0 {
+                format!("{}{}", INDENT, operand)
+            } else {
+                format!("{}AND {}", INDENT, operand)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn split_and<'a>(expr: &'a Expr, out: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryOp {
+            left,
+            op: BinaryOperator::And,
+            right,
+        } => {
+            split_and(left, out);
+            split_and(right, out);
+        }
+        other => out.push(other),
+    }
+}
+
+fn list(items: impl Iterator<Item = String>) -> String {
+    items
+        .map(|item| format!("{}{}", INDENT, item))
+        .collect::<Vec<_>>()
+        .join(",\n")
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", INDENT, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -3,7 +3,17 @@
 //! This library provides functionality to parse and analyze SQL queries,
 //! extracting tables, columns, joins, and CTEs with lineage tracing.
 //! It also includes semantic layer validation and substitution capabilities
-//! to support querying with predefined metrics and filters.
+//! to support querying with predefined metrics and filters. Alongside that:
+//!
+//! - [`lint`]: common query mistakes, with suggested fixes
+//! - [`fan_out`]: measures aggregated across one-to-many joins
+//! - [`fingerprint`] and [`format`](mod@format): fingerprints for caching and
+//!   deduplication, and pretty-printing
+//! - [`references`]: tables and columns resolved against deployed datasets
+//! - [`column_filters`]: predicates injected into the outermost `WHERE`
+//! - [`parameters`]: typed values bound to `{{ name }}` placeholders
+//! - [`table_remapping`]: schemas and tables pointed at another data source
+//!
 //! Designed for integration with a Tokio-based web server.
 
 mod errors;
@@ -13,14 +23,34 @@ pub mod utils;
 pub mod analysis;
 pub mod semantic;
 pub mod row_filtering;
+pub mod lint;
+pub mod fan_out;
+pub mod fingerprint;
+pub mod format;
+pub mod references;
+pub mod column_filters;
+pub mod parameters;
+pub mod table_remapping;
 
 pub use errors::SqlAnalyzerError;
 pub use types::{
     QuerySummary, TableInfo, JoinInfo, CteSummary, 
     SemanticLayer, ValidationMode, Metric, Filter, 
-    Parameter, ParameterType, Relationship
+    Parameter, ParameterType, Relationship, Cardinality, FanOutWarning,
+    LintDiagnostic, LintFix, LintOptions, LintRule, LintSeverity, SourceSpan,
+    QueryFingerprint, ReferenceCatalog, CatalogTable, ReferenceError, ReferenceErrorKind,
+    ColumnFilter, ColumnFilterCondition, FilterValue, ParameterValue, WarehouseDialect,
+    TableMapping
 };
 
 pub use analysis::analyze_query;
 pub use semantic::{validate_semantic_query, substitute_semantic_query, validate_and_substitute_semantic_query};
-pub use row_filtering::apply_row_level_filters;
\ No newline at end of file
+pub use row_filtering::apply_row_level_filters;
+pub use lint::lint_sql;
+pub use fan_out::detect_fan_out_joins;
+pub use fingerprint::fingerprint_query;
+pub use format::format_sql;
+pub use references::resolve_references;
+pub use column_filters::apply_column_filters;
+pub use parameters::{bind_parameters, parameter_names};
+pub use table_remapping::remap_tables;
\ No newline at end of file
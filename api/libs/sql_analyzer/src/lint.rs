@@ -0,0 +1,834 @@
+use crate::errors::SqlAnalyzerError;
+use crate::types::{
+    LintDiagnostic, LintFix, LintOptions, LintRule, LintSeverity, SourceSpan,
+};
+use anyhow::Result;
+use sqlparser::ast::{
+    BinaryOperator, Expr, GroupByExpr, Join, JoinConstraint, JoinOperator, Query, Select,
+    SelectItem, SetExpr, Spanned, Statement, TableFactor, TableWithJoins, Value, Visit, Visitor,
+};
+use sqlparser::dialect::GenericDialect;
+use sqlparser::parser::Parser;
+use sqlparser::tokenizer::Span;
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// Functions treated as aggregates when checking GROUP BY coverage.
+const AGGREGATE_FUNCTIONS: &[&str] = &[
+    "any_value",
+    "approx_count_distinct",
+    "approx_percentile",
+    "arbitrary",
+    "array_agg",
+    "avg",
+    "bit_and",
+    "bit_or",
+    "bool_and",
+    "bool_or",
+    "corr",
+    "count",
+    "count_if",
+    "covar_pop",
+    "covar_samp",
+    "every",
+    "group_concat",
+    "json_agg",
+    "jsonb_agg",
+    "listagg",
+    "max",
+    "median",
+    "min",
+    "mode",
+    "object_agg",
+    "percentile_cont",
+    "percentile_disc",
+    "stddev",
+    "stddev_pop",
+    "stddev_samp",
+    "string_agg",
+    "sum",
+    "var_pop",
+    "var_samp",
+    "variance",
+];
+
+/// Lints a SQL query and returns every diagnostic found, ordered by position.
+///
+/// Only read-only `SELECT` queries are accepted. Rules that depend on table
+/// shape (`SELECT *` on wide tables, `ORDER BY` without `LIMIT` on large
+/// tables) only fire for tables described in `options`.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{lint_sql, LintOptions};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let sql = "SELECT o.region, o.amount / o.quantity FROM sales.orders o";
+///     let diagnostics = lint_sql(sql.to_string(), LintOptions::default()).await?;
+///     for diagnostic in diagnostics {
+///         println!("{:?}: {}", diagnostic.rule, diagnostic.message);
+///     }
+///     Ok(())
+/// }
+/// ```
+pub async fn lint_sql(
+    sql: String,
+    options: LintOptions,
+) -> Result<Vec<LintDiagnostic>, SqlAnalyzerError> {
+    tokio::task::spawn_blocking(move || lint_query(&sql, &options))
+        .await
+        .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))?
+}
+
+fn lint_query(sql: &str, options: &LintOptions) -> Result<Vec<LintDiagnostic>, SqlAnalyzerError> {
+    let ast = Parser::parse_sql(&GenericDialect, sql)?;
+
+    for stmt in &ast {
+        if !matches!(stmt, Statement::Query(_)) {
+            return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+                "Only SELECT queries are supported. Found: {}",
+                stmt
+            )));
+        }
+    }
+
+    let mut linter = Linter::new(options);
+    for stmt in &ast {
+        let _ = stmt.visit(&mut linter);
+    }
+
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| {
+        d.span
+            .map(|s| (s.start_line, s.start_column))
+            .unwrap_or((u64::MAX, u64::MAX))
+    });
+    Ok(diagnostics)
+}
+
+/// Converts a parser span into a `SourceSpan`, dropping empty spans.
+fn to_source_span(span: Span) -> Option<SourceSpan> {
+    if span.start.line == 0 {
+        return None;
+    }
+    Some(SourceSpan {
+        start_line: span.start.line,
+        start_column: span.start.column,
+        end_line: span.end.line,
+        end_column: span.end.column,
+    })
+}
+
+/// A relation in a FROM clause, addressed by its alias or table name.
+#[derive(Debug, Clone)]
+struct Relation {
+    name: String,
+    table: Option<String>,
+}
+
+/// A column reference found while scanning an expression.
+#[derive(Debug, Clone)]
+struct ColumnRef {
+    qualifier: Option<String>,
+    name: String,
+    display: String,
+    span: Span,
+    aggregated: bool,
+}
+
+/// A division whose divisor is not guarded against zero.
+#[derive(Debug, Clone)]
+struct Division {
+    numerator: String,
+    denominator: String,
+    span: Span,
+}
+
+/// Collects column references, aggregates and divisions from an expression,
+/// ignoring anything inside nested subqueries (those are linted separately).
+#[derive(Debug, Default)]
+struct ExprScan {
+    query_depth: usize,
+    aggregate_depth: usize,
+    columns: Vec<ColumnRef>,
+    contains_aggregate: bool,
+    divisions: Vec<Division>,
+}
+
+impl ExprScan {
+    fn scan(expr: &Expr) -> Self {
+        let mut scan = ExprScan::default();
+        let _ = expr.visit(&mut scan);
+        scan
+    }
+}
+
+impl Visitor for ExprScan {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.query_depth += 1;
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, _query: &Query) -> ControlFlow<Self::Break> {
+        self.query_depth -= 1;
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if self.query_depth > 0 {
+            return ControlFlow::Continue(());
+        }
+
+        match expr {
+            Expr::Identifier(ident) => self.columns.push(ColumnRef {
+                qualifier: None,
+                name: ident.value.clone(),
+                display: expr.to_string(),
+                span: ident.span,
+                aggregated: self.aggregate_depth > 0,
+            }),
+            Expr::CompoundIdentifier(idents) if idents.len() >= 2 => {
+                self.columns.push(ColumnRef {
+                    qualifier: Some(idents[idents.len() - 2].value.clone()),
+                    name: idents[idents.len() - 1].value.clone(),
+                    display: expr.to_string(),
+                    span: expr.span(),
+                    aggregated: self.aggregate_depth > 0,
+                })
+            }
+            Expr::Function(function) => {
+                if is_aggregate_function(function) {
+                    self.contains_aggregate = true;
+                }
+                if opens_aggregate_context(function) {
+                    self.aggregate_depth += 1;
+                }
+            }
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::Divide,
+                right,
+            } if !is_zero_safe_divisor(right) => self.divisions.push(Division {
+                numerator: left.to_string(),
+                denominator: right.to_string(),
+                span: expr.span(),
+            }),
+            _ => {}
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_expr(&mut self, expr: &Expr) -> ControlFlow<Self::Break> {
+        if self.query_depth > 0 {
+            return ControlFlow::Continue(());
+        }
+
+        if let Expr::Function(function) = expr {
+            if opens_aggregate_context(function) {
+                self.aggregate_depth -= 1;
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+}
+
+fn function_name(function: &sqlparser::ast::Function) -> String {
+    function
+        .name
+        .0
+        .last()
+        .map(|ident| ident.value.to_lowercase())
+        .unwrap_or_default()
+}
+
+fn is_aggregate_function(function: &sqlparser::ast::Function) -> bool {
+    function.over.is_none() && AGGREGATE_FUNCTIONS.contains(&function_name(function).as_str())
+}
+
+/// Aggregates and window functions both make their arguments exempt from
+/// GROUP BY coverage.
+fn opens_aggregate_context(function: &sqlparser::ast::Function) -> bool {
+    function.over.is_some() || is_aggregate_function(function)
+}
+
+/// Returns true when a divisor cannot be zero or is explicitly guarded.
+fn is_zero_safe_divisor(expr: &Expr) -> bool {
+    match expr {
+        Expr::Value(Value::Number(n, _)) => n.parse::<f64>().map(|v| v != 0.0).unwrap_or(false),
+        Expr::Function(function) => function_name(function) == "nullif",
+        Expr::Nested(inner) => is_zero_safe_divisor(inner),
+        Expr::Cast { expr, .. } => is_zero_safe_divisor(expr),
+        Expr::UnaryOp { expr, .. } => is_zero_safe_divisor(expr),
+        // A CASE expression is how zero is usually handled without NULLIF
+        Expr::Case { .. } => true,
+        _ => false,
+    }
+}
+
+fn join_constraint(operator: &JoinOperator) -> Option<&JoinConstraint> {
+    match operator {
+        JoinOperator::Inner(constraint)
+        | JoinOperator::LeftOuter(constraint)
+        | JoinOperator::RightOuter(constraint)
+        | JoinOperator::FullOuter(constraint) => Some(constraint),
+        _ => None,
+    }
+}
+
+fn relations_for_factor(factor: &TableFactor, out: &mut Vec<Relation>) {
+    match factor {
+        TableFactor::Table { name, alias, .. } => {
+            let table = name.0.last().map(|ident| ident.value.clone());
+            let relation_name = alias
+                .as_ref()
+                .map(|a| a.name.value.clone())
+                .or_else(|| table.clone())
+                .unwrap_or_default();
+            out.push(Relation {
+                name: relation_name,
+                table,
+            });
+        }
+        TableFactor::NestedJoin {
+            table_with_joins, ..
+        } => relations_for_table_with_joins(table_with_joins, out),
+        TableFactor::Derived { alias, .. }
+        | TableFactor::TableFunction { alias, .. }
+        | TableFactor::Function { alias, .. }
+        | TableFactor::UNNEST { alias, .. }
+        | TableFactor::Pivot { alias, .. }
+        | TableFactor::Unpivot { alias, .. } => out.push(Relation {
+            name: alias.as_ref().map(|a| a.name.value.clone()).unwrap_or_default(),
+            table: None,
+        }),
+        _ => {}
+    }
+}
+
+fn relations_for_table_with_joins(table_with_joins: &TableWithJoins, out: &mut Vec<Relation>) {
+    relations_for_factor(&table_with_joins.relation, out);
+    for join in &table_with_joins.joins {
+        relations_for_factor(&join.relation, out);
+    }
+}
+
+/// Collects the SELECT blocks that make up a query body, descending through
+/// set operations. Parenthesised queries are linted on their own.
+fn collect_selects<'a>(body: &'a SetExpr, out: &mut Vec<&'a Select>) {
+    match body {
+        SetExpr::Select(select) => out.push(select),
+        SetExpr::SetOperation { left, right, .. } => {
+            collect_selects(left, out);
+            collect_selects(right, out);
+        }
+        _ => {}
+    }
+}
+
+fn normalize(expr: &str) -> String {
+    expr.to_lowercase()
+}
+
+struct Linter<'a> {
+    options: &'a LintOptions,
+    column_counts: HashMap<String, usize>,
+    large_tables: HashSet<String>,
+    diagnostics: Vec<LintDiagnostic>,
+}
+
+impl<'a> Linter<'a> {
+    fn new(options: &'a LintOptions) -> Self {
+        Self {
+            options,
+            column_counts: options
+                .table_column_counts
+                .iter()
+                .map(|(table, count)| (table.to_lowercase(), *count))
+                .collect(),
+            large_tables: options
+                .large_tables
+                .iter()
+                .map(|table| table.to_lowercase())
+                .collect(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn push(
+        &mut self,
+        rule: LintRule,
+        severity: LintSeverity,
+        message: String,
+        span: Span,
+        fix: LintFix,
+    ) {
+        self.diagnostics.push(LintDiagnostic {
+            rule,
+            severity,
+            message,
+            span: to_source_span(span),
+            fix: Some(fix),
+        });
+    }
+
+    fn lint_query(&mut self, query: &Query) {
+        let mut selects = Vec::new();
+        collect_selects(&query.body, &mut selects);
+
+        // ORDER BY only applies to the outer SELECT when there is exactly one
+        let order_by_exprs: Vec<&Expr> = match (&query.order_by, selects.len()) {
+            (Some(order_by), 1) => order_by.exprs.iter().map(|o| &o.expr).collect(),
+            _ => Vec::new(),
+        };
+
+        for select in &selects {
+            self.lint_select(select, &order_by_exprs);
+        }
+
+        self.check_order_by_without_limit(query, &selects);
+    }
+
+    fn lint_select(&mut self, select: &Select, order_by_exprs: &[&Expr]) {
+        let mut relations = Vec::new();
+        for table_with_joins in &select.from {
+            relations_for_table_with_joins(table_with_joins, &mut relations);
+        }
+
+        self.check_select_star(select, &relations);
+        self.check_implicit_cross_joins(select);
+
+        let projection_aliases: HashSet<String> = select
+            .projection
+            .iter()
+            .filter_map(|item| match item {
+                SelectItem::ExprWithAlias { alias, .. } => Some(alias.value.to_lowercase()),
+                _ => None,
+            })
+            .collect();
+
+        let projection_scans: Vec<(usize, &Expr, ExprScan)> = select
+            .projection
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| match item {
+                SelectItem::UnnamedExpr(expr) | SelectItem::ExprWithAlias { expr, .. } => {
+                    Some((i, expr, ExprScan::scan(expr)))
+                }
+                _ => None,
+            })
+            .collect();
+
+        // Expressions that can legitimately refer to projection aliases
+        let mut alias_scopes: Vec<ExprScan> = Vec::new();
+        if let GroupByExpr::Expressions(exprs, _) = &select.group_by {
+            alias_scopes.extend(exprs.iter().map(ExprScan::scan));
+        }
+        if let Some(having) = &select.having {
+            alias_scopes.push(ExprScan::scan(having));
+        }
+        alias_scopes.extend(order_by_exprs.iter().map(|expr| ExprScan::scan(expr)));
+
+        let mut source_scopes: Vec<ExprScan> = Vec::new();
+        if let Some(selection) = &select.selection {
+            source_scopes.push(ExprScan::scan(selection));
+        }
+        for table_with_joins in &select.from {
+            for join in &table_with_joins.joins {
+                if let Some(JoinConstraint::On(expr)) = join_constraint(&join.join_operator) {
+                    source_scopes.push(ExprScan::scan(expr));
+                }
+            }
+        }
+
+        self.check_group_by(select, &projection_scans, &alias_scopes);
+
+        if relations.len() > 1 {
+            let mut reported = HashSet::new();
+            let projection_columns = projection_scans.iter().flat_map(|(_, _, s)| &s.columns);
+            let source_columns = source_scopes.iter().flat_map(|s| &s.columns);
+            let alias_columns = alias_scopes
+                .iter()
+                .flat_map(|s| &s.columns)
+                .filter(|c| !projection_aliases.contains(&c.name.to_lowercase()));
+            for column in projection_columns.chain(source_columns).chain(alias_columns) {
+                if column.qualifier.is_none() && reported.insert(column.name.to_lowercase()) {
+                    self.report_ambiguous_column(column, &relations);
+                }
+            }
+        }
+
+        let divisions: Vec<Division> = projection_scans
+            .iter()
+            .map(|(_, _, s)| s)
+            .chain(source_scopes.iter())
+            .chain(alias_scopes.iter())
+            .flat_map(|s| s.divisions.iter().cloned())
+            .collect();
+        for division in divisions {
+            self.push(
+                LintRule::UnguardedDivision,
+                LintSeverity::Warning,
+                format!(
+                    "Division by `{}` fails or returns an error when the divisor is zero",
+                    division.denominator
+                ),
+                division.span,
+                LintFix {
+                    description: "Wrap the divisor in NULLIF so zero divisors yield NULL"
+                        .to_string(),
+                    replacement: Some(format!(
+                        "{} / NULLIF({}, 0)",
+                        division.numerator, division.denominator
+                    )),
+                },
+            );
+        }
+    }
+
+    fn check_select_star(&mut self, select: &Select, relations: &[Relation]) {
+        for item in &select.projection {
+            let (targets, span): (Vec<&Relation>, Span) = match item {
+                SelectItem::Wildcard(options) => {
+                    (relations.iter().collect(), options.wildcard_token.0.span)
+                }
+                SelectItem::QualifiedWildcard(name, options) => {
+                    let qualifier = name
+                        .0
+                        .last()
+                        .map(|ident| ident.value.to_lowercase())
+                        .unwrap_or_default();
+                    (
+                        relations
+                            .iter()
+                            .filter(|r| r.name.to_lowercase() == qualifier)
+                            .collect(),
+                        name.span().union(&options.wildcard_token.0.span),
+                    )
+                }
+                _ => continue,
+            };
+
+            for relation in targets {
+                let Some(table) = &relation.table else {
+                    continue;
+                };
+                let Some(count) = self.column_counts.get(&table.to_lowercase()).copied() else {
+                    continue;
+                };
+                if count > self.options.wide_table_threshold {
+                    self.push(
+                        LintRule::SelectStarOnWideTable,
+                        LintSeverity::Warning,
+                        format!(
+                            "`SELECT *` reads all {} columns of `{}`",
+                            count, table
+                        ),
+                        span,
+                        LintFix {
+                            description: format!(
+                                "List only the columns of `{}` the query needs",
+                                relation.name
+                            ),
+                            replacement: None,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_implicit_cross_joins(&mut self, select: &Select) {
+        let linked_pairs = select
+            .selection
+            .as_ref()
+            .map(equality_links)
+            .unwrap_or_default();
+
+        let mut previous: Vec<Relation> = Vec::new();
+        for (i, table_with_joins) in select.from.iter().enumerate() {
+            let mut current = Vec::new();
+            relations_for_factor(&table_with_joins.relation, &mut current);
+
+            if i > 0 {
+                let linked = current.iter().any(|r| {
+                    previous.iter().any(|p| {
+                        linked_pairs.contains(&(r.name.to_lowercase(), p.name.to_lowercase()))
+                            || linked_pairs.contains(&(p.name.to_lowercase(), r.name.to_lowercase()))
+                    })
+                }) || linked_pairs.contains(&(String::new(), String::new()));
+
+                if !linked {
+                    let span = table_with_joins.relation.span();
+                    let name = current
+                        .first()
+                        .map(|r| r.name.clone())
+                        .unwrap_or_else(|| table_with_joins.relation.to_string());
+                    self.push(
+                        LintRule::ImplicitCrossJoin,
+                        LintSeverity::Warning,
+                        format!(
+                            "`{}` is listed in FROM without a join condition, producing a cross join",
+                            name
+                        ),
+                        span,
+                        LintFix {
+                            description: format!(
+                                "Use `JOIN {} ON ...` with a join condition, or `CROSS JOIN` if a cartesian product is intended",
+                                table_with_joins.relation
+                            ),
+                            replacement: None,
+                        },
+                    );
+                }
+            }
+            previous.extend(current);
+
+            for join in &table_with_joins.joins {
+                if matches!(
+                    join_constraint(&join.join_operator),
+                    Some(JoinConstraint::None)
+                ) {
+                    self.report_unconstrained_join(join);
+                }
+                relations_for_factor(&join.relation, &mut previous);
+            }
+        }
+    }
+
+    fn report_unconstrained_join(&mut self, join: &Join) {
+        self.push(
+            LintRule::ImplicitCrossJoin,
+            LintSeverity::Warning,
+            format!(
+                "JOIN to `{}` has no ON or USING condition, producing a cross join",
+                join.relation
+            ),
+            join.relation.span(),
+            LintFix {
+                description: "Add an ON condition, or use `CROSS JOIN` if a cartesian product is intended"
+                    .to_string(),
+                replacement: None,
+            },
+        );
+    }
+
+    fn check_group_by(
+        &mut self,
+        select: &Select,
+        projection_scans: &[(usize, &Expr, ExprScan)],
+        alias_scopes: &[ExprScan],
+    ) {
+        let group_exprs: Vec<&Expr> = match &select.group_by {
+            GroupByExpr::Expressions(exprs, _) => exprs.iter().collect(),
+            // GROUP BY ALL groups by every non-aggregated column
+            GroupByExpr::All(_) => return,
+        };
+
+        let is_aggregate_query = !group_exprs.is_empty()
+            || projection_scans.iter().any(|(_, _, s)| s.contains_aggregate)
+            || (select.having.is_some() && alias_scopes.iter().any(|s| s.contains_aggregate));
+        if !is_aggregate_query {
+            return;
+        }
+
+        let grouped: HashSet<String> = group_exprs.iter().map(|e| normalize(&e.to_string())).collect();
+        let grouped_positions: HashSet<usize> = group_exprs
+            .iter()
+            .filter_map(|e| match e {
+                Expr::Value(Value::Number(n, _)) => n.parse::<usize>().ok(),
+                _ => None,
+            })
+            .collect();
+        let grouped_names: Vec<(Option<String>, String)> = group_exprs
+            .iter()
+            .filter_map(|e| match e {
+                Expr::Identifier(ident) => Some((None, ident.value.to_lowercase())),
+                Expr::CompoundIdentifier(idents) if idents.len() >= 2 => Some((
+                    Some(idents[idents.len() - 2].value.to_lowercase()),
+                    idents[idents.len() - 1].value.to_lowercase(),
+                )),
+                _ => None,
+            })
+            .collect();
+
+        // A column can be functionally dependent on a grouped key (e.g. a
+        // primary key), which only the database knows; without any GROUP BY
+        // the query is certain to fail
+        let severity = if group_exprs.is_empty() {
+            LintSeverity::Error
+        } else {
+            LintSeverity::Warning
+        };
+
+        let mut reported = HashSet::new();
+        for (i, expr, scan) in projection_scans {
+            if grouped.contains(&normalize(&expr.to_string())) || grouped_positions.contains(&(i + 1)) {
+                continue;
+            }
+            if let Some(SelectItem::ExprWithAlias { alias, .. }) = select.projection.get(*i) {
+                if grouped.contains(&alias.value.to_lowercase()) {
+                    continue;
+                }
+            }
+
+            for column in scan.columns.iter().filter(|c| !c.aggregated) {
+                let name = column.name.to_lowercase();
+                let qualifier = column.qualifier.as_ref().map(|q| q.to_lowercase());
+                let is_grouped = grouped.contains(&normalize(&column.display))
+                    || grouped_names.iter().any(|(group_qualifier, group_name)| {
+                        *group_name == name
+                            && (group_qualifier.is_none()
+                                || qualifier.is_none()
+                                || *group_qualifier == qualifier)
+                    });
+                if is_grouped || !reported.insert(normalize(&column.display)) {
+                    continue;
+                }
+
+                self.push(
+                    LintRule::UngroupedColumn,
+                    severity,
+                    format!(
+                        "Column `{}` is neither aggregated nor listed in GROUP BY",
+                        column.display
+                    ),
+                    column.span,
+                    LintFix {
+                        description: format!(
+                            "Add `{}` to the GROUP BY clause or wrap it in an aggregate such as MAX({})",
+                            column.display, column.display
+                        ),
+                        replacement: None,
+                    },
+                );
+            }
+        }
+    }
+
+    fn report_ambiguous_column(&mut self, column: &ColumnRef, relations: &[Relation]) {
+        let candidates = relations
+            .iter()
+            .filter(|r| !r.name.is_empty())
+            .map(|r| format!("{}.{}", r.name, column.name))
+            .collect::<Vec<_>>();
+        self.push(
+            LintRule::AmbiguousColumn,
+            LintSeverity::Warning,
+            format!(
+                "Column `{}` is not qualified but the query reads from {} relations",
+                column.name,
+                relations.len()
+            ),
+            column.span,
+            LintFix {
+                description: format!(
+                    "Qualify the column with its table alias, e.g. {}",
+                    candidates.join(" or ")
+                ),
+                replacement: None,
+            },
+        );
+    }
+
+    fn check_order_by_without_limit(&mut self, query: &Query, selects: &[&Select]) {
+        let Some(order_by) = &query.order_by else {
+            return;
+        };
+        if query.limit.is_some() || query.fetch.is_some() || self.large_tables.is_empty() {
+            return;
+        }
+
+        let mut relations = Vec::new();
+        for select in selects {
+            for table_with_joins in &select.from {
+                relations_for_table_with_joins(table_with_joins, &mut relations);
+            }
+        }
+
+        let large: Vec<String> = relations
+            .iter()
+            .filter_map(|r| r.table.clone())
+            .filter(|table| self.large_tables.contains(&table.to_lowercase()))
+            .collect();
+        if large.is_empty() {
+            return;
+        }
+
+        self.push(
+            LintRule::OrderByWithoutLimit,
+            LintSeverity::Warning,
+            format!(
+                "ORDER BY without LIMIT sorts every row of large table(s): {}",
+                large.join(", ")
+            ),
+            order_by.span(),
+            LintFix {
+                description: "Add a LIMIT clause, or remove the ORDER BY if the order is not needed"
+                    .to_string(),
+                replacement: None,
+            },
+        );
+    }
+}
+
+/// Returns the pairs of relation qualifiers linked by an equality predicate.
+/// An unqualified equality between two columns is recorded as `("", "")`,
+/// since it may link any pair of relations.
+fn equality_links(expr: &Expr) -> HashSet<(String, String)> {
+    fn qualifier(expr: &Expr) -> Option<String> {
+        match expr {
+            Expr::Identifier(_) => Some(String::new()),
+            Expr::CompoundIdentifier(idents) if idents.len() >= 2 => {
+                Some(idents[idents.len() - 2].value.to_lowercase())
+            }
+            Expr::Nested(inner) => qualifier(inner),
+            _ => None,
+        }
+    }
+
+    fn walk(expr: &Expr, out: &mut HashSet<(String, String)>) {
+        match expr {
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And,
+                right,
+            } => {
+                walk(left, out);
+                walk(right, out);
+            }
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::Eq,
+                right,
+            } => {
+                if let (Some(l), Some(r)) = (qualifier(left), qualifier(right)) {
+                    if l.is_empty() || r.is_empty() {
+                        out.insert((String::new(), String::new()));
+                    } else {
+                        out.insert((l, r));
+                    }
+                }
+            }
+            Expr::Nested(inner) => walk(inner, out),
+            _ => {}
+        }
+    }
+
+    let mut links = HashSet::new();
+    walk(expr, &mut links);
+    links
+}
+
+impl Visitor for Linter<'_> {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<Self::Break> {
+        self.lint_query(query);
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,243 @@
+use crate::errors::SqlAnalyzerError;
+use crate::types::{ParameterValue, WarehouseDialect};
+use regex::Regex;
+use sqlparser::ast::{visit_expressions_mut, Expr, Value};
+use sqlparser::parser::{Parser, ParserOptions};
+use sqlparser::tokenizer::{Location, Token, Tokenizer, Whitespace};
+use std::collections::{HashMap, HashSet};
+use std::ops::ControlFlow;
+
+/// Prefix of the placeholder tokens `{{ name }}` is rewritten to before parsing
+pub(crate) const PLACEHOLDER_PREFIX: &str = "$__param_";
+
+/// Binds typed values to the `{{ name }}` placeholders in a query.
+///
+/// Placeholders are located in the parsed AST, so they may only stand where
+/// a value could: a placeholder inside a string literal or used as a table or
+/// column name is an error. Each value is written as a literal in the syntax
+/// of `dialect`, escaped for that warehouse, and its text never passes
+/// through the parser. The query is parsed in the syntax of `dialect` and
+/// its own literals are written back as they were. Every placeholder needs
+/// a value; extra values are ignored, as are placeholders in comments.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{bind_parameters, ParameterValue, WarehouseDialect};
+/// use std::collections::HashMap;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let values = HashMap::from([("min_amount".to_string(), ParameterValue::Number(100.0))]);
+///     let sql = bind_parameters(
+///         "SELECT id FROM orders WHERE amount >= {{ min_amount }}".to_string(),
+///         values,
+///         WarehouseDialect::Postgres,
+///     )
+///     .await?;
+///     assert_eq!(sql, "SELECT id FROM orders WHERE amount >= 100");
+///     Ok(())
+/// }
+/// ```
+pub async fn bind_parameters(
+    sql: String,
+    values: HashMap<String, ParameterValue>,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    tokio::task::spawn_blocking(move || bind(&sql, &values, dialect))
+        .await
+        .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))?
+}
+
+/// Names of the `{{ name }}` placeholders in a query, in order of first use.
+/// Placeholders in comments don't count.
+pub fn parameter_names(
+    sql: &str,
+    dialect: WarehouseDialect,
+) -> Result<Vec<String>, SqlAnalyzerError> {
+    Ok(names_in(&without_comments(sql, dialect)?))
+}
+
+fn names_in(sql: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    placeholder_regex()
+        .captures_iter(sql)
+        .map(|captures| captures[1].to_string())
+        .filter(|name| seen.insert(name.clone()))
+        .collect()
+}
+
+pub(crate) fn placeholder_regex() -> Regex {
+    Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_]*)\s*\}\}").unwrap()
+}
+
+/// The query with each comment replaced by a space, found with the dialect's
+/// tokenizer so comment markers inside strings are left alone
+pub(crate) fn without_comments(
+    sql: &str,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    let tokens = Tokenizer::new(dialect.parser_dialect().as_ref(), sql)
+        .with_unescape(false)
+        .tokenize_with_location()?;
+
+    let mut stripped = String::with_capacity(sql.len());
+    let mut end = 0;
+    for token in tokens {
+        if let Token::Whitespace(
+            Whitespace::SingleLineComment { .. } | Whitespace::MultiLineComment(_),
+        ) = token.token
+        {
+            stripped.push_str(&sql[end..byte_offset(sql, token.span.start)]);
+            stripped.push(' ');
+            end = byte_offset(sql, token.span.end);
+        }
+    }
+    stripped.push_str(&sql[end..]);
+    Ok(stripped)
+}
+
+/// Tokenizer locations count lines and characters from 1
+fn byte_offset(sql: &str, location: Location) -> usize {
+    let mut offset = 0;
+    for (index, line) in sql.split_inclusive('\n').enumerate() {
+        if index as u64 + 1 == location.line {
+            return offset
+                + line
+                    .char_indices()
+                    .nth(location.column.saturating_sub(1) as usize)
+                    .map_or(line.len(), |(index, _)| index);
+        }
+        offset += line.len();
+    }
+    sql.len()
+}
+
+/// The parameter a rewritten `{{ name }}` placeholder stands for. MySQL reads
+/// `$` as the start of an identifier, so there it parses as a bare column.
+pub(crate) fn placeholder_name(expr: &Expr) -> Option<&str> {
+    match expr {
+        Expr::Value(Value::Placeholder(placeholder)) => {
+            placeholder.strip_prefix(PLACEHOLDER_PREFIX)
+        }
+        Expr::Identifier(ident) if ident.quote_style.is_none() => {
+            ident.value.strip_prefix(PLACEHOLDER_PREFIX)
+        }
+        _ => None,
+    }
+}
+
+fn bind(
+    sql: &str,
+    values: &HashMap<String, ParameterValue>,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    if !placeholder_regex().is_match(sql) {
+        return Ok(sql.to_string());
+    }
+    let uncommented = without_comments(sql, dialect)?;
+    let names = names_in(&uncommented);
+    if names.is_empty() {
+        return Ok(sql.to_string());
+    }
+    if let Some(missing) = names.iter().find(|name| !values.contains_key(*name)) {
+        return Err(SqlAnalyzerError::MissingParameter(missing.clone()));
+    }
+
+    let literals = names
+        .iter()
+        .map(|name| Ok((name.clone(), literal(name, &values[name], dialect)?)))
+        .collect::<Result<HashMap<_, _>, SqlAnalyzerError>>()?;
+
+    let tokenized = placeholder_regex().replace_all(&uncommented, |captures: &regex::Captures| {
+        format!("{}{}", PLACEHOLDER_PREFIX, &captures[1])
+    });
+    // Literals are kept unescaped so they print back in the query's own syntax
+    let mut statements = Parser::new(dialect.parser_dialect().as_ref())
+        .with_options(ParserOptions::new().with_unescape(false))
+        .try_with_sql(&tokenized)?
+        .parse_statements()?;
+
+    let mut bound = HashSet::new();
+    let _ = visit_expressions_mut(&mut statements, |expr| {
+        if let Some(name) = placeholder_name(expr) {
+            if let Some(literal) = literals.get(name) {
+                bound.insert(name.to_string());
+                // Placeholders print verbatim, so the literal keeps the
+                // dialect's own escaping rather than sqlparser's
+                *expr = Expr::Value(Value::Placeholder(literal.clone()));
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    if let Some(unbound) = names.iter().find(|name| !bound.contains(*name)) {
+        return Err(SqlAnalyzerError::InvalidParameter(format!(
+            "'{{{{ {} }}}}' must be used as a value, not inside a string literal or as a name",
+            unbound
+        )));
+    }
+
+    Ok(statements
+        .iter()
+        .map(|statement| statement.to_string())
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+fn literal(
+    name: &str,
+    value: &ParameterValue,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    match value {
+        ParameterValue::String(s) => Ok(string_literal(s, dialect)),
+        ParameterValue::Number(n) if n.is_finite() => Ok(n.to_string()),
+        ParameterValue::Number(n) => Err(SqlAnalyzerError::InvalidParameter(format!(
+            "{} is not a valid value for '{}'",
+            n, name
+        ))),
+        ParameterValue::Date(date) if is_iso_date(date) => Ok(format!("CAST('{}' AS DATE)", date)),
+        ParameterValue::Date(date) => Err(SqlAnalyzerError::InvalidParameter(format!(
+            "'{}' is not a YYYY-MM-DD date for '{}'",
+            date, name
+        ))),
+        ParameterValue::Boolean(b) => Ok(match (dialect, b) {
+            // SQL Server has no boolean literals
+            (WarehouseDialect::SqlServer, true) => "1".to_string(),
+            (WarehouseDialect::SqlServer, false) => "0".to_string(),
+            (_, true) => "TRUE".to_string(),
+            (_, false) => "FALSE".to_string(),
+        }),
+    }
+}
+
+pub(crate) fn string_literal(value: &str, dialect: WarehouseDialect) -> String {
+    match dialect {
+        // Standard SQL strings, where only a doubled quote is special
+        WarehouseDialect::Postgres | WarehouseDialect::SqlServer => {
+            format!("'{}'", value.replace('\'', "''"))
+        }
+        // Backslash starts an escape sequence in these warehouses' strings
+        WarehouseDialect::Redshift
+        | WarehouseDialect::Snowflake
+        | WarehouseDialect::BigQuery
+        | WarehouseDialect::Databricks
+        | WarehouseDialect::MySql => {
+            format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+        }
+    }
+}
+
+fn is_iso_date(value: &str) -> bool {
+    let parts: Vec<&str> = value.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return false;
+    };
+    let digits =
+        |part: &str, len: usize| part.len() == len && part.bytes().all(|b| b.is_ascii_digit());
+    digits(year, 4)
+        && digits(month, 2)
+        && digits(day, 2)
+        && (1..=12).contains(&month.parse::<u32>().unwrap_or(0))
+        && (1..=31).contains(&day.parse::<u32>().unwrap_or(0))
+}
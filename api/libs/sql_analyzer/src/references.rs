@@ -0,0 +1,168 @@
+use crate::types::{
+    CatalogTable, QuerySummary, ReferenceCatalog, ReferenceError, ReferenceErrorKind, TableKind,
+};
+
+/// Resolves every base table and column in an analyzed query against a
+/// catalog of deployed datasets.
+///
+/// Reports tables that are not datasets, datasets the user may not query,
+/// and columns missing from a dataset's definition. Unknown names get a
+/// "did you mean" suggestion drawn from the datasets the user can access.
+/// Column checks are skipped for datasets with no recorded columns, since
+/// an empty definition says nothing about which columns exist.
+///
+/// Names are compared case-insensitively.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{analyze_query, resolve_references, CatalogTable, ReferenceCatalog};
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let summary = analyze_query("SELECT o.amout FROM sales.orders o".to_string()).await?;
+///     let catalog = ReferenceCatalog {
+///         tables: vec![CatalogTable {
+///             name: "orders".to_string(),
+///             columns: vec!["id".to_string(), "amount".to_string()],
+///             accessible: true,
+///         }],
+///     };
+///     for error in resolve_references(&summary, &catalog) {
+///         println!("{}", error.message); // Column `amout` does not exist on dataset `orders`. Did you mean `amount`?
+///     }
+///     Ok(())
+/// }
+/// ```
+pub fn resolve_references(summary: &QuerySummary, catalog: &ReferenceCatalog) -> Vec<ReferenceError> {
+    let mut errors = Vec::new();
+    let mut checked_tables = Vec::new();
+
+    let accessible_tables: Vec<&str> = catalog
+        .tables
+        .iter()
+        .filter(|t| t.accessible)
+        .map(|t| t.name.as_str())
+        .collect();
+
+    for table in summary.tables.iter().filter(|t| t.kind == TableKind::Base) {
+        let name = &table.table_identifier;
+        if checked_tables.contains(&name.to_lowercase()) {
+            continue;
+        }
+        checked_tables.push(name.to_lowercase());
+
+        let Some(dataset) = find_table(catalog, name) else {
+            let suggestion = closest_match(name, &accessible_tables);
+            errors.push(ReferenceError {
+                kind: ReferenceErrorKind::UnknownTable,
+                table: name.clone(),
+                column: None,
+                message: with_suggestion(
+                    format!("Table `{}` is not a dataset in this data source.", name),
+                    suggestion.as_deref(),
+                ),
+                suggestion,
+            });
+            continue;
+        };
+
+        if !dataset.accessible {
+            errors.push(ReferenceError {
+                kind: ReferenceErrorKind::TableNotPermitted,
+                table: dataset.name.clone(),
+                column: None,
+                message: format!("You do not have access to dataset `{}`.", dataset.name),
+                suggestion: None,
+            });
+            continue;
+        }
+
+        if dataset.columns.is_empty() {
+            continue;
+        }
+
+        let candidates: Vec<&str> = dataset.columns.iter().map(String::as_str).collect();
+        // Merge columns from every occurrence of the table (e.g. self joins)
+        let mut referenced: Vec<&String> = summary
+            .tables
+            .iter()
+            .filter(|t| t.kind == TableKind::Base && t.table_identifier.eq_ignore_ascii_case(name))
+            .flat_map(|t| t.columns.iter())
+            .collect();
+        referenced.sort();
+        referenced.dedup();
+
+        for column in referenced {
+            if candidates.iter().any(|c| c.eq_ignore_ascii_case(column)) {
+                continue;
+            }
+            let suggestion = closest_match(column, &candidates);
+            errors.push(ReferenceError {
+                kind: ReferenceErrorKind::UnknownColumn,
+                table: dataset.name.clone(),
+                column: Some(column.clone()),
+                message: with_suggestion(
+                    format!(
+                        "Column `{}` does not exist on dataset `{}`.",
+                        column, dataset.name
+                    ),
+                    suggestion.as_deref(),
+                ),
+                suggestion,
+            });
+        }
+    }
+
+    errors
+}
+
+fn find_table<'a>(catalog: &'a ReferenceCatalog, name: &str) -> Option<&'a CatalogTable> {
+    catalog
+        .tables
+        .iter()
+        .find(|t| t.name.eq_ignore_ascii_case(name))
+}
+
+fn with_suggestion(message: String, suggestion: Option<&str>) -> String {
+    match suggestion {
+        Some(suggestion) => format!("{} Did you mean `{}`?", message, suggestion),
+        None => message,
+    }
+}
+
+/// Picks the candidate closest to `name`, if any is close enough to be a
+/// plausible typo or a missing/extra prefix or suffix.
+fn closest_match(name: &str, candidates: &[&str]) -> Option<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    candidates
+        .iter()
+        .filter_map(|candidate| {
+            let lowered = candidate.to_lowercase();
+            let distance = levenshtein(&name, &lowered);
+            let related = distance <= max_distance
+                || (name.len() >= 3
+                    && lowered.len() >= 3
+                    && (lowered.contains(&name) || name.contains(&lowered)));
+            related.then_some((distance, *candidate))
+        })
+        .min_by_key(|(distance, candidate)| (*distance, candidate.len()))
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
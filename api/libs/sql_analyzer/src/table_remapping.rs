@@ -0,0 +1,290 @@
+use crate::errors::SqlAnalyzerError;
+use crate::parameters::{
+    placeholder_name, placeholder_regex, without_comments, PLACEHOLDER_PREFIX,
+};
+use crate::types::{TableMapping, WarehouseDialect};
+use sqlparser::ast::{
+    visit_expressions_mut, Expr, Ident, ObjectName, Query, Statement, TableFactor, Value, Visit,
+    VisitMut, Visitor, VisitorMut,
+};
+use sqlparser::parser::{Parser, ParserOptions};
+use std::collections::HashSet;
+use std::ops::ControlFlow;
+
+/// Points a query's tables at another schema or under other names.
+///
+/// Table references are rewritten in the parsed AST, so names inside string
+/// literals, column names and CTE names are left alone. A table is matched
+/// by `schema.table` first, then by its bare name; its schema is then
+/// renamed if the mapping has an entry for it. Columns qualified with a
+/// renamed table's name (`orders.amount` or `sales.orders.amount`) follow
+/// the rename. `{{ name }}` parameter placeholders are kept as they are.
+/// The query is parsed in the syntax of `dialect` and its literals are
+/// written back as they were; with an empty mapping it is returned
+/// untouched.
+///
+/// # Examples
+/// ```no_run
+/// use sql_analyzer::{remap_tables, TableMapping, WarehouseDialect};
+/// use std::collections::HashMap;
+///
+/// #[tokio::main]
+/// async fn main() -> anyhow::Result<()> {
+///     let mapping = TableMapping {
+///         schemas: HashMap::from([("sales".to_string(), "sales_eu".to_string())]),
+///         tables: HashMap::new(),
+///     };
+///     let sql = remap_tables(
+///         "SELECT SUM(amount) FROM sales.orders".to_string(),
+///         mapping,
+///         WarehouseDialect::Postgres,
+///     )
+///     .await?;
+///     assert_eq!(sql, "SELECT SUM(amount) FROM sales_eu.orders");
+///     Ok(())
+/// }
+/// ```
+pub async fn remap_tables(
+    sql: String,
+    mapping: TableMapping,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    tokio::task::spawn_blocking(move || remap(&sql, &mapping, dialect))
+        .await
+        .map_err(|e| SqlAnalyzerError::Internal(anyhow::anyhow!("Task join error: {}", e)))?
+}
+
+fn remap(
+    sql: &str,
+    mapping: &TableMapping,
+    dialect: WarehouseDialect,
+) -> Result<String, SqlAnalyzerError> {
+    if mapping.schemas.is_empty() && mapping.tables.is_empty() {
+        return Ok(sql.to_string());
+    }
+    for name in mapping.schemas.values().chain(mapping.tables.values()) {
+        if !is_qualified_name(name) {
+            return Err(SqlAnalyzerError::InvalidParameter(format!(
+                "'{}' is not a valid schema or table name",
+                name
+            )));
+        }
+    }
+
+    let uncommented = without_comments(sql, dialect)?;
+    let tokenized = placeholder_regex().replace_all(&uncommented, |captures: &regex::Captures| {
+        format!("{}{}", PLACEHOLDER_PREFIX, &captures[1])
+    });
+    // Literals are kept unescaped so they print back in the query's own syntax
+    let mut statements = Parser::new(dialect.parser_dialect().as_ref())
+        .with_options(ParserOptions::new().with_unescape(false))
+        .try_with_sql(&tokenized)?
+        .parse_statements()?;
+    if statements.len() != 1 {
+        return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+            "expected a single query, found {} statements",
+            statements.len()
+        )));
+    }
+    if !matches!(statements[0], Statement::Query(_)) {
+        return Err(SqlAnalyzerError::UnsupportedStatement(format!(
+            "tables can only be remapped in queries, found: {}",
+            statements[0]
+        )));
+    }
+
+    let mut ctes = CteNames::default();
+    let _ = statements.visit(&mut ctes);
+
+    let mut tables = TableRenamer {
+        mapping,
+        ctes: ctes.0,
+        renamed: Vec::new(),
+    };
+    let _ = VisitMut::visit(&mut statements, &mut tables);
+    let renamed = tables.renamed;
+
+    let _ = visit_expressions_mut(&mut statements, |expr| {
+        // Placeholders print verbatim, so they go back as written
+        if let Some(name) = placeholder_name(expr) {
+            *expr = Expr::Value(Value::Placeholder(format!("{{{{ {} }}}}", name)));
+        } else if let Expr::CompoundIdentifier(idents) = expr {
+            if let Some(prefix) = renamed_prefix(&renamed, &idents[..idents.len() - 1]) {
+                let column = idents.pop();
+                *idents = prefix.into_iter().chain(column).collect();
+            }
+        }
+        ControlFlow::<()>::Continue(())
+    });
+
+    Ok(statements[0].to_string())
+}
+
+/// Names of every CTE in the query, lowercased, which are never remapped
+#[derive(Default)]
+struct CteNames(HashSet<String>);
+
+impl Visitor for CteNames {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        if let Some(with) = &query.with {
+            for cte in &with.cte_tables {
+                self.0.insert(cte.alias.name.value.to_lowercase());
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// A table reference that was rewritten, and whether columns can refer to
+/// it by name (they can't once it has an alias)
+struct Renamed {
+    from: Vec<Ident>,
+    to: Vec<Ident>,
+    aliased: bool,
+}
+
+struct TableRenamer<'a> {
+    mapping: &'a TableMapping,
+    ctes: HashSet<String>,
+    renamed: Vec<Renamed>,
+}
+
+impl VisitorMut for TableRenamer<'_> {
+    type Break = ();
+
+    fn pre_visit_table_factor(&mut self, table_factor: &mut TableFactor) -> ControlFlow<()> {
+        let TableFactor::Table {
+            name, alias, args, ..
+        } = table_factor
+        else {
+            return ControlFlow::Continue(());
+        };
+        // Table-valued functions aren't tables
+        if args.is_some() {
+            return ControlFlow::Continue(());
+        }
+        let ObjectName(idents) = name;
+        if idents.len() == 1 && self.ctes.contains(&idents[0].value.to_lowercase()) {
+            return ControlFlow::Continue(());
+        }
+
+        let remapped = remap_name(self.mapping, idents);
+        if remapped != *idents {
+            self.renamed.push(Renamed {
+                from: idents.clone(),
+                to: remapped.clone(),
+                aliased: alias.is_some(),
+            });
+            *idents = remapped;
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+/// The new name for a table reference, or the same name if the mapping
+/// doesn't cover it
+fn remap_name(mapping: &TableMapping, idents: &[Ident]) -> Vec<Ident> {
+    let Some((table, schema)) = idents.split_last() else {
+        return idents.to_vec();
+    };
+
+    // A qualified table entry (`sales.orders`) wins over a bare one
+    let qualified = schema
+        .last()
+        .and_then(|s| lookup(&mapping.tables, &format!("{}.{}", s.value, table.value)));
+    let table_entry = qualified.or_else(|| lookup(&mapping.tables, &table.value));
+
+    let mut schema = schema.to_vec();
+    let table = match table_entry {
+        Some(entry) => {
+            let mut parts = idents_like(entry, table);
+            let new_table = parts.pop().unwrap_or_else(|| table.clone());
+            if !parts.is_empty() {
+                // A qualified value moves the table to that schema outright
+                return parts.into_iter().chain(Some(new_table)).collect();
+            }
+            new_table
+        }
+        None => table.clone(),
+    };
+
+    let schema_name = join(&schema);
+    if let Some(entry) = lookup(&mapping.schemas, &schema_name) {
+        let template = schema.last().cloned().unwrap_or_else(|| table.clone());
+        schema = idents_like(entry, &template);
+    } else if let Some(last) = schema.last().cloned() {
+        if let Some(entry) = lookup(&mapping.schemas, &last.value) {
+            schema.pop();
+            schema.extend(idents_like(entry, &last));
+        }
+    }
+
+    schema.into_iter().chain(Some(table)).collect()
+}
+
+/// The rewritten form of a column's qualifier, if it names a table that was
+/// renamed: in full (`sales.orders`) or, for unaliased tables, by its bare
+/// name (`orders`)
+fn renamed_prefix(renamed: &[Renamed], prefix: &[Ident]) -> Option<Vec<Ident>> {
+    if prefix.is_empty() {
+        return None;
+    }
+    renamed.iter().find_map(|r| {
+        if r.aliased {
+            return None;
+        }
+        if same_name(prefix, &r.from) {
+            return Some(r.to.clone());
+        }
+        let bare = prefix.len() == 1 && r.from.len() > 1;
+        (bare && same_name(prefix, &r.from[r.from.len() - 1..]))
+            .then(|| r.to[r.to.len() - 1..].to_vec())
+    })
+}
+
+fn same_name(a: &[Ident], b: &[Ident]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(a, b)| a.value.eq_ignore_ascii_case(&b.value))
+}
+
+fn lookup<'a>(
+    entries: &'a std::collections::HashMap<String, String>,
+    name: &str,
+) -> Option<&'a str> {
+    entries
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Splits a dotted name into identifiers quoted the way `like` is
+fn idents_like(name: &str, like: &Ident) -> Vec<Ident> {
+    name.split('.')
+        .map(|part| Ident {
+            value: part.to_string(),
+            quote_style: like.quote_style,
+            span: like.span,
+        })
+        .collect()
+}
+
+fn join(idents: &[Ident]) -> String {
+    idents
+        .iter()
+        .map(|ident| ident.value.as_str())
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Plain or dot-qualified identifier, e.g. `sales_eu` or `analytics.sales_eu`
+fn is_qualified_name(name: &str) -> bool {
+    name.split('.').all(|part| {
+        let mut chars = part.chars();
+        matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+    })
+}
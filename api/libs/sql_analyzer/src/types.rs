@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use sqlparser::dialect::{
+    BigQueryDialect, DatabricksDialect, Dialect, MsSqlDialect, MySqlDialect, PostgreSqlDialect,
+    RedshiftSqlDialect, SnowflakeDialect,
+};
 use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 
@@ -114,6 +118,62 @@ pub struct Relationship {
     pub from_column: String,
     pub to_table: String,
     pub to_column: String,
+    /// Cardinality from `from_table` to `to_table`, when known
+    pub cardinality: Option<Cardinality>,
+}
+
+/// Cardinality of a relationship, read from the `from` side to the `to` side
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Cardinality {
+    OneToOne,
+    OneToMany,
+    ManyToOne,
+    ManyToMany,
+}
+
+impl Cardinality {
+    /// Parses the cardinality strings used in model files and `entity_relationship`
+    /// (e.g. `one-to-many`, `one_to_many`, `1:N`)
+    pub fn parse(value: &str) -> Option<Self> {
+        let normalized = value.trim().to_lowercase().replace(['_', ' '], "-");
+        match normalized.as_str() {
+            "one-to-one" | "1:1" => Some(Cardinality::OneToOne),
+            "one-to-many" | "1:n" | "1:m" => Some(Cardinality::OneToMany),
+            "many-to-one" | "n:1" | "m:1" => Some(Cardinality::ManyToOne),
+            "many-to-many" | "n:n" | "m:n" | "n:m" => Some(Cardinality::ManyToMany),
+            _ => None,
+        }
+    }
+
+    /// The same relationship read from the `to` side
+    pub fn reversed(self) -> Self {
+        match self {
+            Cardinality::OneToMany => Cardinality::ManyToOne,
+            Cardinality::ManyToOne => Cardinality::OneToMany,
+            other => other,
+        }
+    }
+
+    /// Whether each row on the `from` side can match several rows on the `to` side
+    pub fn fans_out(self) -> bool {
+        matches!(self, Cardinality::OneToMany | Cardinality::ManyToMany)
+    }
+}
+
+/// A measure aggregated across a join that duplicates its rows
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FanOutWarning {
+    /// Table whose rows are duplicated by the join
+    pub table: String,
+    /// The aggregate expression that is inflated, e.g. `SUM(o.shipping_cost)`
+    pub measure: String,
+    /// Table on the "many" side of the join that causes the duplication
+    pub fanned_out_by: String,
+    pub cardinality: Cardinality,
+    pub message: String,
+    /// A CTE that pre-aggregates the measure before the join
+    pub suggestion: String,
 }
 
 /// Validation modes for semantic layer queries
@@ -168,6 +228,20 @@ impl SemanticLayer {
         self.relationships.push(relationship);
     }
 
+    /// Returns the cardinality from `from_table` to `to_table`, reading a
+    /// relationship declared in the opposite direction in reverse
+    pub fn relationship_cardinality(&self, from_table: &str, to_table: &str) -> Option<Cardinality> {
+        self.relationships.iter().find_map(|r| {
+            if r.from_table == from_table && r.to_table == to_table {
+                r.cardinality
+            } else if r.from_table == to_table && r.to_table == from_table {
+                r.cardinality.map(Cardinality::reversed)
+            } else {
+                None
+            }
+        })
+    }
+
     /// Checks if two tables are directly related in the semantic layer
     pub fn are_tables_related(&self, table1: &str, table2: &str) -> bool {
         self.relationships.iter().any(|r| {
@@ -209,3 +283,227 @@ impl SemanticLayer {
             .unwrap_or(false)
     }
 }
+
+/// Severity of a lint diagnostic
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LintSeverity {
+    /// The query is almost certainly wrong and will fail or return incorrect results
+    Error,
+    /// The query runs but is likely to be slow, fragile, or subtly incorrect
+    Warning,
+}
+
+/// The rule that produced a lint diagnostic
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LintRule {
+    /// `SELECT *` on a table with more columns than the configured threshold
+    SelectStarOnWideTable,
+    /// A non-aggregated column in the projection is missing from GROUP BY.
+    /// An error only when the query has no GROUP BY at all.
+    UngroupedColumn,
+    /// An unqualified column in a query that reads from more than one relation
+    AmbiguousColumn,
+    /// A comma join or JOIN without a condition that produces a cartesian product
+    ImplicitCrossJoin,
+    /// `ORDER BY` without `LIMIT` on a table known to be large
+    OrderByWithoutLimit,
+    /// Division whose divisor is not guarded against zero with NULLIF
+    UnguardedDivision,
+}
+
+/// A 1-based line/column range in the original SQL text
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct SourceSpan {
+    pub start_line: u64,
+    pub start_column: u64,
+    pub end_line: u64,
+    pub end_column: u64,
+}
+
+/// A suggested change that resolves a lint diagnostic
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LintFix {
+    pub description: String,
+    /// Replacement text for the diagnostic's span, when the fix is mechanical
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<String>,
+}
+
+/// A single issue found while linting a SQL query
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LintDiagnostic {
+    pub rule: LintRule,
+    pub severity: LintSeverity,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub span: Option<SourceSpan>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fix: Option<LintFix>,
+}
+
+/// Table metadata used by the lint rules that depend on table shape or size
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintOptions {
+    /// Table name -> number of columns, used for `SELECT *` checks
+    pub table_column_counts: HashMap<String, usize>,
+    /// Tables with more columns than this are considered wide
+    pub wide_table_threshold: usize,
+    /// Names of tables large enough that unbounded sorts are expensive
+    pub large_tables: HashSet<String>,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            table_column_counts: HashMap::new(),
+            wide_table_threshold: 20,
+            large_tables: HashSet::new(),
+        }
+    }
+}
+
+/// Canonical identity of a query, stable across literal values, whitespace,
+/// identifier case and the order of commutative predicates
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct QueryFingerprint {
+    /// Hex-encoded SHA-256 of `normalized_sql`
+    pub fingerprint: String,
+    /// The query with literals replaced by `?` and predicates in canonical order
+    pub normalized_sql: String,
+}
+
+/// Deployed datasets that query references are resolved against
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ReferenceCatalog {
+    pub tables: Vec<CatalogTable>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CatalogTable {
+    pub name: String,
+    /// Column names from the dataset definition; empty when unknown
+    pub columns: Vec<String>,
+    /// Whether the user may query this dataset
+    pub accessible: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceErrorKind {
+    UnknownTable,
+    UnknownColumn,
+    TableNotPermitted,
+}
+
+/// A table or column in a query that doesn't resolve to a permitted dataset
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReferenceError {
+    pub kind: ReferenceErrorKind,
+    pub table: String,
+    pub column: Option<String>,
+    pub message: String,
+    /// Closest known table or column name, if any is similar enough
+    pub suggestion: Option<String>,
+}
+
+/// A predicate on a single column of a query's result source, injected into
+/// the outermost `WHERE` clause by [`crate::apply_column_filters`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColumnFilter {
+    /// Column name, optionally qualified (`orders.created_at`)
+    pub column: String,
+    pub condition: ColumnFilterCondition,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ColumnFilterCondition {
+    /// `column = value`
+    Equals { value: FilterValue },
+    /// `column IN (values...)`
+    In { values: Vec<FilterValue> },
+    /// `column >= min AND column < max` (`<= max` when `max_inclusive`);
+    /// either bound may be omitted
+    Range {
+        min: Option<FilterValue>,
+        max: Option<FilterValue>,
+        max_inclusive: bool,
+    },
+}
+
+/// A literal compared against a filtered column. Values are always emitted
+/// as SQL literals, never spliced into the query text.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FilterValue {
+    Number(f64),
+    String(String),
+}
+
+/// A typed value bound to a `{{ name }}` placeholder by [`crate::bind_parameters`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum ParameterValue {
+    String(String),
+    Number(f64),
+    /// A calendar date formatted as `YYYY-MM-DD`
+    Date(String),
+    Boolean(bool),
+}
+
+/// Warehouse a query runs on, which decides how bound literals are written
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WarehouseDialect {
+    Postgres,
+    Redshift,
+    Snowflake,
+    BigQuery,
+    Databricks,
+    MySql,
+    SqlServer,
+}
+
+impl WarehouseDialect {
+    /// Maps a data source type (e.g. `postgres`, `supabase`, `mariadb`) to
+    /// the dialect its queries are written in.
+    pub fn from_data_source_type(data_source_type: &str) -> Option<Self> {
+        match data_source_type {
+            "postgres" | "supabase" => Some(WarehouseDialect::Postgres),
+            "redshift" => Some(WarehouseDialect::Redshift),
+            "snowflake" => Some(WarehouseDialect::Snowflake),
+            "bigquery" => Some(WarehouseDialect::BigQuery),
+            "databricks" => Some(WarehouseDialect::Databricks),
+            "mysql" | "mariadb" => Some(WarehouseDialect::MySql),
+            "sqlserver" => Some(WarehouseDialect::SqlServer),
+            _ => None,
+        }
+    }
+
+    /// The sqlparser dialect this warehouse's queries are parsed with
+    pub(crate) fn parser_dialect(&self) -> Box<dyn Dialect> {
+        match self {
+            WarehouseDialect::Postgres => Box::new(PostgreSqlDialect {}),
+            WarehouseDialect::Redshift => Box::new(RedshiftSqlDialect {}),
+            WarehouseDialect::Snowflake => Box::new(SnowflakeDialect),
+            WarehouseDialect::BigQuery => Box::new(BigQueryDialect),
+            WarehouseDialect::Databricks => Box::new(DatabricksDialect),
+            WarehouseDialect::MySql => Box::new(MySqlDialect {}),
+            WarehouseDialect::SqlServer => Box::new(MsSqlDialect {}),
+        }
+    }
+}
+
+/// Schema and table renames applied by [`crate::remap_tables`]. Keys match
+/// case-insensitively; table keys may be bare (`orders`) or qualified
+/// (`sales.orders`), and a qualified value (`sales_eu.orders`) moves the
+/// table to that schema.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableMapping {
+    #[serde(default)]
+    pub schemas: HashMap<String, String>,
+    #[serde(default)]
+    pub tables: HashMap<String, String>,
+}
@@ -1,4 +1,5 @@
 use crate::errors::SqlAnalyzerError;
+use crate::fan_out::find_fan_outs;
 use crate::types::{
     SemanticLayer, ValidationMode, Metric, Filter, Parameter, ParameterType
 };
@@ -1485,7 +1486,17 @@ pub fn validate_query(
         stmt.visit(&mut validator);
     }
     
-    let errors = validator.get_errors();
+    let mut errors = validator.get_errors();
+
+    // Aggregating across a fanned-out join silently inflates measures
+    if mode == ValidationMode::Strict {
+        errors.extend(
+            find_fan_outs(&ast, semantic_layer)
+                .into_iter()
+                .map(|w| format!("{} {}", w.message, w.suggestion)),
+        );
+    }
+
     if errors.is_empty() {
         Ok(())
     } else {
@@ -0,0 +1,170 @@
+use sql_analyzer::{
+    apply_column_filters, ColumnFilter, ColumnFilterCondition, FilterValue, SqlAnalyzerError,
+    WarehouseDialect,
+};
+
+fn string(value: &str) -> FilterValue {
+    FilterValue::String(value.to_string())
+}
+
+fn filter(column: &str, condition: ColumnFilterCondition) -> ColumnFilter {
+    ColumnFilter {
+        column: column.to_string(),
+        condition,
+    }
+}
+
+#[tokio::test]
+async fn test_filters_are_anded_onto_existing_where() {
+    let sql = "SELECT o.region, SUM(o.amount) FROM orders o WHERE o.status = 'paid' OR o.status = 'shipped' GROUP BY o.region";
+    let filters = vec![
+        filter(
+            "o.created_at",
+            ColumnFilterCondition::Range {
+                min: Some(string("2024-01-01")),
+                max: Some(string("2024-02-01")),
+                max_inclusive: false,
+            },
+        ),
+        filter(
+            "o.region",
+            ColumnFilterCondition::In {
+                values: vec![string("EU"), string("US")],
+            },
+        ),
+    ];
+
+    let result = apply_column_filters(sql.to_string(), filters, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "SELECT o.region, SUM(o.amount) FROM orders AS o \
+         WHERE (o.status = 'paid' OR o.status = 'shipped') \
+         AND o.created_at >= '2024-01-01' AND o.created_at < '2024-02-01' \
+         AND o.region IN ('EU', 'US') GROUP BY o.region"
+    );
+}
+
+#[tokio::test]
+async fn test_filter_values_cannot_inject_sql() {
+    let filters = vec![filter(
+        "name",
+        ColumnFilterCondition::Equals {
+            value: string("x' OR '1'='1"),
+        },
+    )];
+
+    let result = apply_column_filters(
+        "SELECT id FROM users".to_string(),
+        filters,
+        WarehouseDialect::Postgres,
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        result,
+        "SELECT id FROM users WHERE name = 'x'' OR ''1''=''1'"
+    );
+}
+
+#[tokio::test]
+async fn test_numeric_range_with_one_bound_and_outer_select_only() {
+    let sql =
+        "WITH big AS (SELECT * FROM orders WHERE amount > 10) SELECT customer_id, amount FROM big";
+    let filters = vec![filter(
+        "amount",
+        ColumnFilterCondition::Range {
+            min: None,
+            max: Some(FilterValue::Number(500.0)),
+            max_inclusive: true,
+        },
+    )];
+
+    let result = apply_column_filters(sql.to_string(), filters, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "WITH big AS (SELECT * FROM orders WHERE amount > 10) SELECT customer_id, amount FROM big WHERE amount <= 500"
+    );
+}
+
+#[tokio::test]
+async fn test_invalid_columns_and_set_operations_are_rejected() {
+    let equals = ColumnFilterCondition::Equals { value: string("a") };
+
+    let bad_column = apply_column_filters(
+        "SELECT id FROM users".to_string(),
+        vec![filter("name; DROP TABLE users", equals.clone())],
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(
+        bad_column,
+        Err(SqlAnalyzerError::InvalidParameter(_))
+    ));
+
+    let union = apply_column_filters(
+        "SELECT id FROM users UNION SELECT id FROM admins".to_string(),
+        vec![filter("id", equals)],
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(
+        union,
+        Err(SqlAnalyzerError::UnsupportedStatement(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_queries_parse_and_values_escape_in_the_warehouse_dialect() {
+    let value = |s: &str| {
+        vec![filter(
+            "region",
+            ColumnFilterCondition::Equals { value: string(s) },
+        )]
+    };
+
+    // Bracketed identifiers only parse as SQL Server
+    let sql_server = apply_column_filters(
+        "SELECT [order id], region FROM [dbo].[orders]".to_string(),
+        value("EU"),
+        WarehouseDialect::SqlServer,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        sql_server,
+        "SELECT [order id], region FROM [dbo].[orders] WHERE region = 'EU'"
+    );
+
+    // Backslash escapes in the query are kept, and values are escaped the same way
+    let mysql = apply_column_filters(
+        r"SELECT region FROM orders WHERE note <> 'it\'s'".to_string(),
+        value(r"x\' OR 1=1 --"),
+        WarehouseDialect::MySql,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        mysql,
+        r"SELECT region FROM orders WHERE (note <> 'it\'s') AND region = 'x\\\' OR 1=1 --'"
+    );
+
+    // A backslash is an ordinary character in Postgres strings
+    let postgres = apply_column_filters(
+        "SELECT region FROM orders".to_string(),
+        value(r"x\' OR 1=1 --"),
+        WarehouseDialect::Postgres,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        postgres,
+        r"SELECT region FROM orders WHERE region = 'x\'' OR 1=1 --'"
+    );
+}
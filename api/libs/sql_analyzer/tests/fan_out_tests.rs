@@ -0,0 +1,198 @@
+use sql_analyzer::{
+    detect_fan_out_joins, validate_semantic_query, Cardinality, Relationship, SemanticLayer,
+    SqlAnalyzerError, ValidationMode,
+};
+
+fn create_semantic_layer() -> SemanticLayer {
+    let mut semantic_layer = SemanticLayer::new();
+
+    semantic_layer.add_table("orders", vec!["id", "customer_id", "shipping_cost", "created_at"]);
+    semantic_layer.add_table("order_items", vec!["id", "order_id", "product_id", "quantity", "price"]);
+    semantic_layer.add_table("customers", vec!["id", "name", "region"]);
+    semantic_layer.add_table("products", vec!["id", "name", "category"]);
+
+    semantic_layer.add_relationship(Relationship {
+        from_table: "orders".to_string(),
+        from_column: "id".to_string(),
+        to_table: "order_items".to_string(),
+        to_column: "order_id".to_string(),
+        cardinality: Some(Cardinality::OneToMany),
+    });
+    semantic_layer.add_relationship(Relationship {
+        from_table: "orders".to_string(),
+        from_column: "customer_id".to_string(),
+        to_table: "customers".to_string(),
+        to_column: "id".to_string(),
+        cardinality: Some(Cardinality::ManyToOne),
+    });
+    semantic_layer.add_relationship(Relationship {
+        from_table: "order_items".to_string(),
+        from_column: "product_id".to_string(),
+        to_table: "products".to_string(),
+        to_column: "id".to_string(),
+        cardinality: Some(Cardinality::ManyToOne),
+    });
+
+    semantic_layer
+}
+
+#[tokio::test]
+async fn test_sum_on_one_side_of_one_to_many_join_is_flagged() {
+    let sql = "SELECT c.region, SUM(o.shipping_cost) AS shipping \
+               FROM orders o JOIN order_items i ON o.id = i.order_id \
+               JOIN customers c ON o.customer_id = c.id \
+               GROUP BY c.region";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].table, "orders");
+    assert_eq!(warnings[0].fanned_out_by, "order_items");
+    assert_eq!(warnings[0].cardinality, Cardinality::OneToMany);
+    assert_eq!(warnings[0].measure, "SUM(o.shipping_cost)");
+    assert!(warnings[0].suggestion.contains("WITH orders_agg AS"));
+    assert!(warnings[0].suggestion.contains("GROUP BY id"));
+}
+
+#[tokio::test]
+async fn test_sum_on_many_side_is_not_flagged() {
+    let sql = "SELECT o.id, SUM(i.quantity * i.price) \
+               FROM orders o JOIN order_items i ON o.id = i.order_id \
+               GROUP BY o.id";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+}
+
+#[tokio::test]
+async fn test_many_to_one_join_is_not_flagged() {
+    let sql = "SELECT c.region, SUM(o.shipping_cost) \
+               FROM orders o JOIN customers c ON o.customer_id = c.id \
+               GROUP BY c.region";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+}
+
+#[tokio::test]
+async fn test_relationship_declared_in_reverse_direction() {
+    // customers -> orders is not declared, only orders -> customers (many-to-one)
+    let sql = "SELECT c.name, COUNT(c.id) \
+               FROM customers c JOIN orders o ON c.id = o.customer_id \
+               GROUP BY c.name";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].table, "customers");
+    assert_eq!(warnings[0].fanned_out_by, "orders");
+}
+
+#[tokio::test]
+async fn test_fan_out_through_intermediate_join() {
+    let sql = "SELECT p.category, SUM(c.id) \
+               FROM customers c JOIN orders o ON c.id = o.customer_id \
+               JOIN order_items i ON i.order_id = o.id \
+               JOIN products p ON i.product_id = p.id \
+               GROUP BY p.category";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].table, "customers");
+}
+
+#[tokio::test]
+async fn test_distinct_and_min_max_are_not_flagged() {
+    let sql = "SELECT COUNT(DISTINCT o.id), MAX(o.shipping_cost), MIN(o.created_at) \
+               FROM orders o JOIN order_items i ON o.id = i.order_id";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+}
+
+#[tokio::test]
+async fn test_pre_aggregated_cte_is_not_flagged() {
+    let sql = "WITH item_totals AS (\
+                 SELECT i.order_id, SUM(i.quantity) AS quantity FROM order_items i GROUP BY i.order_id\
+               ) \
+               SELECT SUM(o.shipping_cost), SUM(t.quantity) \
+               FROM orders o JOIN item_totals t ON o.id = t.order_id";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert!(warnings.is_empty(), "Unexpected warnings: {:?}", warnings);
+}
+
+#[tokio::test]
+async fn test_unknown_cardinality_is_ignored() {
+    let mut semantic_layer = SemanticLayer::new();
+    semantic_layer.add_relationship(Relationship {
+        from_table: "orders".to_string(),
+        from_column: "id".to_string(),
+        to_table: "order_items".to_string(),
+        to_column: "order_id".to_string(),
+        cardinality: None,
+    });
+
+    let sql = "SELECT SUM(o.shipping_cost) FROM orders o JOIN order_items i ON o.id = i.order_id";
+
+    let warnings = detect_fan_out_joins(sql.to_string(), semantic_layer, ValidationMode::Flexible)
+        .await
+        .unwrap();
+
+    assert!(warnings.is_empty());
+}
+
+#[tokio::test]
+async fn test_strict_mode_blocks_fan_out() {
+    let sql = "SELECT SUM(o.shipping_cost) FROM orders o JOIN order_items i ON o.id = i.order_id";
+
+    let result = detect_fan_out_joins(sql.to_string(), create_semantic_layer(), ValidationMode::Strict).await;
+
+    match result {
+        Err(SqlAnalyzerError::FanOutJoin(msg)) => {
+            assert!(msg.contains("order_items"));
+            assert!(msg.contains("Pre-aggregate"));
+        }
+        other => panic!("Expected FanOutJoin error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_strict_semantic_validation_reports_fan_out() {
+    let sql = "SELECT SUM(o.shipping_cost) FROM orders o JOIN order_items i ON o.id = i.order_id";
+
+    let result = validate_semantic_query(sql.to_string(), create_semantic_layer(), ValidationMode::Strict).await;
+
+    match result {
+        Err(SqlAnalyzerError::SemanticValidation(msg)) => assert!(msg.contains("Pre-aggregate")),
+        other => panic!("Expected SemanticValidation error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_cardinality_parse() {
+    assert_eq!(Cardinality::parse("one-to-many"), Some(Cardinality::OneToMany));
+    assert_eq!(Cardinality::parse("MANY_TO_ONE"), Some(Cardinality::ManyToOne));
+    assert_eq!(Cardinality::parse("1:1"), Some(Cardinality::OneToOne));
+    assert_eq!(Cardinality::parse("many to many"), Some(Cardinality::ManyToMany));
+    assert_eq!(Cardinality::parse("sometimes"), None);
+}
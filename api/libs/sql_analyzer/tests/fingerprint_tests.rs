@@ -0,0 +1,168 @@
+use sql_analyzer::{fingerprint_query, format_sql, SqlAnalyzerError};
+
+async fn fingerprint(sql: &str) -> String {
+    fingerprint_query(sql.to_string()).await.unwrap().fingerprint
+}
+
+#[tokio::test]
+async fn test_fingerprint_ignores_literals_whitespace_and_case() {
+    let a = fingerprint("SELECT u.id, u.name FROM users u WHERE u.status = 'active' LIMIT 10").await;
+    let b = fingerprint("select U.ID,\n    u.Name\nfrom USERS u\nwhere u.status = 'churned'\nlimit 50").await;
+
+    assert_eq!(a, b);
+    assert_eq!(a.len(), 64);
+}
+
+#[tokio::test]
+async fn test_fingerprint_ignores_commutative_predicate_order() {
+    let a = fingerprint(
+        "SELECT o.id FROM orders o JOIN customers c ON o.customer_id = c.id \
+         WHERE o.total > 100 AND c.region = 'EU' AND o.status <> 'void'",
+    )
+    .await;
+    let b = fingerprint(
+        "SELECT o.id FROM orders o JOIN customers c ON c.id = o.customer_id \
+         WHERE 'void' <> o.status AND c.region = 'US' AND o.total > 5",
+    )
+    .await;
+
+    assert_eq!(a, b);
+}
+
+#[tokio::test]
+async fn test_fingerprint_collapses_in_lists() {
+    let a = fingerprint("SELECT id FROM users WHERE region IN ('EU', 'US', 'APAC')").await;
+    let b = fingerprint("SELECT id FROM users WHERE region IN ('EU')").await;
+
+    assert_eq!(a, b);
+}
+
+#[tokio::test]
+async fn test_fingerprint_distinguishes_query_shape() {
+    let base = fingerprint("SELECT id FROM users WHERE age > 21").await;
+
+    assert_ne!(base, fingerprint("SELECT id FROM users WHERE age < 21").await);
+    assert_ne!(base, fingerprint("SELECT id, name FROM users WHERE age > 21").await);
+    assert_ne!(base, fingerprint("SELECT id FROM customers WHERE age > 21").await);
+    assert_ne!(base, fingerprint("SELECT id FROM users WHERE age IS NULL").await);
+}
+
+#[tokio::test]
+async fn test_fingerprint_keeps_quoted_identifier_case() {
+    let a = fingerprint("SELECT \"UserId\" FROM users").await;
+    let b = fingerprint("SELECT \"userid\" FROM users").await;
+
+    assert_ne!(a, b);
+}
+
+#[tokio::test]
+async fn test_normalized_sql() {
+    let result = fingerprint_query(
+        "SELECT Region, SUM(amount) AS Total FROM Sales WHERE year = 2024 AND active = true GROUP BY Region"
+            .to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        result.normalized_sql,
+        "SELECT region, sum(amount) AS total FROM sales WHERE active = ? AND year = ? GROUP BY region"
+    );
+}
+
+#[tokio::test]
+async fn test_fingerprint_normalizes_ctes_and_subqueries() {
+    let a = fingerprint(
+        "WITH Recent AS (SELECT id FROM orders WHERE created_at > DATE '2024-01-01') \
+         SELECT COUNT(*) FROM Recent r WHERE r.id IN (SELECT order_id FROM refunds WHERE amount > 10)",
+    )
+    .await;
+    let b = fingerprint(
+        "with recent as (select ID from ORDERS where created_at > date '2023-06-30') \
+         select count(*) from recent R where R.id in (select order_id from refunds where amount > 99)",
+    )
+    .await;
+
+    assert_eq!(a, b);
+}
+
+#[tokio::test]
+async fn test_fingerprint_parse_error() {
+    let result = fingerprint_query("SELECT FROM WHERE".to_string()).await;
+
+    assert!(matches!(result, Err(SqlAnalyzerError::ParseError(_))));
+}
+
+#[tokio::test]
+async fn test_format_simple_query() {
+    let formatted = format_sql(
+        "select o.region, sum(o.amount) as total from sales.orders o join sales.customers c on o.customer_id = c.id \
+         where o.status = 'paid' and c.active = true group by o.region having sum(o.amount) > 100 order by total desc limit 10"
+            .to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        formatted,
+        "SELECT\n  o.region,\n  sum(o.amount) AS total\n\
+         FROM sales.orders AS o\n\
+         JOIN sales.customers AS c ON o.customer_id = c.id\n\
+         WHERE\n  o.status = 'paid'\n  AND c.active = true\n\
+         GROUP BY\n  o.region\n\
+         HAVING\n  sum(o.amount) > 100\n\
+         ORDER BY\n  total DESC\n\
+         LIMIT 10"
+    );
+}
+
+#[tokio::test]
+async fn test_format_ctes_and_derived_tables() {
+    let formatted = format_sql(
+        "WITH totals AS (SELECT customer_id, SUM(amount) AS amount FROM orders GROUP BY customer_id) \
+         SELECT t.customer_id FROM (SELECT customer_id FROM totals WHERE amount > 10) t"
+            .to_string(),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        formatted,
+        "WITH\n  totals AS (\n    SELECT\n      customer_id,\n      SUM(amount) AS amount\n    FROM orders\n    GROUP BY\n      customer_id\n  )\n\
+         SELECT\n  t.customer_id\n\
+         FROM (\n  SELECT\n    customer_id\n  FROM totals\n  WHERE\n    amount > 10\n) AS t"
+    );
+}
+
+#[tokio::test]
+async fn test_format_union() {
+    let formatted = format_sql("SELECT id FROM a UNION ALL SELECT id FROM b".to_string())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        formatted,
+        "SELECT\n  id\nFROM a\nUNION ALL\nSELECT\n  id\nFROM b"
+    );
+}
+
+#[tokio::test]
+async fn test_format_is_stable_and_preserves_semantics() {
+    let sql = "SELECT DISTINCT a.x, b.y FROM a LEFT JOIN b ON a.id = b.a_id WHERE (a.x = 1 OR a.x = 2) AND b.y IS NOT NULL";
+
+    let formatted = format_sql(sql.to_string()).await.unwrap();
+    let reformatted = format_sql(formatted.clone()).await.unwrap();
+    assert_eq!(formatted, reformatted);
+
+    let original = fingerprint(sql).await;
+    assert_eq!(original, fingerprint(&formatted).await);
+}
+
+#[tokio::test]
+async fn test_format_keeps_multiline_string_literals_intact() {
+    let sql = "SELECT id FROM notes WHERE body = 'line one\nline two'";
+
+    let formatted = format_sql(sql.to_string()).await.unwrap();
+
+    assert!(formatted.contains("'line one\nline two'"));
+}
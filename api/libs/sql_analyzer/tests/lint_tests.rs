@@ -0,0 +1,305 @@
+use sql_analyzer::{lint_sql, LintDiagnostic, LintOptions, LintRule, LintSeverity, SqlAnalyzerError};
+use std::collections::{HashMap, HashSet};
+
+fn rules(diagnostics: &[LintDiagnostic]) -> Vec<LintRule> {
+    diagnostics.iter().map(|d| d.rule).collect()
+}
+
+#[tokio::test]
+async fn test_lint_clean_query_has_no_diagnostics() {
+    let sql = "SELECT o.region, SUM(o.amount) AS total \
+               FROM sales.orders o \
+               JOIN sales.customers c ON o.customer_id = c.id \
+               GROUP BY o.region \
+               ORDER BY total DESC \
+               LIMIT 10";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_select_star_on_wide_table() {
+    let sql = "SELECT * FROM analytics.events e";
+
+    let options = LintOptions {
+        table_column_counts: HashMap::from([("events".to_string(), 45)]),
+        ..Default::default()
+    };
+
+    let diagnostics = lint_sql(sql.to_string(), options).await.unwrap();
+
+    assert_eq!(rules(&diagnostics), vec![LintRule::SelectStarOnWideTable]);
+    assert_eq!(diagnostics[0].severity, LintSeverity::Warning);
+    assert!(diagnostics[0].message.contains("45 columns"));
+
+    let span = diagnostics[0].span.expect("Wildcard should have a span");
+    assert_eq!(span.start_line, 1);
+    assert_eq!(span.start_column, 8);
+}
+
+#[tokio::test]
+async fn test_lint_select_star_on_narrow_table_is_allowed() {
+    let sql = "SELECT * FROM analytics.regions r";
+
+    let options = LintOptions {
+        table_column_counts: HashMap::from([("regions".to_string(), 4)]),
+        ..Default::default()
+    };
+
+    let diagnostics = lint_sql(sql.to_string(), options).await.unwrap();
+
+    assert!(diagnostics.is_empty());
+}
+
+#[tokio::test]
+async fn test_lint_qualified_wildcard_only_checks_its_table() {
+    let sql = "SELECT r.*, e.id FROM analytics.events e JOIN analytics.regions r ON e.region_id = r.id";
+
+    let options = LintOptions {
+        table_column_counts: HashMap::from([
+            ("events".to_string(), 45),
+            ("regions".to_string(), 4),
+        ]),
+        ..Default::default()
+    };
+
+    let diagnostics = lint_sql(sql.to_string(), options).await.unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_ungrouped_column() {
+    let sql = "SELECT o.region, o.channel, SUM(o.amount) FROM sales.orders o GROUP BY o.region";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(rules(&diagnostics), vec![LintRule::UngroupedColumn]);
+    // o.channel could be functionally dependent on o.region
+    assert_eq!(diagnostics[0].severity, LintSeverity::Warning);
+    assert!(diagnostics[0].message.contains("o.channel"));
+
+    let span = diagnostics[0].span.expect("Column should have a span");
+    assert_eq!((span.start_line, span.start_column), (1, 18));
+    assert!(diagnostics[0]
+        .fix
+        .as_ref()
+        .unwrap()
+        .description
+        .contains("GROUP BY"));
+}
+
+#[tokio::test]
+async fn test_lint_aggregate_without_group_by() {
+    let sql = "SELECT o.region, COUNT(*) FROM sales.orders o";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(rules(&diagnostics), vec![LintRule::UngroupedColumn]);
+    assert_eq!(diagnostics[0].severity, LintSeverity::Error);
+}
+
+#[tokio::test]
+async fn test_lint_group_by_position_alias_and_expression() {
+    let sql = "SELECT DATE_TRUNC('month', o.created_at) AS month, o.region, o.channel AS ch, SUM(o.amount) \
+               FROM sales.orders o \
+               GROUP BY DATE_TRUNC('month', o.created_at), 2, ch";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_window_function_arguments_are_not_ungrouped() {
+    let sql = "SELECT o.region, SUM(o.amount), RANK() OVER (ORDER BY SUM(o.amount) DESC) \
+               FROM sales.orders o GROUP BY o.region";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_ambiguous_column_in_join() {
+    let sql = "SELECT o.id, status FROM sales.orders o JOIN sales.customers c ON o.customer_id = c.id";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(rules(&diagnostics), vec![LintRule::AmbiguousColumn]);
+    let fix = diagnostics[0].fix.as_ref().unwrap();
+    assert!(fix.description.contains("o.status"));
+    assert!(fix.description.contains("c.status"));
+}
+
+#[tokio::test]
+async fn test_lint_order_by_alias_is_not_ambiguous() {
+    let sql = "SELECT c.name, SUM(o.amount) AS total \
+               FROM sales.orders o JOIN sales.customers c ON o.customer_id = c.id \
+               GROUP BY c.name ORDER BY total DESC LIMIT 5";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_unqualified_column_single_table_is_allowed() {
+    let sql = "SELECT status FROM sales.orders";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty());
+}
+
+#[tokio::test]
+async fn test_lint_implicit_cross_join() {
+    let sql = "SELECT o.id, c.name FROM sales.orders o, sales.customers c";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(rules(&diagnostics), vec![LintRule::ImplicitCrossJoin]);
+    assert!(diagnostics[0].message.contains("`c`"));
+}
+
+#[tokio::test]
+async fn test_lint_comma_join_with_where_condition_is_allowed() {
+    let sql = "SELECT o.id, c.name FROM sales.orders o, sales.customers c WHERE o.customer_id = c.id";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_explicit_cross_join_is_allowed() {
+    let sql = "SELECT d.day, r.name FROM util.days d CROSS JOIN sales.regions r";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_order_by_without_limit_on_large_table() {
+    let sql = "SELECT e.id, e.created_at FROM analytics.events e ORDER BY e.created_at";
+
+    let options = LintOptions {
+        large_tables: HashSet::from(["events".to_string()]),
+        ..Default::default()
+    };
+
+    let diagnostics = lint_sql(sql.to_string(), options.clone()).await.unwrap();
+    assert_eq!(rules(&diagnostics), vec![LintRule::OrderByWithoutLimit]);
+
+    let limited = format!("{} LIMIT 100", sql);
+    let diagnostics = lint_sql(limited, options).await.unwrap();
+    assert!(diagnostics.is_empty());
+}
+
+#[tokio::test]
+async fn test_lint_order_by_without_limit_on_small_table_is_allowed() {
+    let sql = "SELECT r.name FROM sales.regions r ORDER BY r.name";
+
+    let options = LintOptions {
+        large_tables: HashSet::from(["events".to_string()]),
+        ..Default::default()
+    };
+
+    let diagnostics = lint_sql(sql.to_string(), options).await.unwrap();
+
+    assert!(diagnostics.is_empty());
+}
+
+#[tokio::test]
+async fn test_lint_division_without_nullif() {
+    let sql = "SELECT o.revenue / o.orders AS aov FROM sales.daily o";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(rules(&diagnostics), vec![LintRule::UnguardedDivision]);
+    let fix = diagnostics[0].fix.as_ref().unwrap();
+    assert_eq!(
+        fix.replacement.as_deref(),
+        Some("o.revenue / NULLIF(o.orders, 0)")
+    );
+}
+
+#[tokio::test]
+async fn test_lint_guarded_division_is_allowed() {
+    let sql = "SELECT o.revenue / NULLIF(o.orders, 0), o.revenue / 100.0, \
+               o.revenue / CASE WHEN o.orders = 0 THEN NULL ELSE o.orders END \
+               FROM sales.daily o";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert!(diagnostics.is_empty(), "Unexpected diagnostics: {:?}", diagnostics);
+}
+
+#[tokio::test]
+async fn test_lint_reports_issues_inside_ctes_and_subqueries() {
+    let sql = "WITH totals AS (\n\
+                 SELECT t.region, t.channel, SUM(t.amount) AS amount FROM sales.orders t GROUP BY t.region\n\
+               )\n\
+               SELECT x.region, x.amount / x.cnt FROM (SELECT s.region, s.amount, s.cnt FROM totals s) x";
+
+    let diagnostics = lint_sql(sql.to_string(), LintOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(
+        rules(&diagnostics),
+        vec![LintRule::UngroupedColumn, LintRule::UnguardedDivision]
+    );
+    assert_eq!(diagnostics[0].span.unwrap().start_line, 2);
+    assert_eq!(diagnostics[1].span.unwrap().start_line, 4);
+}
+
+#[tokio::test]
+async fn test_lint_rejects_non_select_statements() {
+    let sql = "DELETE FROM sales.orders WHERE id = 1";
+
+    let result = lint_sql(sql.to_string(), LintOptions::default()).await;
+
+    assert!(matches!(
+        result,
+        Err(SqlAnalyzerError::UnsupportedStatement(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_lint_parse_error() {
+    let sql = "SELECT FROM WHERE";
+
+    let result = lint_sql(sql.to_string(), LintOptions::default()).await;
+
+    assert!(matches!(result, Err(SqlAnalyzerError::ParseError(_))));
+}
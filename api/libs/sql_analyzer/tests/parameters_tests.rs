@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use sql_analyzer::{
+    bind_parameters, parameter_names, ParameterValue, SqlAnalyzerError, WarehouseDialect,
+};
+
+fn values(pairs: &[(&str, ParameterValue)]) -> HashMap<String, ParameterValue> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_binds_typed_values_as_literals() {
+    let sql = "SELECT region, SUM(amount) FROM orders \
+               WHERE created_at >= {{ start_date }} AND amount > {{min_amount}} \
+               AND is_test = {{ include_tests }} AND region IN ({{ region }}, 'US') GROUP BY region";
+    let values = values(&[
+        ("start_date", ParameterValue::Date("2024-01-01".to_string())),
+        ("min_amount", ParameterValue::Number(25.5)),
+        ("include_tests", ParameterValue::Boolean(false)),
+        ("region", ParameterValue::String("EU".to_string())),
+    ]);
+
+    let result = bind_parameters(sql.to_string(), values, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "SELECT region, SUM(amount) FROM orders \
+         WHERE created_at >= CAST('2024-01-01' AS DATE) AND amount > 25.5 \
+         AND is_test = FALSE AND region IN ('EU', 'US') GROUP BY region"
+    );
+}
+
+#[tokio::test]
+async fn test_string_escaping_follows_the_dialect() {
+    let sql = "SELECT id FROM customers WHERE name = {{ name }}";
+    let name = ParameterValue::String(r"O'Brien\' OR 1=1 --".to_string());
+
+    let postgres = bind_parameters(
+        sql.to_string(),
+        values(&[("name", name.clone())]),
+        WarehouseDialect::Postgres,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        postgres,
+        r"SELECT id FROM customers WHERE name = 'O''Brien\'' OR 1=1 --'"
+    );
+
+    let bigquery = bind_parameters(
+        sql.to_string(),
+        values(&[("name", name)]),
+        WarehouseDialect::BigQuery,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        bigquery,
+        r"SELECT id FROM customers WHERE name = 'O\'Brien\\\' OR 1=1 --'"
+    );
+}
+
+#[tokio::test]
+async fn test_queries_parse_in_the_warehouse_dialect() {
+    let region = values(&[("region", ParameterValue::String("EU".to_string()))]);
+    let sql =
+        r"SELECT id FROM t WHERE path LIKE 'a\\b%' AND note <> 'it\'s' AND region = {{ region }}";
+
+    for dialect in [WarehouseDialect::BigQuery, WarehouseDialect::MySql] {
+        let result = bind_parameters(sql.to_string(), region.clone(), dialect)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            r"SELECT id FROM t WHERE path LIKE 'a\\b%' AND note <> 'it\'s' AND region = 'EU'"
+        );
+    }
+
+    let sql_server = bind_parameters(
+        "SELECT [id], [order date] FROM [dbo].[orders] WHERE [region] = {{ region }}".to_string(),
+        region,
+        WarehouseDialect::SqlServer,
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        sql_server,
+        "SELECT [id], [order date] FROM [dbo].[orders] WHERE [region] = 'EU'"
+    );
+}
+
+#[tokio::test]
+async fn test_placeholders_must_be_values_with_bound_values() {
+    let name = values(&[("name", ParameterValue::String("x".to_string()))]);
+
+    let in_string = bind_parameters(
+        "SELECT id FROM users WHERE note = 'hi {{ name }}'".to_string(),
+        name.clone(),
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(
+        in_string,
+        Err(SqlAnalyzerError::InvalidParameter(_))
+    ));
+
+    let missing = bind_parameters(
+        "SELECT id FROM users WHERE name = {{ name }} AND age > {{ min_age }}".to_string(),
+        name,
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(missing, Err(SqlAnalyzerError::MissingParameter(p)) if p == "min_age"));
+
+    let bad_date = bind_parameters(
+        "SELECT id FROM users WHERE created_at > {{ since }}".to_string(),
+        values(&[(
+            "since",
+            ParameterValue::Date("2024-01-01' OR '1".to_string()),
+        )]),
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(
+        bad_date,
+        Err(SqlAnalyzerError::InvalidParameter(_))
+    ));
+}
+
+#[test]
+fn test_parameter_names_in_order_of_first_use() {
+    assert_eq!(
+        parameter_names(
+            "SELECT {{ b }}, {{a}}, {{ b }} FROM t WHERE x = {{ not valid }}",
+            WarehouseDialect::Postgres
+        )
+        .unwrap(),
+        vec!["b", "a"]
+    );
+}
+
+#[tokio::test]
+async fn test_placeholders_in_comments_are_ignored() {
+    let sql = "SELECT id FROM orders -- AND region = {{ x }}\n\
+               WHERE amount > {{ min_amount }} /* {{ y }} */ AND note <> '-- {{ z }}'";
+
+    assert_eq!(
+        parameter_names(sql, WarehouseDialect::Postgres).unwrap(),
+        vec!["min_amount", "z"]
+    );
+
+    let result = bind_parameters(
+        sql.replace(" AND note <> '-- {{ z }}'", ""),
+        values(&[("min_amount", ParameterValue::Number(10.0))]),
+        WarehouseDialect::Postgres,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, "SELECT id FROM orders WHERE amount > 10");
+}
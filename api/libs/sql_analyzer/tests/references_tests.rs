@@ -0,0 +1,140 @@
+use sql_analyzer::{
+    analyze_query, resolve_references, CatalogTable, ReferenceCatalog, ReferenceError,
+    ReferenceErrorKind,
+};
+
+fn catalog() -> ReferenceCatalog {
+    ReferenceCatalog {
+        tables: vec![
+            CatalogTable {
+                name: "orders".to_string(),
+                columns: vec![
+                    "id".to_string(),
+                    "customer_id".to_string(),
+                    "amount".to_string(),
+                    "created_at".to_string(),
+                ],
+                accessible: true,
+            },
+            CatalogTable {
+                name: "customers".to_string(),
+                columns: vec!["id".to_string(), "name".to_string(), "region".to_string()],
+                accessible: true,
+            },
+            CatalogTable {
+                name: "payroll".to_string(),
+                columns: vec!["employee_id".to_string(), "salary".to_string()],
+                accessible: false,
+            },
+            CatalogTable {
+                name: "events".to_string(),
+                columns: vec![],
+                accessible: true,
+            },
+        ],
+    }
+}
+
+async fn resolve(sql: &str) -> Vec<ReferenceError> {
+    let summary = analyze_query(sql.to_string()).await.unwrap();
+    resolve_references(&summary, &catalog())
+}
+
+#[tokio::test]
+async fn test_valid_references() {
+    let errors = resolve(
+        "SELECT c.Region, SUM(o.amount) FROM sales.orders o \
+         JOIN sales.customers c ON o.customer_id = c.id GROUP BY c.Region",
+    )
+    .await;
+
+    assert!(errors.is_empty(), "Unexpected errors: {:?}", errors);
+}
+
+#[tokio::test]
+async fn test_unknown_column_with_suggestion() {
+    let errors = resolve("SELECT o.amout FROM sales.orders o").await;
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ReferenceErrorKind::UnknownColumn);
+    assert_eq!(errors[0].table, "orders");
+    assert_eq!(errors[0].column.as_deref(), Some("amout"));
+    assert_eq!(errors[0].suggestion.as_deref(), Some("amount"));
+    assert_eq!(
+        errors[0].message,
+        "Column `amout` does not exist on dataset `orders`. Did you mean `amount`?"
+    );
+}
+
+#[tokio::test]
+async fn test_unknown_column_suggests_prefixed_name() {
+    let errors = resolve("SELECT o.customer FROM sales.orders o").await;
+
+    assert_eq!(errors[0].suggestion.as_deref(), Some("customer_id"));
+}
+
+#[tokio::test]
+async fn test_unknown_column_without_close_match() {
+    let errors = resolve("SELECT o.shipping_method FROM sales.orders o").await;
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].suggestion, None);
+    assert!(!errors[0].message.contains("Did you mean"));
+}
+
+#[tokio::test]
+async fn test_unknown_table_with_suggestion() {
+    let errors = resolve("SELECT c.name FROM sales.custmers c").await;
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ReferenceErrorKind::UnknownTable);
+    assert_eq!(errors[0].suggestion.as_deref(), Some("customers"));
+}
+
+#[tokio::test]
+async fn test_inaccessible_table_is_not_suggested() {
+    let errors = resolve("SELECT p.salary FROM hr.payrol p").await;
+
+    assert_eq!(errors[0].kind, ReferenceErrorKind::UnknownTable);
+    assert_eq!(errors[0].suggestion, None);
+}
+
+#[tokio::test]
+async fn test_table_not_permitted() {
+    let errors = resolve("SELECT p.salary FROM hr.payroll p").await;
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, ReferenceErrorKind::TableNotPermitted);
+    assert_eq!(errors[0].column, None);
+}
+
+#[tokio::test]
+async fn test_dataset_without_recorded_columns_skips_column_checks() {
+    let errors = resolve("SELECT e.anything FROM analytics.events e").await;
+
+    assert!(errors.is_empty(), "Unexpected errors: {:?}", errors);
+}
+
+#[tokio::test]
+async fn test_ctes_and_derived_tables_are_not_datasets() {
+    let errors = resolve(
+        "WITH totals AS (SELECT o.customer_id, SUM(o.amount) AS total FROM sales.orders o GROUP BY o.customer_id) \
+         SELECT t.total FROM totals t",
+    )
+    .await;
+
+    assert!(errors.is_empty(), "Unexpected errors: {:?}", errors);
+}
+
+#[tokio::test]
+async fn test_reports_every_unknown_reference() {
+    let errors = resolve(
+        "SELECT o.amout, c.regin FROM sales.orders o JOIN sales.customers c ON o.customer_id = c.id",
+    )
+    .await;
+
+    let columns: Vec<_> = errors.iter().filter_map(|e| e.column.as_deref()).collect();
+    assert_eq!(errors.len(), 2);
+    assert!(columns.contains(&"amout"));
+    assert!(columns.contains(&"regin"));
+}
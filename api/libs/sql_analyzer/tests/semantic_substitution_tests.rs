@@ -19,6 +19,7 @@ fn create_test_semantic_layer() -> SemanticLayer {
         from_column: "id".to_string(),
         to_table: "orders".to_string(),
         to_column: "user_id".to_string(),
+        cardinality: None,
     });
     
     semantic_layer.add_relationship(sql_analyzer::Relationship {
@@ -26,6 +27,7 @@ fn create_test_semantic_layer() -> SemanticLayer {
         from_column: "id".to_string(),
         to_table: "order_items".to_string(),
         to_column: "order_id".to_string(),
+        cardinality: None,
     });
     
     semantic_layer.add_relationship(sql_analyzer::Relationship {
@@ -33,6 +35,7 @@ fn create_test_semantic_layer() -> SemanticLayer {
         from_column: "id".to_string(),
         to_table: "order_items".to_string(),
         to_column: "product_id".to_string(),
+        cardinality: None,
     });
     
     // Add metrics
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use sql_analyzer::{remap_tables, SqlAnalyzerError, TableMapping, WarehouseDialect};
+
+fn mapping(schemas: &[(&str, &str)], tables: &[(&str, &str)]) -> TableMapping {
+    let to_map = |pairs: &[(&str, &str)]| {
+        pairs
+            .iter()
+            .map(|(from, to)| (from.to_string(), to.to_string()))
+            .collect::<HashMap<_, _>>()
+    };
+    TableMapping {
+        schemas: to_map(schemas),
+        tables: to_map(tables),
+    }
+}
+
+#[tokio::test]
+async fn test_remaps_schemas_and_qualified_columns() {
+    let sql = "SELECT sales.orders.region, SUM(c.amount) FROM sales.orders \
+               JOIN sales.customers c ON c.id = sales.orders.customer_id \
+               WHERE sales.orders.note <> 'sales.orders' GROUP BY sales.orders.region";
+    let mapping = mapping(&[("SALES", "sales_eu")], &[]);
+
+    let result = remap_tables(sql.to_string(), mapping, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "SELECT sales_eu.orders.region, SUM(c.amount) FROM sales_eu.orders \
+         JOIN sales_eu.customers AS c ON c.id = sales_eu.orders.customer_id \
+         WHERE sales_eu.orders.note <> 'sales.orders' GROUP BY sales_eu.orders.region"
+    );
+}
+
+#[tokio::test]
+async fn test_remaps_tables_by_qualified_then_bare_name() {
+    let sql = "SELECT orders.id, e.id FROM sales.orders JOIN billing.orders e ON e.id = orders.id \
+               JOIN events ON events.order_id = orders.id";
+    let mapping = mapping(
+        &[],
+        &[
+            ("billing.orders", "billing.invoices"),
+            ("orders", "orders_v2"),
+            ("events", "analytics.order_events"),
+        ],
+    );
+
+    let result = remap_tables(sql.to_string(), mapping, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "SELECT orders_v2.id, e.id FROM sales.orders_v2 \
+         JOIN billing.invoices AS e ON e.id = orders_v2.id \
+         JOIN analytics.order_events ON analytics.order_events.order_id = orders_v2.id"
+    );
+}
+
+#[tokio::test]
+async fn test_leaves_ctes_and_placeholders_alone() {
+    let sql = "WITH orders AS (SELECT * FROM sales.orders WHERE created_at >= {{ start_date }}) \
+               SELECT COUNT(*) FROM orders WHERE region = {{region}}";
+    let mapping = mapping(&[("sales", "sales_eu")], &[("orders", "orders_v2")]);
+
+    let result = remap_tables(sql.to_string(), mapping, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        "WITH orders AS (SELECT * FROM sales_eu.orders_v2 WHERE created_at >= {{ start_date }}) \
+         SELECT COUNT(*) FROM orders WHERE region = {{ region }}"
+    );
+}
+
+#[tokio::test]
+async fn test_keeps_quoting_and_unmapped_tables() {
+    let sql =
+        r#"SELECT * FROM "Sales"."Orders" JOIN public.regions r ON r.id = "Orders".region_id"#;
+    let mapping = mapping(&[("sales", "Sales_EU")], &[]);
+
+    let result = remap_tables(sql.to_string(), mapping, WarehouseDialect::Postgres)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        result,
+        r#"SELECT * FROM "Sales_EU"."Orders" JOIN public.regions AS r ON r.id = "Orders".region_id"#
+    );
+}
+
+#[tokio::test]
+async fn test_rejects_invalid_names_and_non_queries() {
+    let invalid = mapping(&[("sales", "sales; DROP TABLE users")], &[]);
+    let result = remap_tables(
+        "SELECT 1 FROM sales.orders".to_string(),
+        invalid,
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(result, Err(SqlAnalyzerError::InvalidParameter(_))));
+
+    let mapping = mapping(&[("sales", "sales_eu")], &[]);
+    let result = remap_tables(
+        "DELETE FROM sales.orders".to_string(),
+        mapping.clone(),
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(SqlAnalyzerError::UnsupportedStatement(_))
+    ));
+
+    let result = remap_tables(
+        "SELECT 1; SELECT 2".to_string(),
+        mapping,
+        WarehouseDialect::Postgres,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(SqlAnalyzerError::UnsupportedStatement(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_parses_in_the_warehouse_dialect() {
+    let result = remap_tables(
+        "SELECT [o].[id] FROM [dbo].[orders] AS [o] -- {{ x }}".to_string(),
+        mapping(&[("dbo", "sales_eu")], &[]),
+        WarehouseDialect::SqlServer,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, "SELECT [o].[id] FROM [sales_eu].[orders] AS [o]");
+
+    let sales = mapping(&[("sales", "sales_eu")], &[]);
+    let sql = r"SELECT id FROM sales.orders WHERE path LIKE 'a\\b%' AND note <> 'it\'s'";
+    for dialect in [WarehouseDialect::BigQuery, WarehouseDialect::MySql] {
+        let result = remap_tables(sql.to_string(), sales.clone(), dialect)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            r"SELECT id FROM sales_eu.orders WHERE path LIKE 'a\\b%' AND note <> 'it\'s'"
+        );
+    }
+
+    // With nothing to rename the query isn't even parsed
+    let sql = "SELECT [id] FROM orders /* {{ x }} */ WHERE a = {{y}}";
+    let result = remap_tables(
+        sql.to_string(),
+        TableMapping::default(),
+        WarehouseDialect::Postgres,
+    )
+    .await
+    .unwrap();
+    assert_eq!(result, sql);
+}
@@ -45,16 +45,8 @@ impl CreateDashboardsProcessor {
         // Parse the YAML into DashboardYml
         let dashboard: DashboardYml = serde_yaml::from_str(yaml_content)?;
         
-        let mut metric_ids = Vec::new();
-        
-        // Iterate through all rows and collect metric IDs
-        for row in &dashboard.rows {
-            for item in &row.items {
-                metric_ids.push(item.id);
-            }
-        }
-        
-        Ok(metric_ids)
+        // Markdown and divider items don't reference metrics
+        Ok(dashboard.metric_ids().collect())
     }
     
     /// Create associations between a dashboard and its metrics
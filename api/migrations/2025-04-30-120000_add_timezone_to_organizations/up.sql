@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE organizations
+ADD COLUMN timezone TEXT NOT NULL DEFAULT 'UTC';
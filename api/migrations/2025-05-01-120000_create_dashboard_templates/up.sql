@@ -0,0 +1,19 @@
+-- Your SQL goes here
+
+-- Create the table holding dashboards saved as reusable templates
+CREATE TABLE dashboard_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR NOT NULL,
+    description TEXT,
+    content JSONB NOT NULL,
+    organization_id UUID NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    created_by UUID NOT NULL REFERENCES users(id),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT now(),
+    deleted_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_dashboard_templates_organization_id ON dashboard_templates(organization_id);
+
+COMMENT ON TABLE dashboard_templates IS 'Dashboards and their metrics saved with placeholders for data source, schema and table names.';
+COMMENT ON COLUMN dashboard_templates.content IS 'The dashboard, copies of its metrics and the placeholders filled in when the template is instantiated.';
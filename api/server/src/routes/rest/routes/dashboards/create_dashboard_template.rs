@@ -0,0 +1,45 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use handlers::dashboards::{
+    create_dashboard_template_handler, BusterDashboardTemplate, DashboardTemplateCreateRequest,
+};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+
+/// Saves a dashboard and its metrics as a template
+///
+/// POST /dashboards/:id/template
+pub async fn create_dashboard_template_rest_handler(
+    Path(id): Path<Uuid>,
+    Extension(user): Extension<AuthenticatedUser>,
+    request: Option<Json<DashboardTemplateCreateRequest>>,
+) -> Result<ApiResponse<BusterDashboardTemplate>, (StatusCode, String)> {
+    tracing::info!(
+        "Processing POST request to save dashboard {} as a template, user_id: {}",
+        id,
+        user.id
+    );
+
+    let Json(request) = request.unwrap_or_default();
+    match create_dashboard_template_handler(id, &user, request).await {
+        Ok(template) => Ok(ApiResponse::JsonData(template)),
+        Err(e) => {
+            tracing::error!("Failed to create dashboard template: {}", e);
+            let error_message = e.to_string();
+            if error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, error_message));
+            }
+            if error_message.contains("permission") {
+                return Err((StatusCode::FORBIDDEN, error_message));
+            }
+            if error_message.contains("Placeholder") || error_message.contains("template") {
+                return Err((StatusCode::BAD_REQUEST, error_message));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create dashboard template: {}", e),
+            ))
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    Extension,
+};
+use handlers::dashboards::{diff_dashboard_versions_handler, DashboardVersionDiffResponse};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct DiffDashboardVersionsQueryParams {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// Compares two versions of a dashboard
+///
+/// GET /dashboards/:id/versions/diff?from=1&to=3
+pub async fn diff_dashboard_versions_rest_handler(
+    Path(id): Path<Uuid>,
+    Extension(user): Extension<AuthenticatedUser>,
+    Query(params): Query<DiffDashboardVersionsQueryParams>,
+) -> Result<ApiResponse<DashboardVersionDiffResponse>, (StatusCode, String)> {
+    tracing::info!(
+        "Processing GET request for dashboard version diff with ID: {}, user_id: {}, from: {}, to: {}",
+        id,
+        user.id,
+        params.from,
+        params.to
+    );
+
+    match diff_dashboard_versions_handler(&id, &user, params.from, params.to).await {
+        Ok(diff) => Ok(ApiResponse::JsonData(diff)),
+        Err(e) => {
+            tracing::error!("Failed to diff dashboard versions: {}", e);
+            let error_message = e.to_string();
+            if error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, error_message));
+            }
+            if error_message.contains("permission") {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "You don't have permission to view this dashboard".to_string(),
+                ));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to diff dashboard versions: {}", e),
+            ))
+        }
+    }
+}
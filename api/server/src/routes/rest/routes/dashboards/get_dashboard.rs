@@ -2,7 +2,7 @@ use crate::routes::rest::ApiResponse;
 use axum::extract::{Path, Query};
 use axum::http::StatusCode;
 use axum::Extension;
-use handlers::dashboards::{get_dashboard_handler, BusterDashboardResponse};
+use handlers::dashboards::{get_dashboard_view_handler, BusterDashboardResponse, DashboardView};
 use middleware::AuthenticatedUser;
 use serde::Deserialize;
 use uuid::Uuid;
@@ -13,6 +13,10 @@ pub struct GetDashboardQueryParams {
     pub version_number: Option<i32>,
     /// Optional password for accessing public password-protected dashboards
     pub password: Option<String>,
+    /// Tab to load metrics for; defaults to the first tab
+    pub tab: Option<String>,
+    /// Comma-separated row ids of collapsed sections to load, e.g. `4,9`
+    pub expanded: Option<String>,
 }
 
 pub async fn get_dashboard_rest_handler(
@@ -27,7 +31,31 @@ pub async fn get_dashboard_rest_handler(
         params.version_number
     );
     
-    let dashboard = match get_dashboard_handler(&id, &user, params.version_number, params.password).await {
+    let expanded_sections = match params
+        .expanded
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|id| !id.trim().is_empty())
+        .map(|id| id.trim().parse::<u32>())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(ids) => ids,
+        Err(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "expanded must be comma-separated row ids",
+            ))
+        }
+    };
+    let view = DashboardView {
+        tab: params.tab,
+        expanded_sections,
+    };
+
+    let result =
+        get_dashboard_view_handler(&id, &user, params.version_number, params.password, &view).await;
+    let dashboard = match result {
         Ok(response) => response,
         Err(e) => {
             tracing::error!("Error getting dashboard: {}", e);
@@ -45,6 +73,9 @@ pub async fn get_dashboard_rest_handler(
             if error_message.contains("Version") && error_message.contains("not found") {
                 return Err((StatusCode::NOT_FOUND, "Version not found"));
             }
+            if error_message.contains("Tab") && error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, "Tab not found"));
+            }
             if error_message.contains("not found") {
                 return Err((StatusCode::NOT_FOUND, "Dashboard not found"));
             }
@@ -0,0 +1,48 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use handlers::dashboards::{
+    instantiate_dashboard_template_handler, BusterDashboardResponse,
+    DashboardTemplateInstantiateRequest,
+};
+use middleware::AuthenticatedUser;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+
+/// Creates a dashboard and copies of its metrics from a template, with
+/// placeholders filled in
+///
+/// POST /dashboards/templates/:id/instantiate
+pub async fn instantiate_dashboard_template_rest_handler(
+    Path(id): Path<Uuid>,
+    Extension(user): Extension<AuthenticatedUser>,
+    request: Option<Json<DashboardTemplateInstantiateRequest>>,
+) -> Result<ApiResponse<BusterDashboardResponse>, (StatusCode, String)> {
+    tracing::info!(
+        "Processing POST request to instantiate dashboard template {}, user_id: {}",
+        id,
+        user.id
+    );
+
+    let Json(request) = request.unwrap_or_default();
+    match instantiate_dashboard_template_handler(id, &user, request).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            tracing::error!("Failed to instantiate dashboard template: {}", e);
+            let error_message = e.to_string();
+            if error_message.contains("template not found") {
+                return Err((StatusCode::NOT_FOUND, error_message));
+            }
+            if error_message.contains("Placeholder")
+                || error_message.contains("placeholders")
+                || error_message.contains("doesn't fit")
+                || error_message.contains("problem")
+            {
+                return Err((StatusCode::BAD_REQUEST, error_message));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to instantiate dashboard template: {}", e),
+            ))
+        }
+    }
+}
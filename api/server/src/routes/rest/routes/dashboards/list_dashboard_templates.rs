@@ -0,0 +1,23 @@
+use axum::{http::StatusCode, Extension};
+use handlers::dashboards::{list_dashboard_templates_handler, BusterDashboardTemplate};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+
+/// Lists the organization's dashboard templates
+///
+/// GET /dashboards/templates
+pub async fn list_dashboard_templates_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+) -> Result<ApiResponse<Vec<BusterDashboardTemplate>>, (StatusCode, String)> {
+    match list_dashboard_templates_handler(&user).await {
+        Ok(templates) => Ok(ApiResponse::JsonData(templates)),
+        Err(e) => {
+            tracing::error!("Failed to list dashboard templates: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to list dashboard templates: {}", e),
+            ))
+        }
+    }
+}
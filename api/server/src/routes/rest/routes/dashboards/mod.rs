@@ -6,9 +6,14 @@ use axum::{
 
 // Modules for dashboard endpoints
 mod create_dashboard;
+mod create_dashboard_template;
 mod delete_dashboard;
+mod diff_dashboard_versions;
 mod get_dashboard;
+mod instantiate_dashboard_template;
+mod list_dashboard_templates;
 mod list_dashboards;
+mod restore_dashboard_version;
 mod sharing;
 mod update_dashboard;
 
@@ -22,6 +27,26 @@ pub fn router() -> Router {
             delete(delete_dashboard::delete_dashboards_rest_handler),
         )
         .route("/", get(list_dashboards::list_dashboard_rest_handler))
+        .route(
+            "/templates",
+            get(list_dashboard_templates::list_dashboard_templates_rest_handler),
+        )
+        .route(
+            "/templates/:id/instantiate",
+            post(instantiate_dashboard_template::instantiate_dashboard_template_rest_handler),
+        )
+        .route(
+            "/:id/template",
+            post(create_dashboard_template::create_dashboard_template_rest_handler),
+        )
+        .route(
+            "/:id/versions/diff",
+            get(diff_dashboard_versions::diff_dashboard_versions_rest_handler),
+        )
+        .route(
+            "/:id/versions/:version_number/restore",
+            post(restore_dashboard_version::restore_dashboard_version_rest_handler),
+        )
         .route(
             "/:id/sharing",
             get(sharing::list_dashboard_sharing_rest_handler),
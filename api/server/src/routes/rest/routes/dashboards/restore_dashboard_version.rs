@@ -0,0 +1,51 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use handlers::dashboards::{restore_dashboard_version_handler, BusterDashboardResponse};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RestoreVersionRequest {
+    /// Why the version is being restored
+    pub reason: Option<String>,
+}
+
+/// Restores a dashboard to an earlier version as a new version
+///
+/// POST /dashboards/:id/versions/:version_number/restore
+pub async fn restore_dashboard_version_rest_handler(
+    Path((id, version_number)): Path<(Uuid, i32)>,
+    Extension(user): Extension<AuthenticatedUser>,
+    request: Option<Json<RestoreVersionRequest>>,
+) -> Result<ApiResponse<BusterDashboardResponse>, (StatusCode, String)> {
+    tracing::info!(
+        "Processing POST request to restore dashboard {} to version {}, user_id: {}",
+        id,
+        version_number,
+        user.id
+    );
+
+    let Json(request) = request.unwrap_or_default();
+    match restore_dashboard_version_handler(id, &user, version_number, request.reason).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            tracing::error!("Failed to restore dashboard version: {}", e);
+            let error_message = e.to_string();
+            if error_message.contains("not found") || error_message.contains("unauthorized") {
+                return Err((StatusCode::NOT_FOUND, error_message));
+            }
+            if error_message.contains("does not have permission") {
+                return Err((
+                    StatusCode::FORBIDDEN,
+                    "You do not have permission to update this dashboard".to_string(),
+                ));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to restore dashboard version: {}", e),
+            ))
+        }
+    }
+}
@@ -1,7 +1,10 @@
 #![allow(dead_code, unused_imports, unused_variables)]
 
 use anyhow::Result;
-use axum::{extract::Json, Extension};
+use axum::{
+    extract::{Json, Query},
+    Extension,
+};
 use chrono::{DateTime, Utc};
 use diesel::{upsert::excluded, ExpressionMethods, QueryDsl};
 use diesel_async::RunQueryDsl;
@@ -15,7 +18,7 @@ use tracing::{error};
 // Import from handlers library
 use handlers::utils::user::user_info::get_user_organization_id;
 use handlers::datasets::types::{
-    ValidationError, ValidationResult, 
+    ValidationError, ValidationResult, DeployImpactReport,
     DeployDatasetsRequest, DeployDatasetsColumnsRequest, DeployDatasetsEntityRelationshipsRequest
 };
 use handlers::datasets::deploy::deploy_datasets_handler_core;
@@ -25,10 +28,19 @@ use crate::{
     utils::security::checks::is_user_workspace_admin_or_data_admin,
 };
 
+#[derive(Debug, Deserialize)]
+pub struct DeployDatasetsQuery {
+    /// Deploy even when the impact check finds metrics that would break
+    #[serde(default)]
+    pub force: bool,
+}
+
 #[derive(Serialize)]
 pub struct DeployDatasetsResponse {
     pub results: Vec<ValidationResult>,
     pub summary: DeploymentSummary,
+    pub impact: DeployImpactReport,
+    pub blocked: bool,
 }
 
 #[derive(Serialize)]
@@ -143,6 +155,7 @@ pub struct DatasetValidationFailure {
 // Main API endpoint function
 pub async fn deploy_datasets(
     Extension(user): Extension<AuthenticatedUser>,
+    Query(query): Query<DeployDatasetsQuery>,
     Json(request): Json<Vec<DeployDatasetsRequest>>,
 ) -> Result<ApiResponse<DeployDatasetsResponse>, (StatusCode, String)> {
     let organization_id = match get_user_organization_id(&user.id).await {
@@ -172,7 +185,7 @@ pub async fn deploy_datasets(
     }
 
     // Call handler function
-    match handle_deploy_datasets(&user.id, request).await {
+    match handle_deploy_datasets(&user.id, request, query.force).await {
         Ok(result) => Ok(ApiResponse::JsonData(result)),
         Err(e) => {
             tracing::error!("Error in deploy_datasets: {:?}", e);
@@ -185,6 +198,7 @@ pub async fn deploy_datasets(
 async fn handle_deploy_datasets(
     user_id: &Uuid,
     requests: Vec<DeployDatasetsRequest>,
+    force: bool,
 ) -> Result<DeployDatasetsResponse> {
     // Get organization_id. This logic remains here or could be passed if available earlier.
     let organization_id = match get_user_organization_id(user_id).await {
@@ -198,7 +212,8 @@ async fn handle_deploy_datasets(
     };
 
     // Call the new core handler from the handlers library
-    let results = deploy_datasets_handler_core(user_id, organization_id, requests).await?;
+    let outcome = deploy_datasets_handler_core(user_id, organization_id, requests, force).await?;
+    let results = outcome.results;
 
     let successful_models = results.iter().filter(|r| r.success).count();
     let failed_models = results.iter().filter(|r| !r.success).count();
@@ -228,5 +243,10 @@ async fn handle_deploy_datasets(
             .collect(),
     };
 
-    Ok(DeployDatasetsResponse { results, summary })
+    Ok(DeployDatasetsResponse {
+        results,
+        summary,
+        impact: outcome.impact,
+        blocked: outcome.blocked,
+    })
 }
@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Path, Query},
+    http::StatusCode,
+    Extension,
+};
+use handlers::metrics::{diff_metric_versions_handler, MetricVersionDiffResponse};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct DiffMetricVersionsQueryParams {
+    pub from: i32,
+    pub to: i32,
+}
+
+/// GET /metrics/:id/versions/diff?from=1&to=3
+pub async fn diff_metric_versions_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<DiffMetricVersionsQueryParams>,
+) -> Result<ApiResponse<MetricVersionDiffResponse>, (StatusCode, &'static str)> {
+    tracing::info!(
+        "Processing GET request for metric version diff with ID: {}, user_id: {}, from: {}, to: {}",
+        id,
+        user.id,
+        params.from,
+        params.to
+    );
+
+    match diff_metric_versions_handler(&id, &user, params.from, params.to).await {
+        Ok(diff) => Ok(ApiResponse::JsonData(diff)),
+        Err(e) => {
+            tracing::error!("Error diffing metric versions: {}", e);
+            let error_message = e.to_string();
+            if error_message.contains("don't have permission") {
+                return Err((StatusCode::FORBIDDEN, "Permission denied"));
+            }
+            if error_message.contains("Version") && error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, "Version not found"));
+            }
+            if error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, "Metric not found"));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to diff metric versions",
+            ))
+        }
+    }
+}
@@ -5,6 +5,7 @@ use axum::Extension;
 use handlers::metrics::get_metric_data_handler::{GetMetricDataRequest, MetricDataResponse};
 use middleware::AuthenticatedUser;
 use serde::Deserialize;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Deserialize)]
@@ -12,6 +13,11 @@ pub struct GetMetricDataParams {
     pub version_number: Option<i32>,
     pub limit: Option<i64>,
     pub password: Option<String>,
+    pub dashboard_id: Option<Uuid>,
+    /// JSON object of dashboard filter values keyed by filter id
+    pub filters: Option<String>,
+    /// JSON object of metric parameter values keyed by parameter name
+    pub parameters: Option<String>,
 }
 
 pub async fn get_metric_data_rest_handler(
@@ -24,11 +30,25 @@ pub async fn get_metric_data_rest_handler(
         metric_id
     );
 
+    let filters = match params.filters.as_deref() {
+        Some(filters) => serde_json::from_str::<HashMap<String, serde_json::Value>>(filters)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid filters: {}", e)))?,
+        None => HashMap::new(),
+    };
+    let parameters = match params.parameters.as_deref() {
+        Some(parameters) => serde_json::from_str::<HashMap<String, serde_json::Value>>(parameters)
+            .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid parameters: {}", e)))?,
+        None => HashMap::new(),
+    };
+
     let request = GetMetricDataRequest {
         metric_id,
         version_number: params.version_number,
         limit: params.limit,
         password: params.password,
+        dashboard_id: params.dashboard_id,
+        filters,
+        parameters,
     };
 
     match handlers::metrics::get_metric_data_handler(request, user).await {
@@ -43,6 +63,13 @@ pub async fn get_metric_data_rest_handler(
             } else if error_message.contains("don't have permission") || error_message.contains("not found") || error_message.contains("expired") {
                 // Handle permission, not found, or expired errors with 403 Forbidden
                 Err((StatusCode::FORBIDDEN, error_message))
+            } else if error_message.contains("dashboard filters")
+                || error_message.contains("Invalid value for filter")
+                || error_message.contains("Dashboard has no filter")
+                || error_message.contains("Invalid metric parameters")
+            {
+                // Malformed or unknown filter and parameter values
+                Err((StatusCode::BAD_REQUEST, error_message))
             } else {
                 // Default to 500 for other errors
                 Err((StatusCode::INTERNAL_SERVER_ERROR, error_message))
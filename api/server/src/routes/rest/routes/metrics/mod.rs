@@ -6,9 +6,11 @@ use axum::{
 // Import modules
 mod bulk_update_metrics;
 mod delete_metric;
+mod diff_metric_versions;
 mod get_metric;
 mod get_metric_data;
 mod list_metrics;
+mod restore_metric_version;
 mod sharing;
 mod update_metric;
 
@@ -24,5 +26,13 @@ pub fn router() -> Router {
             "/:id/data",
             get(get_metric_data::get_metric_data_rest_handler),
         )
+        .route(
+            "/:id/versions/diff",
+            get(diff_metric_versions::diff_metric_versions_rest_handler),
+        )
+        .route(
+            "/:id/versions/:version_number/restore",
+            post(restore_metric_version::restore_metric_version_rest_handler),
+        )
         .nest("/:id/sharing", sharing::router())
 }
@@ -0,0 +1,46 @@
+use axum::{extract::Path, http::StatusCode, Extension, Json};
+use handlers::metrics::{restore_metric_version_handler, BusterMetric};
+use middleware::AuthenticatedUser;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::routes::rest::ApiResponse;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RestoreVersionRequest {
+    /// Why the version is being restored
+    pub reason: Option<String>,
+}
+
+/// POST /metrics/:id/versions/:version_number/restore
+pub async fn restore_metric_version_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Path((id, version_number)): Path<(Uuid, i32)>,
+    request: Option<Json<RestoreVersionRequest>>,
+) -> Result<ApiResponse<BusterMetric>, (StatusCode, &'static str)> {
+    tracing::info!(
+        "Processing POST request to restore metric {} to version {}, user_id: {}",
+        id,
+        version_number,
+        user.id
+    );
+
+    let Json(request) = request.unwrap_or_default();
+    match restore_metric_version_handler(&id, &user, version_number, request.reason).await {
+        Ok(metric) => Ok(ApiResponse::JsonData(metric)),
+        Err(e) => {
+            tracing::error!("Error restoring metric version: {}", e);
+            let error_message = e.to_string();
+            if error_message.contains("permission") {
+                return Err((StatusCode::FORBIDDEN, "Permission denied"));
+            }
+            if error_message.contains("Version") && error_message.contains("not found") {
+                return Err((StatusCode::NOT_FOUND, "Version not found"));
+            }
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to restore metric version",
+            ))
+        }
+    }
+}
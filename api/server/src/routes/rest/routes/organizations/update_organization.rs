@@ -16,7 +16,7 @@ pub async fn update_organization(
     Json(payload): Json<UpdateOrganizationRequest>,
 ) -> Result<ApiResponse<OrganizationResponse>, (StatusCode, &'static str)> {
     // Check if there's anything to update
-    if payload.name.is_none() {
+    if payload.name.is_none() && payload.timezone.is_none() {
         return Err((
             StatusCode::BAD_REQUEST,
             "No fields to update",
@@ -31,6 +31,10 @@ pub async fn update_organization(
             if e.to_string().contains("not a workspace admin") {
                 return Err((StatusCode::FORBIDDEN, "User is not a workspace admin"));
             }
+
+            if e.to_string().contains("Invalid timezone") {
+                return Err((StatusCode::BAD_REQUEST, "Invalid timezone"));
+            }
             
             return Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
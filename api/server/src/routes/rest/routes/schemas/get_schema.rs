@@ -0,0 +1,27 @@
+use axum::extract::Path;
+use schemars::schema::RootSchema;
+use serde::Deserialize;
+
+use database::types::{DashboardYml, MetricYml};
+use semantic_layer::models::ModelFile;
+
+use crate::routes::rest::ApiResponse;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaKind {
+    Model,
+    Metric,
+    Dashboard,
+}
+
+/// JSON Schema for a YAML file kind. Unauthenticated so editors can fetch it
+/// directly, e.g. from a `yaml-language-server: $schema=` comment.
+pub async fn get_schema(Path(kind): Path<SchemaKind>) -> ApiResponse<RootSchema> {
+    let schema = match kind {
+        SchemaKind::Model => ModelFile::json_schema(),
+        SchemaKind::Metric => MetricYml::json_schema(),
+        SchemaKind::Dashboard => DashboardYml::json_schema(),
+    };
+    ApiResponse::JsonData(schema)
+}
@@ -0,0 +1,7 @@
+use axum::{routing::get, Router};
+
+mod get_schema;
+
+pub fn router() -> Router {
+    Router::new().route("/:kind", get(get_schema::get_schema))
+}
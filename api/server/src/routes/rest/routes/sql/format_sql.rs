@@ -0,0 +1,25 @@
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use handlers::sql::{format_sql_handler, FormatSqlRequest, FormatSqlResponse};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+
+pub async fn format_sql_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<FormatSqlRequest>,
+) -> Result<ApiResponse<FormatSqlResponse>, (StatusCode, String)> {
+    match format_sql_handler(request, &user).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            let error_message = e.to_string();
+            tracing::error!("Error formatting SQL: {}", error_message);
+
+            if error_message.contains("SQL parsing failed") {
+                Err((StatusCode::BAD_REQUEST, error_message))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, error_message))
+            }
+        }
+    }
+}
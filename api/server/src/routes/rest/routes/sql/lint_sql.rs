@@ -0,0 +1,31 @@
+use axum::http::StatusCode;
+use axum::{Extension, Json};
+use handlers::sql::{lint_sql_handler, LintSqlRequest, LintSqlResponse};
+use middleware::AuthenticatedUser;
+
+use crate::routes::rest::ApiResponse;
+
+pub async fn lint_sql_rest_handler(
+    Extension(user): Extension<AuthenticatedUser>,
+    Json(request): Json<LintSqlRequest>,
+) -> Result<ApiResponse<LintSqlResponse>, (StatusCode, String)> {
+    match lint_sql_handler(request, &user).await {
+        Ok(response) => Ok(ApiResponse::JsonData(response)),
+        Err(e) => {
+            let error_message = e.to_string();
+            tracing::error!("Error linting SQL: {}", error_message);
+
+            if error_message.contains("SQL parsing failed")
+                || error_message.contains("Only SELECT queries are supported")
+            {
+                Err((StatusCode::BAD_REQUEST, error_message))
+            } else if error_message.contains("Data source not found") {
+                Err((StatusCode::NOT_FOUND, error_message))
+            } else if error_message.contains("don't have permission") {
+                Err((StatusCode::FORBIDDEN, error_message))
+            } else {
+                Err((StatusCode::INTERNAL_SERVER_ERROR, error_message))
+            }
+        }
+    }
+}
@@ -1,7 +1,12 @@
 use axum::{routing::post, Router};
 
+mod format_sql;
+mod lint_sql;
 mod run_sql;
 
 pub fn router() -> Router {
-    Router::new().route("/run", post(run_sql::run_sql))
+    Router::new()
+        .route("/run", post(run_sql::run_sql))
+        .route("/lint", post(lint_sql::lint_sql_rest_handler))
+        .route("/format", post(format_sql::format_sql_rest_handler))
 }
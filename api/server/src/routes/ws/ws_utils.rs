@@ -410,6 +410,7 @@ pub async fn get_user_information(user_id: &Uuid) -> Result<UserInfoObject> {
                 organizations::updated_at,
                 organizations::deleted_at,
                 organizations::payment_required,
+                organizations::timezone,
             )
                 .nullable(),
             users_to_organizations::role.nullable(),
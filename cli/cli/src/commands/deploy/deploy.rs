@@ -369,12 +369,9 @@ fn to_deploy_request(model: &Model, sql_content: String) -> DeployDatasetsReques
             name: measure.name.clone(),
             description: measure.description.clone().unwrap_or_default(),
             semantic_type: Some("measure".to_string()),
-            expr: None, // Measure doesn't have a direct `expr` in semantic_layer::Model that maps here, measures are typically simple column references or aggregations defined by their type/agg
+            expr: measure.expr.clone(),
             type_: measure.type_.clone(),
-            // Measures might have an implicit aggregation (like SUM, AVG) based on their type or usage,
-            // but semantic_layer::Measure doesn't explicitly store an `agg` field like `DeployDatasetsColumnsRequest` expects.
-            // This might need further refinement based on how `agg` should be derived for measures.
-            agg: None, // Placeholder for now
+            agg: measure.agg.map(|agg| agg.as_str().to_string()),
             searchable: false, // Measures are typically not directly searched upon like free-text dimensions
         });
     }
@@ -422,7 +419,7 @@ fn to_deploy_request(model: &Model, sql_content: String) -> DeployDatasetsReques
     }
 }
 
-pub async fn deploy(path: Option<&str>, dry_run: bool, recursive: bool) -> Result<()> {
+pub async fn deploy(path: Option<&str>, dry_run: bool, recursive: bool, force: bool) -> Result<()> {
     check_authentication().await?;
 
     let current_dir = std::env::current_dir()?;
@@ -636,7 +633,7 @@ pub async fn deploy(path: Option<&str>, dry_run: bool, recursive: bool) -> Resul
         let client = client.expect("BusterClient should be initialized for non-dry run");
         // ... (rest of deployment logic, calling client.deploy_datasets(deploy_requests_final).await ...) 
         // ... (handle_deploy_response(&response, &mut result, &model_mappings_final, &progress)) ...
-         match client.deploy_datasets(deploy_requests_final).await {
+         match client.deploy_datasets(deploy_requests_final, force).await {
             Ok(response) => handle_deploy_response(&response, &mut result, &model_mappings_final, &progress),
             Err(e) => {
                 eprintln!("❌ Critical error during deployment API call: {}", e.to_string().red());
@@ -787,6 +784,8 @@ fn handle_deploy_response(
 ) {
     let mut has_validation_errors = false;
 
+    print_impact_report(response);
+
     // Process validation results
     println!("\n{}", "🔬 Processing deployment results from Buster Cloud...".dimmed());
     for validation in response.results.iter() {
@@ -874,6 +873,41 @@ fn handle_deploy_response(
     }
 }
 
+/// Prints the metrics and dashboards that reference datasets or columns the
+/// deployment removes
+fn print_impact_report(response: &DeployDatasetsResponse) {
+    if response.impact.impacted_metrics.is_empty() {
+        return;
+    }
+
+    let header = if response.blocked {
+        "⛔ Deployment blocked: these metrics reference datasets or columns that would be removed"
+    } else {
+        "⚠️  Deployed with --force: these metrics reference removed datasets or columns"
+    };
+    println!("\n{}", header.bold().yellow());
+
+    for metric in &response.impact.impacted_metrics {
+        let reason = if metric.dataset_removed {
+            format!("dataset '{}' is not in this deployment", metric.dataset_name)
+        } else {
+            format!(
+                "'{}' no longer defines {}",
+                metric.dataset_name,
+                metric.missing_columns.join(", ")
+            )
+        };
+        println!("  - Metric '{}' ({}): {}", metric.metric_name.purple(), metric.metric_id.to_string().dimmed(), reason);
+        for dashboard in &metric.dashboards {
+            println!("      used on dashboard '{}' ({})", dashboard.name.cyan(), dashboard.id.to_string().dimmed());
+        }
+    }
+
+    if response.blocked {
+        println!("\n💡 Update these metrics, or re-run with {} to deploy anyway.", "buster deploy --force".bold());
+    }
+}
+
 fn parse_semantic_layer_spec(file_path: &Path) -> Result<SemanticLayerSpec> {
     let yml_content = fs::read_to_string(file_path)
         .map_err(|e| anyhow!("Failed to read semantic layer spec file {}: {}", file_path.display(), e))?;
@@ -884,6 +918,7 @@ fn parse_semantic_layer_spec(file_path: &Path) -> Result<SemanticLayerSpec> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use semantic_layer::models::Aggregation;
     use std::fs;
     use tempfile::TempDir;
 
@@ -1053,6 +1088,7 @@ models:
                     type_: Some("string".to_string()),
                     searchable: true, // Example value
                     options: None,
+                    time: None,
                 }
             ],
             measures: vec![
@@ -1060,6 +1096,9 @@ models:
                     name: "measure1".to_string(),
                     description: Some("First measure".to_string()),
                     type_: Some("number".to_string()),
+                    expr: Some("amount".to_string()),
+                    agg: Some(Aggregation::Sum),
+                    percentile: None,
                 }
             ],
             metrics: vec![],
@@ -1084,6 +1123,8 @@ models:
         assert_eq!(request.columns[0].name, "dim1");
         assert_eq!(request.columns[0].searchable, true);
         assert_eq!(request.columns[1].name, "measure1");
+        assert_eq!(request.columns[1].expr.as_deref(), Some("amount"));
+        assert_eq!(request.columns[1].agg.as_deref(), Some("sum"));
         assert!(request.entity_relationships.is_some());
         assert_eq!(request.entity_relationships.as_ref().unwrap().len(), 1);
         assert_eq!(request.entity_relationships.as_ref().unwrap()[0].name, "related_model");
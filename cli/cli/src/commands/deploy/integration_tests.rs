@@ -53,7 +53,7 @@ measures:
     create_test_yaml(temp_dir.path(), "test_model.yml", model_yml)?;
     
     // Test dry run
-    let result = rt.block_on(deploy(Some(temp_dir.path().to_str().unwrap()), true, false));
+    let result = rt.block_on(deploy(Some(temp_dir.path().to_str().unwrap()), true, false, false));
     assert!(result.is_ok());
     
     Ok(())
@@ -109,7 +109,7 @@ database: "model2_db"
     create_test_yaml(&proj2_dir, "model2.yml", model2_yml)?;
     
     // Test dry run
-    let result = rt.block_on(deploy(Some(temp_dir.path().to_str().unwrap()), true, true));
+    let result = rt.block_on(deploy(Some(temp_dir.path().to_str().unwrap()), true, true, false));
     assert!(result.is_ok());
     
     Ok(())
@@ -132,7 +132,7 @@ dimensions:
     create_test_yaml(temp_dir.path(), "incomplete_model.yml", model_yml)?;
     
     // Test dry run - should fail due to missing configurations
-    let result = rt.block_on(deploy(Some(temp_dir.path().to_str().unwrap()), true, false));
+    let result = rt.block_on(deploy(Some(temp_dir.path().to_str().unwrap()), true, false, false));
     assert!(result.is_err());
     
     Ok(())
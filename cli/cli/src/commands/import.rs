@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Context, Result};
+use colored::*;
+use glob::glob;
+use inquire::Confirm;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use semantic_layer::import::lookml::{import_lookml, LookmlFile};
+use semantic_layer::import::metricflow::{import_metricflow, MetricFlowFile};
+use semantic_layer::import::{Import, ImportWarning};
+use semantic_layer::models::Model;
+use semantic_layer::validate::{validate_models, SourcedModel, ValidationError};
+
+use crate::utils::config::BusterConfig;
+
+/// Converts the dbt MetricFlow `semantic_models:` and `metrics:` found in the
+/// project's dbt YAML files into Buster semantic model files.
+pub async fn import_metricflow_command(
+    path_arg: Option<String>,
+    target_output_dir_arg: Option<String>,
+) -> Result<()> {
+    println!("{}", "🚀 Importing dbt MetricFlow semantic models...".bold().blue());
+
+    let buster_config_dir = std::env::current_dir().context("Failed to get current directory")?;
+    let buster_config = BusterConfig::load_from_dir(&buster_config_dir)?.ok_or_else(|| {
+        anyhow!("❌ buster.yml not found in {}. Please run 'buster init' first.", buster_config_dir.display())
+    })?;
+
+    // --- 1. Find dbt YAML files (path_arg, or dbt_project.yml model-paths) ---
+    let search_roots: Vec<PathBuf> = match &path_arg {
+        Some(pa_str) => vec![buster_config_dir.join(pa_str)],
+        None => crate::commands::init::parse_dbt_project_file_content(&buster_config_dir)?
+            .map(|c| c.model_paths)
+            .unwrap_or_else(|| vec!["models".to_string()])
+            .iter()
+            .map(|p| buster_config_dir.join(p))
+            .collect(),
+    };
+    let mut yaml_files: Vec<PathBuf> = Vec::new();
+    for root in &search_roots {
+        if root.is_file() {
+            yaml_files.push(root.clone());
+            continue;
+        }
+        for ext in ["yml", "yaml"] {
+            let glob_pattern = root.join(format!("**/*.{}", ext));
+            match glob(&glob_pattern.to_string_lossy()) {
+                Ok(paths) => yaml_files.extend(paths.flatten().filter(|p| p.is_file())),
+                Err(e) => eprintln!("{}", format!("Error globbing '{}': {}", glob_pattern.display(), e).yellow()),
+            }
+        }
+    }
+    yaml_files.sort();
+
+    // --- 2. Parse the MetricFlow parts of each file ---
+    let mut source_files: Vec<PathBuf> = Vec::new();
+    let mut parsed_files: Vec<MetricFlowFile> = Vec::new();
+    // dbt files we must never overwrite; Buster model files from a previous import are fair game
+    let mut protected: HashSet<PathBuf> = HashSet::new();
+    for yaml_file in yaml_files {
+        let content = fs::read_to_string(&yaml_file)
+            .with_context(|| format!("Failed to read {}", yaml_file.display()))?;
+        if serde_yaml::from_str::<Model>(&content).is_err() {
+            protected.insert(yaml_file.clone());
+        }
+        match MetricFlowFile::from_yaml(&content) {
+            Ok(parsed) if !parsed.is_empty() => {
+                source_files.push(yaml_file);
+                parsed_files.push(parsed);
+            }
+            Ok(_) => {}
+            Err(e) => eprintln!("{}", format!("⚠️ Skipping {}: {}", yaml_file.display(), e).yellow()),
+        }
+    }
+
+    if parsed_files.iter().all(|f| f.semantic_models.is_empty()) {
+        println!("{}", "ℹ️ No MetricFlow semantic models found. Nothing to import.".yellow());
+        return Ok(());
+    }
+    println!("{}", format!("✅ Found MetricFlow definitions in {} file(s).", source_files.len()).dimmed());
+
+    // Models come back in semantic model order, so remember where each came from
+    let model_sources: Vec<&PathBuf> = source_files
+        .iter()
+        .zip(&parsed_files)
+        .flat_map(|(file, parsed)| std::iter::repeat(file).take(parsed.semantic_models.len()))
+        .collect();
+    let Import { models, warnings } = import_metricflow(&parsed_files);
+
+    let validation_errors = report_import(&models, &warnings);
+
+    // --- 3. Determine output paths (never over a dbt file) ---
+    let output_base_dir = output_base_dir(&buster_config, &buster_config_dir, target_output_dir_arg);
+
+    let mut outputs: Vec<(PathBuf, String)> = Vec::new();
+    for (model, source) in models.iter().zip(model_sources) {
+        let dir = match &output_base_dir {
+            Some(dir) => dir.clone(),
+            None => source.parent().map(Path::to_path_buf).unwrap_or_else(|| buster_config_dir.clone()),
+        };
+        let output_path = dir.join(format!("{}.yml", model.name));
+        if protected.contains(&output_path) {
+            eprintln!("{}", format!(
+                "⚠️ Not writing model '{}': {} is a dbt file. Use -o to choose another directory.",
+                model.name, output_path.display()
+            ).yellow());
+            continue;
+        }
+        let yaml = serde_yaml::to_string(model)
+            .with_context(|| format!("Failed to serialize model '{}'", model.name))?;
+        outputs.push((output_path, yaml));
+    }
+
+    // --- 4. Write models ---
+    if !confirm_and_write(&outputs)? {
+        return Ok(());
+    }
+
+    print_summary("MetricFlow", outputs.len(), &warnings, &validation_errors);
+    Ok(())
+}
+
+/// Converts the views and explores of a LookML project into Buster semantic
+/// model files, plus a review report of everything that needs attention.
+pub async fn import_lookml_command(
+    path_arg: Option<String>,
+    target_output_dir_arg: Option<String>,
+) -> Result<()> {
+    println!("{}", "🚀 Importing LookML views and explores...".bold().blue());
+
+    let buster_config_dir = std::env::current_dir().context("Failed to get current directory")?;
+    let buster_config = BusterConfig::load_from_dir(&buster_config_dir)?.ok_or_else(|| {
+        anyhow!("❌ buster.yml not found in {}. Please run 'buster init' first.", buster_config_dir.display())
+    })?;
+
+    // --- 1. Find .lkml files (path_arg, or the current directory) ---
+    let lookml_root = path_arg
+        .map(|pa_str| buster_config_dir.join(pa_str))
+        .unwrap_or_else(|| buster_config_dir.clone());
+    let mut lookml_files: Vec<PathBuf> = if lookml_root.is_file() {
+        vec![lookml_root.clone()]
+    } else {
+        let glob_pattern = lookml_root.join("**/*.lkml");
+        glob(&glob_pattern.to_string_lossy())
+            .with_context(|| format!("Error globbing '{}'", glob_pattern.display()))?
+            .flatten()
+            .filter(|p| p.is_file())
+            .collect()
+    };
+    lookml_files.sort();
+
+    // --- 2. Parse every file; a file that doesn't parse is skipped, not fatal ---
+    let mut parsed_files: Vec<LookmlFile> = Vec::new();
+    for lookml_file in &lookml_files {
+        let content = fs::read_to_string(lookml_file)
+            .with_context(|| format!("Failed to read {}", lookml_file.display()))?;
+        match LookmlFile::parse(&content) {
+            Ok(parsed) => parsed_files.push(parsed),
+            Err(e) => eprintln!("{}", format!("⚠️ Skipping {}: {}", lookml_file.display(), e).yellow()),
+        }
+    }
+
+    if parsed_files.is_empty() {
+        println!("{}", "ℹ️ No LookML files found. Nothing to import.".yellow());
+        return Ok(());
+    }
+    println!("{}", format!("✅ Parsed {} LookML file(s).", parsed_files.len()).dimmed());
+
+    let Import { models, warnings } = import_lookml(&parsed_files);
+    let validation_errors = report_import(&models, &warnings);
+
+    // --- 3. Determine output paths ---
+    let output_dir = output_base_dir(&buster_config, &buster_config_dir, target_output_dir_arg)
+        .unwrap_or_else(|| if lookml_root.is_file() {
+            lookml_root.parent().map(Path::to_path_buf).unwrap_or_else(|| buster_config_dir.clone())
+        } else {
+            lookml_root.clone()
+        });
+    let mut outputs: Vec<(PathBuf, String)> = Vec::new();
+    for model in &models {
+        let yaml = serde_yaml::to_string(model)
+            .with_context(|| format!("Failed to serialize model '{}'", model.name))?;
+        outputs.push((output_dir.join(format!("{}.yml", model.name)), yaml));
+    }
+
+    // --- 4. Write models and the review report ---
+    if !confirm_and_write(&outputs)? {
+        return Ok(());
+    }
+    if !warnings.is_empty() || !validation_errors.is_empty() {
+        let report_path = output_dir.join(LOOKML_REVIEW_REPORT);
+        fs::write(&report_path, review_report(&warnings, &validation_errors))
+            .with_context(|| format!("Failed to write {}", report_path.display()))?;
+        println!("   {} Review report: {}", "📝".yellow(), report_path.display().to_string().yellow());
+    }
+
+    print_summary("LookML", outputs.len(), &warnings, &validation_errors);
+    Ok(())
+}
+
+const LOOKML_REVIEW_REPORT: &str = "lookml_import_review.md";
+
+/// Prints import warnings and validation errors, returning the latter.
+fn report_import(models: &[Model], warnings: &[ImportWarning]) -> Vec<ValidationError> {
+    if !warnings.is_empty() {
+        println!("\n{}", format!("⚠️ {} construct(s) could not be imported exactly:", warnings.len()).yellow());
+        for warning in warnings {
+            println!("  - {}", warning.to_string().yellow());
+        }
+    }
+
+    let sourced: Vec<SourcedModel> = models.iter().map(|model| SourcedModel { file: None, model }).collect();
+    let validation_errors = validate_models(&sourced);
+    if !validation_errors.is_empty() {
+        println!("\n{}", "❌ Imported models have validation errors; fix them before deploying:".red());
+        for error in &validation_errors {
+            println!("  - {}", error.to_string().red());
+        }
+    }
+    validation_errors
+}
+
+/// `-o`, else the first `semantic_model_paths` entry in buster.yml.
+fn output_base_dir(
+    buster_config: &BusterConfig,
+    buster_config_dir: &Path,
+    target_output_dir_arg: Option<String>,
+) -> Option<PathBuf> {
+    target_output_dir_arg
+        .or_else(|| {
+            buster_config.projects.as_ref()
+                .and_then(|p| p.first())
+                .and_then(|proj| proj.semantic_model_paths.as_ref())
+                .and_then(|paths| paths.first().cloned())
+        })
+        .map(|dir| if Path::new(&dir).is_absolute() { PathBuf::from(dir) } else { buster_config_dir.join(dir) })
+}
+
+/// Writes the model files, asking first if any would be overwritten.
+/// Returns false if the user cancelled.
+fn confirm_and_write(outputs: &[(PathBuf, String)]) -> Result<bool> {
+    let existing = outputs.iter().filter(|(path, _)| path.exists()).count();
+    if existing > 0
+        && !Confirm::new(&format!("Overwrite {} existing semantic model file(s)?", existing))
+            .with_default(false)
+            .prompt()?
+    {
+        println!("{}", "ℹ️ Import cancelled. No files were written.".yellow());
+        return Ok(false);
+    }
+
+    for (output_path, yaml) in outputs {
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(output_path, yaml)
+            .with_context(|| format!("Failed to write {}", output_path.display()))?;
+        println!("   {} Imported semantic model: {}", "✨".green(), output_path.display().to_string().green());
+    }
+    Ok(true)
+}
+
+fn review_report(warnings: &[ImportWarning], validation_errors: &[ValidationError]) -> String {
+    let mut report = String::from("# LookML import review\n\n");
+    report.push_str("These LookML definitions could not be imported exactly. Recreate them by hand where needed.\n\n");
+    for warning in warnings {
+        report.push_str(&format!("- [ ] {}\n", warning));
+    }
+    if !validation_errors.is_empty() {
+        report.push_str("\n## Validation errors\n\n");
+        for error in validation_errors {
+            report.push_str(&format!("- [ ] {}\n", error));
+        }
+    }
+    report
+}
+
+fn print_summary(source: &str, imported: usize, warnings: &[ImportWarning], validation_errors: &[ValidationError]) {
+    println!("\n{}", format!("📊 {} Import Summary:", source).bold().green());
+    println!("  --------------------------------------------------");
+    println!("  Semantic models imported : {}", imported.to_string().green());
+    println!("  Warnings                 : {}", warnings.len().to_string().yellow());
+    println!("  Validation errors        : {}", validation_errors.len().to_string().red());
+    println!("  --------------------------------------------------");
+}
@@ -1,9 +1,11 @@
 pub mod auth;
 pub mod deploy;
 pub mod generate;
+pub mod import;
 pub mod init;
 pub mod parse;
 pub mod run;
+pub mod schema;
 pub mod update;
 
 pub use auth::auth_with_args;
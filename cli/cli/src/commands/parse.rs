@@ -11,6 +11,8 @@ use crate::utils::{
 };
 use crate::commands::deploy::deploy::{parse_model_file, resolve_model_configurations};
 use semantic_layer::models::Model;
+use semantic_layer::validate::{validate_models, SourcedModel};
+use std::collections::HashMap;
 
 // A simple progress tracker for the parse command
 #[derive(Debug, Default)]
@@ -218,6 +220,10 @@ pub async fn parse_models_command(path_arg: Option<String>) -> Result<()> {
         }
     };
 
+    // Models are validated together once every file is parsed, so that
+    // entities can point at models defined in other files
+    let mut parsed_models_with_files: Vec<(String, Model)> = Vec::new();
+
     for (yml_path, project_ctx_opt) in files_to_parse_with_context {
         progress.processed_files += 1;
         progress.current_file = yml_path.strip_prefix(&effective_buster_config_dir).unwrap_or(&yml_path).to_string_lossy().into_owned();
@@ -238,29 +244,7 @@ pub async fn parse_models_command(path_arg: Option<String>) -> Result<()> {
                              // Potentially add to errors if this is unexpected
                         }
                         for model in resolved_models {
-                            let mut model_errors: Vec<String> = Vec::new();
-                            // Basic validation: model name should not be empty
-                            if model.name.is_empty() {
-                                model_errors.push("Model name is empty.".to_string());
-                            }
-                            // Further validation could be added here, e.g., checking for data_source_name and schema after resolution
-                            if model.data_source_name.is_none() {
-                                model_errors.push("data_source_name could not be resolved.".to_string());
-                            }
-                            if model.schema.is_none() {
-                                model_errors.push("schema could not be resolved.".to_string());
-                            }
-
-                            if !model_errors.is_empty() {
-                                progress.errors.push((
-                                    progress.current_file.clone(),
-                                    if model.name.is_empty() { "<Unnamed Model>".to_string() } else { model.name.clone() },
-                                    model_errors,
-                                ));
-                            } else {
-                                println!("  ✅ Parsed & validated: {}", model.name.purple());
-                                progress.successes.push((progress.current_file.clone(), model.name.clone()));
-                            }
+                            parsed_models_with_files.push((progress.current_file.clone(), model));
                         }
                     }
                     Err(e) => {
@@ -284,6 +268,48 @@ pub async fn parse_models_command(path_arg: Option<String>) -> Result<()> {
         }
     }
 
+    println!("\n{}", "🔗 Validating models and cross-model references...".dimmed());
+    let sourced_models: Vec<SourcedModel> = parsed_models_with_files
+        .iter()
+        .map(|(file, model)| SourcedModel { file: Some(file.as_str()), model })
+        .collect();
+    let mut semantic_errors: HashMap<(String, String), Vec<String>> = HashMap::new();
+    for error in validate_models(&sourced_models) {
+        let message = match &error.field {
+            Some(field) => format!("{}: {}", field, error.message),
+            None => error.message.clone(),
+        };
+        semantic_errors
+            .entry((error.file.clone().unwrap_or_default(), error.model.clone()))
+            .or_default()
+            .push(message);
+    }
+
+    for (file, model) in &parsed_models_with_files {
+        let mut model_errors: Vec<String> = Vec::new();
+        // Further validation could be added here, e.g., checking for data_source_name and schema after resolution
+        if model.data_source_name.is_none() {
+            model_errors.push("data_source_name could not be resolved.".to_string());
+        }
+        if model.schema.is_none() {
+            model_errors.push("schema could not be resolved.".to_string());
+        }
+        if let Some(errors) = semantic_errors.remove(&(file.clone(), model.name.clone())) {
+            model_errors.extend(errors);
+        }
+
+        if !model_errors.is_empty() {
+            progress.errors.push((
+                file.clone(),
+                if model.name.is_empty() { "<Unnamed Model>".to_string() } else { model.name.clone() },
+                model_errors,
+            ));
+        } else {
+            println!("  ✅ Parsed & validated: {}", model.name.purple());
+            progress.successes.push((file.clone(), model.name.clone()));
+        }
+    }
+
     progress.log_summary();
 
     if !progress.errors.is_empty() {
@@ -0,0 +1,41 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use colored::*;
+use std::fs;
+use std::path::Path;
+
+use database::types::{DashboardYml, MetricYml};
+use semantic_layer::models::ModelFile;
+
+/// The YAML file kinds a JSON Schema can be generated for.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum SchemaKind {
+    Model,
+    Metric,
+    Dashboard,
+}
+
+/// Prints the JSON Schema for a YAML file kind, or writes it to `output`.
+/// Editors pick it up via e.g. `# yaml-language-server: $schema=<path>`.
+pub async fn schema_command(kind: SchemaKind, output: Option<String>) -> Result<()> {
+    let schema = match kind {
+        SchemaKind::Model => ModelFile::json_schema(),
+        SchemaKind::Metric => MetricYml::json_schema(),
+        SchemaKind::Dashboard => DashboardYml::json_schema(),
+    };
+    let json = serde_json::to_string_pretty(&schema).context("Failed to serialize schema")?;
+
+    match output {
+        Some(output) => {
+            let output_path = Path::new(&output);
+            if let Some(parent) = output_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(output_path, json + "\n")
+                .with_context(|| format!("Failed to write {}", output_path.display()))?;
+            println!("{} Wrote {:?} schema to {}", "✅".green(), kind, output_path.display().to_string().green());
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
@@ -60,6 +60,9 @@ pub enum Commands {
         /// Recursively search for model files in subdirectories
         #[arg(long, default_value_t = true)]
         recursive: bool,
+        /// Deploy even if existing metrics reference removed datasets or columns
+        #[arg(long, default_value_t = false)]
+        force: bool,
     },
     /// Generate or update semantic model YAML definitions from dbt project
     Generate {
@@ -72,6 +75,14 @@ pub enum Commands {
         #[arg(long, short = 'o', name = "output-file")]
         // output-file as a more descriptive name for the arg
         target_semantic_file: Option<String>,
+        /// Import dbt MetricFlow `semantic_models:` and `metrics:` from the dbt YAML files
+        /// under `--path` (or the dbt model paths) instead of reading the dbt catalog.
+        #[arg(long, default_value_t = false)]
+        from_metricflow: bool,
+        /// Import LookML views and explores from the `.lkml` files under `--path`
+        /// (or the current directory) instead of reading the dbt catalog.
+        #[arg(long, default_value_t = false, conflicts_with = "from_metricflow")]
+        from_lookml: bool,
     },
     /// Parse and validate semantic model YAML definitions
     Parse {
@@ -80,6 +91,14 @@ pub enum Commands {
         #[arg(long)]
         path: Option<String>,
     },
+    /// Print the JSON Schema for model, metric or dashboard YAML files
+    Schema {
+        #[arg(value_enum)]
+        kind: commands::schema::SchemaKind,
+        /// Write the schema to this file instead of printing it
+        #[arg(long, short = 'o')]
+        output: Option<String>,
+    },
     Start,
     Stop,
 }
@@ -126,18 +145,30 @@ async fn main() {
             path,
             dry_run,
             recursive,
+            force,
         } => {
             async move {
                 check_authentication().await?;
-                commands::deploy::deploy(path.as_deref(), dry_run, recursive).await
+                commands::deploy::deploy(path.as_deref(), dry_run, recursive, force).await
             }
             .await
         }
         Commands::Generate {
             path,
             target_semantic_file,
-        } => commands::generate::generate_semantic_models_command(path, target_semantic_file).await,
+            from_metricflow,
+            from_lookml,
+        } => {
+            if from_metricflow {
+                commands::import::import_metricflow_command(path, target_semantic_file).await
+            } else if from_lookml {
+                commands::import::import_lookml_command(path, target_semantic_file).await
+            } else {
+                commands::generate::generate_semantic_models_command(path, target_semantic_file).await
+            }
+        }
         Commands::Parse { path } => commands::parse::parse_models_command(path).await,
+        Commands::Schema { kind, output } => commands::schema::schema_command(kind, output).await,
         Commands::Start => run::start().await.map_err(anyhow::Error::from),
         Commands::Stop => run::stop().await.map_err(anyhow::Error::from),
     };
@@ -123,12 +123,14 @@ impl BusterClient {
     pub async fn deploy_datasets(
         &self,
         req_body: Vec<DeployDatasetsRequest>,
+        force: bool,
     ) -> Result<DeployDatasetsResponse> {
         let headers = self.build_headers()?;
 
         match self
             .client
             .post(format!("{}/api/v1/datasets/deploy", self.base_url))
+            .query(&[("force", force)])
             .headers(headers)
             .json(&req_body)
             .send()
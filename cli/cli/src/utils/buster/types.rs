@@ -71,6 +71,7 @@ pub struct ValidationResult {
 
 #[derive(Debug, Deserialize)]
 pub struct ValidationError {
+    #[serde(alias = "code")]
     pub error_type: ValidationErrorType,
     pub column_name: Option<String>,
     pub message: String,
@@ -82,6 +83,7 @@ pub enum ValidationErrorType {
     TableNotFound,
     ColumnNotFound,
     TypeMismatch,
+    #[serde(alias = "DATA_SOURCE_ERROR")]
     DataSourceError,
     ModelNotFound,
     InvalidRelationship,
@@ -89,11 +91,46 @@ pub enum ValidationErrorType {
     ProjectNotFound,
     InvalidBusterYml,
     DataSourceMismatch,
+    #[serde(alias = "DEPLOY_IMPACT")]
+    DeployImpact,
+    #[serde(alias = "SEMANTIC_VALIDATION")]
+    SemanticValidation,
+    #[serde(alias = "INTERNAL_ERROR")]
+    InternalError,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct DeployDatasetsResponse {
     pub results: Vec<ValidationResult>,
+    #[serde(default)]
+    pub impact: DeployImpactReport,
+    /// True when the server refused to deploy because metrics would break
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct DeployImpactReport {
+    #[serde(default)]
+    pub impacted_metrics: Vec<ImpactedMetric>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImpactedMetric {
+    pub metric_id: Uuid,
+    pub metric_name: String,
+    pub dataset_name: String,
+    pub dataset_removed: bool,
+    #[serde(default)]
+    pub missing_columns: Vec<String>,
+    #[serde(default)]
+    pub dashboards: Vec<ImpactedDashboard>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImpactedDashboard {
+    pub id: Uuid,
+    pub name: String,
 }
 
 #[derive(Debug, Serialize)]
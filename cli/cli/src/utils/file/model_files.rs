@@ -197,6 +197,7 @@ pub async fn upload_model_files(
     data_source_name: Option<&str>,
     schema: Option<&str>,
     env: Option<&str>,
+    force: bool,
 ) -> Result<()> {
     println!("Uploading model files to Buster");
     if let Some(path) = dir_path {
@@ -278,12 +279,21 @@ pub async fn upload_model_files(
 
     let buster = BusterClient::new(buster_creds.url, buster_creds.api_key)?;
 
-    if let Err(e) = buster.deploy_datasets(post_datasets_req_body).await {
+    let response = match buster.deploy_datasets(post_datasets_req_body, force).await {
+        Ok(response) => response,
+        Err(e) => {
+            return Err(anyhow::anyhow!(
+                "Failed to upload model files to Buster: {}",
+                e
+            ))
+        }
+    };
+    if response.blocked {
         return Err(anyhow::anyhow!(
-            "Failed to upload model files to Buster: {}",
-            e
+            "Upload blocked: {} metric(s) reference datasets or columns these models remove. Upload with force to deploy anyway.",
+            response.impact.impacted_metrics.len()
         ));
-    };
+    }
 
     Ok(())
 }